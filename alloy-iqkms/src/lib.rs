@@ -0,0 +1,256 @@
+//! iqkms alloy signing provider.
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/iqlusioninc/iqkms/main/.img/iqkms-sq.svg"
+)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::arithmetic_side_effects,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_used,
+    missing_docs,
+    rust_2018_idioms,
+    unused_lifetimes,
+    unused_qualifications
+)]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+pub use alloy_signer::Signer;
+
+use alloy_consensus::SignableTransaction;
+use alloy_network::TxSigner;
+use alloy_primitives::{Address, B256, ChainId, Signature, U256};
+use iqkms::{
+    StdError,
+    ethereum::Transaction,
+    proto,
+    tokio::sync::Mutex,
+    tonic,
+    types::ethereum::{self as types, H256},
+};
+use std::{fmt, sync::Arc};
+use tracing::instrument;
+
+/// iqkms alloy signing provider.
+///
+/// Implements [`Signer`] and [`TxSigner`], computing signatures using the key
+/// with the given address stored in iqkms.
+#[derive(Clone)]
+pub struct IqkmsSigner {
+    /// gRPC client
+    client: Arc<Mutex<iqkms::ethereum::SignerClient>>,
+
+    /// Ethereum address of the signing key.
+    address: Address,
+
+    /// EIP-155 chain ID.
+    chain_id: Option<ChainId>,
+}
+
+impl IqkmsSigner {
+    /// Create a new signer, connecting to the given endpoint.
+    pub async fn connect<D>(dst: D, address: Address) -> iqkms::Result<Self>
+    where
+        D: TryInto<tonic::transport::Endpoint>,
+        D::Error: Into<StdError>,
+    {
+        let client = iqkms::ethereum::SignerClient::connect(dst).await?;
+        Ok(Self::new(client, address))
+    }
+
+    /// Create a new signer from the given `iqkms` gRPC client.
+    pub fn new(client: iqkms::ethereum::SignerClient, address: Address) -> Self {
+        Self {
+            client: Arc::new(Mutex::new(client)),
+            address,
+            chain_id: None,
+        }
+    }
+
+    /// Sign the given digest using iqkms.
+    #[instrument(err)]
+    async fn sign_digest(&self, digest: &B256) -> iqkms::Result<Signature> {
+        let mut client = self.client.lock().await;
+        let signature = client
+            .sign_digest(self.iqkms_address(), H256::from(digest.0))
+            .await?;
+
+        let y_parity = match signature.v {
+            27 => false,
+            28 => true,
+            _ => return Err(malformed_signature()),
+        };
+
+        verify(
+            self.address,
+            digest,
+            parse_signature_proto(signature, y_parity)?,
+        )
+    }
+
+    /// Sign the given unsigned transaction (i.e. the payload whose hash is
+    /// signed) using iqkms, which checks it against its chain registry and
+    /// policy.
+    #[instrument(err, skip(unsigned_tx))]
+    async fn sign_unsigned_transaction(
+        &self,
+        unsigned_tx: &[u8],
+        signature_hash: &B256,
+    ) -> iqkms::Result<Signature> {
+        let tx = Transaction::decode_for_signing(unsigned_tx)
+            .map_err(|_| tonic::Status::invalid_argument("unsupported transaction"))?;
+
+        let mut client = self.client.lock().await;
+        let (signature, _) = client.sign_transaction(self.iqkms_address(), &tx).await?;
+
+        let y_parity = match signature.v {
+            0 => false,
+            1 => true,
+            _ => return Err(malformed_signature()),
+        };
+
+        verify(
+            self.address,
+            signature_hash,
+            parse_signature_proto(signature, y_parity)?,
+        )
+    }
+
+    /// Get the address of the signing key as an iqkms address.
+    fn iqkms_address(&self) -> types::Address {
+        types::Address::from(self.address.into_array())
+    }
+}
+
+/// Parse a signature returned by iqkms with the given `y_parity` (decoded
+/// from its `v` value).
+fn parse_signature_proto(
+    signature: proto::ethereum::Signature,
+    y_parity: bool,
+) -> iqkms::Result<Signature> {
+    if signature.r.len() == 32 && signature.s.len() == 32 {
+        Ok(Signature::new(
+            U256::from_be_slice(&signature.r),
+            U256::from_be_slice(&signature.s),
+            y_parity,
+        ))
+    } else {
+        Err(malformed_signature())
+    }
+}
+
+/// Ensure the given signature over the given hash recovers to the given
+/// address.
+fn verify(address: Address, hash: &B256, signature: Signature) -> iqkms::Result<Signature> {
+    let recovered = signature
+        .recover_address_from_prehash(hash)
+        .map_err(|_| malformed_signature())?;
+
+    if recovered == address {
+        Ok(signature)
+    } else {
+        Err(tonic::Status::internal(format!(
+            "signature returned by iqkms recovers to {recovered}, expected {address}"
+        ))
+        .into())
+    }
+}
+
+/// Error returned when iqkms responds with a malformed signature.
+fn malformed_signature() -> iqkms::Error {
+    tonic::Status::internal("malformed signature returned by iqkms").into()
+}
+
+#[async_trait::async_trait]
+impl Signer for IqkmsSigner {
+    #[instrument(err)]
+    async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+        self.sign_digest(hash)
+            .await
+            .map_err(alloy_signer::Error::other)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id
+    }
+
+    fn set_chain_id(&mut self, chain_id: Option<ChainId>) {
+        self.chain_id = chain_id;
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for IqkmsSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    #[instrument(err, skip(tx))]
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        if let Some(chain_id) = self.chain_id
+            && !tx.set_chain_id_checked(chain_id)
+        {
+            return Err(alloy_signer::Error::TransactionChainIdMismatch {
+                signer: chain_id,
+                tx: tx.chain_id().unwrap_or_default(),
+            });
+        }
+
+        let mut unsigned_tx = Vec::new();
+        tx.encode_for_signing(&mut unsigned_tx);
+
+        self.sign_unsigned_transaction(&unsigned_tx, &tx.signature_hash())
+            .await
+            .map_err(alloy_signer::Error::other)
+    }
+}
+
+impl fmt::Debug for IqkmsSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IqkmsSigner")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_signature_proto, verify};
+    use alloy_primitives::{Address, B256, address, b256, hex};
+    use iqkms::proto;
+
+    /// Address of the signing key from the EIP-155 example.
+    const ADDRESS: Address = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+
+    /// Signing hash from the EIP-155 example.
+    const HASH: B256 = b256!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53");
+
+    /// Signature from the EIP-155 example.
+    fn eip155_signature() -> proto::ethereum::Signature {
+        proto::ethereum::Signature {
+            r: hex!("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276").to_vec(),
+            s: hex!("67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").to_vec(),
+            v: 0,
+        }
+    }
+
+    #[test]
+    fn verify_signature() {
+        let signature = parse_signature_proto(eip155_signature(), false).unwrap();
+        assert!(verify(ADDRESS, &HASH, signature).is_ok());
+
+        let other = address!("27b1fdb04752bbc536007a920d24acb045561c26");
+        assert!(verify(other, &HASH, signature).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Integration tests which run against an in-process iqkms gRPC service.
+
+#![allow(clippy::unwrap_used)]
+
+use alloy_consensus::{SignableTransaction, TxEip1559, TxLegacy};
+use alloy_iqkms::{IqkmsSigner, Signer};
+use alloy_network::TxSigner;
+use alloy_primitives::{Address, TxKind, U256, address, eip191_hash_message};
+use alloy_sol_types::{SolStruct, eip712_domain, sol};
+use signing::{SigningKey, SigningService, signature::ecdsa::secp256k1};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+
+/// Signing key used in tests (from the EIP-155 example).
+const SIGNING_KEY: [u8; 32] = [0x46; 32];
+
+/// Address of the test signing key.
+const ADDRESS: Address = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+
+sol! {
+    struct Mail {
+        address from;
+        address to;
+        string contents;
+    }
+}
+
+/// Spawn an iqkms gRPC service on an ephemeral port and connect to it.
+async fn signer() -> IqkmsSigner {
+    signer_with_policy(ethereum::Policy::default()).await
+}
+
+/// Spawn an iqkms gRPC service with the given policy on an ephemeral port and
+/// connect to it.
+async fn signer_with_policy(policy: ethereum::Policy) -> IqkmsSigner {
+    let mut signing_service = SigningService::new();
+    let signing_key = secp256k1::SigningKey::from_bytes(&SIGNING_KEY).unwrap();
+    signing_service
+        .add_key(SigningKey::from(signing_key))
+        .unwrap();
+
+    let signing_service = tower::ServiceBuilder::new()
+        .buffer(1)
+        .service(signing_service);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(ethereum::SignerServer::new(
+                ethereum::SignerService::new(signing_service).with_policy(policy),
+            ))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    IqkmsSigner::connect(format!("http://{addr}"), ADDRESS)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn sign_message() {
+    let signer = signer().await;
+    let msg = b"hello";
+    let signature = signer.sign_message(msg).await.unwrap();
+
+    let recovered = signature
+        .recover_address_from_prehash(&eip191_hash_message(msg))
+        .unwrap();
+
+    assert_eq!(recovered, ADDRESS);
+}
+
+#[tokio::test]
+async fn sign_typed_data() {
+    let signer = signer().await;
+    let domain = eip712_domain! {
+        name: "Ether Mail",
+        version: "1",
+        chain_id: 1,
+    };
+
+    let mail = Mail {
+        from: ADDRESS,
+        to: Address::repeat_byte(0x35),
+        contents: "Hello, Bob!".to_owned(),
+    };
+
+    let signature = signer.sign_typed_data(&mail, &domain).await.unwrap();
+    let recovered = signature
+        .recover_address_from_prehash(&mail.eip712_signing_hash(&domain))
+        .unwrap();
+
+    assert_eq!(recovered, ADDRESS);
+}
+
+/// Legacy transaction from the EIP-155 example.
+#[tokio::test]
+async fn sign_eip155_transaction() {
+    let signer = signer().await.with_chain_id(Some(1));
+
+    let mut tx = TxLegacy {
+        chain_id: None,
+        nonce: 9,
+        gas_price: 20_000_000_000,
+        gas_limit: 21000,
+        to: TxKind::Call(Address::repeat_byte(0x35)),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        input: Default::default(),
+    };
+
+    let signature = signer.sign_transaction(&mut tx).await.unwrap();
+    assert_eq!(tx.chain_id, Some(1));
+
+    let signed = tx.into_signed(signature);
+    let mut encoded = Vec::new();
+    signed.rlp_encode(&mut encoded);
+
+    assert_eq!(
+        alloy_primitives::hex::encode(encoded),
+        "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+    );
+}
+
+#[tokio::test]
+async fn sign_eip1559_transaction() {
+    let signer = signer().await;
+
+    let mut tx = TxEip1559 {
+        chain_id: 1,
+        nonce: 0,
+        gas_limit: 21000,
+        max_fee_per_gas: 40_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(Address::repeat_byte(0x35)),
+        value: U256::from(1u64),
+        ..Default::default()
+    };
+
+    let signature = signer.sign_transaction(&mut tx).await.unwrap();
+    let recovered = signature
+        .recover_address_from_prehash(&tx.signature_hash())
+        .unwrap();
+
+    assert_eq!(recovered, ADDRESS);
+}
+
+#[tokio::test]
+async fn chain_id_mismatch() {
+    let signer = signer().await.with_chain_id(Some(1));
+
+    let mut tx = TxEip1559 {
+        chain_id: 5,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        signer.sign_transaction(&mut tx).await,
+        Err(alloy_signer::Error::TransactionChainIdMismatch { signer: 1, tx: 5 })
+    ));
+}
+
+/// Transactions are checked against the policy by iqkms rather than being
+/// signed as raw digests, so they can be signed even when unchecked signing
+/// is denied.
+#[tokio::test]
+async fn sign_transaction_with_unchecked_signing_denied() {
+    let signer = signer_with_policy(ethereum::Policy::new().deny_unchecked_signing()).await;
+    assert!(signer.sign_message(b"hello").await.is_err());
+
+    let mut tx = TxEip1559 {
+        chain_id: 1,
+        nonce: 0,
+        gas_limit: 21000,
+        max_fee_per_gas: 40_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(Address::repeat_byte(0x35)),
+        value: U256::from(1u64),
+        ..Default::default()
+    };
+
+    let signature = signer.sign_transaction(&mut tx).await.unwrap();
+    let recovered = signature
+        .recover_address_from_prehash(&tx.signature_hash())
+        .unwrap();
+
+    assert_eq!(recovered, ADDRESS);
+}
@@ -3,18 +3,32 @@
 #![cfg(feature = "async")]
 
 use crate::{
-    Error, MAX_MSG_LEN, PublicKey, Result, ed25519,
+    Error, Identity, MAX_MSG_LEN, NodeInfo, PublicKey, Result,
     encryption::{Frame, RecvState, SendState},
     handshake, proto,
     traits::{AsyncReadMsg, AsyncWriteMsg},
 };
-use ed25519_dalek::Signer;
-use prost::Message;
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
+use prost::{DecodeError, Message};
+use std::{
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
+use tokio::{
+    io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf, ReadHalf, WriteHalf},
+    time::Sleep,
+};
 
 #[cfg(doc)]
 use crate::IdentitySecret;
 
+#[cfg(feature = "codec")]
+use {
+    crate::ProtoCodec,
+    tokio_util::codec::{Framed, FramedRead, FramedWrite},
+};
+
 /// Encrypted connection between peers in a CometBFT network, implemented using asynchronous I/O
 /// provided by the Tokio async runtime.
 pub struct AsyncSecretConnection<Io> {
@@ -26,16 +40,16 @@ pub struct AsyncSecretConnection<Io> {
     /// cipher state.
     writer: AsyncSecretWriter<Io>,
 
-    /// Our identity's Ed25519 public key.
+    /// Our identity's public key.
     local_public_key: PublicKey,
 
-    /// Remote peer's Ed25519 public key.
+    /// Remote peer's public key.
     peer_public_key: PublicKey,
 }
 
 impl<Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin> AsyncSecretConnection<Io> {
     /// Performs a handshake and returns a new `AsyncSecretConnection`, authenticating ourselves
-    /// with the provided `Identity` (Ed25519 signing key).
+    /// with the provided [`Identity`] (Ed25519 or secp256k1 signing key).
     ///
     /// The [`IdentitySecret`] type can be used as an `identity_key`.
     ///
@@ -45,13 +59,12 @@ impl<Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin> AsyncSecretConnecti
     /// - if sharing of the signature fails
     /// - if receiving the signature fails
     /// - if verifying the signature fails
-    pub async fn new<Identity>(io: Io, identity_key: &Identity) -> Result<Self>
+    pub async fn new<I>(io: Io, identity_key: &I) -> Result<Self>
     where
-        Identity: Signer<ed25519::Signature>,
-        ed25519::VerifyingKey: for<'a> From<&'a Identity>,
+        I: Identity + ?Sized,
     {
         // Start a handshake process, generating a local ephemeral X25519 public key.
-        let local_public_key: PublicKey = ed25519::VerifyingKey::from(identity_key).into();
+        let local_public_key = identity_key.public_key();
         let (mut initial_state, initial_message) = handshake::InitialState::new();
         let (mut io_read, mut io_write) = io::split(io);
 
@@ -72,19 +85,28 @@ impl<Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin> AsyncSecretConnecti
         let mut reader = AsyncSecretReader {
             io: io_read,
             recv_state: cipher_state.recv_state,
+            max_msg_len: MAX_MSG_LEN,
+            read_timeout: IdleTimeout::default(),
+            ciphertext: Box::new([0u8; Frame::ENCRYPTED_SIZE]),
+            ciphertext_len: 0,
+            plaintext: Vec::with_capacity(Frame::MAX_PLAINTEXT_SIZE),
+            plaintext_pos: 0,
         };
         let mut writer = AsyncSecretWriter {
             io: io_write,
             send_state: cipher_state.send_state,
+            write_timeout: IdleTimeout::default(),
+            ciphertext: Vec::with_capacity(Frame::ENCRYPTED_SIZE),
+            ciphertext_pos: 0,
         };
 
-        // Send our identity's Ed25519 public key and signature over the transcript to the peer.
+        // Send our identity's public key and signature over the transcript to the peer.
         let write_future = writer.write_msg(proto::p2p::AuthSigMessage {
             pub_key: Some(local_public_key.into()),
-            sig: challenge.sign_challenge(identity_key).to_vec(),
+            sig: challenge.sign_challenge(identity_key)?,
         });
 
-        // Read the peer's Ed25519 public key and use it to verify their signature over the
+        // Read the peer's public key and use it to verify their signature over the
         // handshake transcript.
         let read_future = reader.read_msg::<proto::p2p::AuthSigMessage>();
         let (peer_auth_sig_msg, _) = tokio::try_join!(read_future, write_future)?;
@@ -102,6 +124,26 @@ impl<Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin> AsyncSecretConnecti
     }
 }
 
+impl<Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin> AsyncSecretConnection<Io> {
+    /// Send our [`NodeInfo`] to the remote peer and simultaneously receive theirs, as CometBFT
+    /// nodes do immediately after establishing a connection.
+    ///
+    /// # Errors
+    ///
+    /// - if the peer's node info is malformed
+    /// - if the peer's node ID doesn't match their authenticated peer ID
+    /// - if the peer's node info is incompatible with ours (see [`NodeInfo::check_compatible`])
+    pub async fn exchange_node_info(&mut self, node_info: &NodeInfo) -> Result<NodeInfo> {
+        let write_future = self
+            .writer
+            .write_msg(proto::p2p::DefaultNodeInfo::from(node_info));
+        let read_future = self.reader.read_msg::<proto::p2p::DefaultNodeInfo>();
+        let (peer_node_info, _) = tokio::try_join!(read_future, write_future)?;
+
+        node_info.accept_peer(&self.peer_public_key, peer_node_info)
+    }
+}
+
 impl<Io> AsyncSecretConnection<Io> {
     /// Get the local (i.e. our) [`PublicKey`].
     pub fn local_public_key(&self) -> &PublicKey {
@@ -118,6 +160,31 @@ impl<Io> AsyncSecretConnection<Io> {
     pub fn split(self) -> (AsyncSecretReader<Io>, AsyncSecretWriter<Io>) {
         (self.reader, self.writer)
     }
+
+    /// Set the maximum length of messages which can be read.
+    pub(crate) fn set_max_msg_len(&mut self, max_msg_len: usize) {
+        self.reader.max_msg_len = max_msg_len;
+    }
+
+    /// Set how long reading or writing a frame may take before timing out.
+    pub(crate) fn set_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) {
+        self.reader.read_timeout = IdleTimeout::new(read_timeout);
+        self.writer.write_timeout = IdleTimeout::new(write_timeout);
+    }
+}
+
+#[cfg(feature = "codec")]
+impl<Io: AsyncRead + AsyncWrite> AsyncSecretConnection<Io> {
+    /// Wrap this connection in a [`Framed`] object which is a `Stream` and `Sink` of Protobuf
+    /// messages of type `M`, using a [`ProtoCodec`] with this connection's maximum message length.
+    pub fn framed<M: Message + Default>(self) -> Framed<Self, ProtoCodec<M>> {
+        let codec = ProtoCodec::new().with_max_msg_len(self.reader.max_msg_len);
+        Framed::new(self, codec)
+    }
 }
 
 impl<Io: AsyncReadExt + Send + Sync + Unpin> AsyncReadMsg for AsyncSecretConnection<Io> {
@@ -134,54 +201,181 @@ impl<Io: AsyncWriteExt + Send + Sync + Unpin> AsyncWriteMsg for AsyncSecretConne
     }
 }
 
+impl<Io: AsyncRead> AsyncRead for AsyncSecretConnection<Io> {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().reader).poll_read(cx, buf)
+    }
+}
+
+impl<Io: AsyncWrite> AsyncWrite for AsyncSecretConnection<Io> {
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().writer).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_shutdown(cx)
+    }
+}
+
 /// Async encrypted message reader type which wraps the read-half of an underlying I/O object.
+///
+/// Besides the message-oriented [`AsyncReadMsg`] trait, it impls [`AsyncRead`], which reads the
+/// decrypted contents of frames as a byte stream.
 pub struct AsyncSecretReader<Io> {
     /// Inner async I/O reader object this connection type wraps.
     io: ReadHalf<Io>,
 
     /// Symmetric cipher state including the current nonce.
     recv_state: RecvState,
+
+    /// Maximum length of a message.
+    max_msg_len: usize,
+
+    /// How long reading may make no progress before timing out.
+    read_timeout: IdleTimeout,
+
+    /// Ciphertext of the frame currently being read.
+    ciphertext: Box<[u8; Frame::ENCRYPTED_SIZE]>,
+
+    /// Number of bytes of the current frame's ciphertext read so far.
+    ciphertext_len: usize,
+
+    /// Plaintext of the most recently decrypted frame.
+    plaintext: Vec<u8>,
+
+    /// Number of bytes of the plaintext which have already been consumed.
+    plaintext_pos: usize,
 }
 
-impl<Io: AsyncReadExt + Send + Sync + Unpin> AsyncSecretReader<Io> {
-    /// Read and decrypt a frame from the network.
-    #[inline]
-    async fn read_frame(&mut self) -> Result<Frame> {
-        let mut bytes = [0u8; Frame::ENCRYPTED_SIZE];
-        self.io.read_exact(&mut bytes).await?;
+impl<Io: AsyncRead> AsyncSecretReader<Io> {
+    /// Poll to read decrypted data into the given buffer, reading and decrypting the next frame
+    /// once all previously decrypted data has been consumed.
+    ///
+    /// Returns the number of bytes read, which is zero if the connection was closed.
+    fn poll_read_plaintext(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        // Frames may be empty, so keep reading until we have data
+        while self.plaintext_pos == self.plaintext.len() {
+            if !ready!(self.poll_read_frame(cx))? {
+                return Poll::Ready(Ok(0));
+            }
+        }
 
-        let mut frame = Frame::from_ciphertext(bytes);
+        let plaintext = &self.plaintext[self.plaintext_pos..];
+        let len = plaintext.len().min(buf.len());
+        buf[..len].copy_from_slice(&plaintext[..len]);
+        self.plaintext_pos += len;
+        Poll::Ready(Ok(len))
+    }
+
+    /// Poll to read and decrypt the next frame, keeping track of partially read ciphertext across
+    /// calls.
+    ///
+    /// Returns `false` if the connection was closed cleanly at a frame boundary.
+    fn poll_read_frame(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        while self.ciphertext_len < Frame::ENCRYPTED_SIZE {
+            let mut buf = ReadBuf::new(&mut self.ciphertext[self.ciphertext_len..]);
+
+            match Pin::new(&mut self.io).poll_read(cx, &mut buf) {
+                Poll::Ready(result) => {
+                    self.read_timeout.reset();
+                    result?;
+                }
+                Poll::Pending => return self.read_timeout.poll_elapsed(cx).map(|e| Err(e.into())),
+            }
+
+            match buf.filled().len() {
+                0 if self.ciphertext_len == 0 => return Poll::Ready(Ok(false)),
+                0 => return Poll::Ready(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())),
+                len => self.ciphertext_len += len,
+            }
+        }
+
+        self.ciphertext_len = 0;
+        let mut frame = Frame::from_ciphertext(*self.ciphertext);
         self.recv_state.decrypt_frame(&mut frame)?;
-        Ok(frame)
+
+        self.plaintext.clear();
+        self.plaintext.extend_from_slice(frame.plaintext()?);
+        self.plaintext_pos = 0;
+        Poll::Ready(Ok(true))
+    }
+
+    /// Read decrypted data until the given buffer has been filled.
+    async fn read_plaintext_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut filled = 0;
+
+        while filled < buf.len() {
+            match poll_fn(|cx| self.poll_read_plaintext(cx, &mut buf[filled..])).await? {
+                0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                len => filled += len,
+            }
+        }
+
+        Ok(())
     }
 
     /// Read and decrypt a message `M` from the underlying I/O object.
     ///
     /// Core implementation of the `AsyncReadMsg` trait, written as an `async fn` for simplicity.
     async fn _read_msg<M: Message + Default>(&mut self) -> Result<M> {
-        let frame = self.read_frame().await?;
-        let frame_plaintext = frame.plaintext()?;
+        // Read the length prefix on the proto one byte at a time, since it's variable-sized and
+        // may span frames
+        let mut prefix = [0u8; proto::MAX_VARINT_LEN];
+        let mut prefix_len = 0;
+
+        loop {
+            if prefix_len == prefix.len() {
+                return Err(DecodeError::new("invalid varint").into());
+            }
+
+            self.read_plaintext_exact(&mut prefix[prefix_len..=prefix_len])
+                .await?;
+            prefix_len += 1;
+
+            if prefix[prefix_len - 1] & 0x80 == 0 {
+                break;
+            }
+        }
 
-        // Decode the length prefix on the proto
-        let msg_len = proto::decode_length_delimiter_inclusive(frame_plaintext)?;
+        let msg_len = proto::decode_length_delimiter_inclusive(&prefix[..prefix_len])?;
 
-        if msg_len > MAX_MSG_LEN {
+        if msg_len > self.max_msg_len {
             return Err(Error::MessageSize { size: msg_len });
         }
 
-        // Skip the heap if the proto fits in a single message frame
-        if frame_plaintext.len() == msg_len {
-            return Ok(M::decode_length_delimited(frame_plaintext)?);
-        }
-
-        let mut msg = Vec::with_capacity(msg_len);
-        msg.extend_from_slice(frame_plaintext);
-
-        while msg.len() < msg_len {
-            msg.extend_from_slice(self.read_frame().await?.plaintext()?);
-        }
+        let mut msg = vec![0u8; msg_len - prefix_len];
+        self.read_plaintext_exact(&mut msg).await?;
+        Ok(M::decode(msg.as_slice())?)
+    }
+}
 
-        Ok(M::decode_length_delimited(msg.as_slice())?)
+#[cfg(feature = "codec")]
+impl<Io: AsyncRead> AsyncSecretReader<Io> {
+    /// Wrap this reader in a [`FramedRead`] object which is a `Stream` of Protobuf messages of
+    /// type `M`, using a [`ProtoCodec`] with this reader's maximum message length.
+    pub fn framed<M: Message + Default>(self) -> FramedRead<Self, ProtoCodec<M>> {
+        let codec = ProtoCodec::new().with_max_msg_len(self.max_msg_len);
+        FramedRead::new(self, codec)
     }
 }
 
@@ -192,35 +386,129 @@ impl<Io: AsyncReadExt + Send + Sync + Unpin> AsyncReadMsg for AsyncSecretReader<
     }
 }
 
+impl<Io: AsyncRead> AsyncRead for AsyncSecretReader<Io> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let len = ready!(
+            self.get_mut()
+                .poll_read_plaintext(cx, buf.initialize_unfilled())
+        )?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
 /// Async encrypted message writer type which wraps the write-half of an underlying I/O object.
+///
+/// Besides the message-oriented [`AsyncWriteMsg`] trait, it impls [`AsyncWrite`], which encrypts
+/// each write into as many frames as necessary. Small writes are not coalesced, so consider
+/// wrapping it in a [`tokio::io::BufWriter`].
 pub struct AsyncSecretWriter<Io> {
     /// Inner async I/O writer object this connection type wraps.
     io: WriteHalf<Io>,
 
     /// Symmetric cipher state including the current nonce.
     send_state: SendState,
+
+    /// How long writing may make no progress before timing out.
+    write_timeout: IdleTimeout,
+
+    /// Ciphertext of the most recently encrypted frame.
+    ciphertext: Vec<u8>,
+
+    /// Number of bytes of the ciphertext which have already been written.
+    ciphertext_pos: usize,
 }
 
-impl<Io: AsyncWriteExt + Send + Sync + Unpin> AsyncSecretWriter<Io> {
-    /// Encrypt and write a frame to the network.
-    #[inline]
-    async fn write_frame(&mut self, plaintext: &[u8]) -> Result<()> {
-        let mut frame = Frame::from_plaintext(plaintext)?;
+impl<Io: AsyncWrite> AsyncSecretWriter<Io> {
+    /// Poll to encrypt up to one frame's worth of the given data, returning the number of bytes
+    /// which were encrypted.
+    ///
+    /// The resulting frame is written by subsequent calls, or by [`Self::poll_flush_frames`].
+    fn poll_write_plaintext(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        ready!(self.poll_write_ciphertext(cx))?;
+
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let len = buf.len().min(Frame::MAX_PLAINTEXT_SIZE);
+        let mut frame = Frame::from_plaintext(&buf[..len])?;
         self.send_state.encrypt_frame(&mut frame)?;
-        Ok(self.io.write_all(frame.ciphertext()?).await?)
+
+        self.ciphertext.clear();
+        self.ciphertext.extend_from_slice(frame.ciphertext()?);
+        self.ciphertext_pos = 0;
+
+        // The data has been accepted at this point, but try to start writing it right away
+        if let Poll::Ready(Err(e)) = self.poll_write_ciphertext(cx) {
+            return Poll::Ready(Err(e));
+        }
+
+        Poll::Ready(Ok(len))
+    }
+
+    /// Poll to write the remaining ciphertext of the most recently encrypted frame, keeping track
+    /// of partially written ciphertext across calls.
+    fn poll_write_ciphertext(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while self.ciphertext_pos < self.ciphertext.len() {
+            let ciphertext = &self.ciphertext[self.ciphertext_pos..];
+
+            match Pin::new(&mut self.io).poll_write(cx, ciphertext) {
+                Poll::Ready(result) => {
+                    self.write_timeout.reset();
+
+                    match result? {
+                        0 => {
+                            return Poll::Ready(Err(
+                                io::Error::from(io::ErrorKind::WriteZero).into()
+                            ));
+                        }
+                        len => self.ciphertext_pos += len,
+                    }
+                }
+                Poll::Pending => return self.write_timeout.poll_elapsed(cx).map(|e| Err(e.into())),
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Poll to write all encrypted frames and flush the underlying I/O object.
+    fn poll_flush_frames(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_write_ciphertext(cx))?;
+        Poll::Ready(Ok(ready!(Pin::new(&mut self.io).poll_flush(cx))?))
+    }
+
+    /// Encrypt and write the given bytes to the underlying I/O object, splitting them across as
+    /// many frames as necessary.
+    pub(crate) async fn write_bytes(&mut self, mut bytes: &[u8]) -> Result<()> {
+        while !bytes.is_empty() {
+            let len = poll_fn(|cx| self.poll_write_plaintext(cx, bytes)).await?;
+            bytes = &bytes[len..];
+        }
+
+        poll_fn(|cx| self.poll_flush_frames(cx)).await
     }
 
     /// Encrypt and write a message `M` to the underlying I/O object.
     ///
     /// Core implementation of the `AsyncWriteMsg` trait, written as an `async fn` for simplicity.
     async fn _write_msg<M: Message>(&mut self, msg: M) -> Result<()> {
-        let bytes = msg.encode_length_delimited_to_vec();
-
-        for chunk in bytes.chunks(Frame::MAX_PLAINTEXT_SIZE) {
-            self.write_frame(chunk).await?;
-        }
+        self.write_bytes(&msg.encode_length_delimited_to_vec())
+            .await
+    }
+}
 
-        Ok(())
+#[cfg(feature = "codec")]
+impl<Io: AsyncWrite> AsyncSecretWriter<Io> {
+    /// Wrap this writer in a [`FramedWrite`] object which is a `Sink` of Protobuf messages of
+    /// type `M`.
+    pub fn framed<M: Message>(self) -> FramedWrite<Self, ProtoCodec<M>> {
+        FramedWrite::new(self, ProtoCodec::new())
     }
 }
 
@@ -231,6 +519,67 @@ impl<Io: AsyncWriteExt + Send + Sync + Unpin> AsyncWriteMsg for AsyncSecretWrite
     }
 }
 
+impl<Io: AsyncWrite> AsyncWrite for AsyncSecretWriter<Io> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(ready!(self.get_mut().poll_write_plaintext(cx, buf))?))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(ready!(self.get_mut().poll_flush_frames(cx))?))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_ciphertext(cx))?;
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+/// Timeout for I/O operations which haven't made any progress for a given duration.
+#[derive(Debug, Default)]
+struct IdleTimeout {
+    /// How long an operation may make no progress, if there's a timeout at all.
+    duration: Option<Duration>,
+
+    /// Timer which started when the operation stopped making progress.
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl IdleTimeout {
+    /// Create a new timeout with the given duration.
+    fn new(duration: Option<Duration>) -> Self {
+        Self {
+            duration,
+            sleep: None,
+        }
+    }
+
+    /// Reset the timer after the operation has made progress.
+    fn reset(&mut self) {
+        self.sleep = None;
+    }
+
+    /// Poll the timer while the operation is pending, returning an error once the timeout has
+    /// elapsed.
+    fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> Poll<io::Error> {
+        let Some(duration) = self.duration else {
+            return Poll::Pending;
+        };
+
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+
+        ready!(sleep.as_mut().poll(cx));
+        self.sleep = None;
+        Poll::Ready(io::ErrorKind::TimedOut.into())
+    }
+}
+
 /// Read the `handshake::InitialMessage` from the underlying `Io` object.
 async fn read_initial_msg<Io: AsyncReadExt + Unpin>(
     io: &mut Io,
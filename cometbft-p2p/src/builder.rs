@@ -0,0 +1,189 @@
+//! Secret Connection builder.
+
+use crate::{
+    Error, Identity, MAX_MSG_LEN, PeerId, PublicKey, Result, SecretConnection,
+    traits::{TimeoutIo, TryCloneIo},
+};
+use std::{
+    collections::BTreeSet,
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+#[cfg(feature = "async")]
+use {
+    crate::AsyncSecretConnection,
+    tokio::io::{AsyncReadExt, AsyncWriteExt},
+};
+
+/// Builder for [`SecretConnection`] and [`AsyncSecretConnection`] which configures timeouts and
+/// message size limits, and which authorizes the remote peer as part of the handshake.
+///
+/// By default there are no timeouts, messages are limited to [`MAX_MSG_LEN`], and any peer is
+/// accepted.
+#[derive(Clone, Debug)]
+pub struct SecretConnectionBuilder {
+    /// How long the handshake may take.
+    handshake_timeout: Option<Duration>,
+
+    /// How long reads may block after the handshake.
+    read_timeout: Option<Duration>,
+
+    /// How long writes may block after the handshake.
+    write_timeout: Option<Duration>,
+
+    /// Maximum length of a message.
+    max_msg_len: usize,
+
+    /// Peer ID the remote peer must have.
+    expected_peer_id: Option<PeerId>,
+
+    /// Peer IDs the remote peer must be one of.
+    allowed_peers: Option<BTreeSet<PeerId>>,
+}
+
+impl SecretConnectionBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            handshake_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            max_msg_len: MAX_MSG_LEN,
+            expected_peer_id: None,
+            allowed_peers: None,
+        }
+    }
+
+    /// Fail the handshake if it doesn't complete within the given timeout.
+    ///
+    /// For [`SecretConnection`] this bounds each blocking read and write during the handshake.
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail reads from an idle connection after the given timeout.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail writes to a stalled connection after the given timeout.
+    pub fn with_write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum length of messages which can be read.
+    pub fn with_max_msg_len(mut self, max_msg_len: usize) -> Self {
+        self.max_msg_len = max_msg_len;
+        self
+    }
+
+    /// Require the remote peer to have the given peer ID.
+    pub fn with_expected_peer_id(mut self, peer_id: PeerId) -> Self {
+        self.expected_peer_id = Some(peer_id);
+        self
+    }
+
+    /// Only accept remote peers with one of the given peer IDs.
+    pub fn with_allowed_peers(mut self, peer_ids: impl IntoIterator<Item = PeerId>) -> Self {
+        self.allowed_peers = Some(peer_ids.into_iter().collect());
+        self
+    }
+
+    /// Perform a handshake over the given I/O object, authenticating ourselves with the provided
+    /// [`Identity`], and return a new [`SecretConnection`] if the remote peer is authorized.
+    ///
+    /// # Errors
+    ///
+    /// - if the handshake fails or times out
+    /// - if the remote peer doesn't have the expected peer ID or isn't in the allowlist
+    pub fn build<Io, I>(&self, io: Io, identity_key: &I) -> Result<SecretConnection<Io>>
+    where
+        Io: Read + Write + Send + Sync + TryCloneIo + TimeoutIo,
+        I: Identity + ?Sized,
+    {
+        // Timeouts are a property of the underlying socket, which is shared with its clones
+        let timeouts = io.try_clone()?;
+        timeouts.set_read_timeout(self.handshake_timeout)?;
+        timeouts.set_write_timeout(self.handshake_timeout)?;
+
+        let mut conn = SecretConnection::new(io, identity_key).map_err(|err| match err {
+            Error::Io(err)
+                if self.handshake_timeout.is_some()
+                    && matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+            {
+                Error::HandshakeTimeout
+            }
+            err => err,
+        })?;
+
+        self.authorize_peer(conn.peer_public_key())?;
+        timeouts.set_read_timeout(self.read_timeout)?;
+        timeouts.set_write_timeout(self.write_timeout)?;
+        conn.set_max_msg_len(self.max_msg_len);
+        Ok(conn)
+    }
+
+    /// Perform a handshake over the given async I/O object, authenticating ourselves with the
+    /// provided [`Identity`], and return a new [`AsyncSecretConnection`] if the remote peer is
+    /// authorized.
+    ///
+    /// # Errors
+    ///
+    /// - if the handshake fails or times out
+    /// - if the remote peer doesn't have the expected peer ID or isn't in the allowlist
+    #[cfg(feature = "async")]
+    pub async fn build_async<Io, I>(
+        &self,
+        io: Io,
+        identity_key: &I,
+    ) -> Result<AsyncSecretConnection<Io>>
+    where
+        Io: AsyncReadExt + AsyncWriteExt + Send + Sync + Unpin,
+        I: Identity + ?Sized,
+    {
+        let handshake = AsyncSecretConnection::new(io, identity_key);
+        let mut conn = match self.handshake_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, handshake)
+                .await
+                .map_err(|_| Error::HandshakeTimeout)??,
+            None => handshake.await?,
+        };
+
+        self.authorize_peer(conn.peer_public_key())?;
+        conn.set_timeouts(self.read_timeout, self.write_timeout);
+        conn.set_max_msg_len(self.max_msg_len);
+        Ok(conn)
+    }
+
+    /// Ensure the remote peer has the expected peer ID and is in the allowlist.
+    fn authorize_peer(&self, peer_public_key: &PublicKey) -> Result<()> {
+        let peer_id = peer_public_key.peer_id();
+
+        if let Some(expected_peer_id) = self.expected_peer_id {
+            peer_id.verify(expected_peer_id)?;
+        }
+
+        if let Some(allowed_peers) = &self.allowed_peers {
+            if !allowed_peers.contains(&peer_id) {
+                return Err(Error::PeerNotAllowed { peer_id });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SecretConnectionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// NOTE: tests are in `tests/secret_connection.rs` and `tests/async_secret_connection.rs`
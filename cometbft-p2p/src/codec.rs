@@ -0,0 +1,187 @@
+//! `tokio-util` codec for Protobuf messages sent over a Secret Connection.
+
+#![cfg(feature = "codec")]
+
+use crate::{Error, MAX_MSG_LEN, Result, proto};
+use bytes::BytesMut;
+use prost::{DecodeError, Message};
+use std::{fmt, marker::PhantomData};
+use tokio_util::codec::{Decoder, Encoder};
+
+#[cfg(doc)]
+use {
+    crate::{AsyncReadMsg, AsyncSecretConnection},
+    tokio_util::codec::{Framed, FramedRead, FramedWrite},
+};
+
+/// Codec for length-delimited Protobuf messages of type `M`, for use with [`Framed`],
+/// [`FramedRead`], and [`FramedWrite`].
+///
+/// Used with the byte-stream interface of [`AsyncSecretConnection`] and its halves, this provides
+/// a `Stream<Item = Result<M>>` and a `Sink<M>` of messages:
+///
+/// - Reads are cancellation safe: partially received messages are buffered by the framed object,
+///   so a future polling the stream (e.g. in a `select!`) can be dropped without losing data,
+///   unlike with [`AsyncReadMsg::read_msg`].
+/// - Writes apply backpressure: the sink only accepts another message once the data buffered by
+///   the framed object falls below its backpressure boundary, and messages are only guaranteed to
+///   be sent once the sink is flushed.
+pub struct ProtoCodec<M> {
+    /// Maximum length of a message, including its length prefix.
+    max_msg_len: usize,
+
+    /// Type of the messages being encoded and decoded.
+    msg: PhantomData<fn(M) -> M>,
+}
+
+impl<M> ProtoCodec<M> {
+    /// Create a new codec which accepts messages of up to [`MAX_MSG_LEN`].
+    pub fn new() -> Self {
+        Self {
+            max_msg_len: MAX_MSG_LEN,
+            msg: PhantomData,
+        }
+    }
+
+    /// Set the maximum length of messages which can be decoded.
+    pub fn with_max_msg_len(mut self, max_msg_len: usize) -> Self {
+        self.max_msg_len = max_msg_len;
+        self
+    }
+}
+
+impl<M: Message + Default> Decoder for ProtoCodec<M> {
+    type Item = M;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<M>> {
+        // Wait for the entire variable-sized length prefix to arrive
+        let prefix_len = match src
+            .iter()
+            .take(proto::MAX_VARINT_LEN)
+            .position(|byte| byte & 0x80 == 0)
+        {
+            Some(pos) => pos + 1,
+            None if src.len() >= proto::MAX_VARINT_LEN => {
+                return Err(DecodeError::new("invalid varint").into());
+            }
+            None => return Ok(None),
+        };
+
+        let msg_len = proto::decode_length_delimiter_inclusive(&src[..prefix_len])?;
+
+        if msg_len > self.max_msg_len {
+            return Err(Error::MessageSize { size: msg_len });
+        }
+
+        if src.len() < msg_len {
+            src.reserve(msg_len - src.len());
+            return Ok(None);
+        }
+
+        let msg = src.split_to(msg_len);
+        Ok(Some(M::decode(&msg[prefix_len..])?))
+    }
+}
+
+impl<M: Message> Encoder<M> for ProtoCodec<M> {
+    type Error = Error;
+
+    fn encode(&mut self, msg: M, dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(&msg.encode_length_delimited_to_vec());
+        Ok(())
+    }
+}
+
+impl<M> Clone for ProtoCodec<M> {
+    fn clone(&self) -> Self {
+        Self {
+            max_msg_len: self.max_msg_len,
+            msg: PhantomData,
+        }
+    }
+}
+
+impl<M> Default for ProtoCodec<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> fmt::Debug for ProtoCodec<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProtoCodec")
+            .field("max_msg_len", &self.max_msg_len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProtoCodec;
+    use crate::{Error, proto::p2p::PacketMsg};
+    use bytes::BytesMut;
+    use prost::Message;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    fn packet_msg(data: Vec<u8>) -> PacketMsg {
+        PacketMsg {
+            channel_id: 0x20,
+            eof: true,
+            data,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut codec = ProtoCodec::<PacketMsg>::new();
+        let msgs = [vec![], vec![0x42; 1], vec![0x24; 300]].map(packet_msg);
+
+        let mut buf = BytesMut::new();
+        for msg in &msgs {
+            codec.encode(msg.clone(), &mut buf).unwrap();
+        }
+
+        for msg in &msgs {
+            assert_eq!(codec.decode(&mut buf).unwrap().as_ref(), Some(msg));
+        }
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn partial_messages() {
+        let mut codec = ProtoCodec::<PacketMsg>::new();
+        let msg = packet_msg(vec![0x42; 300]);
+        let bytes = msg.encode_length_delimited_to_vec();
+
+        // Feed the message one byte at a time, including a multi-byte length prefix
+        let mut buf = BytesMut::new();
+        for byte in &bytes[..bytes.len() - 1] {
+            buf.extend_from_slice(&[*byte]);
+            assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        }
+
+        buf.extend_from_slice(&bytes[bytes.len() - 1..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(msg));
+    }
+
+    #[test]
+    fn message_too_large() {
+        let mut codec = ProtoCodec::<PacketMsg>::new().with_max_msg_len(100);
+        let mut buf = BytesMut::new();
+        codec.encode(packet_msg(vec![0x42; 100]), &mut buf).unwrap();
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(Error::MessageSize { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_varint() {
+        let mut codec = ProtoCodec::<PacketMsg>::new();
+        let mut buf = BytesMut::from(&[0xff; 10][..]);
+        assert!(matches!(codec.decode(&mut buf), Err(Error::Decode(_))));
+    }
+}
@@ -10,12 +10,24 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
+    /// Invalid channel descriptor (duplicate channel ID, zero priority, or zero capacity).
+    ChannelDescriptor {
+        /// ID of the channel.
+        channel_id: u8,
+    },
+
+    /// Connection has been closed.
+    Closed,
+
     /// Cryptographic errors
     Crypto(CryptoError),
 
     /// Protobuf decode message
     Decode(prost::DecodeError),
 
+    /// Handshake didn't complete within the configured timeout.
+    HandshakeTimeout,
+
     /// I/O error
     Io(std::io::Error),
 
@@ -25,6 +37,24 @@ pub enum Error {
         size: usize,
     },
 
+    /// Remote peer's node info is invalid or incompatible with ours.
+    NodeInfo(NodeInfoError),
+
+    /// Remote peer isn't in the configured allowlist.
+    PeerNotAllowed {
+        /// ID of the peer.
+        peer_id: PeerId,
+    },
+
+    /// Remote peer didn't respond to a ping in time.
+    PongTimeout,
+
+    /// Message sent or received on a channel which hasn't been configured.
+    UnknownChannel {
+        /// ID of the channel.
+        channel_id: i32,
+    },
+
     /// Failure to verify the remote peer ID.
     VerifyPeer(VerifyPeerError),
 }
@@ -32,10 +62,19 @@ pub enum Error {
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Self::ChannelDescriptor { channel_id } => {
+                write!(f, "invalid descriptor for channel {channel_id:#04x}")
+            }
+            Self::Closed => f.write_str("connection closed"),
             Self::Crypto(_) => f.write_str("cryptographic error"),
             Self::Decode(_) => f.write_str("malformed protocol message (version mismatch?)"),
+            Self::HandshakeTimeout => f.write_str("handshake timed out"),
             Self::Io(_) => f.write_str("I/O error"),
             Self::MessageSize { size } => write!(f, "unexpected message size ({size} bytes)"),
+            Self::NodeInfo(_) => f.write_str("node info handshake failed"),
+            Self::PeerNotAllowed { peer_id } => write!(f, "peer {peer_id} is not allowed"),
+            Self::PongTimeout => f.write_str("timed out waiting for pong from peer"),
+            Self::UnknownChannel { channel_id } => write!(f, "unknown channel {channel_id:#04x}"),
             Self::VerifyPeer(_) => f.write_str("peer verification failed"),
         }
     }
@@ -47,6 +86,7 @@ impl std::error::Error for Error {
             Self::Crypto(e) => Some(e),
             Self::Decode(e) => Some(e),
             Self::Io(e) => Some(e),
+            Self::NodeInfo(e) => Some(e),
             Self::VerifyPeer(e) => Some(e),
             _ => None,
         }
@@ -59,6 +99,12 @@ impl From<CryptoError> for Error {
     }
 }
 
+impl From<NodeInfoError> for Error {
+    fn from(err: NodeInfoError) -> Self {
+        Error::NodeInfo(err)
+    }
+}
+
 impl From<VerifyPeerError> for Error {
     fn from(err: VerifyPeerError) -> Self {
         Error::VerifyPeer(err)
@@ -83,6 +129,15 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(e) => e,
+            err => std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
 /// Opaque type for cryptographic errors which still internally tracks what went wrong for debugging
 /// purposes.
 ///
@@ -158,3 +213,58 @@ impl Display for VerifyPeerError {
 }
 
 impl std::error::Error for VerifyPeerError {}
+
+/// Node info handshake error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum NodeInfoError {
+    /// Peers are running different versions of the block protocol.
+    BlockVersion {
+        /// Our block protocol version.
+        local: u64,
+
+        /// Remote peer's block protocol version.
+        remote: u64,
+    },
+
+    /// Node info is malformed.
+    Malformed {
+        /// Reason why the node info is malformed.
+        reason: String,
+    },
+
+    /// Peers are on different networks (i.e. chains).
+    Network {
+        /// Our network.
+        local: String,
+
+        /// Remote peer's network.
+        remote: String,
+    },
+
+    /// Peers don't have any channels in common.
+    NoCommonChannels,
+
+    /// Remote peer has our node ID, i.e. we connected to ourselves.
+    SelfConnection,
+}
+
+impl Display for NodeInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlockVersion { local, remote } => write!(
+                f,
+                "peer is on a different block version: expected {local}, got {remote}"
+            ),
+            Self::Malformed { reason } => write!(f, "malformed node info: {reason}"),
+            Self::Network { local, remote } => write!(
+                f,
+                "peer is on a different network: expected \"{local}\", got \"{remote}\""
+            ),
+            Self::NoCommonChannels => f.write_str("peer has no common channels"),
+            Self::SelfConnection => f.write_str("connected to self"),
+        }
+    }
+}
+
+impl std::error::Error for NodeInfoError {}
@@ -2,17 +2,15 @@
 //!
 //! Performs an authenticated key exchange, first using ephemeral X25519 D-H to establish a shared
 //! symmetric key, then using Merlin to compute a transcript hash over the handshake and signing
-//! the result using Ed25519, and finally providing the signature in the handshake response message.
+//! the result using the node's identity key (Ed25519 or secp256k1), and finally providing the
+//! signature in the handshake response message.
 //!
 //! For more information, see the specification:
 //!
 //! <https://github.com/cometbft/cometbft/blob/015f455/spec/p2p/legacy-docs/peer.md#authenticated-encryption-handshake>
 
 use crate::{
-    CryptoError, EphemeralPublic, PublicKey, Result,
-    ed25519::{self, Signer},
-    encryption::CipherState,
-    kdf::Kdf,
+    CryptoError, EphemeralPublic, Identity, PublicKey, Result, encryption::CipherState, kdf::Kdf,
     proto,
 };
 use merlin::Transcript;
@@ -204,11 +202,14 @@ pub(crate) struct AwaitingResponse {
 
 impl AwaitingResponse {
     /// Sign the transcript challenge computed using Merlin.
-    pub fn sign_challenge<S>(&self, signer: &S) -> ed25519::Signature
+    ///
+    /// # Errors
+    /// - if the identity fails to produce a signature
+    pub fn sign_challenge<I>(&self, identity: &I) -> Result<Vec<u8>>
     where
-        S: Signer<ed25519::Signature>,
+        I: Identity + ?Sized,
     {
-        signer.sign(&self.transcript_hash)
+        identity.sign_challenge(&self.transcript_hash)
     }
 
     /// Returns a verified pubkey of the remote peer.
@@ -312,11 +313,11 @@ mod tests {
 
         let alice_sig = proto::p2p::AuthSigMessage {
             pub_key: Some(PublicKey::from(&alice_pk).into()),
-            sig: alice_challenge.sign_challenge(&alice_sk).to_vec(),
+            sig: alice_challenge.sign_challenge(&alice_sk).unwrap(),
         };
         let bob_sig = proto::p2p::AuthSigMessage {
             pub_key: Some(PublicKey::from(&bob_pk).into()),
-            sig: bob_challenge.sign_challenge(&bob_sk).to_vec(),
+            sig: bob_challenge.sign_challenge(&bob_sk).unwrap(),
         };
 
         assert_eq!(
@@ -335,6 +336,92 @@ mod tests {
         assert_eq!(bob_pk, bob_authenticated_pk.ed25519().unwrap());
     }
 
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn handshake_secp256k1() {
+        use crate::{
+            PeerId, Secp256k1IdentitySecret,
+            test_vectors::{
+                ALICE_SECP256K1_HANDSHAKE_SIG_MSG, ALICE_SECP256K1_PEER_ID, ALICE_SECP256K1_PK,
+                ALICE_SECP256K1_SK, BOB_SECP256K1_HANDSHAKE_SIG_MSG, BOB_SECP256K1_PEER_ID,
+                BOB_SECP256K1_PK, BOB_SECP256K1_SK,
+            },
+        };
+
+        let alice_sk = Secp256k1IdentitySecret::from_bytes(&ALICE_SECP256K1_SK).unwrap();
+        let bob_sk = Secp256k1IdentitySecret::from_bytes(&BOB_SECP256K1_SK).unwrap();
+
+        let alice_pk = PublicKey::from(&alice_sk);
+        let bob_pk = PublicKey::from(&bob_sk);
+
+        assert_eq!(
+            alice_pk,
+            PublicKey::from_raw_secp256k1(&ALICE_SECP256K1_PK).unwrap()
+        );
+        assert_eq!(
+            bob_pk,
+            PublicKey::from_raw_secp256k1(&BOB_SECP256K1_PK).unwrap()
+        );
+        assert_eq!(alice_pk.peer_id(), PeerId(ALICE_SECP256K1_PEER_ID));
+        assert_eq!(bob_pk.peer_id(), PeerId(BOB_SECP256K1_PEER_ID));
+
+        let (mut alice_hs, _) = InitialState::new_with_ephemeral_key(ALICE_X25519_SK);
+        let (mut bob_hs, _) = InitialState::new_with_ephemeral_key(BOB_X25519_SK);
+
+        let (alice_challenge, _alice_cs) = alice_hs.got_key(BOB_X25519_PK).unwrap();
+        let (bob_challenge, _bob_cs) = bob_hs.got_key(ALICE_X25519_PK).unwrap();
+
+        let alice_sig = proto::p2p::AuthSigMessage {
+            pub_key: Some(alice_pk.into()),
+            sig: alice_challenge.sign_challenge(&alice_sk).unwrap(),
+        };
+        let bob_sig = proto::p2p::AuthSigMessage {
+            pub_key: Some(bob_pk.into()),
+            sig: bob_challenge.sign_challenge(&bob_sk).unwrap(),
+        };
+
+        assert_eq!(
+            &alice_sig.encode_length_delimited_to_vec(),
+            &ALICE_SECP256K1_HANDSHAKE_SIG_MSG
+        );
+        assert_eq!(
+            &bob_sig.encode_length_delimited_to_vec(),
+            &BOB_SECP256K1_HANDSHAKE_SIG_MSG
+        );
+
+        assert_eq!(bob_challenge.got_signature(alice_sig).unwrap(), alice_pk);
+        assert_eq!(alice_challenge.got_signature(bob_sig).unwrap(), bob_pk);
+    }
+
+    /// CometBFT rejects malleable high-S secp256k1 signatures.
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn handshake_secp256k1_rejects_high_s() {
+        use crate::test_vectors::ALICE_SECP256K1_HANDSHAKE_SIG_MSG;
+
+        /// secp256k1 group order
+        const ORDER: [u8; 32] =
+            hex_literal::hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+
+        let (mut bob_hs, _) = InitialState::new_with_ephemeral_key(BOB_X25519_SK);
+        let (bob_challenge, _bob_cs) = bob_hs.got_key(ALICE_X25519_PK).unwrap();
+
+        let mut alice_sig = proto::p2p::AuthSigMessage::decode_length_delimited(
+            ALICE_SECP256K1_HANDSHAKE_SIG_MSG.as_slice(),
+        )
+        .unwrap();
+
+        // s' = n - s
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = i16::from(ORDER[i]) - i16::from(alice_sig.sig[32 + i]) - borrow;
+            borrow = i16::from(diff < 0);
+            alice_sig.sig[32 + i] = diff.rem_euclid(256) as u8;
+        }
+
+        assert!(bob_challenge.got_signature(alice_sig).is_err());
+    }
+
     #[test]
     fn handshake_rejects_low_order_points() {
         for point in LOW_ORDER_POINTS {
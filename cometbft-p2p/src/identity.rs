@@ -0,0 +1,126 @@
+//! Secret Connection node identities.
+
+use crate::{PublicKey, Result, ed25519};
+use ed25519_dalek::Signer;
+
+#[cfg(feature = "secp256k1")]
+use {crate::CryptoError, k256::ecdsa, rand_core::CryptoRngCore};
+
+/// Node identity keys which can authenticate the Secret Connection handshake.
+///
+/// Impl'd for all Ed25519 signers (e.g. [`IdentitySecret`][`crate::IdentitySecret`]), as well as
+/// [`Secp256k1IdentitySecret`] when the `secp256k1` feature is enabled.
+pub trait Identity {
+    /// Get the public key which identifies this node.
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign the challenge computed from the handshake transcript, returning the signature in the
+    /// serialization expected by CometBFT.
+    ///
+    /// # Errors
+    /// - if the signer fails to produce a signature
+    fn sign_challenge(&self, challenge: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl<S> Identity for S
+where
+    S: Signer<ed25519::Signature>,
+    ed25519::VerifyingKey: for<'a> From<&'a S>,
+{
+    fn public_key(&self) -> PublicKey {
+        ed25519::VerifyingKey::from(self).into()
+    }
+
+    fn sign_challenge(&self, challenge: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.try_sign(challenge)?.to_vec())
+    }
+}
+
+/// secp256k1 node identity secret keys.
+///
+/// Signs challenges the same way as CometBFT's `secp256k1.PrivKey`: ECDSA over the SHA-256 digest
+/// with RFC 6979 nonces, serialized as 64-byte `r || s` with `s` normalized to low-S.
+#[cfg(feature = "secp256k1")]
+#[derive(Clone)]
+pub struct Secp256k1IdentitySecret(ecdsa::SigningKey);
+
+#[cfg(feature = "secp256k1")]
+impl Secp256k1IdentitySecret {
+    /// Generate a random identity secret.
+    pub fn generate(rng: &mut impl CryptoRngCore) -> Self {
+        Self(ecdsa::SigningKey::random(rng))
+    }
+
+    /// Parse an identity secret from its 32-byte serialized scalar.
+    ///
+    /// # Errors
+    /// - if the bytes are not a valid secp256k1 scalar (i.e. zero or overflowing)
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self> {
+        ecdsa::SigningKey::from_bytes(bytes.into())
+            .map(Self)
+            .map_err(|_| CryptoError::SIGNATURE.into())
+    }
+
+    /// Get the verifying key which corresponds to this identity secret.
+    pub fn verifying_key(&self) -> ecdsa::VerifyingKey {
+        *self.0.verifying_key()
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl Identity for Secp256k1IdentitySecret {
+    fn public_key(&self) -> PublicKey {
+        self.verifying_key().into()
+    }
+
+    fn sign_challenge(&self, challenge: &[u8]) -> Result<Vec<u8>> {
+        use k256::ecdsa::signature::Signer as _;
+        let signature: ecdsa::Signature = self.0.try_sign(challenge)?;
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl From<ecdsa::SigningKey> for Secp256k1IdentitySecret {
+    fn from(signing_key: ecdsa::SigningKey) -> Self {
+        Self(signing_key)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl std::fmt::Debug for Secp256k1IdentitySecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Secp256k1IdentitySecret")
+            .field(&self.public_key())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "secp256k1"))]
+mod tests {
+    use super::{Identity, Secp256k1IdentitySecret};
+    use hex_literal::hex;
+
+    /// RFC 6979 test vector widely used by Bitcoin libraries (including `btcec`, which CometBFT
+    /// uses for secp256k1): secret key 1 signing "Satoshi Nakamoto".
+    #[test]
+    fn secp256k1_rfc6979_vector() {
+        let mut sk = [0u8; 32];
+        sk[31] = 1;
+
+        let identity = Secp256k1IdentitySecret::from_bytes(&sk).unwrap();
+        assert_eq!(
+            identity.sign_challenge(b"Satoshi Nakamoto").unwrap(),
+            hex!(
+                "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8"
+                "2442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5"
+            )
+        );
+    }
+
+    #[test]
+    fn secp256k1_rejects_invalid_scalars() {
+        assert!(Secp256k1IdentitySecret::from_bytes(&[0u8; 32]).is_err());
+        assert!(Secp256k1IdentitySecret::from_bytes(&[0xffu8; 32]).is_err());
+    }
+}
@@ -10,6 +10,7 @@
     unused_import_braces,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
 
 //! # Usage
 //!
@@ -41,6 +42,9 @@
 //! The [`SecretConnection`] type (`conn`) impls the [`ReadMsg`] and [`WriteMsg`] traits which can
 //! be used to receive and send Protobuf messages which impl the [`prost::Message`] trait.
 //!
+//! The [`SecretConnectionBuilder`] type can be used to instead verify the remote peer ID as part of
+//! establishing the connection, as well as to configure timeouts and message size limits.
+//!
 //! ## Async usage
 //!
 //! Enable the `async` crate feature to take advantage of the async support in this crate, which is
@@ -72,17 +76,42 @@
 //! traits which can be used to asynchronously receive and send Protobuf messages which impl the
 //! [`prost::Message`] trait.
 //!
+//! It also impls Tokio's [`AsyncRead`][tokio::io::AsyncRead] and
+//! [`AsyncWrite`][tokio::io::AsyncWrite] traits, which can be used to tunnel arbitrary byte
+//! streams over the connection. Each write is encrypted into one or more frames of its own.
+//!
+//! ## `Stream`/`Sink` usage
+//!
+//! Enable the `codec` crate feature to use [`AsyncSecretConnection`] as a `Stream` and `Sink` of
+//! Protobuf messages via `tokio-util`'s `Framed` type and the `ProtoCodec` type. Unlike
+//! [`AsyncReadMsg::read_msg`], polling the resulting stream is cancellation safe, and the sink
+//! applies backpressure when data can't be written as fast as it's sent.
+//!
 //! ## Splitting connections
 //!
 //! The [`SecretConnection::split`] and [`AsyncSecretConnection::split`] methods can be used to
 //! split a connection into separate read and write halves. This is particularly useful with async
 //! to be able to simultaneously read and write to a connection.
+//!
+//! ## Multiplexed connections
+//!
+//! CometBFT nodes don't exchange Protobuf messages directly over a Secret Connection, but instead
+//! multiplex the messages of their reactors over it as chunked packets on prioritized channels.
+//! With the `async` crate feature enabled, [`MConnection`] can be used to talk to them.
+//!
+//! Before multiplexing, peers exchange [`NodeInfo`] and disconnect if it's incompatible, which can
+//! be done with [`AsyncSecretConnection::exchange_node_info`].
 
 mod async_secret_connection;
+mod builder;
+mod codec;
 mod encryption;
 mod error;
 mod handshake;
+mod identity;
 mod kdf;
+mod mconnection;
+mod node_info;
 mod peer_id;
 mod proto;
 mod public_key;
@@ -91,23 +120,38 @@ mod test_vectors;
 mod traits;
 
 pub use crate::{
-    error::{CryptoError, Error, Result, VerifyPeerError},
+    builder::SecretConnectionBuilder,
+    error::{CryptoError, Error, NodeInfoError, Result, VerifyPeerError},
+    identity::Identity,
+    node_info::{NodeInfo, ProtocolVersion},
     peer_id::PeerId,
     public_key::PublicKey,
     secret_connection::{SecretConnection, SecretReader, SecretWriter},
-    traits::{ReadMsg, TryCloneIo, WriteMsg},
+    traits::{ReadMsg, TimeoutIo, TryCloneIo, WriteMsg},
 };
 pub use rand_core;
 
+#[cfg(feature = "secp256k1")]
+pub use {crate::identity::Secp256k1IdentitySecret, k256};
+
+#[cfg(feature = "codec")]
+pub use {crate::codec::ProtoCodec, tokio_util};
+
 #[cfg(feature = "async")]
 pub use crate::{
     async_secret_connection::{AsyncSecretConnection, AsyncSecretReader, AsyncSecretWriter},
+    mconnection::{
+        ChannelDescriptor, MConnection, MConnectionConfig, MConnectionReceiver, MConnectionSender,
+    },
     traits::{AsyncReadMsg, AsyncWriteMsg},
 };
 
-/// Secret Connection node identity secret keys.
+/// Channel identifiers, which determine which reactor a message belongs to.
+pub type ChannelId = u8;
+
+/// Secret Connection node identity secret keys (Ed25519).
 ///
-/// Ed25519 is currently the only supported signature algorithm.
+/// Enable the `secp256k1` crate feature to use [`Secp256k1IdentitySecret`] instead.
 pub type IdentitySecret = ed25519::SigningKey;
 
 pub(crate) use curve25519_dalek::montgomery::MontgomeryPoint as EphemeralPublic;
@@ -115,5 +159,8 @@ pub(crate) use ed25519_dalek as ed25519;
 
 /// Message size limit which applies to length-delimited messages read via the [`ReadMsg`] trait.
 ///
+/// Secret Connections use this limit by default, which can be changed with
+/// [`SecretConnectionBuilder::with_max_msg_len`].
+///
 /// Ensures we won't allocate excessively large buffers when consuming incoming requests.
 pub const MAX_MSG_LEN: usize = 1_048_576; // 1 MiB
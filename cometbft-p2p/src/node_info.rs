@@ -0,0 +1,372 @@
+//! Node info exchanged by peers after establishing a Secret Connection.
+
+use crate::{ChannelId, NodeInfoError, PeerId, PublicKey, proto};
+
+/// Maximum number of channels a node may advertise.
+const MAX_NUM_CHANNELS: usize = 16;
+
+/// Versions of the protocols spoken by a node.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ProtocolVersion {
+    /// P2P protocol version.
+    pub p2p: u64,
+
+    /// Block protocol version, which must match between peers.
+    pub block: u64,
+
+    /// Application version.
+    pub app: u64,
+}
+
+impl ProtocolVersion {
+    /// Protocol versions used by CometBFT v0.37 and v0.38.
+    pub const COMETBFT_V0_38: Self = Self {
+        p2p: 8,
+        block: 11,
+        app: 0,
+    };
+}
+
+/// Information about a node which is sent to peers after establishing a Secret Connection,
+/// (`DefaultNodeInfo` in CometBFT).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeInfo {
+    /// Versions of the protocols spoken by the node.
+    pub protocol_version: ProtocolVersion,
+
+    /// Node ID, which must match the peer ID of the node's Secret Connection identity.
+    pub node_id: PeerId,
+
+    /// Address the node accepts P2P connections on, e.g. `tcp://0.0.0.0:26656`.
+    pub listen_addr: String,
+
+    /// Network (i.e. chain ID) the node belongs to.
+    pub network: String,
+
+    /// Software version of the node.
+    pub version: String,
+
+    /// IDs of the channels the node speaks.
+    pub channels: Vec<ChannelId>,
+
+    /// Human-readable name of the node.
+    pub moniker: String,
+
+    /// Transaction indexing status, i.e. `on` or `off`.
+    pub tx_index: String,
+
+    /// Address of the node's RPC server.
+    pub rpc_address: String,
+}
+
+impl NodeInfo {
+    /// Create new node info for the given node ID with [`ProtocolVersion::COMETBFT_V0_38`] and
+    /// empty optional fields.
+    pub fn new(
+        node_id: PeerId,
+        network: impl Into<String>,
+        listen_addr: impl Into<String>,
+        moniker: impl Into<String>,
+        channels: impl Into<Vec<ChannelId>>,
+    ) -> Self {
+        Self {
+            protocol_version: ProtocolVersion::COMETBFT_V0_38,
+            node_id,
+            listen_addr: listen_addr.into(),
+            network: network.into(),
+            version: String::new(),
+            channels: channels.into(),
+            moniker: moniker.into(),
+            tx_index: String::new(),
+            rpc_address: String::new(),
+        }
+    }
+
+    /// Check the node info is well-formed.
+    ///
+    /// # Errors
+    ///
+    /// - if the listen address isn't a `host:port` pair
+    /// - if there are too many channels or duplicate channels
+    /// - if the version, moniker, or RPC address aren't printable ASCII
+    /// - if the transaction indexing status isn't `on`, `off`, or empty
+    pub fn validate(&self) -> Result<(), NodeInfoError> {
+        if !is_valid_addr(&self.listen_addr) {
+            return Err(malformed("invalid listen address"));
+        }
+
+        if !self.version.is_empty() && !is_ascii_text(&self.version) {
+            return Err(malformed("invalid version"));
+        }
+
+        if self.channels.len() > MAX_NUM_CHANNELS {
+            return Err(malformed("too many channels"));
+        }
+
+        for (i, channel) in self.channels.iter().enumerate() {
+            if self.channels[..i].contains(channel) {
+                return Err(malformed("duplicate channel"));
+            }
+        }
+
+        if !is_ascii_text(&self.moniker) {
+            return Err(malformed("invalid moniker"));
+        }
+
+        if !matches!(self.tx_index.as_str(), "" | "on" | "off") {
+            return Err(malformed("invalid tx index status"));
+        }
+
+        if !self.rpc_address.is_empty() && !is_ascii_text(&self.rpc_address) {
+            return Err(malformed("invalid RPC address"));
+        }
+
+        Ok(())
+    }
+
+    /// Check a remote peer's node info is compatible with ours, i.e. it uses the same block
+    /// protocol version, is on the same network, and has at least one channel in common with us.
+    pub fn check_compatible(&self, other: &NodeInfo) -> Result<(), NodeInfoError> {
+        if self.protocol_version.block != other.protocol_version.block {
+            return Err(NodeInfoError::BlockVersion {
+                local: self.protocol_version.block,
+                remote: other.protocol_version.block,
+            });
+        }
+
+        if self.network != other.network {
+            return Err(NodeInfoError::Network {
+                local: self.network.clone(),
+                remote: other.network.clone(),
+            });
+        }
+
+        // If we don't speak any channels (e.g. during testing) we're compatible with anyone, but a
+        // peer which doesn't speak any channels has none in common with us
+        if self.channels.is_empty() {
+            return Ok(());
+        }
+
+        if !self.channels.iter().any(|ch| other.channels.contains(ch)) {
+            return Err(NodeInfoError::NoCommonChannels);
+        }
+
+        Ok(())
+    }
+
+    /// Accept the node info received from the peer authenticated with the given public key,
+    /// ensuring it's valid, claims the peer's authenticated ID, and is compatible with ours.
+    pub(crate) fn accept_peer(
+        &self,
+        peer_public_key: &PublicKey,
+        peer_node_info: proto::p2p::DefaultNodeInfo,
+    ) -> crate::Result<NodeInfo> {
+        let peer_node_info = NodeInfo::try_from(peer_node_info)?;
+
+        peer_node_info.node_id.verify(peer_public_key.peer_id())?;
+
+        if peer_node_info.node_id == self.node_id {
+            return Err(NodeInfoError::SelfConnection.into());
+        }
+
+        self.check_compatible(&peer_node_info)?;
+        Ok(peer_node_info)
+    }
+}
+
+impl From<&NodeInfo> for proto::p2p::DefaultNodeInfo {
+    fn from(node_info: &NodeInfo) -> proto::p2p::DefaultNodeInfo {
+        proto::p2p::DefaultNodeInfo {
+            protocol_version: Some(proto::p2p::ProtocolVersion {
+                p2p: node_info.protocol_version.p2p,
+                block: node_info.protocol_version.block,
+                app: node_info.protocol_version.app,
+            }),
+            default_node_id: node_info.node_id.to_string(),
+            listen_addr: node_info.listen_addr.clone(),
+            network: node_info.network.clone(),
+            version: node_info.version.clone(),
+            channels: node_info.channels.clone(),
+            moniker: node_info.moniker.clone(),
+            other: Some(proto::p2p::DefaultNodeInfoOther {
+                tx_index: node_info.tx_index.clone(),
+                rpc_address: node_info.rpc_address.clone(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<proto::p2p::DefaultNodeInfo> for NodeInfo {
+    type Error = NodeInfoError;
+
+    fn try_from(proto: proto::p2p::DefaultNodeInfo) -> Result<NodeInfo, NodeInfoError> {
+        let protocol_version = proto.protocol_version.unwrap_or_default();
+        let other = proto.other.unwrap_or_default();
+
+        let node_info = NodeInfo {
+            protocol_version: ProtocolVersion {
+                p2p: protocol_version.p2p,
+                block: protocol_version.block,
+                app: protocol_version.app,
+            },
+            node_id: proto
+                .default_node_id
+                .parse()
+                .map_err(|_| malformed("invalid node ID"))?,
+            listen_addr: proto.listen_addr,
+            network: proto.network,
+            version: proto.version,
+            channels: proto.channels,
+            moniker: proto.moniker,
+            tx_index: other.tx_index,
+            rpc_address: other.rpc_address,
+        };
+
+        node_info.validate()?;
+        Ok(node_info)
+    }
+}
+
+/// Create a [`NodeInfoError::Malformed`] error.
+fn malformed(reason: &str) -> NodeInfoError {
+    NodeInfoError::Malformed {
+        reason: reason.to_owned(),
+    }
+}
+
+/// Is the given string non-blank printable ASCII?
+fn is_ascii_text(s: &str) -> bool {
+    s.bytes().all(|b| (0x20..=0x7e).contains(&b)) && !s.trim_matches(' ').is_empty()
+}
+
+/// Is the given address a `host:port` pair, optionally prefixed with a protocol?
+fn is_valid_addr(addr: &str) -> bool {
+    let host_port = addr.split_once("://").map_or(addr, |(_, rest)| rest);
+
+    host_port
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodeInfo, ProtocolVersion};
+    use crate::{Error, NodeInfoError, PeerId, PublicKey, ed25519, proto};
+    use prost::Message;
+
+    fn alice_pk() -> PublicKey {
+        PublicKey::from(&ed25519::SigningKey::from_bytes(&[1; 32]))
+    }
+
+    fn node_info(node_id: PeerId) -> NodeInfo {
+        NodeInfo::new(
+            node_id,
+            "cosmoshub-4",
+            "tcp://0.0.0.0:26656",
+            "alice",
+            [0x20, 0x30],
+        )
+    }
+
+    #[test]
+    fn proto_round_trip() {
+        let node_info = node_info(alice_pk().peer_id());
+        let proto = proto::p2p::DefaultNodeInfo::from(&node_info);
+        let bytes = proto.encode_to_vec();
+
+        let decoded = proto::p2p::DefaultNodeInfo::decode(bytes.as_slice()).unwrap();
+        assert_eq!(NodeInfo::try_from(decoded).unwrap(), node_info);
+    }
+
+    #[test]
+    fn validate() {
+        let valid = node_info(PeerId([1; 20]));
+        assert!(valid.validate().is_ok());
+
+        let mut invalid = valid.clone();
+        invalid.listen_addr = "0.0.0.0".to_owned();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = valid.clone();
+        invalid.channels = vec![0x20, 0x20];
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = valid.clone();
+        invalid.channels = (0..17).collect();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = valid.clone();
+        invalid.moniker = "  ".to_owned();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = valid.clone();
+        invalid.tx_index = "maybe".to_owned();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn check_compatible() {
+        let ours = node_info(PeerId([1; 20]));
+        let theirs = node_info(PeerId([2; 20]));
+        assert!(ours.check_compatible(&theirs).is_ok());
+
+        let mut other_version = theirs.clone();
+        other_version.protocol_version = ProtocolVersion {
+            block: 12,
+            ..ProtocolVersion::COMETBFT_V0_38
+        };
+        assert_eq!(
+            ours.check_compatible(&other_version),
+            Err(NodeInfoError::BlockVersion {
+                local: 11,
+                remote: 12
+            })
+        );
+
+        let mut other_network = theirs.clone();
+        other_network.network = "osmosis-1".to_owned();
+        assert!(matches!(
+            ours.check_compatible(&other_network),
+            Err(NodeInfoError::Network { .. })
+        ));
+
+        let mut other_channels = theirs.clone();
+        other_channels.channels = vec![0x40];
+        assert_eq!(
+            ours.check_compatible(&other_channels),
+            Err(NodeInfoError::NoCommonChannels)
+        );
+
+        let mut no_channels = theirs.clone();
+        no_channels.channels = vec![];
+        assert_eq!(
+            ours.check_compatible(&no_channels),
+            Err(NodeInfoError::NoCommonChannels)
+        );
+        assert!(no_channels.check_compatible(&ours).is_ok());
+    }
+
+    #[test]
+    fn accept_peer() {
+        let alice_pk = alice_pk();
+        let ours = node_info(PeerId([2; 20]));
+
+        let theirs = node_info(alice_pk.peer_id());
+        assert_eq!(
+            ours.accept_peer(&alice_pk, (&theirs).into()).unwrap(),
+            theirs
+        );
+
+        let impostor = node_info(PeerId([3; 20]));
+        assert!(matches!(
+            ours.accept_peer(&alice_pk, (&impostor).into()),
+            Err(Error::VerifyPeer(_))
+        ));
+
+        let ourselves = node_info(alice_pk.peer_id());
+        assert!(matches!(
+            ourselves.accept_peer(&alice_pk, (&ourselves).into()),
+            Err(Error::NodeInfo(NodeInfoError::SelfConnection))
+        ));
+    }
+}
@@ -36,8 +36,92 @@ pub(crate) mod p2p {
         #[prost(bytes = "vec", tag = "2")]
         pub sig: Vec<u8>,
     }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Message)]
+    pub struct ProtocolVersion {
+        #[prost(uint64, tag = "1")]
+        pub p2p: u64,
+        #[prost(uint64, tag = "2")]
+        pub block: u64,
+        #[prost(uint64, tag = "3")]
+        pub app: u64,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct DefaultNodeInfo {
+        #[prost(message, optional, tag = "1")]
+        pub protocol_version: Option<ProtocolVersion>,
+        #[prost(string, tag = "2")]
+        pub default_node_id: String,
+        #[prost(string, tag = "3")]
+        pub listen_addr: String,
+        #[prost(string, tag = "4")]
+        pub network: String,
+        #[prost(string, tag = "5")]
+        pub version: String,
+        #[prost(bytes = "vec", tag = "6")]
+        pub channels: Vec<u8>,
+        #[prost(string, tag = "7")]
+        pub moniker: String,
+        #[prost(message, optional, tag = "8")]
+        pub other: Option<DefaultNodeInfoOther>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct DefaultNodeInfoOther {
+        #[prost(string, tag = "1")]
+        pub tx_index: String,
+        #[prost(string, tag = "2")]
+        pub rpc_address: String,
+    }
+
+    #[cfg(feature = "async")]
+    #[derive(Clone, Copy, PartialEq, Message)]
+    pub struct PacketPing {}
+
+    #[cfg(feature = "async")]
+    #[derive(Clone, Copy, PartialEq, Message)]
+    pub struct PacketPong {}
+
+    #[cfg(feature = "async")]
+    #[derive(Clone, PartialEq, Message)]
+    pub struct PacketMsg {
+        #[prost(int32, tag = "1")]
+        pub channel_id: i32,
+        #[prost(bool, tag = "2")]
+        pub eof: bool,
+        #[prost(bytes = "vec", tag = "3")]
+        pub data: Vec<u8>,
+    }
+
+    #[cfg(feature = "async")]
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Packet {
+        #[prost(oneof = "packet::Sum", tags = "1, 2, 3")]
+        pub sum: Option<packet::Sum>,
+    }
+    #[cfg(feature = "async")]
+    /// Nested message and enum types in `Packet`.
+    pub mod packet {
+        use prost::Oneof;
+
+        #[allow(clippy::enum_variant_names)]
+        #[derive(Clone, PartialEq, Oneof)]
+        pub enum Sum {
+            #[prost(message, tag = "1")]
+            PacketPing(super::PacketPing),
+            #[prost(message, tag = "2")]
+            PacketPong(super::PacketPong),
+            #[prost(message, tag = "3")]
+            PacketMsg(super::PacketMsg),
+        }
+    }
 }
 
+/// Maximum length of an encoded varint, i.e. a length prefix.
+#[cfg(feature = "async")]
+pub(crate) const MAX_VARINT_LEN: usize = 10;
+
 /// Decode the total length of a length-delimited Protobuf or other LEB128-prefixed message,
 /// including the length of the length prefix itself (which is variable-sized).
 pub(crate) fn decode_length_delimiter_inclusive(frame: &[u8]) -> crate::Result<usize> {
@@ -4,13 +4,27 @@ use crate::{Error, IdentitySecret, PeerId, Result, ed25519, proto};
 use ed25519_dalek::Verifier;
 use prost::DecodeError;
 use sha2::{Sha256, digest::Digest};
-use std::fmt::{self, Debug, Display};
+use std::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+};
 
-/// Secret Connection peer identity public keys (signing, presently Ed25519-only)
-#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg(feature = "secp256k1")]
+use {k256::ecdsa, ripemd::Ripemd160};
+
+/// Size of a compressed secp256k1 public key.
+#[cfg(feature = "secp256k1")]
+const SECP256K1_PUBLIC_KEY_SIZE: usize = 33;
+
+/// Secret Connection peer identity public keys (signing)
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub enum PublicKey {
     /// Ed25519 Secret Connection Keys
     Ed25519(ed25519::VerifyingKey),
+
+    /// secp256k1 Secret Connection Keys
+    #[cfg(feature = "secp256k1")]
+    Secp256k1(ecdsa::VerifyingKey),
 }
 
 impl PublicKey {
@@ -22,17 +36,43 @@ impl PublicKey {
         Ok(ed25519::VerifyingKey::try_from(bytes).map(Self::Ed25519)?)
     }
 
+    /// From raw compressed (33-byte) secp256k1 public key bytes
+    ///
+    /// # Errors
+    /// - if the bytes given are invalid
+    #[cfg(feature = "secp256k1")]
+    pub fn from_raw_secp256k1(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != SECP256K1_PUBLIC_KEY_SIZE {
+            return Err(DecodeError::new("expected a 33-byte compressed secp256k1 key").into());
+        }
+
+        Ok(ecdsa::VerifyingKey::from_sec1_bytes(bytes).map(Self::Secp256k1)?)
+    }
+
     /// Get Ed25519 public key.
     #[must_use]
     pub fn ed25519(self) -> Option<ed25519::VerifyingKey> {
         match self {
             Self::Ed25519(pk) => Some(pk),
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(_) => None,
+        }
+    }
+
+    /// Get secp256k1 public key.
+    #[cfg(feature = "secp256k1")]
+    #[must_use]
+    pub fn secp256k1(self) -> Option<ecdsa::VerifyingKey> {
+        match self {
+            Self::Secp256k1(pk) => Some(pk),
+            Self::Ed25519(_) => None,
         }
     }
 
     /// Get the remote [`PeerId`].
     ///
-    /// This is a 20-byte fingerprint of the public key.
+    /// This is a 20-byte fingerprint of the public key: the first 20 bytes of its SHA-256 digest
+    /// for Ed25519, and `RIPEMD160(SHA256(compressed key))` for secp256k1.
     #[must_use]
     pub fn peer_id(self) -> PeerId {
         match self {
@@ -40,6 +80,11 @@ impl PublicKey {
                 let digest = Sha256::digest(pk.as_bytes());
                 PeerId(digest[..20].try_into().expect("should be 20 bytes"))
             }
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(pk) => {
+                let digest = Ripemd160::digest(Sha256::digest(pk.to_encoded_point(true)));
+                PeerId(digest.into())
+            }
         }
     }
 
@@ -47,11 +92,33 @@ impl PublicKey {
     pub fn to_proto(&self) -> proto::crypto::PublicKey {
         let pk = match self {
             Self::Ed25519(pk) => proto::crypto::public_key::Sum::Ed25519(pk.as_ref().to_vec()),
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(pk) => proto::crypto::public_key::Sum::Secp256k1(
+                pk.to_encoded_point(true).as_bytes().to_vec(),
+            ),
         };
 
         proto::crypto::PublicKey { sum: Some(pk) }
     }
 
+    /// Serialize this public key as bytes (compressed for secp256k1).
+    fn to_vec(self) -> Vec<u8> {
+        match self {
+            Self::Ed25519(pk) => pk.to_bytes().to_vec(),
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(pk) => pk.to_encoded_point(true).as_bytes().to_vec(),
+        }
+    }
+
+    /// Name of the algorithm of this public key.
+    fn algorithm(&self) -> &'static str {
+        match self {
+            Self::Ed25519(_) => "Ed25519",
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(_) => "Secp256k1",
+        }
+    }
+
     /// Verify the given message and signature using this public key.
     pub(crate) fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<()> {
         match self {
@@ -59,31 +126,37 @@ impl PublicKey {
                 let sig = ed25519::Signature::try_from(sig)?;
                 Ok(ed25519_vk.verify(msg, &sig)?)
             }
+            // Rejects high-S signatures, like CometBFT
+            #[cfg(feature = "secp256k1")]
+            Self::Secp256k1(secp256k1_vk) => {
+                use k256::ecdsa::signature::Verifier as _;
+                let sig = ecdsa::Signature::try_from(sig)?;
+                Ok(secp256k1_vk.verify(msg, &sig)?)
+            }
         }
     }
 }
 
 impl Display for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Ed25519(ed25519_key) => {
-                write!(f, "PublicKey::Ed25519(")?;
-                for byte in ed25519_key.to_bytes() {
-                    write!(f, "{byte:02x}")?;
-                }
-                write!(f, ")")?;
-            }
+        write!(f, "PublicKey::{}(", self.algorithm())?;
+        for byte in self.to_vec() {
+            write!(f, "{byte:02x}")?;
         }
-
-        Ok(())
+        write!(f, ")")
     }
 }
 
 impl Debug for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            PublicKey::Ed25519(_) => write!(f, "PublicKey::Ed25519({self})"),
-        }
+        write!(f, "PublicKey::{}({self})", self.algorithm())
+    }
+}
+
+impl Hash for PublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.algorithm().hash(state);
+        self.to_vec().hash(state);
     }
 }
 
@@ -99,12 +172,33 @@ impl From<&ed25519::VerifyingKey> for PublicKey {
     }
 }
 
+#[cfg(feature = "secp256k1")]
+impl From<ecdsa::VerifyingKey> for PublicKey {
+    fn from(pk: ecdsa::VerifyingKey) -> Self {
+        Self::Secp256k1(pk)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl From<&ecdsa::VerifyingKey> for PublicKey {
+    fn from(pk: &ecdsa::VerifyingKey) -> Self {
+        Self::from(*pk)
+    }
+}
+
 impl From<&IdentitySecret> for PublicKey {
     fn from(sk: &IdentitySecret) -> Self {
         Self::Ed25519(sk.verifying_key())
     }
 }
 
+#[cfg(feature = "secp256k1")]
+impl From<&crate::Secp256k1IdentitySecret> for PublicKey {
+    fn from(sk: &crate::Secp256k1IdentitySecret) -> Self {
+        Self::Secp256k1(sk.verifying_key())
+    }
+}
+
 impl From<PublicKey> for proto::crypto::PublicKey {
     fn from(pk: PublicKey) -> Self {
         pk.to_proto()
@@ -138,6 +232,12 @@ impl TryFrom<&proto::crypto::PublicKey> for PublicKey {
                             .into()
                     })
             }
+            #[cfg(feature = "secp256k1")]
+            Some(proto::crypto::public_key::Sum::Secp256k1(bytes)) => {
+                Self::from_raw_secp256k1(bytes).map_err(|_| {
+                    DecodeError::new("malformed PublicKey proto with invalid secp256k1 key").into()
+                })
+            }
             _ => Err(DecodeError::new(
                 "malformed PublicKey proto or unsupported public key algorithm",
             )
@@ -145,3 +245,56 @@ impl TryFrom<&proto::crypto::PublicKey> for PublicKey {
         }
     }
 }
+
+#[cfg(all(test, feature = "secp256k1"))]
+mod tests {
+    use super::PublicKey;
+    use crate::{PeerId, proto};
+    use hex_literal::hex;
+
+    /// secp256k1 generator point, i.e. the public key for the secret scalar 1.
+    const SECP256K1_PK: [u8; 33] =
+        hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+
+    #[test]
+    fn secp256k1_peer_id() {
+        let pk = PublicKey::from_raw_secp256k1(&SECP256K1_PK).unwrap();
+
+        // `RIPEMD160(SHA256(pk))`, same as BIP-173's P2WPKH test vector for this key
+        assert_eq!(
+            pk.peer_id(),
+            PeerId(hex!("751e76e8199196d454941c45d1b3a323f1433bd6"))
+        );
+    }
+
+    #[test]
+    fn secp256k1_proto_round_trip() {
+        let pk = PublicKey::from_raw_secp256k1(&SECP256K1_PK).unwrap();
+        let proto = pk.to_proto();
+        assert_eq!(
+            proto.sum,
+            Some(proto::crypto::public_key::Sum::Secp256k1(
+                SECP256K1_PK.to_vec()
+            ))
+        );
+        assert_eq!(PublicKey::try_from(proto).unwrap(), pk);
+    }
+
+    #[test]
+    fn secp256k1_rejects_uncompressed_keys() {
+        let uncompressed = hex!(
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+        );
+
+        assert!(PublicKey::from_raw_secp256k1(&uncompressed).is_err());
+        assert!(
+            PublicKey::try_from(proto::crypto::PublicKey {
+                sum: Some(proto::crypto::public_key::Sum::Secp256k1(
+                    uncompressed.to_vec()
+                )),
+            })
+            .is_err()
+        );
+    }
+}
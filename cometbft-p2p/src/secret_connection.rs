@@ -1,8 +1,7 @@
 //! Encrypted connection between peers in a CometBFT network.
 
 use crate::{
-    Error, MAX_MSG_LEN, PublicKey, Result,
-    ed25519::{self, Signer},
+    Error, Identity, MAX_MSG_LEN, NodeInfo, PublicKey, Result,
     encryption::{Frame, RecvState, SendState},
     handshake, proto,
     traits::{ReadMsg, TryCloneIo, WriteMsg},
@@ -38,16 +37,16 @@ pub struct SecretConnection<Io> {
     /// cipher state.
     writer: SecretWriter<Io>,
 
-    /// Our identity's Ed25519 public key.
+    /// Our identity's public key.
     local_public_key: PublicKey,
 
-    /// Remote peer's Ed25519 public key.
+    /// Remote peer's public key.
     peer_public_key: PublicKey,
 }
 
 impl<Io: Read + Write + Send + Sync + TryCloneIo> SecretConnection<Io> {
     /// Performs a handshake and returns a new `SecretConnection`, authenticating ourselves with the
-    /// provided `Identity` (Ed25519 signing key).
+    /// provided [`Identity`] (Ed25519 or secp256k1 signing key).
     ///
     /// The [`IdentitySecret`] type can be used as an `identity_key`.
     ///
@@ -57,13 +56,12 @@ impl<Io: Read + Write + Send + Sync + TryCloneIo> SecretConnection<Io> {
     /// - if sharing of the signature fails
     /// - if receiving the signature fails
     /// - if verifying the signature fails
-    pub fn new<Identity>(mut io: Io, identity_key: &Identity) -> Result<Self>
+    pub fn new<I>(mut io: Io, identity_key: &I) -> Result<Self>
     where
-        Identity: Signer<ed25519::Signature>,
-        ed25519::VerifyingKey: for<'a> From<&'a Identity>,
+        I: Identity + ?Sized,
     {
         // Start a handshake process, generating a local ephemeral X25519 public key.
-        let local_public_key: PublicKey = ed25519::VerifyingKey::from(identity_key).into();
+        let local_public_key = identity_key.public_key();
         let (mut initial_state, initial_message) = handshake::InitialState::new();
 
         // Send our ephemeral X25519 public key to the remote peer (unencrypted).
@@ -81,19 +79,20 @@ impl<Io: Read + Write + Send + Sync + TryCloneIo> SecretConnection<Io> {
         let mut reader = SecretReader {
             io,
             recv_state: cipher_state.recv_state,
+            max_msg_len: MAX_MSG_LEN,
         };
         let mut writer = SecretWriter {
             io: io2,
             send_state: cipher_state.send_state,
         };
 
-        // Send our identity's Ed25519 public key and signature over the transcript to the peer.
+        // Send our identity's public key and signature over the transcript to the peer.
         writer.write_msg(&proto::p2p::AuthSigMessage {
             pub_key: Some(local_public_key.into()),
-            sig: challenge.sign_challenge(identity_key).to_vec(),
+            sig: challenge.sign_challenge(identity_key)?,
         })?;
 
-        // Read the peer's Ed25519 public key and use it to verify their signature over the
+        // Read the peer's public key and use it to verify their signature over the
         // handshake transcript.
         let auth_sig_msg: proto::p2p::AuthSigMessage = reader.read_msg()?;
 
@@ -110,6 +109,25 @@ impl<Io: Read + Write + Send + Sync + TryCloneIo> SecretConnection<Io> {
     }
 }
 
+impl<Io: Read + Write> SecretConnection<Io> {
+    /// Send our [`NodeInfo`] to the remote peer and receive theirs, as CometBFT nodes do
+    /// immediately after establishing a connection.
+    ///
+    /// # Errors
+    ///
+    /// - if the peer's node info is malformed
+    /// - if the peer's node ID doesn't match their authenticated peer ID
+    /// - if the peer's node info is incompatible with ours (see [`NodeInfo::check_compatible`])
+    pub fn exchange_node_info(&mut self, node_info: &NodeInfo) -> Result<NodeInfo> {
+        self.writer
+            .write_msg(&proto::p2p::DefaultNodeInfo::from(node_info))?;
+        self.writer.flush()?;
+
+        let peer_node_info = self.reader.read_msg()?;
+        node_info.accept_peer(&self.peer_public_key, peer_node_info)
+    }
+}
+
 impl<M: Message + Default, Io: Read> ReadMsg<M> for SecretConnection<Io> {
     fn read_msg(&mut self) -> Result<M> {
         self.reader.read_msg()
@@ -145,6 +163,11 @@ impl<Io> SecretConnection<Io> {
     pub fn split(self) -> (SecretReader<Io>, SecretWriter<Io>) {
         (self.reader, self.writer)
     }
+
+    /// Set the maximum length of messages which can be read.
+    pub(crate) fn set_max_msg_len(&mut self, max_msg_len: usize) {
+        self.reader.max_msg_len = max_msg_len;
+    }
 }
 
 /// Encrypted message reader type which wraps the read-half of an underlying I/O object.
@@ -154,6 +177,9 @@ pub struct SecretReader<Io> {
 
     /// Symmetric cipher state including the current nonce.
     recv_state: RecvState,
+
+    /// Maximum length of a message.
+    max_msg_len: usize,
 }
 
 impl<Io: Read> SecretReader<Io> {
@@ -177,7 +203,7 @@ impl<M: Message + Default, Io: Read> ReadMsg<M> for SecretReader<Io> {
         // Decode the length prefix on the proto
         let msg_len = proto::decode_length_delimiter_inclusive(frame_plaintext)?;
 
-        if msg_len > MAX_MSG_LEN {
+        if msg_len > self.max_msg_len {
             return Err(Error::MessageSize { size: msg_len });
         }
 
@@ -56,3 +56,40 @@ pub(crate) const ENCRYPTION_KEY1: [u8; 32] =
     hex!("4de5c254243a6a7b52fafd0b0c4db59175975cd435e99b7f758265aaaeeea063");
 pub(crate) const ENCRYPTION_KEY2: [u8; 32] =
     hex!("6083a1a00e5ea92cdc380c55013f3c87d87ade022666fd5aad4ae3a1530d0885");
+
+/// Alice and Bob's secp256k1 identity (signature) secret keys
+#[cfg(feature = "secp256k1")]
+pub(crate) const ALICE_SECP256K1_SK: [u8; 32] =
+    hex!("a0d068d7c44e951610d54a7eb90279e8a31b61128d44d2dd92311763c468185c");
+#[cfg(feature = "secp256k1")]
+pub(crate) const BOB_SECP256K1_SK: [u8; 32] =
+    hex!("b07e65300419ce0b5d7274bcbc67fcfd3fb68272de9aa52a452a6889c7d33fd5");
+
+/// Alice and Bob's compressed secp256k1 identity (signature) public keys
+#[cfg(feature = "secp256k1")]
+pub(crate) const ALICE_SECP256K1_PK: [u8; 33] =
+    hex!("02c2d743eda8fa87f7fb0715d0286d6a9ad9b3c5c9c5466ec7089531bbb2981b65");
+#[cfg(feature = "secp256k1")]
+pub(crate) const BOB_SECP256K1_PK: [u8; 33] =
+    hex!("02756a8ddf8fbbfa8bbe49d8f1be9dd4c117fe929afef3d3092e4034b38018bb07");
+
+/// Alice and Bob's secp256k1 peer IDs, i.e. `RIPEMD160(SHA256(pk))`
+#[cfg(feature = "secp256k1")]
+pub(crate) const ALICE_SECP256K1_PEER_ID: [u8; 20] =
+    hex!("e85e647492a0b0bb159ba53428f628ce5dd886b1");
+#[cfg(feature = "secp256k1")]
+pub(crate) const BOB_SECP256K1_PEER_ID: [u8; 20] = hex!("42f395aeffbd0af8922f54c2774bb207f9d49554");
+
+/// Alice and Bob's signature messages authenticating the handshake transcript with secp256k1
+/// identities.
+///
+/// Unlike the vectors above, these were generated independently of this crate using OpenSSL's
+/// RFC 6979 ECDSA (normalized to low-S, as `btcec` does for CometBFT) over the same transcript.
+#[cfg(feature = "secp256k1")]
+pub(crate) const ALICE_SECP256K1_HANDSHAKE_SIG_MSG: [u8; 104] = hex!(
+    "670a23122102c2d743eda8fa87f7fb0715d0286d6a9ad9b3c5c9c5466ec7089531bbb2981b651240e18a72d07b28907ec208d13f07a9008ea5dfa26b0ef3aa5c76dddb1f4d9d32e677209eb68924cdb741fbcb5ee35cf71337a1209545e89f43dd1604aa1f4d7aea"
+);
+#[cfg(feature = "secp256k1")]
+pub(crate) const BOB_SECP256K1_HANDSHAKE_SIG_MSG: [u8; 104] = hex!(
+    "670a23122102756a8ddf8fbbfa8bbe49d8f1be9dd4c117fe929afef3d3092e4034b38018bb071240c34abd798b2c62f117e5fb182a70e0d70b29cdab1db2f7f3d41565bc2cb6a1306ee82d67a513ce1974df98265cd1cd4a8a3b341b09ea3a89a8a18d14804c96d1"
+);
@@ -2,7 +2,10 @@
 
 use crate::{Error, MAX_MSG_LEN, Result, proto};
 use prost::Message;
-use std::io::{self, Read, Write};
+use std::{
+    io::{self, Read, Write},
+    time::Duration,
+};
 
 #[cfg(feature = "async")]
 use std::future::Future;
@@ -17,6 +20,13 @@ use std::future::Future;
 pub trait AsyncReadMsg {
     /// Read from the underlying I/O object, decrypting the data and decoding it into the given
     /// Protobuf message.
+    ///
+    /// # Cancellation safety
+    ///
+    /// This method is not cancellation safe: if the returned future is dropped after part of a
+    /// message has been read, the rest of the message is left unread and subsequent reads will
+    /// fail. With the `codec` crate feature, use `ProtoCodec` to read messages from a `Stream`
+    /// which is cancellation safe.
     fn read_msg<M: Message + Default>(&mut self) -> impl Future<Output = Result<M>> + Send + Sync;
 }
 
@@ -27,6 +37,11 @@ pub trait AsyncWriteMsg {
     /// I/O object.
     ///
     /// Deliberately takes ownership of the message to send to simplify writing async code.
+    ///
+    /// # Cancellation safety
+    ///
+    /// This method is not cancellation safe: if the returned future is dropped, the message may
+    /// have been partially written.
     fn write_msg<M: Message>(&mut self, msg: M) -> impl Future<Output = Result<()>> + Send + Sync;
 }
 
@@ -103,4 +118,34 @@ impl TryCloneIo for std::os::unix::net::UnixStream {
     }
 }
 
+/// Set timeouts for blocking reads and writes on an I/O object.
+pub trait TimeoutIo {
+    /// Set the read timeout, or disable it with `None`.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    /// Set the write timeout, or disable it with `None`.
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl TimeoutIo for std::net::TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_write_timeout(timeout)
+    }
+}
+
+#[cfg(unix)]
+impl TimeoutIo for std::os::unix::net::UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_write_timeout(timeout)
+    }
+}
+
 // NOTE: tested indirectly via `SecretConnection`
@@ -4,10 +4,15 @@
 
 mod common;
 
-use cometbft_p2p::{AsyncReadMsg, AsyncSecretConnection, AsyncWriteMsg, IdentitySecret};
+use cometbft_p2p::{
+    AsyncReadMsg, AsyncSecretConnection, AsyncWriteMsg, Error, IdentitySecret, NodeInfo,
+    NodeInfoError, PublicKey, SecretConnectionBuilder,
+};
 use common::{PingRequest, PongResponse, TestServer};
+use prost::Message;
 use rand_core::{OsRng, RngCore};
-use std::{os::unix, thread};
+use std::{io, os::unix, thread, time::Duration};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
 
 /// Maximum example message length to generate.
 ///
@@ -55,6 +60,284 @@ async fn integration_test() {
     server_handle.join().unwrap();
 }
 
+#[tokio::test]
+async fn node_info_exchange() {
+    let alice_sk = IdentitySecret::generate(&mut OsRng);
+    let bob_sk = IdentitySecret::generate(&mut OsRng);
+    let alice_info = NodeInfo::new(
+        PublicKey::from(&alice_sk).peer_id(),
+        "cosmoshub-4",
+        "tcp://0.0.0.0:26656",
+        "alice",
+        [0x20, 0x30],
+    );
+    let bob_info = NodeInfo::new(
+        PublicKey::from(&bob_sk).peer_id(),
+        "cosmoshub-4",
+        "tcp://0.0.0.0:26656",
+        "bob",
+        [0x30, 0x40],
+    );
+
+    let (sock_a, sock_b) = tokio::net::UnixStream::pair().unwrap();
+    let (mut alice, mut bob) = tokio::try_join!(
+        AsyncSecretConnection::new(sock_a, &alice_sk),
+        AsyncSecretConnection::new(sock_b, &bob_sk)
+    )
+    .unwrap();
+
+    let (info_from_bob, info_from_alice) = tokio::try_join!(
+        alice.exchange_node_info(&alice_info),
+        bob.exchange_node_info(&bob_info)
+    )
+    .unwrap();
+    assert_eq!(info_from_bob, bob_info);
+    assert_eq!(info_from_alice, alice_info);
+
+    // Bob claims to be on another network
+    let osmosis_info = NodeInfo {
+        network: "osmosis-1".to_owned(),
+        ..bob_info
+    };
+    let (alice_result, _) = tokio::join!(
+        alice.exchange_node_info(&alice_info),
+        bob.exchange_node_info(&osmosis_info)
+    );
+    assert!(matches!(
+        alice_result,
+        Err(Error::NodeInfo(NodeInfoError::Network { .. }))
+    ));
+
+    // Bob claims to be Alice
+    let (alice_result, _) = tokio::join!(
+        alice.exchange_node_info(&alice_info),
+        bob.exchange_node_info(&alice_info)
+    );
+    assert!(matches!(alice_result, Err(Error::VerifyPeer(_))));
+}
+
+#[tokio::test]
+async fn builder_timeouts() {
+    let alice_sk = IdentitySecret::generate(&mut OsRng);
+    let bob_sk = IdentitySecret::generate(&mut OsRng);
+    let bob_peer_id = PublicKey::from(&bob_sk).peer_id();
+
+    // Peer never sends its initial message
+    let (sock_a, _sock_b) = tokio::net::UnixStream::pair().unwrap();
+    let result = SecretConnectionBuilder::new()
+        .with_handshake_timeout(Duration::from_millis(50))
+        .build_async(sock_a, &alice_sk)
+        .await;
+    assert!(matches!(result, Err(Error::HandshakeTimeout)));
+
+    // Peer completes the handshake but never sends anything afterwards
+    let (sock_a, sock_b) = tokio::net::UnixStream::pair().unwrap();
+    let builder = SecretConnectionBuilder::new()
+        .with_handshake_timeout(Duration::from_secs(5))
+        .with_read_timeout(Duration::from_millis(50))
+        .with_expected_peer_id(bob_peer_id);
+    let (mut alice, _bob) = tokio::try_join!(
+        builder.build_async(sock_a, &alice_sk),
+        AsyncSecretConnection::new(sock_b, &bob_sk)
+    )
+    .unwrap();
+
+    let result = alice.read_msg::<PongResponse>().await;
+    assert!(matches!(result, Err(Error::Io(err)) if err.kind() == io::ErrorKind::TimedOut));
+}
+
+/// Open a pair of connections over an in-memory pipe whose small buffer forces frames to be read
+/// and written in several parts.
+async fn duplex_connection_pair() -> (
+    AsyncSecretConnection<DuplexStream>,
+    AsyncSecretConnection<DuplexStream>,
+) {
+    let alice_sk = IdentitySecret::generate(&mut OsRng);
+    let bob_sk = IdentitySecret::generate(&mut OsRng);
+    let (io_a, io_b) = tokio::io::duplex(100);
+
+    tokio::try_join!(
+        AsyncSecretConnection::new(io_a, &alice_sk),
+        AsyncSecretConnection::new(io_b, &bob_sk)
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn byte_stream() {
+    let (alice, bob) = duplex_connection_pair().await;
+    let (mut alice_reader, mut alice_writer) = alice.split();
+    let (mut bob_reader, mut bob_writer) = bob.split();
+
+    let mut data = vec![0u8; 3 * MAX_MSG_LEN];
+    OsRng.fill_bytes(&mut data);
+
+    // Writes and reads of odd sizes which straddle frame boundaries
+    let write_sizes = [1, 1023, 1024, 1025, 3000, 7];
+    let read_size = 333;
+
+    // Alice writes and reads concurrently, since the pipe's buffer is small
+    let alice_data = data.clone();
+    let alice_writes = tokio::spawn(async move {
+        let mut remaining = alice_data.as_slice();
+        for size in write_sizes.iter().cycle() {
+            if remaining.is_empty() {
+                break;
+            }
+
+            let (chunk, rest) = remaining.split_at((*size).min(remaining.len()));
+            alice_writer.write_all(chunk).await.unwrap();
+            remaining = rest;
+        }
+
+        alice_writer.shutdown().await.unwrap();
+    });
+    let alice_reads = tokio::spawn(async move {
+        let mut echoed = Vec::new();
+        alice_reader.read_to_end(&mut echoed).await.unwrap();
+        echoed
+    });
+
+    // Echo everything back to Alice
+    let mut received = Vec::new();
+    let mut buf = vec![0u8; read_size];
+    loop {
+        let len = bob_reader.read(&mut buf).await.unwrap();
+        if len == 0 {
+            break;
+        }
+
+        received.extend_from_slice(&buf[..len]);
+        bob_writer.write_all(&buf[..len]).await.unwrap();
+    }
+    bob_writer.shutdown().await.unwrap();
+
+    alice_writes.await.unwrap();
+    assert_eq!(received, data);
+    assert_eq!(alice_reads.await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn byte_stream_and_messages() {
+    let (mut alice, mut bob) = duplex_connection_pair().await;
+
+    // Messages can be read as bytes
+    let ping = PingRequest {
+        msg: vec![0x42; 2000],
+    };
+    let (_, read_result) = tokio::join!(alice.write_msg(ping.clone()), async {
+        let mut bytes = vec![0u8; ping.encoded_len() + 2];
+        bob.read_exact(&mut bytes).await.map(|_| bytes)
+    });
+    assert_eq!(read_result.unwrap(), ping.encode_length_delimited_to_vec());
+
+    // Several messages written at once, straddling frame boundaries, can be read individually
+    let pongs: Vec<_> = [1, 1500, 0, 700]
+        .into_iter()
+        .map(|len| PongResponse {
+            msg: vec![0x24; len],
+        })
+        .collect();
+    let bytes: Vec<u8> = pongs
+        .iter()
+        .flat_map(|pong| pong.encode_length_delimited_to_vec())
+        .collect();
+
+    let (write_result, received) = tokio::join!(
+        async {
+            alice.write_all(&bytes).await?;
+            alice.flush().await
+        },
+        async {
+            let mut received = Vec::new();
+            for _ in 0..pongs.len() {
+                received.push(bob.read_msg::<PongResponse>().await.unwrap());
+            }
+            received
+        }
+    );
+    write_result.unwrap();
+    assert_eq!(received, pongs);
+}
+
+#[cfg(feature = "codec")]
+#[tokio::test]
+async fn framed() {
+    use futures_util::{SinkExt, StreamExt};
+
+    let (alice, bob) = duplex_connection_pair().await;
+    let mut alice = alice.framed::<PingRequest>();
+    let (bob_reader, bob_writer) = bob.split();
+    let mut bob_stream = bob_reader.framed::<PingRequest>();
+    let mut bob_sink = bob_writer.framed::<PingRequest>();
+
+    // Messages straddling frame boundaries, more than fit in the pipe's buffer at once
+    let msgs: Vec<_> = [0, 1, 1500, 5000, 1023]
+        .into_iter()
+        .map(|len| PingRequest {
+            msg: vec![0x42; len],
+        })
+        .collect();
+
+    let (send_result, received) = tokio::join!(
+        async {
+            for msg in &msgs {
+                alice.feed(msg.clone()).await?;
+            }
+            alice.flush().await
+        },
+        bob_stream.by_ref().take(msgs.len()).collect::<Vec<_>>()
+    );
+    send_result.unwrap();
+    let received: Vec<_> = received.into_iter().map(Result::unwrap).collect();
+    assert_eq!(received, msgs);
+
+    // Echo the messages back
+    tokio::spawn(async move {
+        for msg in received {
+            bob_sink.send(msg).await.unwrap();
+        }
+        bob_sink.close().await.unwrap();
+    });
+
+    let echoed: Vec<_> = alice.map(Result::unwrap).collect().await;
+    assert_eq!(echoed, msgs);
+}
+
+#[cfg(feature = "codec")]
+#[tokio::test]
+async fn framed_cancellation_safety() {
+    use futures_util::StreamExt;
+
+    let (alice, mut bob) = duplex_connection_pair().await;
+    let mut alice = alice.framed::<PingRequest>();
+
+    let msg = PingRequest {
+        msg: vec![0x42; 5000],
+    };
+    let bytes = msg.encode_length_delimited_to_vec();
+
+    // Trickle the message in, so reads of it are repeatedly interrupted
+    tokio::spawn(async move {
+        for chunk in bytes.chunks(700) {
+            bob.write_all(chunk).await.unwrap();
+            bob.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    });
+
+    let mut timeouts = 0;
+    let received = loop {
+        match tokio::time::timeout(Duration::from_millis(1), alice.next()).await {
+            Ok(result) => break result.unwrap().unwrap(),
+            Err(_) => timeouts += 1,
+        }
+    };
+
+    assert!(timeouts > 0);
+    assert_eq!(received, msg);
+}
+
 // Get a temporary path for the Unix domain socket
 fn sock_temp_path() -> std::path::PathBuf {
     std::env::temp_dir().join(format!(
@@ -1,6 +1,6 @@
 //! Common functionality shared between `SecretConnection` and `AsyncSecretConnection` tests.
 
-use cometbft_p2p::{IdentitySecret, ReadMsg, SecretConnection, TryCloneIo, WriteMsg};
+use cometbft_p2p::{Identity, ReadMsg, SecretConnection, TryCloneIo, WriteMsg};
 use prost::Message;
 use std::io::{Read, Write};
 
@@ -14,7 +14,7 @@ impl<Io> TestServer<Io>
 where
     Io: Read + Write + Send + Sync + TryCloneIo + 'static,
 {
-    pub fn run(io: Io, sk: IdentitySecret, num_requests: usize) {
+    pub fn run<I: Identity>(io: Io, sk: I, num_requests: usize) {
         let mut server = TestServer {
             conn: SecretConnection::new(io, &sk).unwrap(),
         };
@@ -0,0 +1,167 @@
+//! MConnection integration tests.
+
+#![cfg(all(feature = "async", unix))]
+
+use cometbft_p2p::{
+    AsyncSecretConnection, ChannelDescriptor, Error, IdentitySecret, MConnection,
+    MConnectionConfig, rand_core::OsRng,
+};
+use std::time::Duration;
+use tokio::net::UnixStream;
+
+/// Consensus "state" channel ID.
+const STATE_CHANNEL: u8 = 0x20;
+
+/// Mempool channel ID.
+const MEMPOOL_CHANNEL: u8 = 0x30;
+
+/// Open a pair of secret connections to each other.
+async fn secret_connection_pair() -> (
+    AsyncSecretConnection<UnixStream>,
+    AsyncSecretConnection<UnixStream>,
+) {
+    let alice_sk = IdentitySecret::generate(&mut OsRng);
+    let bob_sk = IdentitySecret::generate(&mut OsRng);
+    let (sock_a, sock_b) = UnixStream::pair().unwrap();
+
+    tokio::try_join!(
+        AsyncSecretConnection::new(sock_a, &alice_sk),
+        AsyncSecretConnection::new(sock_b, &bob_sk)
+    )
+    .unwrap()
+}
+
+fn channels() -> [ChannelDescriptor; 2] {
+    [
+        ChannelDescriptor::new(STATE_CHANNEL, 6),
+        ChannelDescriptor::new(MEMPOOL_CHANNEL, 1),
+    ]
+}
+
+fn fast_pings() -> MConnectionConfig {
+    MConnectionConfig {
+        ping_interval: Duration::from_millis(20),
+        pong_timeout: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn send_and_receive() {
+    let (conn_a, conn_b) = secret_connection_pair().await;
+    let (alice, alice_tx, _alice_rx) = MConnection::new(conn_a, channels(), fast_pings()).unwrap();
+    let (bob, _bob_tx, mut bob_rx) = MConnection::new(conn_b, channels(), fast_pings()).unwrap();
+    tokio::spawn(alice.run());
+    tokio::spawn(bob.run());
+
+    // Spans many packets and frames
+    let large_msg: Vec<u8> = (0..5000u32).map(|n| n as u8).collect();
+
+    alice_tx
+        .send(MEMPOOL_CHANNEL, large_msg.clone())
+        .await
+        .unwrap();
+    alice_tx
+        .send(STATE_CHANNEL, b"hello".to_vec())
+        .await
+        .unwrap();
+    alice_tx.send(STATE_CHANNEL, Vec::new()).await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.push(bob_rx.recv().await.unwrap());
+    }
+
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            (STATE_CHANNEL, Vec::new()),
+            (STATE_CHANNEL, b"hello".to_vec()),
+            (MEMPOOL_CHANNEL, large_msg)
+        ]
+    );
+
+    // Outlive several ping intervals and pong timeouts
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    alice_tx.send(STATE_CHANNEL, b"still here").await.unwrap();
+    assert_eq!(
+        bob_rx.recv().await.unwrap(),
+        (STATE_CHANNEL, b"still here".to_vec())
+    );
+}
+
+#[tokio::test]
+async fn pong_timeout() {
+    let (conn_a, _conn_b) = secret_connection_pair().await;
+    let (alice, _alice_tx, _alice_rx) = MConnection::new(conn_a, channels(), fast_pings()).unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), alice.run())
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(Error::PongTimeout)));
+}
+
+#[tokio::test]
+async fn unknown_channel() {
+    let (conn_a, conn_b) = secret_connection_pair().await;
+    let (alice, alice_tx, _alice_rx) = MConnection::new(conn_a, channels(), fast_pings()).unwrap();
+    let (bob, _bob_tx, _bob_rx) = MConnection::new(
+        conn_b,
+        [ChannelDescriptor::new(STATE_CHANNEL, 1)],
+        fast_pings(),
+    )
+    .unwrap();
+    tokio::spawn(alice.run());
+
+    assert!(matches!(
+        alice_tx.send(0x40, b"hello".to_vec()).await,
+        Err(Error::UnknownChannel { channel_id: 0x40 })
+    ));
+
+    alice_tx
+        .send(MEMPOOL_CHANNEL, b"hello".to_vec())
+        .await
+        .unwrap();
+    assert!(matches!(
+        bob.run().await,
+        Err(Error::UnknownChannel { channel_id: 0x30 })
+    ));
+}
+
+#[tokio::test]
+async fn invalid_channel_descriptors() {
+    let (conn_a, conn_b) = secret_connection_pair().await;
+
+    let duplicate = [
+        ChannelDescriptor::new(STATE_CHANNEL, 1),
+        ChannelDescriptor::new(STATE_CHANNEL, 2),
+    ];
+    assert!(matches!(
+        MConnection::new(conn_a, duplicate, MConnectionConfig::default()),
+        Err(Error::ChannelDescriptor {
+            channel_id: STATE_CHANNEL
+        })
+    ));
+
+    let zero_priority = [ChannelDescriptor::new(MEMPOOL_CHANNEL, 0)];
+    assert!(matches!(
+        MConnection::new(conn_b, zero_priority, MConnectionConfig::default()),
+        Err(Error::ChannelDescriptor {
+            channel_id: MEMPOOL_CHANNEL
+        })
+    ));
+}
+
+#[tokio::test]
+async fn stops_when_receiver_dropped() {
+    let (conn_a, _conn_b) = secret_connection_pair().await;
+    let (alice, alice_tx, alice_rx) = MConnection::new(conn_a, channels(), fast_pings()).unwrap();
+    drop(alice_rx);
+
+    assert!(alice.run().await.is_ok());
+    assert!(matches!(
+        alice_tx.send(STATE_CHANNEL, b"hello".to_vec()).await,
+        Err(Error::Closed)
+    ));
+}
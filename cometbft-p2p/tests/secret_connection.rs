@@ -4,10 +4,13 @@
 
 mod common;
 
-use cometbft_p2p::{IdentitySecret, ReadMsg, SecretConnection, WriteMsg};
+use cometbft_p2p::{
+    Error, IdentitySecret, PeerId, PublicKey, ReadMsg, SecretConnection, SecretConnectionBuilder,
+    WriteMsg,
+};
 use common::{PingRequest, PongResponse, TestServer};
 use proptest::{collection, prelude::*};
-use std::{os::unix::net::UnixStream, thread};
+use std::{os::unix::net::UnixStream, thread, time::Duration};
 
 /// Maximum example message length to generate.
 ///
@@ -48,3 +51,99 @@ proptest! {
         server_handle.join().unwrap();
     }
 }
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn secp256k1_integration_test() {
+    use cometbft_p2p::{PublicKey, Secp256k1IdentitySecret};
+    use rand_core::OsRng;
+
+    let alice_sk = Secp256k1IdentitySecret::generate(&mut OsRng);
+    let bob_sk = Secp256k1IdentitySecret::generate(&mut OsRng);
+    let bob_pk = PublicKey::from(&bob_sk);
+
+    let (sock_a, sock_b) = UnixStream::pair().unwrap();
+    let server_handle = thread::spawn(move || TestServer::run(sock_b, bob_sk, NUM_REQUESTS));
+
+    let mut conn = SecretConnection::new(sock_a, &alice_sk).unwrap();
+    assert_eq!(conn.peer_public_key(), &bob_pk);
+    assert_eq!(conn.peer_public_key().secp256k1(), bob_pk.secp256k1());
+
+    for _ in 0..NUM_REQUESTS {
+        let example_msg = vec![0x42; MAX_MSG_LEN];
+        conn.write_msg(&PingRequest {
+            msg: example_msg.clone(),
+        })
+        .unwrap();
+
+        let resp: PongResponse = conn.read_msg().unwrap();
+        assert_eq!(&resp.msg, &example_msg);
+    }
+
+    server_handle.join().unwrap();
+}
+
+#[test]
+fn builder_handshake_timeout() {
+    let alice_sk = IdentitySecret::from_bytes(&[1; 32]);
+    let (sock_a, _sock_b) = UnixStream::pair().unwrap();
+
+    let result = SecretConnectionBuilder::new()
+        .with_handshake_timeout(Duration::from_millis(50))
+        .build(sock_a, &alice_sk);
+    assert!(matches!(result, Err(Error::HandshakeTimeout)));
+}
+
+#[test]
+fn builder_authorizes_peer() {
+    let alice_sk = IdentitySecret::from_bytes(&[1; 32]);
+    let bob_sk = IdentitySecret::from_bytes(&[2; 32]);
+    let bob_peer_id = PublicKey::from(&bob_sk).peer_id();
+    let other_peer_id = PeerId([3; 20]);
+
+    let handshake = |builder: SecretConnectionBuilder| {
+        let bob_sk = bob_sk.clone();
+        let (sock_a, sock_b) = UnixStream::pair().unwrap();
+        let bob = thread::spawn(move || SecretConnection::new(sock_b, &bob_sk));
+        let result = builder.build(sock_a, &alice_sk);
+        let _ = bob.join().unwrap();
+        result
+    };
+
+    assert!(handshake(SecretConnectionBuilder::new().with_expected_peer_id(bob_peer_id)).is_ok());
+    assert!(matches!(
+        handshake(SecretConnectionBuilder::new().with_expected_peer_id(other_peer_id)),
+        Err(Error::VerifyPeer(_))
+    ));
+
+    assert!(
+        handshake(SecretConnectionBuilder::new().with_allowed_peers([other_peer_id, bob_peer_id]))
+            .is_ok()
+    );
+    assert!(matches!(
+        handshake(SecretConnectionBuilder::new().with_allowed_peers([other_peer_id])),
+        Err(Error::PeerNotAllowed { peer_id }) if peer_id == bob_peer_id
+    ));
+}
+
+#[test]
+fn builder_limits_msg_len() {
+    let alice_sk = IdentitySecret::from_bytes(&[1; 32]);
+    let bob_sk = IdentitySecret::from_bytes(&[2; 32]);
+    let (sock_a, sock_b) = UnixStream::pair().unwrap();
+
+    let bob = thread::spawn(move || {
+        let mut conn = SecretConnection::new(sock_b, &bob_sk).unwrap();
+        conn.write_msg(&PingRequest { msg: vec![0; 2048] }).unwrap();
+    });
+
+    let mut conn = SecretConnectionBuilder::new()
+        .with_max_msg_len(1024)
+        .with_read_timeout(Duration::from_secs(5))
+        .build(sock_a, &alice_sk)
+        .unwrap();
+
+    let result: Result<PingRequest, _> = conn.read_msg();
+    assert!(matches!(result, Err(Error::MessageSize { .. })));
+    bob.join().unwrap();
+}
@@ -0,0 +1,89 @@
+//! Error types.
+
+use iqkms::{
+    tonic,
+    types::ethereum::{ChainId, H160},
+};
+use std::fmt;
+
+/// `Result` type with the `ethers-iqkms` crate's [`Error`] type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Chain ID of the address doesn't match the signer's chain ID.
+    ChainIdMismatch {
+        /// Chain ID of the address.
+        address: ChainId,
+
+        /// Chain ID the signer was configured with.
+        signer: ChainId,
+    },
+
+    /// Error encoding EIP-712 typed data.
+    Eip712 {
+        /// Reason why encoding failed.
+        reason: String,
+    },
+
+    /// Signature returned by iqkms was malformed.
+    SignatureMalformed,
+
+    /// Signature returned by iqkms does not recover to the signer's address.
+    SignatureInvalid {
+        /// Address of the signer.
+        expected: H160,
+
+        /// Address recovered from the signature.
+        recovered: H160,
+    },
+
+    /// Error communicating with iqkms.
+    Transport(iqkms::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ChainIdMismatch { address, signer } => write!(
+                f,
+                "address chain ID ({}) does not match signer chain ID ({})",
+                address, signer
+            ),
+            Error::Eip712 { reason } => write!(f, "EIP-712 encoding error: {}", reason),
+            Error::SignatureMalformed => f.write_str("malformed signature returned by iqkms"),
+            Error::SignatureInvalid {
+                expected,
+                recovered,
+            } => write!(
+                f,
+                "signature recovered to {:?} (expected {:?})",
+                recovered, expected
+            ),
+            Error::Transport(err) => write!(f, "iqkms error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<iqkms::Error> for Error {
+    fn from(err: iqkms::Error) -> Error {
+        Error::Transport(err)
+    }
+}
+
+impl From<tonic::transport::Error> for Error {
+    fn from(err: tonic::transport::Error) -> Error {
+        iqkms::Error::from(err).into()
+    }
+}
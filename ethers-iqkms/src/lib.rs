@@ -16,16 +16,20 @@
     unused_lifetimes,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
 
+mod error;
+
+pub use crate::error::{Error, Result};
 pub use ethers_core::types;
 
 use ethers_core::types::{
-    Signature,
+    RecoveryMessage, Signature,
     transaction::{eip712::Eip712, eip2718::TypedTransaction},
 };
 use ethers_signers::Signer;
 use iqkms::{
-    Error, StdError, proto,
+    StdError, proto,
     tokio::sync::Mutex,
     tonic,
     types::{
@@ -50,7 +54,7 @@ pub struct IqkmsSigner {
 
 impl IqkmsSigner {
     /// Create a new signer, connecting to the given endpoint.
-    pub async fn connect<D>(dst: D, address: Address, chain_id: ChainId) -> iqkms::Result<Self>
+    pub async fn connect<D>(dst: D, address: Address, chain_id: ChainId) -> Result<Self>
     where
         D: TryInto<tonic::transport::Endpoint>,
         D::Error: Into<StdError>,
@@ -65,10 +69,14 @@ impl IqkmsSigner {
         client: iqkms::ethereum::SignerClient,
         address: Address,
         chain_id: ChainId,
-    ) -> iqkms::Result<Self> {
+    ) -> Result<Self> {
         if let Some(id) = address.chain_id {
-            // TODO(tarcieri): real error handling
-            assert_eq!(id, chain_id);
+            if id != chain_id {
+                return Err(Error::ChainIdMismatch {
+                    address: id,
+                    signer: chain_id,
+                });
+            }
         }
 
         Ok(Self {
@@ -79,19 +87,53 @@ impl IqkmsSigner {
     }
 
     /// Sign a digest with this signer's key and add the eip155 `v` value
-    /// corresponding to the `chain_id` this signer was initialized with.
+    /// corresponding to the given `chain_id`.
     #[instrument(err)]
-    async fn sign_digest_with_eip155(&self, digest: H256) -> iqkms::Result<Signature> {
-        let mut client = self.client.lock().await;
-        let signature = client
-            .sign_digest_with_eip155(self.address, digest, self.chain_id)
+    async fn sign_digest_with_eip155(&self, digest: H256, chain_id: ChainId) -> Result<Signature> {
+        let signature = self
+            .client
+            .lock()
+            .await
+            .sign_digest_with_eip155(self.address, digest, chain_id)
             .await?;
 
-        parse_signature_proto(signature)
+        self.verify(digest, parse_signature_proto(signature)?)
+    }
+
+    /// Sign a digest with this signer's key without applying EIP-155.
+    #[instrument(err)]
+    async fn sign_digest(&self, digest: H256) -> Result<Signature> {
+        let signature = self
+            .client
+            .lock()
+            .await
+            .sign_digest(self.address, digest)
+            .await?;
+
+        self.verify(digest, parse_signature_proto(signature)?)
+    }
+
+    /// Ensure the given signature over the given digest recovers to this
+    /// signer's address.
+    fn verify(&self, digest: H256, signature: Signature) -> Result<Signature> {
+        let expected = H160::from(self.address);
+        let recovered = signature
+            .recover(RecoveryMessage::Hash(digest))
+            .map_err(|_| Error::SignatureMalformed)?;
+
+        if recovered == expected {
+            Ok(signature)
+        } else {
+            Err(Error::SignatureInvalid {
+                expected,
+                recovered,
+            })
+        }
     }
 }
 
-fn parse_signature_proto(signature: proto::ethereum::Signature) -> iqkms::Result<Signature> {
+/// Parse a signature returned by iqkms.
+fn parse_signature_proto(signature: proto::ethereum::Signature) -> Result<Signature> {
     if signature.r.len() == 32 && signature.s.len() == 32 {
         Ok(Signature {
             r: U256::from_big_endian(&signature.r),
@@ -99,7 +141,7 @@ fn parse_signature_proto(signature: proto::ethereum::Signature) -> iqkms::Result
             v: signature.v,
         })
     } else {
-        todo!()
+        Err(Error::SignatureMalformed)
     }
 }
 
@@ -108,28 +150,33 @@ impl Signer for IqkmsSigner {
     type Error = Error;
 
     #[instrument(err, skip(msg))]
-    async fn sign_message<S>(&self, msg: S) -> iqkms::Result<Signature>
+    async fn sign_message<S>(&self, msg: S) -> Result<Signature>
     where
         S: AsRef<[u8]> + Send + Sync,
     {
         let digest = H256::from(Keccak256::digest(msg).as_ref());
-        self.sign_digest_with_eip155(digest).await
+        self.sign_digest_with_eip155(digest, self.chain_id).await
     }
 
     #[instrument(err)]
-    async fn sign_transaction(&self, _tx: &TypedTransaction) -> iqkms::Result<Signature> {
-        todo!()
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature> {
+        // RLP (for sighash) must have the same chain ID as `v` in the signature
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        self.sign_digest_with_eip155(tx.sighash(), chain_id).await
     }
 
-    async fn sign_typed_data<T>(&self, payload: &T) -> iqkms::Result<Signature>
+    async fn sign_typed_data<T>(&self, payload: &T) -> Result<Signature>
     where
         T: Eip712 + Send + Sync,
     {
-        // TODO(tarcieri): don't panic
-        let digest = payload.encode_eip712().expect("EIP-712 encoding error");
-        let mut client = self.client.lock().await;
-        let signature = client.sign_digest(self.address, H256::from(digest)).await?;
-        parse_signature_proto(signature)
+        let digest = payload.encode_eip712().map_err(|e| Error::Eip712 {
+            reason: e.to_string(),
+        })?;
+
+        self.sign_digest(H256::from(digest)).await
     }
 
     fn address(&self) -> H160 {
@@ -156,3 +203,92 @@ impl fmt::Debug for IqkmsSigner {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, IqkmsSigner, parse_signature_proto};
+    use iqkms::{
+        proto, tonic,
+        types::ethereum::{Address, H256, U256},
+    };
+
+    /// Address of the signing key from the EIP-155 example.
+    const ADDRESS: &str = "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F";
+
+    /// Signing hash from the EIP-155 example.
+    const DIGEST: [u8; 32] = [
+        0xda, 0xf5, 0xa7, 0x79, 0xae, 0x97, 0x2f, 0x97, 0x21, 0x97, 0x30, 0x3d, 0x7b, 0x57, 0x47,
+        0x46, 0xc7, 0xef, 0x83, 0xea, 0xda, 0xc0, 0xf2, 0x79, 0x1a, 0xd2, 0x3d, 0xb9, 0x2e, 0x4c,
+        0x8e, 0x53,
+    ];
+
+    /// Create a signer whose client connects lazily (i.e. never, in these tests).
+    async fn signer(address: Address, chain_id: u64) -> Result<IqkmsSigner, Error> {
+        let channel = tonic::transport::Endpoint::from_static("http://[::1]:1").connect_lazy();
+        let client = proto::ethereum::signer_client::SignerClient::new(channel);
+        IqkmsSigner::new(client.into(), address, chain_id).await
+    }
+
+    /// Signature from the EIP-155 example.
+    fn eip155_signature() -> proto::ethereum::Signature {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+
+        U256::from_dec_str(
+            "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+        )
+        .unwrap()
+        .to_big_endian(&mut r);
+
+        U256::from_dec_str(
+            "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+        )
+        .unwrap()
+        .to_big_endian(&mut s);
+
+        proto::ethereum::Signature {
+            r: r.to_vec(),
+            s: s.to_vec(),
+            v: 37,
+        }
+    }
+
+    #[tokio::test]
+    async fn chain_id_mismatch() {
+        let mut address = ADDRESS.parse::<Address>().unwrap();
+        address.chain_id = Some(5);
+
+        assert_eq!(
+            signer(address, 1).await.unwrap_err(),
+            Error::ChainIdMismatch {
+                address: 5,
+                signer: 1
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_signature() {
+        let mut signature = eip155_signature();
+        signature.r.pop();
+        assert_eq!(
+            parse_signature_proto(signature).unwrap_err(),
+            Error::SignatureMalformed
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_signature() {
+        let signature = parse_signature_proto(eip155_signature()).unwrap();
+
+        let valid_signer = signer(ADDRESS.parse().unwrap(), 1).await.unwrap();
+        assert!(valid_signer.verify(H256::from(DIGEST), signature).is_ok());
+
+        let other = "0x27b1fdb04752bbc536007a920d24acb045561c26";
+        let other_signer = signer(other.parse().unwrap(), 1).await.unwrap();
+        assert!(matches!(
+            other_signer.verify(H256::from(DIGEST), signature),
+            Err(Error::SignatureInvalid { .. })
+        ));
+    }
+}
@@ -7,6 +7,10 @@
 
 pub use ::digest::*;
 
+#[cfg(feature = "ripemd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ripemd")))]
+pub use ripemd;
+
 #[cfg(feature = "sha2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
 pub use sha2;
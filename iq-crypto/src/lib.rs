@@ -24,6 +24,7 @@
     unused_lifetimes,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
 
 #[cfg(feature = "alloc")]
 #[allow(unused_extern_crates)]
@@ -1,9 +1,17 @@
 //! Digital signature algorithms.
 
+#[cfg(feature = "bls12_381")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+pub mod bls12_381;
+
 #[cfg(feature = "ecdsa")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ecdsa")))]
 pub mod ecdsa;
 
+#[cfg(feature = "ed25519")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+pub mod ed25519;
+
 mod algorithm;
 
 pub use self::algorithm::Algorithm;
@@ -4,6 +4,11 @@
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 #[non_exhaustive]
 pub enum Algorithm {
+    /// BLS signatures with BLS12-381 (minimal-pubkey-size variant).
+    #[cfg(feature = "bls12_381")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+    Bls12381,
+
     /// ECDSA with NIST P-256.
     #[cfg(feature = "nistp256")]
     #[cfg_attr(docsrs, doc(cfg(feature = "nistp256")))]
@@ -0,0 +1,260 @@
+//! BLS12-381 signature support.
+//!
+//! Implements the "minimal-pubkey-size" variant of BLS signatures as used by
+//! Ethereum consensus: verifying keys are elements of G1 (48-bytes compressed)
+//! and signatures are elements of G2 (96-bytes compressed), with messages
+//! hashed to G2 using the proof-of-possession ciphersuite.
+
+use crate::{Error, Result};
+use bls12_381::{
+    G1Affine, G2Affine, G2Projective, Scalar,
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing,
+};
+use core::fmt;
+use signature::{Signer, Verifier};
+use zeroize::Zeroize;
+
+/// Domain separation tag for the proof-of-possession ciphersuite.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// BLS12-381 signing key.
+pub struct SigningKey {
+    scalar: Scalar,
+}
+
+impl SigningKey {
+    /// Size of a serialized signing key in bytes.
+    pub const BYTE_SIZE: usize = 32;
+
+    /// Initialize from a raw scalar value (big endian).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut repr: [u8; Self::BYTE_SIZE] = bytes.try_into().map_err(|_| Error)?;
+        repr.reverse();
+
+        let scalar = Option::<Scalar>::from(Scalar::from_bytes(&repr));
+        repr.zeroize();
+
+        match scalar {
+            Some(scalar) if scalar != Scalar::zero() => Ok(Self { scalar }),
+            _ => Err(Error),
+        }
+    }
+
+    /// Get the verifying key that corresponds to this signing key.
+    #[allow(clippy::arithmetic_side_effects)] // scalar multiplication
+    pub fn verifying_key(&self) -> VerifyingKey {
+        let point = G1Affine::from(G1Affine::generator() * self.scalar);
+        VerifyingKey {
+            bytes: point.to_compressed(),
+        }
+    }
+}
+
+impl Signer<Signature> for SigningKey {
+    #[allow(clippy::arithmetic_side_effects)] // scalar multiplication
+    fn try_sign(&self, msg: &[u8]) -> signature::Result<Signature> {
+        let point = G2Affine::from(hash_to_g2(msg) * self.scalar);
+        Ok(Signature {
+            bytes: point.to_compressed(),
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for SigningKey {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.scalar.zeroize();
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("verifying_key", &self.verifying_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// BLS12-381 verifying key: a compressed G1 point.
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct VerifyingKey {
+    bytes: [u8; Self::BYTE_SIZE],
+}
+
+impl VerifyingKey {
+    /// Size of a serialized verifying key in bytes.
+    pub const BYTE_SIZE: usize = 48;
+
+    /// Parse a compressed G1 point, rejecting the identity.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; Self::BYTE_SIZE] = bytes.try_into().map_err(|_| Error)?;
+        let point = Option::<G1Affine>::from(G1Affine::from_compressed(&bytes)).ok_or(Error)?;
+
+        if bool::from(point.is_identity()) {
+            return Err(Error);
+        }
+
+        Ok(Self { bytes })
+    }
+
+    /// Serialize this verifying key as a compressed G1 point.
+    pub fn to_bytes(&self) -> [u8; Self::BYTE_SIZE] {
+        self.bytes
+    }
+
+    /// Decompress the underlying G1 point.
+    fn to_point(self) -> Result<G1Affine> {
+        Option::from(G1Affine::from_compressed(&self.bytes)).ok_or(Error)
+    }
+}
+
+impl AsRef<[u8]> for VerifyingKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl TryFrom<&[u8]> for VerifyingKey {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl Verifier<Signature> for VerifyingKey {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
+        let public_key = self.to_point().map_err(|_| signature::Error::new())?;
+        let sig = signature.to_point().map_err(|_| signature::Error::new())?;
+        let msg_point = G2Affine::from(hash_to_g2(msg));
+
+        if pairing(&public_key, &msg_point) == pairing(&G1Affine::generator(), &sig) {
+            Ok(())
+        } else {
+            Err(signature::Error::new())
+        }
+    }
+}
+
+impl fmt::Debug for VerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VerifyingKey(0x")?;
+        for byte in &self.bytes {
+            write!(f, "{byte:02x}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// BLS12-381 signature: a compressed G2 point.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Signature {
+    bytes: [u8; Self::BYTE_SIZE],
+}
+
+impl Signature {
+    /// Size of a serialized signature in bytes.
+    pub const BYTE_SIZE: usize = 96;
+
+    /// Serialize this signature as a compressed G2 point.
+    pub fn to_bytes(&self) -> [u8; Self::BYTE_SIZE] {
+        self.bytes
+    }
+
+    /// Decompress the underlying G2 point.
+    fn to_point(self) -> Result<G2Affine> {
+        Option::from(G2Affine::from_compressed(&self.bytes)).ok_or(Error)
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl signature::Signature for Signature {
+    fn from_bytes(bytes: &[u8]) -> signature::Result<Self> {
+        let bytes: [u8; Self::BYTE_SIZE] = bytes.try_into().map_err(|_| signature::Error::new())?;
+        let sig = Self { bytes };
+        sig.to_point().map_err(|_| signature::Error::new())?;
+        Ok(sig)
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        <Self as signature::Signature>::from_bytes(bytes).map_err(Into::into)
+    }
+}
+
+/// Hash a message to a G2 point using the proof-of-possession ciphersuite.
+fn hash_to_g2(msg: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(msg, DST)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Signature, SigningKey, VerifyingKey};
+    use hex_literal::hex;
+    use signature::{Signer, Verifier};
+
+    // Test vectors cross-checked against `blst`
+    const SECRET_KEY: [u8; 32] =
+        hex!("263dbd792f5b1be47ed85f8938c0f29586af0d3ac7b977f21c278fe1462040e3");
+    const MESSAGE: [u8; 32] = [0x56; 32];
+    const SIGNATURE: [u8; 96] = hex!(
+        "882730e5d03f6b42c3abc26d3372625034e1d871b65a8a6b900a56dae22da98a"
+        "bbe1b68f85e49fe7652a55ec3d0591c20767677e33e5cbb1207315c41a9ac03b"
+        "e39c2e7668edc043d6cb1d9fd93033caa8a1c5b0e84bedaeb6c64972503a43eb"
+    );
+
+    #[test]
+    fn verifying_key() {
+        let sk = SigningKey::from_bytes(&hex!(
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            sk.verifying_key().to_bytes(),
+            hex!(
+                "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27"
+                "f4ae4040902382ae2910c15e2b420d07"
+            )
+        );
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let sk = SigningKey::from_bytes(&SECRET_KEY).unwrap();
+        let sig = sk.sign(&MESSAGE);
+        assert_eq!(sig.to_bytes(), SIGNATURE);
+
+        let vk = VerifyingKey::from_bytes(sk.verifying_key().as_ref()).unwrap();
+        assert!(vk.verify(&MESSAGE, &sig).is_ok());
+        assert!(vk.verify(&[0u8; 32], &sig).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(SigningKey::from_bytes(&[0u8; 32]).is_err());
+        assert!(SigningKey::from_bytes(&[0xffu8; 32]).is_err());
+        assert!(SigningKey::from_bytes(&SECRET_KEY[..31]).is_err());
+
+        let mut identity = [0u8; 48];
+        identity[0] = 0xc0;
+        assert!(VerifyingKey::from_bytes(&identity).is_err());
+        assert!(Signature::try_from(&[0u8; 96][..]).is_err());
+    }
+}
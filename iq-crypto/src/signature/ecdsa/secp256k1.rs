@@ -56,19 +56,13 @@ impl TryFrom<&[u8]> for SigningKey {
 
 impl PrehashSigner<Signature> for SigningKey {
     fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<Signature> {
-        self.inner.sign_prehash(prehash)
+        PrehashSigner::<Signature>::sign_prehash(&*self.inner, prehash)
     }
 }
 
 impl PrehashSigner<RecoverableSignature> for SigningKey {
     fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<RecoverableSignature> {
-        let prehash = <[u8; 32]>::try_from(prehash).map_err(|_| signature::Error::new())?;
-        let sig = PrehashSigner::<Signature>::sign_prehash(self, &prehash)?;
-        RecoverableSignature::from_digest_bytes_trial_recovery(
-            &self.verifying_key(),
-            prehash.as_ref().into(),
-            &sig,
-        )
+        PrehashSigner::<RecoverableSignature>::sign_prehash(&*self.inner, prehash)
     }
 }
 
@@ -81,14 +75,17 @@ impl fmt::Debug for SigningKey {
 }
 
 /// ECDSA/secp256k1 signer
-pub trait Secp256k1Signer: PrehashSigner<Signature> {
+///
+/// Signers must be able to produce recoverable signatures, ideally by
+/// computing the recovery ID at signing time rather than via trial recovery.
+pub trait Secp256k1Signer: PrehashSigner<Signature> + PrehashSigner<RecoverableSignature> {
     /// Get the ECDSA verifying key for this signer
     fn verifying_key(&self) -> VerifyingKey;
 }
 
 impl<T> Secp256k1Signer for T
 where
-    T: PrehashSigner<Signature>,
+    T: PrehashSigner<Signature> + PrehashSigner<RecoverableSignature>,
     VerifyingKey: for<'a> From<&'a T>,
 {
     fn verifying_key(&self) -> VerifyingKey {
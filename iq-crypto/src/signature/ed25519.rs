@@ -0,0 +1,213 @@
+//! Ed25519 signature support.
+//!
+//! Implements Ed25519 as specified in RFC 8032, with strict verification
+//! which rejects small order keys and non-canonical signatures.
+
+use crate::{Error, Result};
+use core::fmt;
+use ed25519_dalek::Signer as _;
+use rand_core::{CryptoRng, RngCore};
+use signature::{Signer, Verifier};
+use zeroize::Zeroize;
+
+/// Ed25519 signing key.
+pub struct SigningKey {
+    inner: ed25519_dalek::SigningKey,
+}
+
+impl SigningKey {
+    /// Size of a serialized signing key (i.e. seed) in bytes.
+    pub const BYTE_SIZE: usize = 32;
+
+    /// Generate a random signing key.
+    pub fn generate(rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        let mut seed = [0u8; Self::BYTE_SIZE];
+        rng.fill_bytes(&mut seed);
+
+        let inner = ed25519_dalek::SigningKey::from_bytes(&seed);
+        seed.zeroize();
+        Self { inner }
+    }
+
+    /// Initialize from a 32-byte seed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let seed = bytes.try_into().map_err(|_| Error)?;
+
+        Ok(Self {
+            inner: ed25519_dalek::SigningKey::from_bytes(seed),
+        })
+    }
+
+    /// Get the verifying key that corresponds to this signing key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey {
+            bytes: self.inner.verifying_key().to_bytes(),
+        }
+    }
+}
+
+impl Signer<Signature> for SigningKey {
+    fn try_sign(&self, msg: &[u8]) -> signature::Result<Signature> {
+        Ok(Signature {
+            bytes: self.inner.sign(msg).to_bytes(),
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for SigningKey {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("verifying_key", &self.verifying_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Ed25519 verifying key: a compressed Edwards point.
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct VerifyingKey {
+    bytes: [u8; Self::BYTE_SIZE],
+}
+
+impl VerifyingKey {
+    /// Size of a serialized verifying key in bytes.
+    pub const BYTE_SIZE: usize = 32;
+
+    /// Parse a compressed Edwards point, rejecting small order points.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; Self::BYTE_SIZE] = bytes.try_into().map_err(|_| Error)?;
+        let point = ed25519_dalek::VerifyingKey::from_bytes(&bytes).map_err(|_| Error)?;
+
+        if point.is_weak() {
+            return Err(Error);
+        }
+
+        Ok(Self { bytes })
+    }
+
+    /// Serialize this verifying key as a compressed Edwards point.
+    pub fn to_bytes(&self) -> [u8; Self::BYTE_SIZE] {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for VerifyingKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl TryFrom<&[u8]> for VerifyingKey {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl Verifier<Signature> for VerifyingKey {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
+        let public_key = ed25519_dalek::VerifyingKey::from_bytes(&self.bytes)
+            .map_err(|_| signature::Error::new())?;
+
+        public_key
+            .verify_strict(msg, &ed25519_dalek::Signature::from_bytes(&signature.bytes))
+            .map_err(|_| signature::Error::new())
+    }
+}
+
+impl fmt::Debug for VerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VerifyingKey(0x")?;
+        for byte in &self.bytes {
+            write!(f, "{byte:02x}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Ed25519 signature.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Signature {
+    bytes: [u8; Self::BYTE_SIZE],
+}
+
+impl Signature {
+    /// Size of a serialized signature in bytes.
+    pub const BYTE_SIZE: usize = 64;
+
+    /// Serialize this signature as bytes.
+    pub fn to_bytes(&self) -> [u8; Self::BYTE_SIZE] {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl signature::Signature for Signature {
+    fn from_bytes(bytes: &[u8]) -> signature::Result<Self> {
+        let bytes = bytes.try_into().map_err(|_| signature::Error::new())?;
+        Ok(Self { bytes })
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        <Self as signature::Signature>::from_bytes(bytes).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Signature, SigningKey, VerifyingKey};
+    use hex_literal::hex;
+    use signature::{Signer, Verifier};
+
+    // RFC 8032 Section 7.1: TEST 2
+    const SECRET_KEY: [u8; 32] =
+        hex!("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb");
+    const PUBLIC_KEY: [u8; 32] =
+        hex!("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+    const MESSAGE: [u8; 1] = [0x72];
+    const SIGNATURE: [u8; 64] = hex!(
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da"
+        "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+    );
+
+    #[test]
+    fn sign_and_verify() {
+        let sk = SigningKey::from_bytes(&SECRET_KEY).unwrap();
+        assert_eq!(sk.verifying_key().to_bytes(), PUBLIC_KEY);
+
+        let sig = sk.sign(&MESSAGE);
+        assert_eq!(sig.to_bytes(), SIGNATURE);
+
+        let vk = VerifyingKey::from_bytes(&PUBLIC_KEY).unwrap();
+        assert!(vk.verify(&MESSAGE, &sig).is_ok());
+        assert!(vk.verify(&[], &sig).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(SigningKey::from_bytes(&SECRET_KEY[..31]).is_err());
+
+        // Identity point (small order)
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(VerifyingKey::from_bytes(&identity).is_err());
+        assert!(Signature::try_from(&[0u8; 63][..]).is_err());
+    }
+}
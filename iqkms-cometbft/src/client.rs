@@ -0,0 +1,473 @@
+//! Privval client which dials validator nodes.
+
+use crate::{Error, PrivValidator, Result, proto::privval::Message};
+use p2p::{
+    AsyncReadMsg, AsyncSecretConnection, AsyncWriteMsg, IdentitySecret, PeerId,
+    SecretConnectionBuilder,
+};
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
+use tokio::{
+    net::TcpStream,
+    time::{sleep, timeout},
+};
+use tower::Service;
+use tracing::{info, warn};
+use types::BoxError;
+
+#[cfg(unix)]
+use {
+    prost::Message as _,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    },
+};
+
+/// Address of a validator node's privval listener.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidatorAddr {
+    /// TCP address with the expected peer ID of the validator, e.g.
+    /// `tcp://f88883b673fc69d7869cab098de3bafc2ff76eb8@example.com:26659`.
+    ///
+    /// Connections are authenticated and encrypted using CometBFT's secret
+    /// connection protocol.
+    Tcp {
+        /// Expected peer ID of the validator.
+        peer_id: PeerId,
+
+        /// Host of the validator.
+        host: String,
+
+        /// Port of the privval listener.
+        port: u16,
+    },
+
+    /// Unix domain socket, e.g. `unix:///var/run/cometbft/privval.sock`.
+    Unix {
+        /// Path to the socket.
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for ValidatorAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorAddr::Tcp {
+                peer_id,
+                host,
+                port,
+            } => write!(f, "tcp://{}@{}:{}", peer_id, host, port),
+            ValidatorAddr::Unix { path } => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for ValidatorAddr {
+    type Err = Error;
+
+    fn from_str(addr: &str) -> Result<Self> {
+        let malformed = || Error::AddrMalformed {
+            addr: addr.to_owned(),
+        };
+
+        if let Some(rest) = addr.strip_prefix("tcp://") {
+            let (peer_id, host_port) = rest.split_once('@').ok_or_else(malformed)?;
+            let (host, port) = host_port.rsplit_once(':').ok_or_else(malformed)?;
+
+            if host.is_empty() {
+                return Err(malformed());
+            }
+
+            Ok(ValidatorAddr::Tcp {
+                peer_id: peer_id.parse().map_err(|_| malformed())?,
+                host: host.to_owned(),
+                port: port.parse().map_err(|_| malformed())?,
+            })
+        } else if let Some(path) = addr.strip_prefix("unix://") {
+            if path.is_empty() {
+                return Err(malformed());
+            }
+
+            Ok(ValidatorAddr::Unix { path: path.into() })
+        } else {
+            Err(malformed())
+        }
+    }
+}
+
+/// Exponential backoff between reconnection attempts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Backoff {
+    /// Delay before the first reconnection attempt.
+    min: Duration,
+
+    /// Maximum delay between reconnection attempts.
+    max: Duration,
+
+    /// Delay before the next reconnection attempt.
+    current: Duration,
+}
+
+impl Backoff {
+    /// Create a new backoff which starts at `min` and doubles after every
+    /// failed attempt up to `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    /// Get the delay before the next attempt, increasing subsequent delays.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = self.current.saturating_mul(2).min(self.max);
+        delay
+    }
+
+    /// Reset the delay after a successful connection.
+    pub fn reset(&mut self) {
+        self.current = self.min;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+/// Timeouts for connections to a validator.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timeouts {
+    /// How long connecting to the validator may take, including the secret
+    /// connection handshake.
+    pub connect: Duration,
+
+    /// How long reads from an idle TCP connection may block. Validators ping
+    /// their signer every few seconds, so a healthy connection is never idle
+    /// for long.
+    pub read: Duration,
+
+    /// How long writes to a stalled TCP connection may block.
+    pub write: Duration,
+}
+
+impl Timeouts {
+    /// Use the same timeout for connecting, reading, and writing.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            connect: timeout,
+            read: timeout,
+            write: timeout,
+        }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+/// Privval client which dials a validator node and services its signing
+/// requests, reconnecting with backoff whenever the connection is lost.
+pub struct Client<S> {
+    /// Address of the validator.
+    addr: ValidatorAddr,
+
+    /// Identity key used to authenticate to the validator over TCP.
+    identity_key: IdentitySecret,
+
+    /// Request handler.
+    validator: PrivValidator<S>,
+
+    /// Backoff between reconnection attempts.
+    backoff: Backoff,
+
+    /// Connection timeouts.
+    timeouts: Timeouts,
+}
+
+impl<S> Client<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    /// Create a new client for the validator at the given address.
+    pub fn new(
+        addr: ValidatorAddr,
+        identity_key: IdentitySecret,
+        validator: PrivValidator<S>,
+    ) -> Self {
+        Self {
+            addr,
+            identity_key,
+            validator,
+            backoff: Backoff::default(),
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Set the backoff between reconnection attempts.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the connection timeouts.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Connect to the validator and service its requests, reconnecting
+    /// whenever the connection fails. Never returns.
+    pub async fn run(mut self) {
+        loop {
+            match Connection::open(&self.addr, &self.identity_key, &self.timeouts).await {
+                Ok(conn) => {
+                    info!(
+                        addr = %self.addr,
+                        chain_id = self.validator.chain_id(),
+                        "connected to validator"
+                    );
+                    self.backoff.reset();
+
+                    if let Err(err) = self.serve(conn).await {
+                        warn!(addr = %self.addr, "validator connection closed: {}", err);
+                    }
+                }
+                Err(err) => warn!(addr = %self.addr, "error connecting to validator: {}", err),
+            }
+
+            sleep(self.backoff.next_delay()).await;
+        }
+    }
+
+    /// Service requests from the validator until the connection fails.
+    async fn serve(&self, mut conn: Connection) -> Result<()> {
+        loop {
+            let request = conn.read_msg().await?;
+            let response = self.validator.handle(request).await?;
+            conn.write_msg(response).await?;
+        }
+    }
+}
+
+/// Connection to a validator.
+enum Connection {
+    /// Secret connection over TCP.
+    Tcp(Box<AsyncSecretConnection<TcpStream>>),
+
+    /// Unix domain socket.
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Connection {
+    /// Open a connection to the given validator.
+    async fn open(
+        addr: &ValidatorAddr,
+        identity_key: &IdentitySecret,
+        timeouts: &Timeouts,
+    ) -> Result<Self> {
+        let timed_out = |_| Error::Connection {
+            reason: format!("timed out connecting to {}", addr),
+        };
+
+        match addr {
+            ValidatorAddr::Tcp {
+                peer_id,
+                host,
+                port,
+            } => {
+                let socket = timeout(timeouts.connect, TcpStream::connect((host.as_str(), *port)))
+                    .await
+                    .map_err(timed_out)??;
+
+                let conn = SecretConnectionBuilder::new()
+                    .with_handshake_timeout(timeouts.connect)
+                    .with_read_timeout(timeouts.read)
+                    .with_write_timeout(timeouts.write)
+                    .with_expected_peer_id(*peer_id)
+                    .build_async(socket, identity_key)
+                    .await?;
+
+                Ok(Connection::Tcp(Box::new(conn)))
+            }
+            #[cfg(unix)]
+            ValidatorAddr::Unix { path } => {
+                let socket = timeout(timeouts.connect, UnixStream::connect(path))
+                    .await
+                    .map_err(timed_out)??;
+
+                Ok(Connection::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            ValidatorAddr::Unix { .. } => Err(Error::Connection {
+                reason: "Unix domain sockets are unsupported on this platform".to_owned(),
+            }),
+        }
+    }
+
+    /// Read a request from the validator.
+    async fn read_msg(&mut self) -> Result<Message> {
+        match self {
+            Connection::Tcp(conn) => Ok(conn.read_msg().await?),
+            #[cfg(unix)]
+            Connection::Unix(socket) => {
+                // Read the varint length prefix one byte at a time
+                let mut prefix = Vec::with_capacity(10);
+                loop {
+                    let byte = socket.read_u8().await?;
+                    prefix.push(byte);
+
+                    if byte & 0x80 == 0 || prefix.len() == 10 {
+                        break;
+                    }
+                }
+
+                let len = prost::decode_length_delimiter(prefix.as_slice())?;
+                if len > p2p::MAX_MSG_LEN {
+                    return Err(p2p::Error::MessageSize { size: len }.into());
+                }
+
+                let mut buf = vec![0u8; len];
+                socket.read_exact(&mut buf).await?;
+                Ok(Message::decode(buf.as_slice())?)
+            }
+        }
+    }
+
+    /// Write a response to the validator.
+    async fn write_msg(&mut self, msg: Message) -> Result<()> {
+        match self {
+            Connection::Tcp(conn) => Ok(conn.write_msg(msg).await?),
+            #[cfg(unix)]
+            Connection::Unix(socket) => {
+                socket
+                    .write_all(&msg.encode_length_delimited_to_vec())
+                    .await?;
+                Ok(socket.flush().await?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, Client, Connection, Timeouts, ValidatorAddr};
+    use crate::{
+        PrivValidator, SignState,
+        proto::privval::{Message, PingRequest, message::Sum},
+    };
+    use p2p::{AsyncReadMsg, AsyncSecretConnection, AsyncWriteMsg, IdentitySecret, PublicKey};
+    use signing::signature::ed25519;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tower::ServiceBuilder;
+
+    #[test]
+    fn parse_addr() {
+        let addr = "tcp://f88883b673fc69d7869cab098de3bafc2ff76eb8@example.com:26659";
+        assert!(matches!(
+            addr.parse::<ValidatorAddr>().unwrap(),
+            ValidatorAddr::Tcp { ref host, port: 26659, .. } if host == "example.com"
+        ));
+        assert_eq!(addr.parse::<ValidatorAddr>().unwrap().to_string(), addr);
+
+        assert_eq!(
+            "unix:///tmp/privval.sock".parse::<ValidatorAddr>().unwrap(),
+            ValidatorAddr::Unix {
+                path: "/tmp/privval.sock".into()
+            }
+        );
+
+        for addr in [
+            "example.com:26659",
+            "tcp://example.com:26659",
+            "tcp://f88883b673fc69d7869cab098de3bafc2ff76eb8@example.com",
+            "tcp://f88883@example.com:26659",
+            "unix://",
+        ] {
+            assert!(addr.parse::<ValidatorAddr>().is_err(), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn backoff() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<_> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn ping_over_secret_connection() {
+        let validator_identity = IdentitySecret::from_bytes(&[0x01; 32]);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = ValidatorAddr::Tcp {
+            peer_id: PublicKey::from(&validator_identity).peer_id(),
+            host: "127.0.0.1".to_owned(),
+            port: listener.local_addr().unwrap().port(),
+        };
+
+        let signing_key = ed25519::SigningKey::from_bytes(&[0x42; 32]).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let mut signing_service = signing::SigningService::new();
+        signing_service
+            .add_key(signing::SigningKey::from(signing_key))
+            .unwrap();
+
+        let service = ServiceBuilder::new().buffer(1).service(signing_service);
+        let client = Client::new(
+            addr,
+            IdentitySecret::from_bytes(&[0x02; 32]),
+            PrivValidator::new(verifying_key, service, SignState::in_memory("cosmoshub-4")),
+        );
+        tokio::spawn(client.run());
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut conn = AsyncSecretConnection::new(socket, &validator_identity)
+            .await
+            .unwrap();
+
+        conn.write_msg(Message::from(Sum::PingRequest(PingRequest {})))
+            .await
+            .unwrap();
+
+        let response: Message = conn.read_msg().await.unwrap();
+        assert!(matches!(response.sum, Some(Sum::PingResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn handshake_timeout() {
+        let validator_identity = IdentitySecret::from_bytes(&[0x01; 32]);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = ValidatorAddr::Tcp {
+            peer_id: PublicKey::from(&validator_identity).peer_id(),
+            host: "127.0.0.1".to_owned(),
+            port: listener.local_addr().unwrap().port(),
+        };
+
+        // Accept the connection but never perform the handshake
+        let accept = tokio::spawn(async move { listener.accept().await.unwrap() });
+
+        let timeouts = Timeouts::new(Duration::from_millis(100));
+        let identity_key = IdentitySecret::from_bytes(&[0x02; 32]);
+        assert!(
+            Connection::open(&addr, &identity_key, &timeouts)
+                .await
+                .is_err()
+        );
+        drop(accept.await.unwrap());
+    }
+}
@@ -0,0 +1,133 @@
+//! Error types.
+
+use std::fmt;
+
+/// `Result` type with the `iqkms-cometbft` crate's [`Error`] type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Malformed validator address.
+    AddrMalformed {
+        /// Requested address.
+        addr: String,
+    },
+
+    /// Request is for a different chain than the one the signer is
+    /// configured for.
+    ChainIdMismatch {
+        /// Chain ID the signer is configured for.
+        expected: String,
+
+        /// Chain ID in the request.
+        actual: String,
+    },
+
+    /// Connection to the validator failed.
+    Connection {
+        /// Reason why the connection failed.
+        reason: String,
+    },
+
+    /// Signing the message could cause a double sign.
+    DoubleSign {
+        /// Reason why the message could be a double sign.
+        reason: String,
+    },
+
+    /// Malformed message received from the validator.
+    MessageMalformed {
+        /// Reason why the message is malformed.
+        reason: String,
+    },
+
+    /// Double-sign protection state file could not be accessed.
+    SignStateFile {
+        /// Reason why the state file couldn't be accessed.
+        reason: String,
+    },
+
+    /// Signing operation failed.
+    SigningFailed {
+        /// Reason why the signing operation failed.
+        reason: String,
+    },
+
+    /// Signing service is not ready to accept requests.
+    SigningServiceUnavailable,
+
+    /// Signing service returned a response which doesn't match the request
+    /// (e.g. a signature produced by a key which isn't Ed25519).
+    UnexpectedResponse,
+}
+
+impl Error {
+    /// Get the privval `RemoteSignerError` code associated with this error.
+    pub(crate) fn code(&self) -> i32 {
+        match self {
+            Error::ChainIdMismatch { .. } => 1,
+            Error::MessageMalformed { .. } => 2,
+            Error::DoubleSign { .. } => 3,
+            _ => 4,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AddrMalformed { addr } => {
+                write!(f, "validator address malformed: \"{}\"", addr)
+            }
+            Error::ChainIdMismatch { expected, actual } => write!(
+                f,
+                "chain ID mismatch: expected \"{}\", got \"{}\"",
+                expected, actual
+            ),
+            Error::Connection { reason } => write!(f, "connection error: {}", reason),
+            Error::DoubleSign { reason } => write!(f, "double sign: {}", reason),
+            Error::MessageMalformed { reason } => write!(f, "message malformed: {}", reason),
+            Error::SignStateFile { reason } => {
+                write!(f, "double-sign protection state file error: {}", reason)
+            }
+            Error::SigningFailed { reason } => f.write_str(reason),
+            Error::SigningServiceUnavailable => f.write_str("signing service unavailable"),
+            Error::UnexpectedResponse => f.write_str("unexpected response from signing service"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<p2p::Error> for Error {
+    fn from(error: p2p::Error) -> Error {
+        Error::Connection {
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl From<prost::DecodeError> for Error {
+    fn from(error: prost::DecodeError) -> Error {
+        Error::MessageMalformed {
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl From<signing::Error> for Error {
+    fn from(_: signing::Error) -> Error {
+        Error::SigningFailed {
+            reason: "signing operation failed".to_owned(),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::Connection {
+            reason: error.to_string(),
+        }
+    }
+}
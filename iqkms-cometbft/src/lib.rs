@@ -0,0 +1,41 @@
+//! iqkms CometBFT services.
+//!
+//! Implements a CometBFT validator remote signer (a.k.a. "privval") which
+//! dials validator nodes over CometBFT's secret connection protocol (or Unix
+//! domain sockets) and signs votes and proposals using Ed25519 consensus keys
+//! stored in the keyring (see [`Client`]). Votes and proposals are checked
+//! against the last signed [`SignState`] of each chain to prevent double
+//! signing.
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/iqlusioninc/iqkms/main/.img/iqkms-sq.svg"
+)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_used,
+    missing_docs,
+    rust_2018_idioms,
+    unused_lifetimes,
+    unused_qualifications
+)]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+mod client;
+mod error;
+mod validator;
+
+pub mod canonical;
+pub mod proto;
+pub mod sign_state;
+
+pub use crate::{
+    client::{Backoff, Client, Timeouts, ValidatorAddr},
+    error::{Error, Result},
+    sign_state::SignState,
+    validator::PrivValidator,
+};
+pub use p2p::{IdentitySecret, PeerId};
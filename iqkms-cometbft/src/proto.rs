@@ -0,0 +1,312 @@
+//! Vendored protos from tendermint-rs.
+// TODO(tarcieri): replace with `cometbft-proto` when released.
+
+#![allow(missing_docs)]
+
+pub mod google {
+    use prost::Message;
+
+    /// Point in time independent of any time zone or local calendar.
+    #[derive(Clone, Copy, Eq, PartialEq, Message)]
+    pub struct Timestamp {
+        #[prost(int64, tag = "1")]
+        pub seconds: i64,
+        #[prost(int32, tag = "2")]
+        pub nanos: i32,
+    }
+}
+
+pub mod crypto {
+    use prost::Message;
+
+    /// PublicKey defines the keys available for use with Validators
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct PublicKey {
+        #[prost(oneof = "public_key::Sum", tags = "1, 2")]
+        pub sum: Option<public_key::Sum>,
+    }
+    /// Nested message and enum types in `PublicKey`.
+    pub mod public_key {
+        use prost::Oneof;
+
+        #[derive(Clone, Eq, PartialEq, Oneof)]
+        pub enum Sum {
+            #[prost(bytes, tag = "1")]
+            Ed25519(Vec<u8>),
+            #[prost(bytes, tag = "2")]
+            Secp256k1(Vec<u8>),
+        }
+    }
+}
+
+pub mod types {
+    use super::google::Timestamp;
+    use prost::{Enumeration, Message};
+
+    /// SignedMsgType is a type of signed message in the consensus.
+    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Enumeration)]
+    #[repr(i32)]
+    pub enum SignedMsgType {
+        Unknown = 0,
+        /// Votes
+        Prevote = 1,
+        Precommit = 2,
+        /// Proposals
+        Proposal = 32,
+    }
+
+    /// Header of a set of block parts.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct PartSetHeader {
+        #[prost(uint32, tag = "1")]
+        pub total: u32,
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+    }
+
+    /// BlockID
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct BlockId {
+        #[prost(bytes = "vec", tag = "1")]
+        pub hash: Vec<u8>,
+        #[prost(message, optional, tag = "2")]
+        pub part_set_header: Option<PartSetHeader>,
+    }
+
+    /// Vote represents a prevote or precommit vote from validators for
+    /// consensus.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct Vote {
+        #[prost(enumeration = "SignedMsgType", tag = "1")]
+        pub r#type: i32,
+        #[prost(int64, tag = "2")]
+        pub height: i64,
+        #[prost(int32, tag = "3")]
+        pub round: i32,
+        /// zero if vote is nil.
+        #[prost(message, optional, tag = "4")]
+        pub block_id: Option<BlockId>,
+        #[prost(message, optional, tag = "5")]
+        pub timestamp: Option<Timestamp>,
+        #[prost(bytes = "vec", tag = "6")]
+        pub validator_address: Vec<u8>,
+        #[prost(int32, tag = "7")]
+        pub validator_index: i32,
+        #[prost(bytes = "vec", tag = "8")]
+        pub signature: Vec<u8>,
+        /// Vote extension provided by the application. Only valid for
+        /// precommit messages.
+        #[prost(bytes = "vec", tag = "9")]
+        pub extension: Vec<u8>,
+        #[prost(bytes = "vec", tag = "10")]
+        pub extension_signature: Vec<u8>,
+    }
+
+    /// Block proposal.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct Proposal {
+        #[prost(enumeration = "SignedMsgType", tag = "1")]
+        pub r#type: i32,
+        #[prost(int64, tag = "2")]
+        pub height: i64,
+        #[prost(int32, tag = "3")]
+        pub round: i32,
+        #[prost(int32, tag = "4")]
+        pub pol_round: i32,
+        #[prost(message, optional, tag = "5")]
+        pub block_id: Option<BlockId>,
+        #[prost(message, optional, tag = "6")]
+        pub timestamp: Option<Timestamp>,
+        #[prost(bytes = "vec", tag = "7")]
+        pub signature: Vec<u8>,
+    }
+
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct CanonicalPartSetHeader {
+        #[prost(uint32, tag = "1")]
+        pub total: u32,
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+    }
+
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct CanonicalBlockId {
+        #[prost(bytes = "vec", tag = "1")]
+        pub hash: Vec<u8>,
+        #[prost(message, optional, tag = "2")]
+        pub part_set_header: Option<CanonicalPartSetHeader>,
+    }
+
+    /// CanonicalVote is for validator signing. This type will not be present
+    /// in a block. Canonicalization uses fixed size integers for height and
+    /// round.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct CanonicalVote {
+        #[prost(enumeration = "SignedMsgType", tag = "1")]
+        pub r#type: i32,
+        #[prost(sfixed64, tag = "2")]
+        pub height: i64,
+        #[prost(sfixed64, tag = "3")]
+        pub round: i64,
+        #[prost(message, optional, tag = "4")]
+        pub block_id: Option<CanonicalBlockId>,
+        #[prost(message, optional, tag = "5")]
+        pub timestamp: Option<Timestamp>,
+        #[prost(string, tag = "6")]
+        pub chain_id: String,
+    }
+
+    /// CanonicalProposal is for validator signing.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct CanonicalProposal {
+        #[prost(enumeration = "SignedMsgType", tag = "1")]
+        pub r#type: i32,
+        #[prost(sfixed64, tag = "2")]
+        pub height: i64,
+        #[prost(sfixed64, tag = "3")]
+        pub round: i64,
+        #[prost(int64, tag = "4")]
+        pub pol_round: i64,
+        #[prost(message, optional, tag = "5")]
+        pub block_id: Option<CanonicalBlockId>,
+        #[prost(message, optional, tag = "6")]
+        pub timestamp: Option<Timestamp>,
+        #[prost(string, tag = "7")]
+        pub chain_id: String,
+    }
+
+    /// CanonicalVoteExtension provides us a way to serialize a vote extension
+    /// from a particular validator such that we can sign over those
+    /// serialized bytes.
+    #[derive(Clone, Eq, PartialEq, Message)]
+    pub struct CanonicalVoteExtension {
+        #[prost(bytes = "vec", tag = "1")]
+        pub extension: Vec<u8>,
+        #[prost(sfixed64, tag = "2")]
+        pub height: i64,
+        #[prost(sfixed64, tag = "3")]
+        pub round: i64,
+        #[prost(string, tag = "4")]
+        pub chain_id: String,
+    }
+}
+
+pub mod privval {
+    use super::{
+        crypto::PublicKey,
+        types::{Proposal, Vote},
+    };
+
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct RemoteSignerError {
+        #[prost(int32, tag = "1")]
+        pub code: i32,
+        #[prost(string, tag = "2")]
+        pub description: String,
+    }
+
+    /// PubKeyRequest requests the consensus public key from the remote
+    /// signer.
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct PubKeyRequest {
+        #[prost(string, tag = "1")]
+        pub chain_id: String,
+    }
+
+    /// PubKeyResponse is a response message containing the public key.
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct PubKeyResponse {
+        #[prost(message, optional, tag = "1")]
+        pub pub_key: Option<PublicKey>,
+        #[prost(message, optional, tag = "2")]
+        pub error: Option<RemoteSignerError>,
+    }
+
+    /// SignVoteRequest is a request to sign a vote
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct SignVoteRequest {
+        #[prost(message, optional, tag = "1")]
+        pub vote: Option<Vote>,
+        #[prost(string, tag = "2")]
+        pub chain_id: String,
+        /// if true, the signer may skip signing the extension bytes.
+        #[prost(bool, tag = "3")]
+        pub skip_extension_signing: bool,
+    }
+
+    /// SignedVoteResponse is a response containing a signed vote or an error
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct SignedVoteResponse {
+        #[prost(message, optional, tag = "1")]
+        pub vote: Option<Vote>,
+        #[prost(message, optional, tag = "2")]
+        pub error: Option<RemoteSignerError>,
+    }
+
+    /// SignProposalRequest is a request to sign a proposal
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct SignProposalRequest {
+        #[prost(message, optional, tag = "1")]
+        pub proposal: Option<Proposal>,
+        #[prost(string, tag = "2")]
+        pub chain_id: String,
+    }
+
+    /// SignedProposalResponse is response containing a signed proposal or an
+    /// error
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct SignedProposalResponse {
+        #[prost(message, optional, tag = "1")]
+        pub proposal: Option<Proposal>,
+        #[prost(message, optional, tag = "2")]
+        pub error: Option<RemoteSignerError>,
+    }
+
+    /// PingRequest is a request to confirm that the connection is alive.
+    #[derive(Clone, Copy, Eq, PartialEq, prost::Message)]
+    pub struct PingRequest {}
+
+    /// PingResponse is a response to confirm that the connection is alive.
+    #[derive(Clone, Copy, Eq, PartialEq, prost::Message)]
+    pub struct PingResponse {}
+
+    #[derive(Clone, Eq, PartialEq, prost::Message)]
+    pub struct Message {
+        #[prost(oneof = "message::Sum", tags = "1, 2, 3, 4, 5, 6, 7, 8")]
+        pub sum: Option<message::Sum>,
+    }
+    /// Nested message and enum types in `Message`.
+    pub mod message {
+        use super::{
+            PingRequest, PingResponse, PubKeyRequest, PubKeyResponse, SignProposalRequest,
+            SignVoteRequest, SignedProposalResponse, SignedVoteResponse,
+        };
+        use prost::Oneof;
+
+        #[derive(Clone, Eq, PartialEq, Oneof)]
+        pub enum Sum {
+            #[prost(message, tag = "1")]
+            PubKeyRequest(PubKeyRequest),
+            #[prost(message, tag = "2")]
+            PubKeyResponse(PubKeyResponse),
+            #[prost(message, tag = "3")]
+            SignVoteRequest(SignVoteRequest),
+            #[prost(message, tag = "4")]
+            SignedVoteResponse(SignedVoteResponse),
+            #[prost(message, tag = "5")]
+            SignProposalRequest(SignProposalRequest),
+            #[prost(message, tag = "6")]
+            SignedProposalResponse(SignedProposalResponse),
+            #[prost(message, tag = "7")]
+            PingRequest(PingRequest),
+            #[prost(message, tag = "8")]
+            PingResponse(PingResponse),
+        }
+    }
+
+    impl From<message::Sum> for Message {
+        fn from(sum: message::Sum) -> Message {
+            Message { sum: Some(sum) }
+        }
+    }
+}
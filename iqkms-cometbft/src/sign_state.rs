@@ -0,0 +1,580 @@
+//! Double-sign protection.
+//!
+//! Validators are slashed for signing two different votes or proposals at the
+//! same height, round, and step. Before a message is signed, its position in
+//! the consensus process is checked against the last message signed for the
+//! chain, and the new position is persisted.
+//!
+//! The state is stored in the `priv_validator_state.json` format used by
+//! tmkms, which is replaced atomically and synced to disk before the
+//! corresponding message is signed, so a crash can never cause a signature to
+//! be forgotten.
+//!
+//! While open, the state holds an exclusive lock on a `.lock` file next to the
+//! state file, so it can't be used (or imported into) by more than one
+//! process at a time.
+
+use crate::{
+    Error, Result,
+    proto::types::{BlockId as ProtoBlockId, Proposal, SignedMsgType, Vote},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::{
+    cmp::Ordering,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use types::hex;
+
+/// Step of the consensus process a message was signed at.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Step {
+    /// Nothing has been signed at this height and round.
+    #[default]
+    None = 0,
+
+    /// Block proposal.
+    Propose = 1,
+
+    /// Prevote.
+    Prevote = 2,
+
+    /// Precommit.
+    Precommit = 3,
+}
+
+impl TryFrom<u8> for Step {
+    type Error = Error;
+
+    fn try_from(step: u8) -> Result<Self> {
+        match step {
+            0 => Ok(Step::None),
+            1 => Ok(Step::Propose),
+            2 => Ok(Step::Prevote),
+            3 => Ok(Step::Precommit),
+            _ => Err(malformed(format!("invalid step: {}", step))),
+        }
+    }
+}
+
+/// Hash of a block and the header of its part set.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockId {
+    /// Block hash.
+    #[serde(with = "hex_bytes")]
+    pub hash: Vec<u8>,
+
+    /// Header of the block's part set.
+    #[serde(alias = "parts")]
+    pub part_set_header: PartSetHeader,
+}
+
+impl BlockId {
+    /// Convert a block ID from a consensus message, returning `None` if it's
+    /// absent or zero (i.e. the message is for `nil`).
+    fn from_proto(block_id: Option<&ProtoBlockId>) -> Option<Self> {
+        let block_id = block_id?;
+        let part_set_header = block_id.part_set_header.clone().unwrap_or_default();
+
+        Some(Self {
+            hash: block_id.hash.clone(),
+            part_set_header: PartSetHeader {
+                total: part_set_header.total,
+                hash: part_set_header.hash,
+            },
+        })
+        .filter(|block_id| !block_id.is_zero())
+    }
+
+    /// Is this the zero block ID?
+    fn is_zero(&self) -> bool {
+        self.hash.is_empty()
+            && self.part_set_header.total == 0
+            && self.part_set_header.hash.is_empty()
+    }
+}
+
+/// Header of a block's part set.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PartSetHeader {
+    /// Number of parts.
+    #[serde(deserialize_with = "int_or_string")]
+    pub total: u32,
+
+    /// Merkle root of the parts.
+    #[serde(with = "hex_bytes")]
+    pub hash: Vec<u8>,
+}
+
+/// Position in the consensus process of the last message signed for a
+/// chain, along with the block it was for.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConsensusState {
+    /// Block height.
+    pub height: i64,
+
+    /// Consensus round.
+    pub round: i32,
+
+    /// Consensus step.
+    pub step: Step,
+
+    /// Block the message was for (`None` for `nil`).
+    pub block_id: Option<BlockId>,
+}
+
+impl ConsensusState {
+    /// Get the consensus state a vote is signed at.
+    pub fn from_vote(vote: &Vote) -> Result<Self> {
+        let step = match SignedMsgType::try_from(vote.r#type) {
+            Ok(SignedMsgType::Prevote) => Step::Prevote,
+            Ok(SignedMsgType::Precommit) => Step::Precommit,
+            _ => return Err(malformed(format!("invalid vote type: {}", vote.r#type))),
+        };
+
+        Self::new(vote.height, vote.round, step, vote.block_id.as_ref())
+    }
+
+    /// Get the consensus state a proposal is signed at.
+    pub fn from_proposal(proposal: &Proposal) -> Result<Self> {
+        Self::new(
+            proposal.height,
+            proposal.round,
+            Step::Propose,
+            proposal.block_id.as_ref(),
+        )
+    }
+
+    /// Create a new consensus state, ensuring the height and round are valid.
+    fn new(height: i64, round: i32, step: Step, block_id: Option<&ProtoBlockId>) -> Result<Self> {
+        if height <= 0 || round < 0 {
+            return Err(malformed(format!(
+                "invalid height/round: {}/{}",
+                height, round
+            )));
+        }
+
+        Ok(Self {
+            height,
+            round,
+            step,
+            block_id: BlockId::from_proto(block_id),
+        })
+    }
+
+    /// Compare the height, round, and step of this state with another.
+    fn cmp_hrs(&self, other: &Self) -> Ordering {
+        (self.height, self.round, self.step).cmp(&(other.height, other.round, other.step))
+    }
+
+    /// Parse a `priv_validator_state.json` file, as written by tmkms or
+    /// CometBFT.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: StateFile = serde_json::from_str(json)
+            .map_err(|e| malformed(format!("malformed priv_validator_state.json: {}", e)))?;
+
+        Ok(Self {
+            height: file.height,
+            round: file.round,
+            step: Step::try_from(file.step)?,
+            block_id: file.block_id.filter(|block_id| !block_id.is_zero()),
+        })
+    }
+
+    /// Serialize this state as a `priv_validator_state.json` file.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&StateFile {
+            height: self.height,
+            round: self.round,
+            step: self.step as u8,
+            block_id: self.block_id.clone(),
+        })
+        .map_err(state_file_error)
+    }
+}
+
+impl fmt::Display for ConsensusState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{:?}", self.height, self.round, self.step)
+    }
+}
+
+/// Last signed consensus state of a chain.
+#[derive(Debug)]
+pub struct SignState {
+    /// Chain the state belongs to.
+    chain_id: String,
+
+    /// Path the state is persisted to (if any).
+    path: Option<PathBuf>,
+
+    /// Lock file which is exclusively locked while the state is open (only
+    /// held, never read).
+    _lock: Option<File>,
+
+    /// Position of the last signed message.
+    consensus_state: ConsensusState,
+}
+
+impl SignState {
+    /// Open the state file for the given chain at the given path, creating it
+    /// if it doesn't exist.
+    ///
+    /// Returns an error if the state file is already open, including in
+    /// another process.
+    pub fn open(chain_id: impl Into<String>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let lock = lock(&path)?;
+
+        let consensus_state = match fs::read_to_string(&path) {
+            Ok(json) => ConsensusState::from_json(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let consensus_state = ConsensusState::default();
+                persist(&path, &consensus_state)?;
+                consensus_state
+            }
+            Err(e) => return Err(state_file_error(e)),
+        };
+
+        Ok(Self {
+            chain_id: chain_id.into(),
+            path: Some(path),
+            _lock: Some(lock),
+            consensus_state,
+        })
+    }
+
+    /// Create state which is only held in memory.
+    ///
+    /// The state is lost when it's dropped, so this is only suitable for
+    /// testing.
+    pub fn in_memory(chain_id: impl Into<String>) -> Self {
+        Self {
+            chain_id: chain_id.into(),
+            path: None,
+            _lock: None,
+            consensus_state: ConsensusState::default(),
+        }
+    }
+
+    /// Get the chain the state belongs to.
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Get the position of the last signed message.
+    pub fn consensus_state(&self) -> &ConsensusState {
+        &self.consensus_state
+    }
+
+    /// Check that signing a message at the given consensus state can't cause
+    /// a double sign, and persist it as the last signed state.
+    ///
+    /// Refuses states which precede the last signed state, or which are at
+    /// the same height, round, and step but for a different block. Signing
+    /// the same message again (including with a different timestamp) is
+    /// allowed.
+    pub fn update(&mut self, consensus_state: ConsensusState) -> Result<()> {
+        match consensus_state.cmp_hrs(&self.consensus_state) {
+            Ordering::Less => Err(double_sign(format!(
+                "{} regresses from last signed state {}",
+                consensus_state, self.consensus_state
+            ))),
+            Ordering::Equal if consensus_state.block_id != self.consensus_state.block_id => Err(
+                double_sign(format!("conflicting block ID at {}", consensus_state)),
+            ),
+            Ordering::Equal => Ok(()),
+            Ordering::Greater => self.store(consensus_state),
+        }
+    }
+
+    /// Import the state from a `priv_validator_state.json` file (e.g. when
+    /// migrating from tmkms).
+    ///
+    /// The imported state must not precede the current state.
+    pub fn import(&mut self, json: &str) -> Result<()> {
+        let consensus_state = ConsensusState::from_json(json)?;
+
+        if consensus_state.cmp_hrs(&self.consensus_state) == Ordering::Less {
+            return Err(double_sign(format!(
+                "imported state {} regresses from last signed state {}",
+                consensus_state, self.consensus_state
+            )));
+        }
+
+        self.store(consensus_state)
+    }
+
+    /// Persist the given state and make it the current state.
+    fn store(&mut self, consensus_state: ConsensusState) -> Result<()> {
+        if let Some(path) = &self.path {
+            persist(path, &consensus_state)?;
+        }
+
+        self.consensus_state = consensus_state;
+        Ok(())
+    }
+}
+
+/// Exclusively lock the lock file of the state file at the given path,
+/// returning an error if it's already locked.
+fn lock(path: &Path) -> Result<File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(state_file_error)?;
+
+    #[cfg(unix)]
+    rustix::fs::flock(&file, rustix::fs::FlockOperation::NonBlockingLockExclusive).map_err(
+        |_| {
+            state_file_error(format!(
+                "state file is in use: {} is locked",
+                Path::new(&lock_path).display()
+            ))
+        },
+    )?;
+
+    Ok(file)
+}
+
+/// Atomically replace the state file at the given path, syncing it to disk.
+fn persist(path: &Path, consensus_state: &ConsensusState) -> Result<()> {
+    let json = consensus_state.to_json()?;
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    let mut file = File::create(&tmp_path).map_err(state_file_error)?;
+    file.write_all(json.as_bytes())
+        .and_then(|_| file.sync_all())
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(state_file_error)?;
+
+    // Sync the directory so the rename is durable
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(state_file_error)?;
+    }
+
+    Ok(())
+}
+
+/// `priv_validator_state.json` file.
+#[derive(Deserialize, Serialize)]
+struct StateFile {
+    #[serde(deserialize_with = "int_or_string", serialize_with = "quoted")]
+    height: i64,
+    #[serde(deserialize_with = "int_or_string")]
+    round: i32,
+    step: u8,
+    #[serde(default)]
+    block_id: Option<BlockId>,
+}
+
+/// Error for a message which would be a double sign.
+fn double_sign(reason: String) -> Error {
+    Error::DoubleSign { reason }
+}
+
+/// Error for a malformed message or state file.
+fn malformed(reason: String) -> Error {
+    Error::MessageMalformed { reason }
+}
+
+/// Error for a failure to access the state file.
+fn state_file_error(err: impl ToString) -> Error {
+    Error::SignStateFile {
+        reason: err.to_string(),
+    }
+}
+
+/// Deserialize an integer which may be encoded as a string (as Amino JSON
+/// does for 64-bit integers).
+fn int_or_string<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum IntOrString<T> {
+        Int(T),
+        String(String),
+    }
+
+    match IntOrString::deserialize(deserializer)? {
+        IntOrString::Int(n) => Ok(n),
+        IntOrString::String(s) => s.parse().map_err(de::Error::custom),
+    }
+}
+
+/// Serialize an integer as a decimal string.
+fn quoted<S: Serializer>(n: &i64, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&n.to_string())
+}
+
+/// Serialize bytes as uppercase hex.
+mod hex_bytes {
+    use super::{Deserialize, Deserializer, Serializer, de, hex};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::upper::encode_string(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::mixed::decode_vec(String::deserialize(deserializer)?)
+            .map_err(|_| de::Error::custom("expected hex"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsensusState, SignState, Step};
+    use crate::{
+        Error,
+        proto::types::{BlockId, PartSetHeader, SignedMsgType, Vote},
+    };
+    use std::{env, fs, process};
+
+    fn prevote(height: i64, round: i32, hash: u8) -> ConsensusState {
+        let vote = Vote {
+            r#type: SignedMsgType::Prevote.into(),
+            height,
+            round,
+            block_id: Some(BlockId {
+                hash: vec![hash; 32],
+                part_set_header: Some(PartSetHeader {
+                    total: 1,
+                    hash: vec![hash; 32],
+                }),
+            }),
+            ..Default::default()
+        };
+
+        ConsensusState::from_vote(&vote).unwrap()
+    }
+
+    fn is_double_sign(result: crate::Result<()>) -> bool {
+        matches!(result, Err(Error::DoubleSign { .. }))
+    }
+
+    #[test]
+    fn refuses_regressions() {
+        let mut state = SignState::in_memory("cosmoshub-4");
+        state.update(prevote(10, 1, 1)).unwrap();
+
+        assert!(is_double_sign(state.update(prevote(9, 5, 1))));
+        assert!(is_double_sign(state.update(prevote(10, 0, 1))));
+
+        let proposal = ConsensusState {
+            step: Step::Propose,
+            ..prevote(10, 1, 1)
+        };
+        assert!(is_double_sign(state.update(proposal)));
+
+        let precommit = ConsensusState {
+            step: Step::Precommit,
+            ..prevote(10, 1, 1)
+        };
+        state.update(precommit).unwrap();
+        state.update(prevote(11, 0, 2)).unwrap();
+    }
+
+    #[test]
+    fn refuses_conflicting_block_ids() {
+        let mut state = SignState::in_memory("cosmoshub-4");
+        state.update(prevote(10, 1, 1)).unwrap();
+
+        // Re-signing the same vote (e.g. with a different timestamp) is fine
+        state.update(prevote(10, 1, 1)).unwrap();
+
+        assert!(is_double_sign(state.update(prevote(10, 1, 2))));
+
+        let nil = ConsensusState {
+            block_id: None,
+            ..prevote(10, 1, 1)
+        };
+        assert!(is_double_sign(state.update(nil)));
+    }
+
+    #[test]
+    fn persistence() {
+        let path = env::temp_dir().join(format!("iqkms-sign-state-{}.json", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut state = SignState::open("cosmoshub-4", &path).unwrap();
+        assert_eq!(state.consensus_state(), &ConsensusState::default());
+        state.update(prevote(10, 1, 1)).unwrap();
+        drop(state);
+
+        let mut state = SignState::open("cosmoshub-4", &path).unwrap();
+        assert_eq!(state.consensus_state(), &prevote(10, 1, 1));
+        assert!(is_double_sign(state.update(prevote(10, 1, 2))));
+        drop(state);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("json.lock")).unwrap();
+    }
+
+    #[test]
+    fn exclusive_lock() {
+        let path = env::temp_dir().join(format!("iqkms-sign-state-lock-{}.json", process::id()));
+
+        let state = SignState::open("cosmoshub-4", &path).unwrap();
+        assert!(matches!(
+            SignState::open("cosmoshub-4", &path),
+            Err(Error::SignStateFile { .. })
+        ));
+
+        drop(state);
+        SignState::open("cosmoshub-4", &path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("json.lock")).unwrap();
+    }
+
+    #[test]
+    fn import_tmkms_state() {
+        let json = r#"{
+            "height": "1234",
+            "round": "2",
+            "step": 3,
+            "block_id": {
+                "hash": "0101010101010101010101010101010101010101010101010101010101010101",
+                "parts": {
+                    "total": "1",
+                    "hash": "0101010101010101010101010101010101010101010101010101010101010101"
+                }
+            }
+        }"#;
+
+        let mut state = SignState::in_memory("cosmoshub-4");
+        state.import(json).unwrap();
+
+        let expected = ConsensusState {
+            step: Step::Precommit,
+            ..prevote(1234, 2, 1)
+        };
+        assert_eq!(state.consensus_state(), &expected);
+        assert!(is_double_sign(state.update(prevote(1234, 2, 1))));
+
+        // CometBFT's own state file, without a block ID
+        let mut state = SignState::in_memory("cosmoshub-4");
+        state.update(prevote(2000, 0, 1)).unwrap();
+        assert!(is_double_sign(
+            state.import(r#"{"height":"1000","round":0,"step":2}"#)
+        ));
+
+        let round_trip = ConsensusState::from_json(&expected.to_json().unwrap()).unwrap();
+        assert_eq!(round_trip, expected);
+    }
+}
@@ -0,0 +1,450 @@
+//! Privval request handling.
+
+use crate::sign_state::ConsensusState;
+use crate::{
+    Error, Result, SignState, canonical,
+    proto::{
+        crypto::{PublicKey, public_key},
+        privval::{
+            Message, PingResponse, PubKeyResponse, RemoteSignerError, SignProposalRequest,
+            SignVoteRequest, SignedProposalResponse, SignedVoteResponse, message::Sum,
+        },
+    },
+};
+use signing::{VerifyingKey, signature::ed25519};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tower::{Service, ServiceExt};
+use tracing::{info, warn};
+use types::{BoxError, Bytes};
+
+/// Privval request handler for a single validator, which signs votes and
+/// proposals for one chain using an Ed25519 consensus key from the keyring.
+#[derive(Clone)]
+pub struct PrivValidator<S> {
+    /// Chain ID the validator signs messages for.
+    chain_id: String,
+
+    /// Consensus key of the validator.
+    verifying_key: ed25519::VerifyingKey,
+
+    /// Reference to the signing service.
+    signing_service: S,
+
+    /// Last signed state, consulted before signing votes and proposals.
+    sign_state: Arc<Mutex<SignState>>,
+}
+
+impl<S> PrivValidator<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    /// Create a new request handler which signs messages for the chain the
+    /// given double-sign protection state belongs to, using the given
+    /// consensus key.
+    pub fn new(
+        verifying_key: ed25519::VerifyingKey,
+        signing_service: S,
+        sign_state: SignState,
+    ) -> Self {
+        Self::with_shared_state(
+            verifying_key,
+            signing_service,
+            Arc::new(Mutex::new(sign_state)),
+        )
+    }
+
+    /// Create a new request handler whose double-sign protection state is
+    /// shared with other handlers, e.g. ones for other nodes of the same
+    /// validator (such as sentries) which must never sign conflicting
+    /// messages.
+    pub fn with_shared_state(
+        verifying_key: ed25519::VerifyingKey,
+        signing_service: S,
+        sign_state: Arc<Mutex<SignState>>,
+    ) -> Self {
+        let chain_id = sign_state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .chain_id()
+            .to_owned();
+
+        Self {
+            chain_id,
+            verifying_key,
+            signing_service,
+            sign_state,
+        }
+    }
+
+    /// Get the chain ID the validator signs messages for.
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Handle a request from the validator, returning the response to send.
+    ///
+    /// Requests which can't be serviced (e.g. ones for another chain) are
+    /// answered with a `RemoteSignerError`. Errors are only returned for
+    /// messages which aren't requests.
+    pub async fn handle(&self, request: Message) -> Result<Message> {
+        let response = match request.sum {
+            Some(Sum::PubKeyRequest(request)) => {
+                Sum::PubKeyResponse(match self.check_chain_id(&request.chain_id) {
+                    Ok(()) => PubKeyResponse {
+                        pub_key: Some(PublicKey {
+                            sum: Some(public_key::Sum::Ed25519(
+                                self.verifying_key.to_bytes().to_vec(),
+                            )),
+                        }),
+                        error: None,
+                    },
+                    Err(err) => PubKeyResponse {
+                        pub_key: None,
+                        error: Some(remote_signer_error(err)),
+                    },
+                })
+            }
+            Some(Sum::SignVoteRequest(request)) => {
+                Sum::SignedVoteResponse(match self.sign_vote(request).await {
+                    Ok(vote) => SignedVoteResponse {
+                        vote: Some(vote),
+                        error: None,
+                    },
+                    Err(err) => SignedVoteResponse {
+                        vote: None,
+                        error: Some(remote_signer_error(err)),
+                    },
+                })
+            }
+            Some(Sum::SignProposalRequest(request)) => {
+                Sum::SignedProposalResponse(match self.sign_proposal(request).await {
+                    Ok(proposal) => SignedProposalResponse {
+                        proposal: Some(proposal),
+                        error: None,
+                    },
+                    Err(err) => SignedProposalResponse {
+                        proposal: None,
+                        error: Some(remote_signer_error(err)),
+                    },
+                })
+            }
+            Some(Sum::PingRequest(_)) => Sum::PingResponse(PingResponse {}),
+            _ => {
+                return Err(Error::MessageMalformed {
+                    reason: "expected a privval request".to_owned(),
+                });
+            }
+        };
+
+        Ok(response.into())
+    }
+
+    /// Sign a vote (and its extension, if requested).
+    async fn sign_vote(&self, request: SignVoteRequest) -> Result<crate::proto::types::Vote> {
+        self.check_chain_id(&request.chain_id)?;
+
+        let mut vote = request.vote.ok_or_else(|| Error::MessageMalformed {
+            reason: "missing vote".to_owned(),
+        })?;
+
+        self.sign_state()?
+            .update(ConsensusState::from_vote(&vote)?)?;
+
+        let sign_bytes = canonical::vote_sign_bytes(&self.chain_id, &vote);
+        vote.signature = self.sign(sign_bytes).await?;
+
+        if !request.skip_extension_signing && canonical::is_non_nil_precommit(&vote) {
+            let sign_bytes = canonical::vote_extension_sign_bytes(&self.chain_id, &vote);
+            vote.extension_signature = self.sign(sign_bytes).await?;
+        }
+
+        info!(
+            chain_id = %self.chain_id,
+            height = vote.height,
+            round = vote.round,
+            r#type = vote.r#type,
+            "signed vote"
+        );
+
+        Ok(vote)
+    }
+
+    /// Sign a block proposal.
+    async fn sign_proposal(
+        &self,
+        request: SignProposalRequest,
+    ) -> Result<crate::proto::types::Proposal> {
+        self.check_chain_id(&request.chain_id)?;
+
+        let mut proposal = request.proposal.ok_or_else(|| Error::MessageMalformed {
+            reason: "missing proposal".to_owned(),
+        })?;
+
+        self.sign_state()?
+            .update(ConsensusState::from_proposal(&proposal)?)?;
+
+        let sign_bytes = canonical::proposal_sign_bytes(&self.chain_id, &proposal);
+        proposal.signature = self.sign(sign_bytes).await?;
+
+        info!(
+            chain_id = %self.chain_id,
+            height = proposal.height,
+            round = proposal.round,
+            "signed proposal"
+        );
+
+        Ok(proposal)
+    }
+
+    /// Lock the double-sign protection state.
+    fn sign_state(&self) -> Result<MutexGuard<'_, SignState>> {
+        self.sign_state.lock().map_err(|_| Error::SignStateFile {
+            reason: "double-sign protection state lock poisoned".to_owned(),
+        })
+    }
+
+    /// Ensure a request is for the chain this validator signs messages for.
+    fn check_chain_id(&self, chain_id: &str) -> Result<()> {
+        if chain_id == self.chain_id {
+            Ok(())
+        } else {
+            let err = Error::ChainIdMismatch {
+                expected: self.chain_id.clone(),
+                actual: chain_id.to_owned(),
+            };
+            warn!("{}", err);
+            Err(err)
+        }
+    }
+
+    /// Sign the given message using the validator's consensus key.
+    async fn sign(&self, msg: Vec<u8>) -> Result<Vec<u8>> {
+        let request = signing::Request::Sign {
+            key_handle: VerifyingKey::Ed25519(self.verifying_key).into(),
+            msg: Bytes::from(msg),
+        };
+
+        let mut signing_service = self.signing_service.clone();
+        let response = signing_service
+            .ready()
+            .await
+            .map_err(|_| Error::SigningServiceUnavailable)?
+            .call(request)
+            .await
+            .map_err(signing::Error::from)?;
+
+        match response {
+            signing::Response::Sign {
+                verifying_key: VerifyingKey::Ed25519(vk),
+                signature,
+            } if vk == self.verifying_key && signature.len() == ed25519::Signature::BYTE_SIZE => {
+                Ok(signature.to_vec())
+            }
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+}
+
+/// Convert an error into a privval `RemoteSignerError`.
+fn remote_signer_error(err: Error) -> RemoteSignerError {
+    RemoteSignerError {
+        code: err.code(),
+        description: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrivValidator;
+    use crate::{
+        SignState, canonical,
+        proto::{
+            crypto::public_key,
+            privval::{Message, PingRequest, PubKeyRequest, SignVoteRequest, message::Sum},
+            types::{BlockId, PartSetHeader, SignedMsgType, Vote},
+        },
+    };
+    use signing::signature::{Verifier, ed25519};
+    use tower::{ServiceBuilder, buffer::Buffer};
+
+    const CHAIN_ID: &str = "cosmoshub-4";
+
+    type SigningService = Buffer<signing::SigningService, signing::Request>;
+
+    fn validator() -> (PrivValidator<SigningService>, ed25519::VerifyingKey) {
+        let signing_key = ed25519::SigningKey::from_bytes(&[0x42; 32]).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let mut signing_service = signing::SigningService::new();
+        signing_service
+            .add_key(signing::SigningKey::from(signing_key))
+            .unwrap();
+
+        let service = ServiceBuilder::new().buffer(1).service(signing_service);
+        (
+            PrivValidator::new(verifying_key, service, SignState::in_memory(CHAIN_ID)),
+            verifying_key,
+        )
+    }
+
+    fn precommit() -> Vote {
+        Vote {
+            r#type: SignedMsgType::Precommit.into(),
+            height: 12345,
+            round: 2,
+            block_id: Some(BlockId {
+                hash: vec![0xab; 32],
+                part_set_header: Some(PartSetHeader {
+                    total: 1,
+                    hash: vec![0xcd; 32],
+                }),
+            }),
+            extension: b"extension".to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_vote() {
+        let (validator, verifying_key) = validator();
+        let request = Message::from(Sum::SignVoteRequest(SignVoteRequest {
+            vote: Some(precommit()),
+            chain_id: CHAIN_ID.to_owned(),
+            skip_extension_signing: false,
+        }));
+
+        let vote = match validator.handle(request).await.unwrap().sum {
+            Some(Sum::SignedVoteResponse(response)) => response.vote.unwrap(),
+            other => unreachable!("unexpected response: {:?}", other),
+        };
+
+        let signature = ed25519::Signature::try_from(vote.signature.as_slice()).unwrap();
+        let sign_bytes = canonical::vote_sign_bytes(CHAIN_ID, &precommit());
+        assert!(verifying_key.verify(&sign_bytes, &signature).is_ok());
+
+        let signature = ed25519::Signature::try_from(vote.extension_signature.as_slice()).unwrap();
+        let sign_bytes = canonical::vote_extension_sign_bytes(CHAIN_ID, &precommit());
+        assert!(verifying_key.verify(&sign_bytes, &signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn refuses_double_sign() {
+        let (validator, _) = validator();
+        let sign_vote = |vote: Vote| {
+            validator.handle(Message::from(Sum::SignVoteRequest(SignVoteRequest {
+                vote: Some(vote),
+                chain_id: CHAIN_ID.to_owned(),
+                skip_extension_signing: true,
+            })))
+        };
+
+        let response = sign_vote(precommit()).await.unwrap();
+        assert!(matches!(
+            response.sum,
+            Some(Sum::SignedVoteResponse(response)) if response.vote.is_some()
+        ));
+
+        // Same vote with a different timestamp
+        let mut vote = precommit();
+        vote.timestamp = Some(crate::proto::google::Timestamp {
+            seconds: 1,
+            nanos: 0,
+        });
+        let response = sign_vote(vote).await.unwrap();
+        assert!(matches!(
+            response.sum,
+            Some(Sum::SignedVoteResponse(response)) if response.vote.is_some()
+        ));
+
+        // Conflicting block ID
+        let mut vote = precommit();
+        vote.block_id.as_mut().unwrap().hash = vec![0xef; 32];
+        let response = sign_vote(vote).await.unwrap();
+        assert!(matches!(
+            response.sum,
+            Some(Sum::SignedVoteResponse(response))
+                if response.vote.is_none() && response.error.as_ref().unwrap().code == 3
+        ));
+    }
+
+    #[tokio::test]
+    async fn shared_state_refuses_double_sign() {
+        let (validator, verifying_key) = validator();
+        let sentry = PrivValidator::with_shared_state(
+            verifying_key,
+            validator.signing_service.clone(),
+            validator.sign_state.clone(),
+        );
+        assert_eq!(sentry.chain_id(), CHAIN_ID);
+
+        let request = |vote: Vote| {
+            Message::from(Sum::SignVoteRequest(SignVoteRequest {
+                vote: Some(vote),
+                chain_id: CHAIN_ID.to_owned(),
+                skip_extension_signing: true,
+            }))
+        };
+
+        let response = validator.handle(request(precommit())).await.unwrap();
+        assert!(matches!(
+            response.sum,
+            Some(Sum::SignedVoteResponse(response)) if response.vote.is_some()
+        ));
+
+        // Conflicting block ID signed via another node of the same validator
+        let mut vote = precommit();
+        vote.block_id.as_mut().unwrap().hash = vec![0xef; 32];
+        let response = sentry.handle(request(vote)).await.unwrap();
+        assert!(matches!(
+            response.sum,
+            Some(Sum::SignedVoteResponse(response))
+                if response.vote.is_none() && response.error.as_ref().unwrap().code == 3
+        ));
+    }
+
+    #[tokio::test]
+    async fn chain_id_mismatch() {
+        let (validator, _) = validator();
+        let request = Message::from(Sum::SignVoteRequest(SignVoteRequest {
+            vote: Some(precommit()),
+            chain_id: "osmosis-1".to_owned(),
+            skip_extension_signing: false,
+        }));
+
+        let response = match validator.handle(request).await.unwrap().sum {
+            Some(Sum::SignedVoteResponse(response)) => response,
+            other => unreachable!("unexpected response: {:?}", other),
+        };
+
+        assert!(response.vote.is_none());
+        assert_eq!(response.error.unwrap().code, 1);
+    }
+
+    #[tokio::test]
+    async fn pub_key_and_ping() {
+        let (validator, verifying_key) = validator();
+        let request = Message::from(Sum::PubKeyRequest(PubKeyRequest {
+            chain_id: CHAIN_ID.to_owned(),
+        }));
+
+        let pub_key = match validator.handle(request).await.unwrap().sum {
+            Some(Sum::PubKeyResponse(response)) => response.pub_key.unwrap(),
+            other => unreachable!("unexpected response: {:?}", other),
+        };
+        assert_eq!(
+            pub_key.sum,
+            Some(public_key::Sum::Ed25519(verifying_key.to_bytes().to_vec()))
+        );
+
+        let response = validator
+            .handle(Sum::PingRequest(PingRequest {}).into())
+            .await
+            .unwrap();
+        assert!(matches!(response.sum, Some(Sum::PingResponse(_))));
+    }
+}
@@ -0,0 +1,119 @@
+//! Error types.
+
+use std::fmt;
+
+/// `Result` type with the `iqkms-cosmos` crate's [`Error`] type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Account number of the sign doc doesn't match the expected one.
+    AccountNumberMismatch {
+        /// Expected account number.
+        expected: u64,
+
+        /// Account number in the sign doc.
+        actual: u64,
+    },
+
+    /// Chain ID of the sign doc doesn't match the expected one.
+    ChainIdMismatch {
+        /// Expected chain ID.
+        expected: String,
+
+        /// Chain ID in the sign doc.
+        actual: String,
+    },
+
+    /// Malformed sign doc.
+    MessageMalformed {
+        /// Reason why the message is malformed.
+        reason: String,
+    },
+
+    /// Operation is not permitted by the signing policy.
+    PolicyViolation {
+        /// Reason why the operation was denied.
+        reason: String,
+    },
+
+    /// Malformed public key.
+    PublicKeyMalformed,
+
+    /// Signing operation failed.
+    SigningFailed {
+        /// Reason why the signing operation failed.
+        reason: String,
+    },
+
+    /// Signing service is not ready to accept requests.
+    SigningServiceUnavailable,
+
+    /// Signing service returned a response which doesn't match the request
+    /// (e.g. a signature produced by a key which isn't ECDSA/secp256k1).
+    UnexpectedResponse,
+}
+
+impl Error {
+    /// Get the `tonic::Code` associated with this error.
+    fn code(&self) -> tonic::Code {
+        match self {
+            Error::AccountNumberMismatch { .. } => tonic::Code::InvalidArgument,
+            Error::ChainIdMismatch { .. } => tonic::Code::InvalidArgument,
+            Error::MessageMalformed { .. } => tonic::Code::InvalidArgument,
+            Error::PolicyViolation { .. } => tonic::Code::PermissionDenied,
+            Error::PublicKeyMalformed => tonic::Code::InvalidArgument,
+            Error::SigningFailed { .. } => tonic::Code::Internal,
+            Error::SigningServiceUnavailable => tonic::Code::Unavailable,
+            Error::UnexpectedResponse => tonic::Code::Internal,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AccountNumberMismatch { expected, actual } => write!(
+                f,
+                "account number mismatch: expected {}, got {}",
+                expected, actual
+            ),
+            Error::ChainIdMismatch { expected, actual } => write!(
+                f,
+                "chain ID mismatch: expected \"{}\", got \"{}\"",
+                expected, actual
+            ),
+            Error::MessageMalformed { reason } => write!(f, "message malformed: {}", reason),
+            Error::PolicyViolation { reason } => write!(f, "policy violation: {}", reason),
+            Error::PublicKeyMalformed => f.write_str("secp256k1 public key malformed"),
+            Error::SigningFailed { reason } => f.write_str(reason),
+            Error::SigningServiceUnavailable => f.write_str("signing service unavailable"),
+            Error::UnexpectedResponse => f.write_str("unexpected response from signing service"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for tonic::Status {
+    fn from(error: Error) -> tonic::Status {
+        tonic::Status::new(error.code(), error.to_string())
+    }
+}
+
+impl From<signing::Error> for Error {
+    fn from(_: signing::Error) -> Error {
+        Error::SigningFailed {
+            reason: "signing operation failed".to_owned(),
+        }
+    }
+}
+
+impl From<prost::DecodeError> for Error {
+    fn from(error: prost::DecodeError) -> Error {
+        Error::MessageMalformed {
+            reason: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! iqkms Cosmos services.
+//!
+//! Implements an RPC service which signs Cosmos SDK transactions, either in
+//! `SIGN_MODE_DIRECT` (Protobuf `SignDoc`s) or `SIGN_MODE_LEGACY_AMINO_JSON`
+//! (amino JSON `StdSignDoc`s), after validating their chain ID and account
+//! number and checking their messages against a [`Policy`].
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/iqlusioninc/iqkms/main/.img/iqkms-sq.svg"
+)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_used,
+    missing_docs,
+    rust_2018_idioms,
+    unused_lifetimes,
+    unused_qualifications
+)]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+mod error;
+mod policy;
+mod sign_doc;
+mod signer;
+
+pub use crate::{
+    error::{Error, Result},
+    policy::Policy,
+    signer::SignerService,
+};
+pub use proto::cosmos::signer_server::SignerServer;
@@ -0,0 +1,78 @@
+//! Signing policy.
+
+use crate::{Error, Result};
+use std::collections::BTreeSet;
+
+/// Policy restricting which operations the signer will perform.
+///
+/// By default transactions containing any type of message may be signed.
+/// Once a message type has been allowed, only transactions whose messages
+/// are all of allowed types may be signed.
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    /// Message types transactions may contain (all types if empty).
+    allowed_msg_types: BTreeSet<String>,
+}
+
+impl Policy {
+    /// Create a new policy with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow transactions containing messages of the given type.
+    ///
+    /// Types are Protobuf type URLs for `SIGN_MODE_DIRECT` (e.g.
+    /// `/cosmos.bank.v1beta1.MsgSend`) and amino type names for amino JSON
+    /// (e.g. `cosmos-sdk/MsgSend`), so both must be allowed to sign a
+    /// message type in either mode.
+    pub fn allow_msg_type(mut self, msg_type: impl Into<String>) -> Self {
+        self.allowed_msg_types.insert(msg_type.into());
+        self
+    }
+
+    /// Check that every message in a transaction is permitted by this policy.
+    pub fn check_msg_types<'a>(&self, msg_types: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        if self.allowed_msg_types.is_empty() {
+            return Ok(());
+        }
+
+        for msg_type in msg_types {
+            if !self.allowed_msg_types.contains(msg_type) {
+                return Err(Error::PolicyViolation {
+                    reason: format!("message type not allowed: \"{}\"", msg_type),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+
+    #[test]
+    fn allow_msg_type() {
+        assert!(Policy::new().check_msg_types(["anything"]).is_ok());
+
+        let policy = Policy::new()
+            .allow_msg_type("/cosmos.bank.v1beta1.MsgSend")
+            .allow_msg_type("cosmos-sdk/MsgSend");
+
+        assert!(
+            policy
+                .check_msg_types(["/cosmos.bank.v1beta1.MsgSend", "cosmos-sdk/MsgSend"])
+                .is_ok()
+        );
+        assert!(
+            policy
+                .check_msg_types([
+                    "/cosmos.bank.v1beta1.MsgSend",
+                    "/cosmos.authz.v1beta1.MsgExec"
+                ])
+                .is_err()
+        );
+    }
+}
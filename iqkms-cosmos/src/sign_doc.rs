@@ -0,0 +1,370 @@
+//! Cosmos SDK sign docs.
+//!
+//! Transactions are signed in one of two modes:
+//!
+//! - `SIGN_MODE_DIRECT`: the signer signs the Protobuf encoding of a
+//!   [`SignDoc`], which contains the raw bytes of the transaction body.
+//! - `SIGN_MODE_LEGACY_AMINO_JSON`: the signer signs an amino JSON
+//!   `StdSignDoc`, serialized with sorted keys and without whitespace.
+//!
+//! In both cases the signature is computed over the SHA-256 digest of the
+//! sign bytes.
+
+use crate::{Error, Result};
+use prost::Message;
+use serde_json::Value;
+
+/// Largest integer which can be represented exactly by Go's `float64`, which
+/// is what the Cosmos SDK decodes JSON numbers into when sorting sign docs.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// `cosmos.tx.v1beta1.SignDoc`: the document signed in `SIGN_MODE_DIRECT`.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct SignDoc {
+    /// Protobuf-encoded `TxBody`.
+    #[prost(bytes = "vec", tag = "1")]
+    pub body_bytes: Vec<u8>,
+
+    /// Protobuf-encoded `AuthInfo`.
+    #[prost(bytes = "vec", tag = "2")]
+    pub auth_info_bytes: Vec<u8>,
+
+    /// Chain ID the transaction is for.
+    #[prost(string, tag = "3")]
+    pub chain_id: String,
+
+    /// Account number of the signer on the chain.
+    #[prost(uint64, tag = "4")]
+    pub account_number: u64,
+}
+
+/// `cosmos.tx.v1beta1.TxBody`: the body of a transaction.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TxBody {
+    /// Messages to be executed.
+    #[prost(message, repeated, tag = "1")]
+    pub messages: Vec<Any>,
+
+    /// Memo attached to the transaction.
+    #[prost(string, tag = "2")]
+    pub memo: String,
+
+    /// Block height after which the transaction is no longer valid.
+    #[prost(uint64, tag = "3")]
+    pub timeout_height: u64,
+
+    /// Extension options which must be understood by the chain.
+    #[prost(message, repeated, tag = "1023")]
+    pub extension_options: Vec<Any>,
+
+    /// Extension options which may be ignored by the chain.
+    #[prost(message, repeated, tag = "2047")]
+    pub non_critical_extension_options: Vec<Any>,
+}
+
+/// `google.protobuf.Any`: a message along with its type URL.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Any {
+    /// Type URL of the message, e.g. `/cosmos.bank.v1beta1.MsgSend`.
+    #[prost(string, tag = "1")]
+    pub type_url: String,
+
+    /// Protobuf-encoded message.
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+/// Validated sign doc, ready to be signed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SignPayload {
+    /// Canonical bytes to sign.
+    pub sign_bytes: Vec<u8>,
+
+    /// Types of the messages in the transaction: type URLs for
+    /// `SIGN_MODE_DIRECT`, and amino type names for amino JSON.
+    pub msg_types: Vec<String>,
+}
+
+impl SignPayload {
+    /// Validate a Protobuf-encoded `SignDoc` against the expected chain ID and
+    /// account number.
+    ///
+    /// The sign bytes are the re-encoded `SignDoc`, so unknown fields are
+    /// never signed.
+    pub fn from_direct(sign_doc: &[u8], chain_id: &str, account_number: u64) -> Result<Self> {
+        let sign_doc = SignDoc::decode(sign_doc)?;
+        check_chain_id(chain_id, &sign_doc.chain_id)?;
+        check_account_number(account_number, sign_doc.account_number)?;
+
+        let body = TxBody::decode(sign_doc.body_bytes.as_slice())?;
+        if body.messages.is_empty() {
+            return Err(malformed("transaction contains no messages"));
+        }
+
+        Ok(Self {
+            sign_bytes: sign_doc.encode_to_vec(),
+            msg_types: body.messages.into_iter().map(|msg| msg.type_url).collect(),
+        })
+    }
+
+    /// Validate an amino JSON `StdSignDoc` against the expected chain ID and
+    /// account number.
+    ///
+    /// The sign bytes are the sign doc re-serialized the way the Cosmos SDK
+    /// does, i.e. with keys sorted and HTML characters escaped.
+    pub fn from_amino_json(sign_doc: &str, chain_id: &str, account_number: u64) -> Result<Self> {
+        let sign_doc: Value = serde_json::from_str(sign_doc)
+            .map_err(|e| malformed(format!("invalid sign doc JSON: {}", e)))?;
+
+        let fields = sign_doc
+            .as_object()
+            .ok_or_else(|| malformed("sign doc is not a JSON object"))?;
+
+        for key in fields.keys() {
+            if ![
+                "account_number",
+                "chain_id",
+                "fee",
+                "memo",
+                "msgs",
+                "sequence",
+                "timeout_height",
+            ]
+            .contains(&key.as_str())
+            {
+                return Err(malformed(format!("unknown sign doc field: \"{}\"", key)));
+            }
+        }
+
+        check_chain_id(chain_id, string_field(&sign_doc, "chain_id")?)?;
+        check_account_number(account_number, uint_field(&sign_doc, "account_number")?)?;
+        uint_field(&sign_doc, "sequence")?;
+        string_field(&sign_doc, "memo")?;
+
+        if !sign_doc["fee"].is_object() {
+            return Err(malformed("missing or invalid \"fee\""));
+        }
+
+        if fields.contains_key("timeout_height") {
+            uint_field(&sign_doc, "timeout_height")?;
+        }
+
+        let msgs = sign_doc["msgs"]
+            .as_array()
+            .filter(|msgs| !msgs.is_empty())
+            .ok_or_else(|| malformed("missing or empty \"msgs\""))?;
+
+        let msg_types = msgs
+            .iter()
+            .map(|msg| match (msg["type"].as_str(), msg.get("value")) {
+                (Some(msg_type), Some(_)) => Ok(msg_type.to_owned()),
+                _ => Err(malformed("message must have a \"type\" and \"value\"")),
+            })
+            .collect::<Result<_>>()?;
+
+        check_numbers(&sign_doc)?;
+
+        Ok(Self {
+            sign_bytes: canonical_json(&sign_doc)?.into_bytes(),
+            msg_types,
+        })
+    }
+}
+
+/// Serialize JSON the way Go's `encoding/json` does: object keys sorted (by
+/// `serde_json::Map`), no whitespace, and `<`, `>`, `&`, U+2028, and U+2029
+/// escaped.
+///
+/// These characters can only appear inside strings, so they're escaped after
+/// serialization.
+fn canonical_json(value: &Value) -> Result<String> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| malformed(format!("couldn't serialize sign doc: {}", e)))?;
+
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            _ => escaped.push(c),
+        }
+    }
+
+    Ok(escaped)
+}
+
+/// Reject numbers which Go would serialize differently after decoding them
+/// into a `float64` (amino JSON encodes 64-bit integers as strings, so
+/// well-formed sign docs only contain small integers).
+fn check_numbers(value: &Value) -> Result<()> {
+    match value {
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) if n <= MAX_SAFE_INTEGER => Ok(()),
+            (_, Some(n)) if n.unsigned_abs() <= MAX_SAFE_INTEGER => Ok(()),
+            _ => Err(malformed(format!("unsupported number in sign doc: {}", n))),
+        },
+        Value::Array(values) => values.iter().try_for_each(check_numbers),
+        Value::Object(fields) => fields.values().try_for_each(check_numbers),
+        Value::Null | Value::Bool(_) | Value::String(_) => Ok(()),
+    }
+}
+
+/// Check the chain ID of a sign doc.
+fn check_chain_id(expected: &str, actual: &str) -> Result<()> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::ChainIdMismatch {
+            expected: expected.to_owned(),
+            actual: actual.to_owned(),
+        })
+    }
+}
+
+/// Check the account number of a sign doc.
+fn check_account_number(expected: u64, actual: u64) -> Result<()> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::AccountNumberMismatch { expected, actual })
+    }
+}
+
+/// Get a string field of an amino JSON sign doc.
+fn string_field<'a>(sign_doc: &'a Value, name: &str) -> Result<&'a str> {
+    sign_doc[name]
+        .as_str()
+        .ok_or_else(|| malformed(format!("missing or invalid \"{}\"", name)))
+}
+
+/// Get an integer field of an amino JSON sign doc, which amino encodes as a
+/// decimal string.
+fn uint_field(sign_doc: &Value, name: &str) -> Result<u64> {
+    string_field(sign_doc, name)?
+        .parse()
+        .map_err(|_| malformed(format!("invalid \"{}\"", name)))
+}
+
+/// Create a [`Error::MessageMalformed`] with the given reason.
+fn malformed(reason: impl Into<String>) -> Error {
+    Error::MessageMalformed {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Any, SignDoc, SignPayload, TxBody};
+    use crate::Error;
+    use prost::Message;
+
+    /// Amino JSON sign doc for a bank send.
+    const AMINO_SIGN_DOC: &str = r#"{
+        "chain_id": "cosmoshub-4",
+        "account_number": "42",
+        "sequence": "7",
+        "fee": {"amount": [{"denom": "uatom", "amount": "5000"}], "gas": "200000"},
+        "msgs": [{
+            "type": "cosmos-sdk/MsgSend",
+            "value": {
+                "from_address": "cosmos1a",
+                "to_address": "cosmos1b",
+                "amount": [{"denom": "uatom", "amount": "1"}]
+            }
+        }],
+        "memo": "<&>"
+    }"#;
+
+    fn sign_doc(chain_id: &str, account_number: u64) -> Vec<u8> {
+        let body = TxBody {
+            messages: vec![Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_owned(),
+                value: vec![0x0a, 0x01, 0x61],
+            }],
+            memo: "hello".to_owned(),
+            ..Default::default()
+        };
+
+        SignDoc {
+            body_bytes: body.encode_to_vec(),
+            auth_info_bytes: vec![0x12, 0x00],
+            chain_id: chain_id.to_owned(),
+            account_number,
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn direct() {
+        let sign_doc = sign_doc("cosmoshub-4", 42);
+        let payload = SignPayload::from_direct(&sign_doc, "cosmoshub-4", 42).unwrap();
+        assert_eq!(payload.sign_bytes, sign_doc);
+        assert_eq!(payload.msg_types, ["/cosmos.bank.v1beta1.MsgSend"]);
+
+        assert!(matches!(
+            SignPayload::from_direct(&sign_doc, "osmosis-1", 42),
+            Err(Error::ChainIdMismatch { .. })
+        ));
+        assert!(matches!(
+            SignPayload::from_direct(&sign_doc, "cosmoshub-4", 43),
+            Err(Error::AccountNumberMismatch {
+                expected: 43,
+                actual: 42
+            })
+        ));
+    }
+
+    #[test]
+    fn direct_drops_unknown_fields() {
+        let mut sign_doc = sign_doc("cosmoshub-4", 42);
+        let canonical = sign_doc.clone();
+        sign_doc.extend_from_slice(&[0x2a, 0x01, 0xff]);
+
+        let payload = SignPayload::from_direct(&sign_doc, "cosmoshub-4", 42).unwrap();
+        assert_eq!(payload.sign_bytes, canonical);
+    }
+
+    #[test]
+    fn amino_json() {
+        let payload = SignPayload::from_amino_json(AMINO_SIGN_DOC, "cosmoshub-4", 42).unwrap();
+        assert_eq!(payload.msg_types, ["cosmos-sdk/MsgSend"]);
+        assert_eq!(
+            String::from_utf8(payload.sign_bytes).unwrap(),
+            concat!(
+                r#"{"account_number":"42","chain_id":"cosmoshub-4","#,
+                r#""fee":{"amount":[{"amount":"5000","denom":"uatom"}],"gas":"200000"},"#,
+                r#""memo":"\u003c\u0026\u003e","#,
+                r#""msgs":[{"type":"cosmos-sdk/MsgSend","value":{"amount":[{"amount":"1","denom":"uatom"}],"#,
+                r#""from_address":"cosmos1a","to_address":"cosmos1b"}}],"sequence":"7"}"#
+            )
+        );
+
+        assert!(matches!(
+            SignPayload::from_amino_json(AMINO_SIGN_DOC, "cosmoshub-4", 1),
+            Err(Error::AccountNumberMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn amino_json_malformed() {
+        for sign_doc in [
+            "[]",
+            r#"{"chain_id":"cosmoshub-4","account_number":"42","sequence":"7","fee":{},"msgs":[],"memo":""}"#,
+            r#"{"chain_id":"cosmoshub-4","account_number":42,"sequence":"7","fee":{},"msgs":[{"type":"a","value":{}}],"memo":""}"#,
+            r#"{"chain_id":"cosmoshub-4","account_number":"42","sequence":"7","fee":{},"msgs":[{"type":"a","value":{}}],"memo":"","extra":1}"#,
+            r#"{"chain_id":"cosmoshub-4","account_number":"42","sequence":"7","fee":{"gas":1.5},"msgs":[{"type":"a","value":{}}],"memo":""}"#,
+        ] {
+            assert!(
+                matches!(
+                    SignPayload::from_amino_json(sign_doc, "cosmoshub-4", 42),
+                    Err(Error::MessageMalformed { .. })
+                ),
+                "{}",
+                sign_doc
+            );
+        }
+    }
+}
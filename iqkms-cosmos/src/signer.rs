@@ -0,0 +1,288 @@
+//! iqkms Cosmos RPC service.
+
+use crate::{Error, Policy, sign_doc::SignPayload};
+use proto::cosmos::{SignAminoJsonRequest, SignDirectRequest, Signature, signer_server::Signer};
+use signing::{VerifyingKey, signature::ecdsa::secp256k1};
+use tonic::{Request, Response, Status};
+use tower::{Service, ServiceExt};
+use tracing::{info, trace};
+use types::{BoxError, hex};
+
+/// `tracing` target for audit log entries.
+const AUDIT_TARGET: &str = "iqkms::audit";
+
+/// Size of a compressed secp256k1 public key.
+const COMPRESSED_PUBLIC_KEY_SIZE: usize = 33;
+
+/// Size of an `r || s` secp256k1 signature.
+const SIGNATURE_SIZE: usize = 64;
+
+/// Signer gRPC service.
+#[derive(Clone)]
+pub struct SignerService<S> {
+    /// Reference to the signer service.
+    signing_service: S,
+
+    /// Policy restricting which operations may be performed.
+    policy: Policy,
+}
+
+impl<S> SignerService<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    /// Create a new RPC service with the given keyring.
+    pub fn new(signing_service: S) -> Self {
+        Self {
+            signing_service,
+            policy: Policy::default(),
+        }
+    }
+
+    /// Set the policy restricting which operations may be performed.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sign a validated sign doc with the key with the given public key after
+    /// checking it against the policy.
+    ///
+    /// Returns a low-S `r || s` signature over the SHA-256 digest of the sign
+    /// bytes.
+    pub(crate) async fn sign(
+        &self,
+        public_key: &[u8],
+        payload: SignPayload,
+    ) -> Result<Signature, Error> {
+        let verifying_key = parse_public_key(public_key)?;
+        self.policy
+            .check_msg_types(payload.msg_types.iter().map(String::as_str))?;
+
+        info!(
+            target: AUDIT_TARGET,
+            signer = %hex::lower::encode_string(public_key),
+            "signing Cosmos transaction: {}",
+            payload.msg_types.join(", ")
+        );
+
+        let request = signing::Request::Sign {
+            key_handle: VerifyingKey::EcdsaSecp256k1(verifying_key).into(),
+            msg: payload.sign_bytes.into(),
+        };
+
+        match self.call_service(request).await? {
+            signing::Response::Sign {
+                signature,
+                verifying_key: VerifyingKey::EcdsaSecp256k1(vk),
+            } if vk == verifying_key && signature.len() == SIGNATURE_SIZE => Ok(Signature {
+                signature: signature.to_vec(),
+            }),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Make a request to the signing service.
+    async fn call_service(&self, req: signing::Request) -> Result<signing::Response, Error> {
+        let mut signing_service = self.signing_service.clone();
+
+        signing_service
+            .ready()
+            .await
+            .map_err(|_| Error::SigningServiceUnavailable)?
+            .call(req)
+            .await
+            .map_err(|e| signing::Error::from(e).into())
+    }
+}
+
+#[tonic::async_trait]
+impl<S> Signer for SignerService<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    async fn sign_direct(
+        &self,
+        request: Request<SignDirectRequest>,
+    ) -> Result<Response<Signature>, Status> {
+        trace!("sign_direct[{:?}]: {:?}", request.remote_addr(), request);
+
+        let request = request.into_inner();
+        let payload =
+            SignPayload::from_direct(&request.sign_doc, &request.chain_id, request.account_number)?;
+
+        Ok(self
+            .sign(&request.public_key, payload)
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_amino_json(
+        &self,
+        request: Request<SignAminoJsonRequest>,
+    ) -> Result<Response<Signature>, Status> {
+        trace!(
+            "sign_amino_json[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let payload = SignPayload::from_amino_json(
+            &request.sign_doc,
+            &request.chain_id,
+            request.account_number,
+        )?;
+
+        Ok(self
+            .sign(&request.public_key, payload)
+            .await
+            .map(Response::new)?)
+    }
+}
+
+/// Parse a compressed secp256k1 public key.
+fn parse_public_key(bytes: &[u8]) -> Result<secp256k1::VerifyingKey, Error> {
+    if bytes.len() != COMPRESSED_PUBLIC_KEY_SIZE {
+        return Err(Error::PublicKeyMalformed);
+    }
+
+    secp256k1::VerifyingKey::from_sec1_bytes(bytes).map_err(|_| Error::PublicKeyMalformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignerService;
+    use crate::{
+        Policy,
+        sign_doc::{Any, SignDoc, TxBody},
+    };
+    use prost::Message;
+    use proto::cosmos::{SignAminoJsonRequest, SignDirectRequest, signer_server::Signer};
+    use signing::signature::{Verifier, ecdsa::secp256k1};
+    use tonic::{Code, Request};
+    use tower::{ServiceBuilder, buffer::Buffer};
+
+    type Service = SignerService<Buffer<signing::SigningService, signing::Request>>;
+
+    fn signer() -> (Service, secp256k1::VerifyingKey) {
+        let signing_key = secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let mut signing_service = signing::SigningService::new();
+        signing_service
+            .add_key(signing::SigningKey::from(signing_key))
+            .unwrap();
+
+        let service = SignerService::new(ServiceBuilder::new().buffer(1).service(signing_service));
+        (service, verifying_key)
+    }
+
+    fn sign_direct_request(verifying_key: &secp256k1::VerifyingKey) -> SignDirectRequest {
+        let body = TxBody {
+            messages: vec![Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_owned(),
+                value: vec![],
+            }],
+            ..Default::default()
+        };
+
+        SignDirectRequest {
+            public_key: verifying_key.to_bytes().to_vec(),
+            sign_doc: SignDoc {
+                body_bytes: body.encode_to_vec(),
+                auth_info_bytes: vec![],
+                chain_id: "cosmoshub-4".to_owned(),
+                account_number: 42,
+            }
+            .encode_to_vec(),
+            chain_id: "cosmoshub-4".to_owned(),
+            account_number: 42,
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_direct() {
+        let (service, verifying_key) = signer();
+        let request = sign_direct_request(&verifying_key);
+        let sign_doc = request.sign_doc.clone();
+
+        let response = Signer::sign_direct(&service, Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let signature = secp256k1::Signature::try_from(response.signature.as_slice()).unwrap();
+        assert!(signature.normalize_s().is_none());
+        assert!(verifying_key.verify(&sign_doc, &signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn sign_amino_json() {
+        let (service, verifying_key) = signer();
+        let request = SignAminoJsonRequest {
+            public_key: verifying_key.to_bytes().to_vec(),
+            sign_doc: r#"{"msgs":[{"type":"cosmos-sdk/MsgSend","value":{}}],"memo":"","fee":{},"sequence":"0","chain_id":"cosmoshub-4","account_number":"42"}"#.to_owned(),
+            chain_id: "cosmoshub-4".to_owned(),
+            account_number: 42,
+        };
+
+        let response = Signer::sign_amino_json(&service, Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let signature = secp256k1::Signature::try_from(response.signature.as_slice()).unwrap();
+        let sign_bytes = br#"{"account_number":"42","chain_id":"cosmoshub-4","fee":{},"memo":"","msgs":[{"type":"cosmos-sdk/MsgSend","value":{}}],"sequence":"0"}"#;
+        assert!(verifying_key.verify(sign_bytes, &signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn mismatches() {
+        let (service, verifying_key) = signer();
+
+        let mut request = sign_direct_request(&verifying_key);
+        request.chain_id = "osmosis-1".to_owned();
+        let status = Signer::sign_direct(&service, Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let mut request = sign_direct_request(&verifying_key);
+        request.account_number = 1;
+        let status = Signer::sign_direct(&service, Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let mut request = sign_direct_request(&verifying_key);
+        request.public_key.truncate(32);
+        let status = Signer::sign_direct(&service, Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn msg_type_not_allowed() {
+        let (service, verifying_key) = signer();
+        let service = service
+            .with_policy(Policy::new().allow_msg_type("/cosmos.staking.v1beta1.MsgDelegate"));
+
+        let status =
+            Signer::sign_direct(&service, Request::new(sign_direct_request(&verifying_key)))
+                .await
+                .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
+}
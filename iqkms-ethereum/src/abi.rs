@@ -0,0 +1,90 @@
+//! Contract ABI registry.
+
+use crate::{Error, Result};
+use std::{collections::BTreeMap, fmt};
+use types::ethereum::{
+    Address, H160,
+    abi::{Abi, Call},
+};
+
+/// Registry of contract ABIs, used to decode calldata of transactions sent to
+/// known contracts for policy evaluation and audit logging.
+#[derive(Clone, Debug, Default)]
+pub struct AbiRegistry {
+    /// Registered contracts by address.
+    contracts: BTreeMap<H160, Contract>,
+}
+
+/// Contract with a registered ABI.
+#[derive(Clone, Debug)]
+struct Contract {
+    /// Human-readable name of the contract.
+    name: String,
+
+    /// ABI of the contract.
+    abi: Abi,
+}
+
+impl AbiRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the ABI of the contract with the given address.
+    pub fn with_contract(mut self, address: Address, name: impl Into<String>, abi: Abi) -> Self {
+        self.contracts.insert(
+            address.hash,
+            Contract {
+                name: name.into(),
+                abi,
+            },
+        );
+        self
+    }
+
+    /// Decode calldata sent to the contract with the given address.
+    ///
+    /// Returns `None` if no ABI is registered for the contract or there is no
+    /// calldata (i.e. a plain ETH transfer). Calldata sent to a registered
+    /// contract which doesn't match its ABI is an error.
+    pub fn decode_call(&self, to: &Address, calldata: &[u8]) -> Result<Option<ContractCall>> {
+        let contract = match self.contracts.get(&to.hash) {
+            Some(contract) if !calldata.is_empty() => contract,
+            _ => return Ok(None),
+        };
+
+        let call = contract
+            .abi
+            .decode_call(calldata)
+            .map_err(|_| Error::MessageMalformed {
+                reason: format!("calldata does not match the ABI of {}", contract.name),
+            })?;
+
+        Ok(Some(ContractCall {
+            contract: contract.name.clone(),
+            address: to.hash.into(),
+            call,
+        }))
+    }
+}
+
+/// Decoded call to a registered contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractCall {
+    /// Name of the contract.
+    pub contract: String,
+
+    /// Address of the contract.
+    pub address: Address,
+
+    /// Decoded function call.
+    pub call: Call,
+}
+
+/// Renders the call as e.g. `USDC.transfer(to: 0x..., amount: 1000)`.
+impl fmt::Display for ContractCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.contract, self.call)
+    }
+}
@@ -0,0 +1,317 @@
+//! Chain registry.
+
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+use types::ethereum::{
+    Address, ChainId, H160,
+    transaction::{Transaction, TxEip7702},
+};
+
+/// Mixed-case checksum encoding used for addresses on a chain.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ChecksumStyle {
+    /// EIP-55 checksums (chain-agnostic).
+    ///
+    /// <https://eips.ethereum.org/EIPS/eip-55>
+    #[default]
+    Eip55,
+
+    /// EIP-1191 checksums, which incorporate the chain ID.
+    ///
+    /// <https://eips.ethereum.org/EIPS/eip-1191>
+    Eip1191,
+}
+
+/// Chain which requests may be signed for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chain {
+    /// EIP-155 chain ID.
+    id: ChainId,
+
+    /// Human-readable name of the chain.
+    name: String,
+
+    /// Does the chain support EIP-1559 (and later) typed transactions?
+    eip1559: bool,
+
+    /// Checksum encoding used for addresses on this chain.
+    checksum: ChecksumStyle,
+}
+
+impl Chain {
+    /// Create a new chain with the given ID and name, which supports EIP-1559
+    /// and uses EIP-55 address checksums.
+    pub fn new(id: ChainId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            eip1559: true,
+            checksum: ChecksumStyle::default(),
+        }
+    }
+
+    /// Set whether the chain supports EIP-1559 (and later) typed
+    /// transactions.
+    pub fn with_eip1559(mut self, eip1559: bool) -> Self {
+        self.eip1559 = eip1559;
+        self
+    }
+
+    /// Set the checksum encoding used for addresses on this chain.
+    pub fn with_checksum(mut self, checksum: ChecksumStyle) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Get the EIP-155 chain ID.
+    pub fn id(&self) -> ChainId {
+        self.id
+    }
+
+    /// Get the human-readable name of the chain.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Does the chain support EIP-1559 (and later) typed transactions?
+    pub fn supports_eip1559(&self) -> bool {
+        self.eip1559
+    }
+
+    /// Get the checksum encoding used for addresses on this chain.
+    pub fn checksum(&self) -> ChecksumStyle {
+        self.checksum
+    }
+
+    /// Get the given address as rendered on this chain.
+    pub fn address(&self, hash: H160) -> Address {
+        Address {
+            hash,
+            chain_id: self.checksum_chain_id(),
+        }
+    }
+
+    /// Parse an address, validating its checksum according to this chain's
+    /// checksum encoding.
+    pub fn parse_address(&self, s: &str) -> Result<Address> {
+        Address::parse_checksummed(s, self.checksum_chain_id())
+            .map_err(|_| Error::AddressMalformed { addr: s.to_owned() })
+    }
+
+    /// Chain ID to include in address checksums, if any.
+    fn checksum_chain_id(&self) -> Option<ChainId> {
+        match self.checksum {
+            ChecksumStyle::Eip55 => None,
+            ChecksumStyle::Eip1191 => Some(self.id),
+        }
+    }
+}
+
+/// Registry of the chains requests may be signed for.
+///
+/// Requests for chains which aren't registered are rejected. The default
+/// registry contains Ethereum mainnet and its public testnets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainRegistry {
+    /// Registered chains.
+    chains: BTreeMap<ChainId, Chain>,
+}
+
+impl ChainRegistry {
+    /// Create an empty registry.
+    pub fn empty() -> Self {
+        Self {
+            chains: BTreeMap::new(),
+        }
+    }
+
+    /// Register the given chain, replacing any existing chain with the same
+    /// ID.
+    pub fn with_chain(mut self, chain: Chain) -> Self {
+        self.chains.insert(chain.id, chain);
+        self
+    }
+
+    /// Get the chain with the given ID.
+    pub fn get(&self, chain_id: ChainId) -> Result<&Chain> {
+        self.chains
+            .get(&chain_id)
+            .ok_or(Error::UnknownChain { chain_id })
+    }
+
+    /// Parse an address, validating its checksum according to the given
+    /// chain's checksum encoding.
+    ///
+    /// Chain ID `0` (i.e. any chain) uses EIP-55 checksums.
+    pub fn parse_address(&self, s: &str, chain_id: ChainId) -> Result<Address> {
+        if chain_id == 0 {
+            s.parse::<Address>()
+                .map_err(|_| Error::AddressMalformed { addr: s.to_owned() })
+        } else {
+            self.get(chain_id)?.parse_address(s)
+        }
+    }
+
+    /// Get the given address as rendered on the given chain.
+    ///
+    /// Chain ID `0` (i.e. any chain) uses EIP-55 checksums.
+    pub fn address(&self, hash: H160, chain_id: ChainId) -> Result<Address> {
+        if chain_id == 0 {
+            Ok(hash.into())
+        } else {
+            Ok(self.get(chain_id)?.address(hash))
+        }
+    }
+
+    /// Iterate over the registered chains.
+    pub fn iter(&self) -> impl Iterator<Item = &Chain> {
+        self.chains.values()
+    }
+
+    /// Check that an EIP-7702 authorization's chain ID is either `0` (i.e.
+    /// valid on any chain) or a registered chain.
+    pub fn check_authorization_chain(&self, chain_id: ChainId) -> Result<()> {
+        if chain_id == 0 {
+            Ok(())
+        } else {
+            self.get(chain_id).map(|_| ())
+        }
+    }
+
+    /// Check that a transaction is bound to a registered chain and only uses
+    /// features supported by that chain.
+    ///
+    /// Legacy transactions without EIP-155 replay protection are rejected as
+    /// they're valid on every chain.
+    pub fn check_transaction(&self, tx: &Transaction) -> Result<&Chain> {
+        let chain_id = tx.chain_id().ok_or_else(|| Error::MessageMalformed {
+            reason: "transaction is missing a chain ID (EIP-155)".to_owned(),
+        })?;
+
+        let chain = self.get(chain_id)?;
+
+        let requires_eip1559 = !matches!(tx, Transaction::Legacy(_) | Transaction::Eip2930(_));
+
+        if requires_eip1559 && !chain.eip1559 {
+            return Err(Error::MessageMalformed {
+                reason: format!(
+                    "chain {} does not support EIP-1559 transactions",
+                    chain.name
+                ),
+            });
+        }
+
+        if let Transaction::Eip7702(TxEip7702 {
+            authorization_list, ..
+        }) = tx
+        {
+            for signed in authorization_list {
+                self.check_authorization_chain(signed.authorization.chain_id)?;
+            }
+        }
+
+        Ok(chain)
+    }
+}
+
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .with_chain(Chain::new(1, "mainnet"))
+            .with_chain(Chain::new(17000, "holesky"))
+            .with_chain(Chain::new(560048, "hoodi"))
+            .with_chain(Chain::new(11155111, "sepolia"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chain, ChainRegistry, ChecksumStyle};
+    use types::{
+        Bytes,
+        ethereum::{
+            U256,
+            transaction::{Transaction, TxEip1559, TxLegacy},
+        },
+    };
+
+    /// RSK mainnet, which uses EIP-1191 checksums and no EIP-1559.
+    fn rsk() -> Chain {
+        Chain::new(30, "rsk")
+            .with_eip1559(false)
+            .with_checksum(ChecksumStyle::Eip1191)
+    }
+
+    fn legacy_tx(chain_id: Option<u64>) -> Transaction {
+        TxLegacy {
+            chain_id,
+            gas_price: U256::one(),
+            gas_limit: 21000,
+            input: Bytes::new(),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn rejects_unknown_chains() {
+        let registry = ChainRegistry::default();
+        assert_eq!(registry.get(1).unwrap().name(), "mainnet");
+        assert!(registry.get(30).is_err());
+        assert!(registry.check_transaction(&legacy_tx(Some(30))).is_err());
+        assert!(registry.check_authorization_chain(0).is_ok());
+        assert!(registry.check_authorization_chain(30).is_err());
+    }
+
+    #[test]
+    fn rejects_unprotected_transactions() {
+        let registry = ChainRegistry::default();
+        assert!(registry.check_transaction(&legacy_tx(Some(1))).is_ok());
+        assert!(registry.check_transaction(&legacy_tx(None)).is_err());
+    }
+
+    #[test]
+    fn rejects_eip1559_on_unsupported_chains() {
+        let registry = ChainRegistry::empty().with_chain(rsk());
+        let tx = Transaction::from(TxEip1559 {
+            chain_id: 30,
+            ..Default::default()
+        });
+
+        assert!(registry.check_transaction(&tx).is_err());
+        assert!(registry.check_transaction(&legacy_tx(Some(30))).is_ok());
+    }
+
+    /// Test vectors from EIP-1191.
+    #[test]
+    fn eip1191_addresses() {
+        let chain = rsk();
+        let addr = chain
+            .parse_address("0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD")
+            .unwrap();
+
+        assert_eq!(
+            addr.to_string(),
+            "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+        );
+
+        // EIP-55 checksums aren't valid on EIP-1191 chains
+        assert!(
+            chain
+                .parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+                .is_err()
+        );
+
+        // ...and vice versa
+        let mainnet = Chain::new(1, "mainnet");
+        assert!(
+            mainnet
+                .parse_address("0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD")
+                .is_err()
+        );
+        assert_eq!(
+            mainnet.address(addr.hash).to_string(),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+    }
+}
@@ -0,0 +1,582 @@
+//! iqkms Ethereum consensus layer RPC service.
+
+use crate::{Error, SlashingProtection};
+use proto::ethereum::{
+    self as pb, BlsSignature, SignAggregateAndProofRequest, SignAttestationRequest,
+    SignBlockRequest, SignRandaoRevealRequest, SignSelectionProofRequest, SignVoluntaryExitRequest,
+    consensus_signer_server::ConsensusSigner,
+};
+use signing::{VerifyingKey, signature::bls12_381};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tonic::{Request, Response, Status};
+use tower::{Service, ServiceExt};
+use tracing::trace;
+use types::{
+    BoxError, Bytes,
+    ethereum::{
+        H256,
+        consensus::{
+            AggregateAndProof, Attestation, AttestationData, BeaconBlockHeader, Bitlist,
+            Checkpoint, Fork, ForkInfo, Root, VoluntaryExit, capella_fork_version,
+            randao_signing_root, selection_proof_signing_root,
+        },
+    },
+};
+
+/// Consensus signer gRPC service.
+#[derive(Clone)]
+pub struct ConsensusSignerService<S> {
+    /// Reference to the signer service.
+    signing_service: S,
+
+    /// Slashing protection database consulted before signing block proposals
+    /// and attestations.
+    slashing_protection: Arc<Mutex<SlashingProtection>>,
+}
+
+impl<S> ConsensusSignerService<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    /// Create a new RPC service with the given keyring and slashing
+    /// protection database.
+    pub fn new(signing_service: S, slashing_protection: SlashingProtection) -> Self {
+        Self {
+            signing_service,
+            slashing_protection: Arc::new(Mutex::new(slashing_protection)),
+        }
+    }
+
+    /// Lock the slashing protection database.
+    fn slashing_protection(&self) -> Result<MutexGuard<'_, SlashingProtection>, Error> {
+        self.slashing_protection
+            .lock()
+            .map_err(|_| Error::SlashingProtectionDb {
+                reason: "slashing protection database lock poisoned".to_owned(),
+            })
+    }
+
+    /// Sign the given signing root using the given BLS12-381 key.
+    async fn sign_root(
+        &self,
+        verifying_key: bls12_381::VerifyingKey,
+        signing_root: Root,
+    ) -> Result<BlsSignature, Error> {
+        let request = signing::Request::Sign {
+            key_handle: VerifyingKey::Bls12381(verifying_key).into(),
+            msg: Bytes::copy_from_slice(signing_root.as_bytes()),
+        };
+
+        let mut signing_service = self.signing_service.clone();
+        let response = signing_service
+            .ready()
+            .await
+            .map_err(|_| Error::SigningServiceUnavailable)?
+            .call(request)
+            .await
+            .map_err(signing::Error::from)?;
+
+        match response {
+            signing::Response::Sign {
+                verifying_key: VerifyingKey::Bls12381(vk),
+                signature,
+            } if vk == verifying_key && signature.len() == bls12_381::Signature::BYTE_SIZE => {
+                Ok(BlsSignature {
+                    signature: signature.to_vec(),
+                })
+            }
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<S> ConsensusSigner for ConsensusSignerService<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    async fn sign_block(
+        &self,
+        request: Request<SignBlockRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!("sign_block[{:?}]: {:?}", request.remote_addr(), request);
+
+        let request = request.into_inner();
+        let pubkey = pubkey(&request.pubkey)?;
+        let fork_info = fork_info(request.fork_info)?;
+        let block = block_header(required(request.block, "block")?)?;
+        let signing_root = block.signing_root(&fork_info);
+
+        self.slashing_protection()?.record_block(
+            fork_info.genesis_validators_root,
+            &pubkey,
+            block.slot,
+            signing_root,
+        )?;
+
+        Ok(self
+            .sign_root(pubkey, signing_root)
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_attestation(
+        &self,
+        request: Request<SignAttestationRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!(
+            "sign_attestation[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let pubkey = pubkey(&request.pubkey)?;
+        let fork_info = fork_info(request.fork_info)?;
+        let data = attestation_data(request.attestation)?;
+        let signing_root = data.signing_root(&fork_info);
+
+        self.slashing_protection()?.record_attestation(
+            fork_info.genesis_validators_root,
+            &pubkey,
+            data.source.epoch,
+            data.target.epoch,
+            signing_root,
+        )?;
+
+        Ok(self
+            .sign_root(pubkey, signing_root)
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_aggregate_and_proof(
+        &self,
+        request: Request<SignAggregateAndProofRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!(
+            "sign_aggregate_and_proof[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let fork_info = fork_info(request.fork_info)?;
+        let aggregate_and_proof = aggregate_and_proof(required(
+            request.aggregate_and_proof,
+            "aggregate_and_proof",
+        )?)?;
+
+        Ok(self
+            .sign_root(
+                pubkey(&request.pubkey)?,
+                aggregate_and_proof.signing_root(&fork_info),
+            )
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_selection_proof(
+        &self,
+        request: Request<SignSelectionProofRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!(
+            "sign_selection_proof[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let fork_info = fork_info(request.fork_info)?;
+
+        Ok(self
+            .sign_root(
+                pubkey(&request.pubkey)?,
+                selection_proof_signing_root(request.slot, &fork_info),
+            )
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_randao_reveal(
+        &self,
+        request: Request<SignRandaoRevealRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!(
+            "sign_randao_reveal[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let fork_info = fork_info(request.fork_info)?;
+
+        Ok(self
+            .sign_root(
+                pubkey(&request.pubkey)?,
+                randao_signing_root(request.epoch, &fork_info),
+            )
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_voluntary_exit(
+        &self,
+        request: Request<SignVoluntaryExitRequest>,
+    ) -> Result<Response<BlsSignature>, Status> {
+        trace!(
+            "sign_voluntary_exit[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let fork_info = fork_info(request.fork_info)?;
+        let exit = required(request.voluntary_exit, "voluntary_exit")?;
+        let exit = VoluntaryExit {
+            epoch: exit.epoch,
+            validator_index: exit.validator_index,
+        };
+
+        // EIP-7044: voluntary exits are always signed using the Capella fork
+        // version, regardless of the fork info supplied by the caller
+        let genesis_validators_root = fork_info.genesis_validators_root;
+        let capella_fork_version =
+            capella_fork_version(genesis_validators_root).ok_or_else(|| {
+                Error::MessageMalformed {
+                    reason: "unknown network: no Capella fork version for genesis_validators_root"
+                        .to_owned(),
+                }
+            })?;
+
+        Ok(self
+            .sign_root(
+                pubkey(&request.pubkey)?,
+                exit.signing_root(capella_fork_version, genesis_validators_root),
+            )
+            .await
+            .map(Response::new)?)
+    }
+}
+
+/// Ensure a required message field is present.
+fn required<T>(field: Option<T>, name: &str) -> Result<T, Error> {
+    field.ok_or_else(|| Error::MessageMalformed {
+        reason: format!("missing {}", name),
+    })
+}
+
+/// Parse a BLS12-381 public key.
+fn pubkey(bytes: &[u8]) -> Result<bls12_381::VerifyingKey, Error> {
+    bls12_381::VerifyingKey::from_bytes(bytes).map_err(|_| Error::MessageMalformed {
+        reason: "malformed BLS12-381 public key".to_owned(),
+    })
+}
+
+/// Parse a fixed-size byte array field.
+fn bytes<const N: usize>(field: &[u8], name: &str) -> Result<[u8; N], Error> {
+    field.try_into().map_err(|_| Error::MessageMalformed {
+        reason: format!("{} must be {} bytes", name, N),
+    })
+}
+
+/// Parse a 32-byte root field.
+fn root(field: &[u8], name: &str) -> Result<Root, Error> {
+    bytes::<32>(field, name).map(H256)
+}
+
+/// Parse fork info.
+fn fork_info(fork_info: Option<pb::ForkInfo>) -> Result<ForkInfo, Error> {
+    let fork_info = required(fork_info, "fork_info")?;
+    let fork = required(fork_info.fork, "fork")?;
+
+    Ok(ForkInfo {
+        fork: Fork {
+            previous_version: bytes(&fork.previous_version, "previous_version")?,
+            current_version: bytes(&fork.current_version, "current_version")?,
+            epoch: fork.epoch,
+        },
+        genesis_validators_root: root(
+            &fork_info.genesis_validators_root,
+            "genesis_validators_root",
+        )?,
+    })
+}
+
+/// Parse a beacon block header.
+fn block_header(header: pb::BeaconBlockHeader) -> Result<BeaconBlockHeader, Error> {
+    Ok(BeaconBlockHeader {
+        slot: header.slot,
+        proposer_index: header.proposer_index,
+        parent_root: root(&header.parent_root, "parent_root")?,
+        state_root: root(&header.state_root, "state_root")?,
+        body_root: root(&header.body_root, "body_root")?,
+    })
+}
+
+/// Parse a checkpoint.
+fn checkpoint(checkpoint: Option<pb::Checkpoint>, name: &str) -> Result<Checkpoint, Error> {
+    let checkpoint = required(checkpoint, name)?;
+
+    Ok(Checkpoint {
+        epoch: checkpoint.epoch,
+        root: root(&checkpoint.root, name)?,
+    })
+}
+
+/// Parse attestation data.
+fn attestation_data(data: Option<pb::AttestationData>) -> Result<AttestationData, Error> {
+    let data = required(data, "attestation")?;
+
+    Ok(AttestationData {
+        slot: data.slot,
+        index: data.index,
+        beacon_block_root: root(&data.beacon_block_root, "beacon_block_root")?,
+        source: checkpoint(data.source, "source")?,
+        target: checkpoint(data.target, "target")?,
+    })
+}
+
+/// Parse an aggregate-and-proof.
+fn aggregate_and_proof(msg: pb::AggregateAndProof) -> Result<AggregateAndProof, Error> {
+    let aggregate = required(msg.aggregate, "aggregate")?;
+
+    let committee_bits = if aggregate.committee_bits.is_empty() {
+        None
+    } else {
+        Some(bytes(&aggregate.committee_bits, "committee_bits")?)
+    };
+
+    let aggregate = Attestation {
+        aggregation_bits: Bitlist::from_ssz_bytes(&aggregate.aggregation_bits).map_err(|_| {
+            Error::MessageMalformed {
+                reason: "malformed aggregation_bits".to_owned(),
+            }
+        })?,
+        data: attestation_data(aggregate.data)?,
+        signature: bytes(&aggregate.signature, "signature")?,
+        committee_bits,
+    };
+
+    if aggregate.aggregation_bits.len() > aggregate.aggregation_bits_limit() {
+        return Err(Error::MessageMalformed {
+            reason: "too many aggregation_bits".to_owned(),
+        });
+    }
+
+    Ok(AggregateAndProof {
+        aggregator_index: msg.aggregator_index,
+        aggregate,
+        selection_proof: bytes(&msg.selection_proof, "selection_proof")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConsensusSignerService;
+    use crate::SlashingProtection;
+    use hex_literal::hex;
+    use proto::ethereum::{
+        AttestationData, Checkpoint, Fork, ForkInfo, SignAttestationRequest,
+        SignVoluntaryExitRequest, VoluntaryExit, consensus_signer_server::ConsensusSigner,
+    };
+    use signing::{
+        SigningService,
+        signature::{Signature, Verifier, bls12_381},
+    };
+    use tonic::{Code, Request};
+    use tower::buffer::Buffer;
+    use types::ethereum::{H256, consensus};
+
+    const SECRET_KEY: [u8; 32] = [
+        0x26, 0x3d, 0xbd, 0x79, 0x2f, 0x5b, 0x1b, 0xe4, 0x7e, 0xd8, 0x5f, 0x89, 0x38, 0xc0, 0xf2,
+        0x95, 0x86, 0xaf, 0x0d, 0x3a, 0xc7, 0xb9, 0x77, 0xf2, 0x1c, 0x27, 0x8f, 0xe1, 0x46, 0x20,
+        0x40, 0xe3,
+    ];
+
+    fn attestation_request(pubkey: Vec<u8>) -> SignAttestationRequest {
+        SignAttestationRequest {
+            pubkey,
+            fork_info: Some(ForkInfo {
+                fork: Some(Fork {
+                    previous_version: vec![4, 0, 0, 0],
+                    current_version: vec![5, 0, 0, 0],
+                    epoch: 364032,
+                }),
+                genesis_validators_root: vec![0x4b; 32],
+            }),
+            attestation: Some(AttestationData {
+                slot: 11649024,
+                index: 3,
+                beacon_block_root: vec![0x11; 32],
+                source: Some(Checkpoint {
+                    epoch: 364030,
+                    root: vec![0x22; 32],
+                }),
+                target: Some(Checkpoint {
+                    epoch: 364031,
+                    root: vec![0x33; 32],
+                }),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_attestation() {
+        let signing_key = bls12_381::SigningKey::from_bytes(&SECRET_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let mut signing_service = SigningService::new();
+        signing_service.add_key(signing_key).unwrap();
+        let service = ConsensusSignerService::new(
+            Buffer::new(signing_service, 1),
+            SlashingProtection::in_memory(),
+        );
+
+        let request = attestation_request(verifying_key.to_bytes().to_vec());
+        let signature = ConsensusSigner::sign_attestation(&service, Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .signature;
+
+        let fork_info = consensus::ForkInfo {
+            fork: consensus::Fork {
+                previous_version: [4, 0, 0, 0],
+                current_version: [5, 0, 0, 0],
+                epoch: 364032,
+            },
+            genesis_validators_root: H256([0x4b; 32]),
+        };
+        let data = consensus::AttestationData {
+            slot: 11649024,
+            index: 3,
+            beacon_block_root: H256([0x11; 32]),
+            source: consensus::Checkpoint {
+                epoch: 364030,
+                root: H256([0x22; 32]),
+            },
+            target: consensus::Checkpoint {
+                epoch: 364031,
+                root: H256([0x33; 32]),
+            },
+        };
+
+        let signature = bls12_381::Signature::from_bytes(&signature).unwrap();
+        let signing_root = data.signing_root(&fork_info);
+        assert!(
+            verifying_key
+                .verify(signing_root.as_bytes(), &signature)
+                .is_ok()
+        );
+
+        // Signing the same attestation again is permitted, but a double vote isn't
+        ConsensusSigner::sign_attestation(&service, Request::new(request.clone()))
+            .await
+            .unwrap();
+
+        let mut double_vote = request;
+        double_vote.attestation.as_mut().unwrap().beacon_block_root = vec![0x44; 32];
+        let code = ConsensusSigner::sign_attestation(&service, Request::new(double_vote))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn sign_voluntary_exit() {
+        let signing_key = bls12_381::SigningKey::from_bytes(&SECRET_KEY).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let mut signing_service = SigningService::new();
+        signing_service.add_key(signing_key).unwrap();
+        let service = ConsensusSignerService::new(
+            Buffer::new(signing_service, 1),
+            SlashingProtection::in_memory(),
+        );
+
+        // Mainnet genesis validators root
+        let genesis_validators_root = H256(hex!(
+            "4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+        ));
+
+        // Request supplies the Electra fork info, which must be ignored
+        let mut request = SignVoluntaryExitRequest {
+            pubkey: verifying_key.to_bytes().to_vec(),
+            fork_info: Some(ForkInfo {
+                fork: Some(Fork {
+                    previous_version: vec![4, 0, 0, 0],
+                    current_version: vec![5, 0, 0, 0],
+                    epoch: 364032,
+                }),
+                genesis_validators_root: genesis_validators_root.as_bytes().to_vec(),
+            }),
+            voluntary_exit: Some(VoluntaryExit {
+                epoch: 364032,
+                validator_index: 7,
+            }),
+        };
+
+        let signature =
+            ConsensusSigner::sign_voluntary_exit(&service, Request::new(request.clone()))
+                .await
+                .unwrap()
+                .into_inner()
+                .signature;
+
+        let exit = consensus::VoluntaryExit {
+            epoch: 364032,
+            validator_index: 7,
+        };
+        let signing_root = exit.signing_root([3, 0, 0, 0], genesis_validators_root);
+        let signature = bls12_381::Signature::from_bytes(&signature).unwrap();
+        assert!(
+            verifying_key
+                .verify(signing_root.as_bytes(), &signature)
+                .is_ok()
+        );
+
+        // Exits for unknown networks are rejected
+        request.fork_info.as_mut().unwrap().genesis_validators_root = vec![0x4b; 32];
+        let code = ConsensusSigner::sign_voluntary_exit(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn malformed_request() {
+        let service = ConsensusSignerService::new(
+            Buffer::new(SigningService::new(), 1),
+            SlashingProtection::in_memory(),
+        );
+
+        let mut request = attestation_request(vec![0; 48]);
+        let code = ConsensusSigner::sign_attestation(&service, Request::new(request.clone()))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::InvalidArgument);
+
+        let signing_key = bls12_381::SigningKey::from_bytes(&SECRET_KEY).unwrap();
+        request.pubkey = signing_key.verifying_key().to_bytes().to_vec();
+        request.attestation.as_mut().unwrap().beacon_block_root = vec![0x11; 31];
+        let code = ConsensusSigner::sign_attestation(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::InvalidArgument);
+    }
+}
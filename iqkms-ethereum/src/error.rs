@@ -17,6 +17,30 @@ pub enum Error {
     /// Malformed Keccak256 digest.
     DigestMalformed,
 
+    /// Malformed message to be signed.
+    MessageMalformed {
+        /// Reason why the message is malformed.
+        reason: String,
+    },
+
+    /// Operation is not permitted by the signing policy.
+    PolicyViolation {
+        /// Reason why the operation was denied.
+        reason: String,
+    },
+
+    /// Signing the message could get the validator slashed.
+    Slashable {
+        /// Reason why the message is slashable.
+        reason: String,
+    },
+
+    /// Slashing protection database could not be accessed.
+    SlashingProtectionDb {
+        /// Reason why the database couldn't be accessed.
+        reason: String,
+    },
+
     /// Signing key not found.
     SigningKeyNotFound {
         /// Requested address.
@@ -28,6 +52,19 @@ pub enum Error {
         /// Reason why the signing operation failed.
         reason: String,
     },
+
+    /// Signing service is not ready to accept requests.
+    SigningServiceUnavailable,
+
+    /// Chain is not present in the chain registry.
+    UnknownChain {
+        /// Requested chain ID.
+        chain_id: u64,
+    },
+
+    /// Signing service returned a response which doesn't match the request
+    /// (e.g. a signature produced by a key which isn't ECDSA/secp256k1).
+    UnexpectedResponse,
 }
 
 impl Error {
@@ -36,8 +73,15 @@ impl Error {
         match self {
             Error::AddressMalformed { .. } => tonic::Code::InvalidArgument,
             Error::DigestMalformed => tonic::Code::InvalidArgument,
+            Error::MessageMalformed { .. } => tonic::Code::InvalidArgument,
+            Error::PolicyViolation { .. } => tonic::Code::PermissionDenied,
+            Error::Slashable { .. } => tonic::Code::PermissionDenied,
+            Error::SlashingProtectionDb { .. } => tonic::Code::Internal,
             Error::SigningKeyNotFound { .. } => tonic::Code::NotFound,
             Error::SigningFailed { .. } => tonic::Code::Internal,
+            Error::SigningServiceUnavailable => tonic::Code::Unavailable,
+            Error::UnknownChain { .. } => tonic::Code::InvalidArgument,
+            Error::UnexpectedResponse => tonic::Code::Internal,
         }
     }
 }
@@ -49,12 +93,23 @@ impl fmt::Display for Error {
                 write!(f, "Ethereum address malformed: \"{}\"", addr)
             }
             Error::DigestMalformed => write!(f, "Keccak256 digest malformed"),
+            Error::MessageMalformed { reason } => write!(f, "message malformed: {}", reason),
+            Error::PolicyViolation { reason } => write!(f, "policy violation: {}", reason),
+            Error::Slashable { reason } => write!(f, "slashable message: {}", reason),
+            Error::SlashingProtectionDb { reason } => {
+                write!(f, "slashing protection database error: {}", reason)
+            }
             Error::SigningKeyNotFound { addr } => write!(f, "signing key not found: \"{}\"", addr),
             Error::SigningFailed { reason } => f.write_str(reason),
+            Error::SigningServiceUnavailable => f.write_str("signing service unavailable"),
+            Error::UnknownChain { chain_id } => write!(f, "unknown chain ID: {}", chain_id),
+            Error::UnexpectedResponse => f.write_str("unexpected response from signing service"),
         }
     }
 }
 
+impl std::error::Error for Error {}
+
 impl From<Error> for tonic::Status {
     fn from(error: Error) -> tonic::Status {
         tonic::Status::new(error.code(), error.to_string())
@@ -74,12 +129,3 @@ impl From<signing::signature::Error> for Error {
         signing::Error.into()
     }
 }
-
-impl From<types::Error> for Error {
-    fn from(_: types::Error) -> Error {
-        // TODO(tarcieri): non-bogus implementation
-        Error::AddressMalformed {
-            addr: "0xdeadbeef".to_owned(),
-        }
-    }
-}
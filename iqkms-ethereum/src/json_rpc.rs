@@ -0,0 +1,751 @@
+//! Ethereum JSON-RPC signer API.
+//!
+//! Implements the subset of the Ethereum JSON-RPC API used by external signers
+//! such as Clef and Web3Signer, allowing existing tooling (e.g. geth's
+//! `--signer` or Foundry) to use keys stored in iqkms:
+//!
+//! - `eth_accounts`
+//! - `eth_sign`
+//! - `personal_sign`
+//! - `eth_signTransaction`
+//! - `eth_signTypedData_v4`
+//!
+//! <https://ethereum.org/en/developers/docs/apis/json-rpc/>
+
+mod transaction_request;
+
+use self::transaction_request::TransactionRequest;
+use crate::{Error, SignerService};
+use proto::ethereum::Signature;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tower::Service;
+use tracing::trace;
+use types::{
+    BoxError, Bytes,
+    ethereum::{Address, ChainId, eip191, eip712::TypedData},
+    hex,
+};
+
+/// JSON-RPC protocol version.
+const JSONRPC_VERSION: &str = "2.0";
+
+/// JSON-RPC service which computes signatures using the Ethereum signer service.
+#[derive(Clone)]
+pub struct JsonRpcService<S> {
+    /// Ethereum signer service.
+    signer: SignerService<S>,
+
+    /// Chain whose address checksums are used by requests which don't carry a
+    /// chain ID (`0` for EIP-55 checksums).
+    chain_id: ChainId,
+}
+
+impl<S> JsonRpcService<S>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    /// Create a new JSON-RPC service backed by the given Ethereum signer service.
+    pub fn new(signer: SignerService<S>) -> Self {
+        Self {
+            signer,
+            chain_id: 0,
+        }
+    }
+
+    /// Set the chain whose address checksums are used by `eth_accounts`,
+    /// `eth_sign` and `personal_sign`, and by transactions and typed data
+    /// which don't specify a chain ID.
+    ///
+    /// Defaults to `0`, i.e. EIP-55 checksums.
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Handle a serialized JSON-RPC request (or batch of requests), returning
+    /// the serialized response.
+    ///
+    /// Returns `None` if there is nothing to respond with, i.e. the request
+    /// consisted solely of notifications.
+    pub async fn handle(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let response = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(requests)) if !requests.is_empty() => {
+                let mut responses = Vec::with_capacity(requests.len());
+
+                for request in requests {
+                    responses.extend(self.handle_value(request).await);
+                }
+
+                if responses.is_empty() {
+                    return None;
+                }
+
+                Value::Array(responses)
+            }
+            Ok(request) => self.handle_value(request).await?,
+            Err(_) => Response::error(Value::Null, RpcError::parse_error()).into(),
+        };
+
+        // Serializing a `serde_json::Value` is infallible
+        Some(serde_json::to_vec(&response).unwrap_or_default())
+    }
+
+    /// Handle an individual JSON-RPC request, returning `None` for
+    /// notifications (i.e. requests without an ID).
+    async fn handle_value(&self, request: Value) -> Option<Value> {
+        let request = match serde_json::from_value::<Request>(request) {
+            Ok(request) if request.jsonrpc == JSONRPC_VERSION => request,
+            _ => return Some(Response::error(Value::Null, RpcError::invalid_request()).into()),
+        };
+
+        trace!("json_rpc[{}]: {:?}", request.method, request.params);
+
+        let result = match request.method.as_str() {
+            "eth_accounts" => self.accounts().await,
+            "eth_sign" => self.eth_sign(&request.params).await,
+            "personal_sign" => self.personal_sign(&request.params).await,
+            "eth_signTransaction" => self.sign_transaction(&request.params).await,
+            "eth_signTypedData_v4" => self.sign_typed_data(&request.params).await,
+            _ => Err(RpcError::method_not_found(&request.method)),
+        };
+
+        // Notifications are processed, but never responded to
+        let id = request.id?;
+
+        Some(match result {
+            Ok(result) => Response::result(id, result).into(),
+            Err(err) => Response::error(id, err).into(),
+        })
+    }
+
+    /// `eth_accounts`: list the addresses of all available keys.
+    async fn accounts(&self) -> Result<Value, RpcError> {
+        let chains = self.signer.chains();
+
+        self.signer
+            .addresses()
+            .await?
+            .into_iter()
+            .map(|addr| Ok(chains.address(addr.hash, self.chain_id)?.to_string()))
+            .collect()
+    }
+
+    /// `eth_sign`: sign an EIP-191 "personal" message.
+    ///
+    /// Params: `[address, data]`
+    async fn eth_sign(&self, params: &Value) -> Result<Value, RpcError> {
+        let (address, data) = params_pair(params)?;
+        let address = self.parse_address(address, self.chain_id)?;
+        self.sign_personal_message(address, &parse_bytes(data)?)
+            .await
+    }
+
+    /// `personal_sign`: sign an EIP-191 "personal" message.
+    ///
+    /// Params: `[data, address]`
+    async fn personal_sign(&self, params: &Value) -> Result<Value, RpcError> {
+        let (data, address) = params_pair(params)?;
+        let address = self.parse_address(address, self.chain_id)?;
+        self.sign_personal_message(address, &parse_bytes(data)?)
+            .await
+    }
+
+    /// `eth_signTransaction`: sign a transaction, returning the signed
+    /// transaction envelope serialized as hex.
+    ///
+    /// Params: `[transaction]`
+    async fn sign_transaction(&self, params: &Value) -> Result<Value, RpcError> {
+        let request = params
+            .get(0)
+            .cloned()
+            .ok_or_else(|| RpcError::invalid_params("missing transaction"))?;
+
+        let request = serde_json::from_value::<TransactionRequest>(request)
+            .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+
+        let chains = self.signer.chains();
+        let from = request.signer(chains)?;
+        let tx = request.into_transaction(chains)?;
+        let signature = self.signer.sign_transaction(from, &tx).await?;
+        let signed_tx = tx.encode_signed(&signature);
+
+        Ok(encode_hex(&signed_tx))
+    }
+
+    /// `eth_signTypedData_v4`: sign EIP-712 typed data.
+    ///
+    /// Params: `[address, typedData]` where `typedData` is either a JSON
+    /// object or a string containing JSON.
+    async fn sign_typed_data(&self, params: &Value) -> Result<Value, RpcError> {
+        let (address, typed_data) = params_pair(params)?;
+
+        let typed_data = match typed_data {
+            Value::String(s) => serde_json::from_str::<TypedData>(s),
+            other => serde_json::from_value::<TypedData>(other.clone()),
+        }
+        .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+
+        let digest = typed_data
+            .signing_hash()
+            .map_err(|_| RpcError::invalid_params("invalid EIP-712 typed data"))?;
+
+        let chain_id = domain_chain_id(&typed_data)?.unwrap_or(self.chain_id);
+        let address = self.parse_address(address, chain_id)?;
+        let signature = self.sign_digest(address, digest.as_bytes()).await?;
+
+        Ok(encode_hex(&signature_bytes(&signature)?))
+    }
+
+    /// Parse an Ethereum address parameter, validating its checksum according
+    /// to the given chain's checksum encoding.
+    fn parse_address(&self, value: &Value, chain_id: ChainId) -> Result<Address, RpcError> {
+        let addr = value
+            .as_str()
+            .ok_or_else(|| RpcError::invalid_params("expected address string"))?;
+
+        Ok(self.signer.chains().parse_address(addr, chain_id)?)
+    }
+
+    /// Sign the given message with EIP-191 "personal" message hashing.
+    async fn sign_personal_message(&self, address: Address, msg: &[u8]) -> Result<Value, RpcError> {
+        let digest = eip191::hash_message(msg);
+        let signature = self.sign_digest(address, digest.as_bytes()).await?;
+        Ok(encode_hex(&signature_bytes(&signature)?))
+    }
+
+    /// Sign the given digest using the signer service, if the policy permits
+    /// signing data it can't check.
+    async fn sign_digest(&self, address: Address, digest: &[u8]) -> Result<Signature, RpcError> {
+        Ok(self
+            .signer
+            .sign_unchecked_digest(address, Bytes::copy_from_slice(digest))
+            .await?)
+    }
+}
+
+/// JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Protocol version: must be `2.0`.
+    jsonrpc: String,
+
+    /// Request ID, which is absent for notifications.
+    #[serde(default, deserialize_with = "deserialize_id")]
+    id: Option<Value>,
+
+    /// Method to invoke.
+    method: String,
+
+    /// Method parameters.
+    #[serde(default)]
+    params: Value,
+}
+
+/// Deserialize a request ID which is present, distinguishing a `null` ID from
+/// an absent one.
+fn deserialize_id<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// JSON-RPC response.
+#[derive(Debug, Serialize)]
+struct Response {
+    /// Protocol version: always `2.0`.
+    jsonrpc: &'static str,
+
+    /// ID of the request this is a response to.
+    id: Value,
+
+    /// Result of a successful request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+
+    /// Error for a failed request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl Response {
+    /// Create a successful response.
+    fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Create an error response.
+    fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl From<Response> for Value {
+    fn from(response: Response) -> Value {
+        serde_json::to_value(response).unwrap_or(Value::Null)
+    }
+}
+
+/// JSON-RPC error object.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct RpcError {
+    /// Error code.
+    code: i64,
+
+    /// Error message.
+    message: String,
+}
+
+impl RpcError {
+    /// Invalid JSON was received.
+    fn parse_error() -> Self {
+        Self {
+            code: -32700,
+            message: "parse error".to_owned(),
+        }
+    }
+
+    /// The JSON sent is not a valid request object.
+    fn invalid_request() -> Self {
+        Self {
+            code: -32600,
+            message: "invalid request".to_owned(),
+        }
+    }
+
+    /// The method does not exist or is not available.
+    fn method_not_found(method: &str) -> Self {
+        Self {
+            code: -32601,
+            message: format!("the method {method} does not exist/is not available"),
+        }
+    }
+
+    /// Invalid method parameters.
+    pub(crate) fn invalid_params(reason: impl Into<String>) -> Self {
+        Self {
+            code: -32602,
+            message: reason.into(),
+        }
+    }
+
+    /// Server error, i.e. the signing operation failed.
+    fn server_error(message: String) -> Self {
+        Self {
+            code: -32000,
+            message,
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(err: Error) -> RpcError {
+        match err {
+            Error::AddressMalformed { .. }
+            | Error::DigestMalformed
+            | Error::UnknownChain { .. } => RpcError::invalid_params(err.to_string()),
+            _ => RpcError::server_error(err.to_string()),
+        }
+    }
+}
+
+/// Get a pair of positional parameters.
+fn params_pair(params: &Value) -> Result<(&Value, &Value), RpcError> {
+    match params.as_array().map(Vec::as_slice) {
+        Some([a, b, ..]) => Ok((a, b)),
+        _ => Err(RpcError::invalid_params("expected 2 parameters")),
+    }
+}
+
+/// Get the chain ID from the EIP-712 domain of the given typed data, if
+/// present.
+///
+/// The chain ID may be given as a number, a decimal string, or a `0x`-prefixed
+/// hex string.
+fn domain_chain_id(typed_data: &TypedData) -> Result<Option<ChainId>, RpcError> {
+    let chain_id = match typed_data.domain.get("chainId") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Number(n)) => n.as_u64(),
+        Some(Value::String(s)) => match s.strip_prefix("0x") {
+            Some(hex) => ChainId::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        Some(_) => None,
+    };
+
+    chain_id
+        .map(Some)
+        .ok_or_else(|| RpcError::invalid_params("malformed EIP-712 domain chainId"))
+}
+
+/// Parse a `0x`-prefixed hex data parameter.
+pub(crate) fn parse_bytes(value: &Value) -> Result<Vec<u8>, RpcError> {
+    value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|s| hex::mixed::decode_vec(s).ok())
+        .ok_or_else(|| RpcError::invalid_params("expected 0x-prefixed hex data"))
+}
+
+/// Encode the given bytes as `0x`-prefixed hex.
+fn encode_hex(bytes: &[u8]) -> Value {
+    json!(format!("0x{}", hex::lower::encode_string(bytes)))
+}
+
+/// Serialize a signature as `r || s || v`.
+fn signature_bytes(signature: &Signature) -> Result<Vec<u8>, RpcError> {
+    let v = u8::try_from(signature.v).map_err(|_| Error::SigningFailed {
+        reason: "invalid recovery ID".to_owned(),
+    })?;
+
+    let mut bytes = Vec::with_capacity(65);
+    bytes.extend_from_slice(&signature.r);
+    bytes.extend_from_slice(&signature.s);
+    bytes.push(v);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonRpcService, parse_bytes};
+    use crate::{Chain, ChainRegistry, ChecksumStyle, Policy, SignerService};
+    use hex_literal::hex;
+    use serde_json::{Value, json};
+    use signing::{SigningKey, SigningService};
+    use tower::{ServiceBuilder, buffer::Buffer};
+    use types::crypto::signature::ecdsa::secp256k1;
+
+    /// Signing key used in tests (from the EIP-155 example).
+    const SIGNING_KEY: [u8; 32] = [0x46; 32];
+
+    /// Address of the test signing key.
+    const ADDRESS: &str = "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F";
+
+    /// Address of the test signing key with an EIP-1191 checksum for RSK
+    /// mainnet (chain ID 30).
+    const RSK_ADDRESS: &str = "0x9D8A62f656a8d1615C1294Fd71E9Cfb3E4855A4F";
+
+    type TestService = Buffer<SigningService, signing::Request>;
+
+    fn json_rpc_service() -> JsonRpcService<TestService> {
+        json_rpc_service_with_policy(Policy::default())
+    }
+
+    fn json_rpc_service_with_policy(policy: Policy) -> JsonRpcService<TestService> {
+        JsonRpcService::new(signer_service().with_policy(policy))
+    }
+
+    fn signer_service() -> SignerService<TestService> {
+        let mut signing_service = SigningService::new();
+        let signing_key = secp256k1::SigningKey::from_bytes(&SIGNING_KEY).unwrap();
+        signing_service
+            .add_key(SigningKey::from(signing_key))
+            .unwrap();
+
+        SignerService::new(ServiceBuilder::new().buffer(1).service(signing_service))
+    }
+
+    async fn call(service: &JsonRpcService<TestService>, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = service.handle(request.to_string().as_bytes()).await;
+        serde_json::from_slice(&response.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn eth_accounts() {
+        let service = json_rpc_service();
+        let response = call(&service, "eth_accounts", json!([])).await;
+        assert_eq!(response["result"], json!([ADDRESS]));
+    }
+
+    #[tokio::test]
+    async fn personal_sign() {
+        let service = json_rpc_service();
+        let response = call(&service, "personal_sign", json!(["0x68656c6c6f", ADDRESS])).await;
+        let signature = response["result"].as_str().unwrap();
+        assert_eq!(signature.len(), 2 + 65 * 2);
+
+        let eth_sign_response = call(&service, "eth_sign", json!([ADDRESS, "0x68656c6c6f"])).await;
+        assert_eq!(eth_sign_response["result"], response["result"]);
+    }
+
+    #[tokio::test]
+    async fn deny_unchecked_signing() {
+        let service = json_rpc_service_with_policy(Policy::new().deny_unchecked_signing());
+
+        for (method, params) in [
+            ("personal_sign", json!(["0x68656c6c6f", ADDRESS])),
+            ("eth_sign", json!([ADDRESS, "0x68656c6c6f"])),
+        ] {
+            let response = call(&service, method, params).await;
+            assert_eq!(response["error"]["code"], -32000);
+        }
+
+        // Transactions are still checked against the policy and signed
+        let tx = json!({
+            "from": ADDRESS,
+            "to": "0x3535353535353535353535353535353535353535",
+            "nonce": "0x9",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "value": "0xde0b6b3a7640000",
+            "chainId": "0x1"
+        });
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert!(response["result"].is_string());
+    }
+
+    #[tokio::test]
+    async fn sign_typed_data_v4() {
+        // Example from EIP-712
+        let typed_data = json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" }
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!"
+            }
+        });
+
+        let service = json_rpc_service();
+        let response = call(
+            &service,
+            "eth_signTypedData_v4",
+            json!([ADDRESS, typed_data]),
+        )
+        .await;
+        let signature = parse_bytes(&response["result"]).unwrap();
+        assert_eq!(signature.len(), 65);
+
+        // Typed data may also be passed as a JSON string
+        let string_response = call(
+            &service,
+            "eth_signTypedData_v4",
+            json!([ADDRESS, typed_data.to_string()]),
+        )
+        .await;
+        assert_eq!(string_response["result"], response["result"]);
+
+        // Signature is over the EIP-712 signing hash of the example
+        let mut recoverable = signature.clone();
+        recoverable[64] -= 27;
+        let signature = secp256k1::RecoverableSignature::try_from(recoverable.as_slice()).unwrap();
+        let digest = hex!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+        assert_eq!(
+            signature
+                .recover_verifying_key_from_digest_bytes(&digest.into())
+                .unwrap(),
+            secp256k1::SigningKey::from_bytes(&SIGNING_KEY)
+                .unwrap()
+                .verifying_key()
+        );
+
+        let response = call(&service, "eth_signTypedData_v4", json!([ADDRESS, "{}"])).await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn sign_eip155_transaction() {
+        let service = json_rpc_service();
+        let tx = json!({
+            "from": ADDRESS,
+            "to": "0x3535353535353535353535353535353535353535",
+            "nonce": "0x9",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "value": "0xde0b6b3a7640000",
+            "chainId": "0x1"
+        });
+
+        // Expected output from the EIP-155 example
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert_eq!(
+            response["result"],
+            "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[tokio::test]
+    async fn set_code_transaction_policy() {
+        let delegate = "0x1111111111111111111111111111111111111111";
+        let tx = json!({
+            "from": ADDRESS,
+            "to": ADDRESS,
+            "nonce": "0x0",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "maxFeePerGas": "0x9502f9000",
+            "gas": "0xc350",
+            "chainId": "0x1",
+            "authorizationList": [{
+                "chainId": "0x1",
+                "address": delegate,
+                "nonce": "0x1",
+                "yParity": "0x0",
+                "r": "0x3",
+                "s": "0x4"
+            }]
+        });
+
+        let service = json_rpc_service();
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert_eq!(response["error"]["code"], -32000);
+
+        let policy = Policy::new().allow_delegate(delegate.parse().unwrap());
+        let service = json_rpc_service_with_policy(policy);
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert!(response["result"].as_str().unwrap().starts_with("0x04"));
+    }
+
+    #[tokio::test]
+    async fn eip1191_chain() {
+        let rsk = Chain::new(30, "rsk")
+            .with_eip1559(false)
+            .with_checksum(ChecksumStyle::Eip1191);
+        let chains = ChainRegistry::default().with_chain(rsk);
+        let service = JsonRpcService::new(signer_service().with_chains(chains)).with_chain_id(30);
+
+        let response = call(&service, "eth_accounts", json!([])).await;
+        assert_eq!(response["result"], json!([RSK_ADDRESS]));
+
+        let response = call(
+            &service,
+            "personal_sign",
+            json!(["0x68656c6c6f", RSK_ADDRESS]),
+        )
+        .await;
+        assert!(response["result"].is_string());
+
+        let response = call(&service, "personal_sign", json!(["0x68656c6c6f", ADDRESS])).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        // Transaction addresses are checksummed for the transaction's chain
+        let mut tx = json!({
+            "from": RSK_ADDRESS,
+            "to": "0x3535353535353535353535353535353535353535",
+            "nonce": "0x9",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "value": "0xde0b6b3a7640000",
+            "chainId": "0x1e"
+        });
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert!(response["result"].is_string());
+
+        tx["from"] = json!(ADDRESS);
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        tx["chainId"] = json!("0x1");
+        let response = call(&service, "eth_signTransaction", json!([tx])).await;
+        assert!(response["result"].is_string());
+
+        // Typed data addresses are checksummed for the domain's chain
+        let typed_data = json!({
+            "types": {
+                "EIP712Domain": [{ "name": "chainId", "type": "uint256" }],
+                "Message": [{ "name": "contents", "type": "string" }]
+            },
+            "primaryType": "Message",
+            "domain": { "chainId": 1 },
+            "message": { "contents": "hello" }
+        });
+        let response = call(
+            &service,
+            "eth_signTypedData_v4",
+            json!([ADDRESS, typed_data]),
+        )
+        .await;
+        assert!(response["result"].is_string());
+
+        let response = call(
+            &service,
+            "eth_signTypedData_v4",
+            json!([RSK_ADDRESS, typed_data]),
+        )
+        .await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn unknown_method() {
+        let service = json_rpc_service();
+        let response = call(&service, "eth_sendTransaction", json!([])).await;
+        assert_eq!(response["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn batch_request() {
+        let service = json_rpc_service();
+        let request = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "eth_accounts" },
+            { "jsonrpc": "2.0", "id": 2, "method": "eth_accounts" }
+        ]);
+
+        let response = service.handle(request.to_string().as_bytes()).await;
+        let response: Value = serde_json::from_slice(&response.unwrap()).unwrap();
+        assert_eq!(response.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn notifications() {
+        let service = json_rpc_service();
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "eth_accounts" });
+        let response = service.handle(notification.to_string().as_bytes()).await;
+        assert_eq!(response, None);
+
+        let batch = json!([notification, notification]);
+        let response = service.handle(batch.to_string().as_bytes()).await;
+        assert_eq!(response, None);
+
+        // Only the requests in a batch are responded to, including those with a `null` ID
+        let batch = json!([
+            notification,
+            { "jsonrpc": "2.0", "id": null, "method": "eth_accounts" }
+        ]);
+        let response = service.handle(batch.to_string().as_bytes()).await;
+        let response: Value = serde_json::from_slice(&response.unwrap()).unwrap();
+        assert_eq!(response.as_array().unwrap().len(), 1);
+        assert_eq!(response[0]["id"], Value::Null);
+        assert_eq!(response[0]["result"], json!([ADDRESS]));
+    }
+}
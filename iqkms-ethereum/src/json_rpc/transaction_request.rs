@@ -0,0 +1,329 @@
+//! Transaction objects accepted by `eth_signTransaction`.
+
+use super::{RpcError, parse_bytes};
+use crate::ChainRegistry;
+use serde::Deserialize;
+use serde_json::Value;
+use types::{
+    Bytes,
+    ethereum::{
+        Address, ChainId, H256, U256,
+        eip7702::{Authorization, SignedAuthorization},
+        transaction::{
+            AccessList, AccessListItem, Signature, Transaction, TxEip1559, TxEip2930, TxEip4844,
+            TxEip7702, TxLegacy,
+        },
+    },
+};
+
+/// Transaction object as accepted by `eth_signTransaction`.
+///
+/// All quantities are `0x`-prefixed hex strings.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct TransactionRequest {
+    /// Address of the signing key.
+    from: String,
+
+    /// Recipient address (absent for contract creation).
+    to: Option<String>,
+
+    /// Gas limit.
+    #[serde(alias = "gasLimit")]
+    gas: Option<String>,
+
+    /// Gas price (legacy and EIP-2930 transactions).
+    gas_price: Option<String>,
+
+    /// Maximum total fee per gas (EIP-1559).
+    max_fee_per_gas: Option<String>,
+
+    /// Maximum priority fee per gas (EIP-1559).
+    max_priority_fee_per_gas: Option<String>,
+
+    /// Amount of wei to transfer.
+    value: Option<String>,
+
+    /// Input data.
+    #[serde(alias = "input")]
+    data: Option<Value>,
+
+    /// Sender account nonce.
+    nonce: Option<String>,
+
+    /// EIP-155 chain ID.
+    chain_id: Option<String>,
+
+    /// EIP-2718 transaction type.
+    #[serde(rename = "type")]
+    tx_type: Option<String>,
+
+    /// EIP-2930 access list.
+    access_list: Option<Vec<AccessListItemRequest>>,
+
+    /// Maximum fee per unit of blob gas (EIP-4844).
+    max_fee_per_blob_gas: Option<String>,
+
+    /// Versioned hashes of blob KZG commitments (EIP-4844).
+    blob_versioned_hashes: Option<Vec<String>>,
+
+    /// Signed authorizations (EIP-7702).
+    authorization_list: Option<Vec<AuthorizationRequest>>,
+}
+
+impl TransactionRequest {
+    /// Get the address of the signing key, validating its checksum according
+    /// to the chain the transaction is for.
+    pub(super) fn signer(&self, chains: &ChainRegistry) -> Result<Address, RpcError> {
+        let chain_id = optional_u64(self.chain_id.as_deref())?.unwrap_or_default();
+        Ok(chains.parse_address(&self.from, chain_id)?)
+    }
+
+    /// Convert this request into a [`Transaction`], validating address
+    /// checksums according to the chain the transaction is for.
+    pub(super) fn into_transaction(self, chains: &ChainRegistry) -> Result<Transaction, RpcError> {
+        let nonce = required_u64(self.nonce.as_deref(), "nonce")?;
+        let gas_limit = required_u64(self.gas.as_deref(), "gas")?;
+        let value = optional_u256(self.value.as_deref())?.unwrap_or_default();
+        let chain_id = optional_u64(self.chain_id.as_deref())?;
+        let parse_address = |addr: &str| chains.parse_address(addr, chain_id.unwrap_or_default());
+
+        let to = match self.to.as_deref() {
+            Some(to) => Some(parse_address(to)?),
+            None => None,
+        };
+
+        let input = match &self.data {
+            Some(data) => Bytes::from(parse_bytes(data)?),
+            None => Bytes::new(),
+        };
+
+        let access_list = match self.access_list {
+            Some(items) => AccessList(
+                items
+                    .into_iter()
+                    .map(|item| item.into_item(parse_address))
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => AccessList::default(),
+        };
+
+        let tx_type = match optional_u64(self.tx_type.as_deref())? {
+            Some(tx_type) => tx_type,
+            None if self.authorization_list.is_some() => u64::from(TxEip7702::TX_TYPE),
+            None if self.blob_versioned_hashes.is_some() => u64::from(TxEip4844::TX_TYPE),
+            None if self.max_fee_per_gas.is_some() => u64::from(TxEip1559::TX_TYPE),
+            None if !access_list.0.is_empty() => u64::from(TxEip2930::TX_TYPE),
+            None => 0,
+        };
+
+        let require_chain_id =
+            || chain_id.ok_or_else(|| RpcError::invalid_params("missing chainId"));
+        let require_to = || to.ok_or_else(|| RpcError::invalid_params("missing to"));
+
+        match u8::try_from(tx_type) {
+            Ok(0) => Ok(TxLegacy {
+                chain_id,
+                nonce,
+                gas_price: required_u256(self.gas_price.as_deref(), "gasPrice")?,
+                gas_limit,
+                to,
+                value,
+                input,
+            }
+            .into()),
+            Ok(TxEip2930::TX_TYPE) => Ok(TxEip2930 {
+                chain_id: require_chain_id()?,
+                nonce,
+                gas_price: required_u256(self.gas_price.as_deref(), "gasPrice")?,
+                gas_limit,
+                to,
+                value,
+                input,
+                access_list,
+            }
+            .into()),
+            Ok(TxEip1559::TX_TYPE) => Ok(TxEip1559 {
+                chain_id: require_chain_id()?,
+                nonce,
+                max_priority_fee_per_gas: required_u256(
+                    self.max_priority_fee_per_gas.as_deref(),
+                    "maxPriorityFeePerGas",
+                )?,
+                max_fee_per_gas: required_u256(self.max_fee_per_gas.as_deref(), "maxFeePerGas")?,
+                gas_limit,
+                to,
+                value,
+                input,
+                access_list,
+            }
+            .into()),
+            Ok(TxEip4844::TX_TYPE) => Ok(TxEip4844 {
+                chain_id: require_chain_id()?,
+                nonce,
+                max_priority_fee_per_gas: required_u256(
+                    self.max_priority_fee_per_gas.as_deref(),
+                    "maxPriorityFeePerGas",
+                )?,
+                max_fee_per_gas: required_u256(self.max_fee_per_gas.as_deref(), "maxFeePerGas")?,
+                gas_limit,
+                to: require_to()?,
+                value,
+                input,
+                access_list,
+                max_fee_per_blob_gas: required_u256(
+                    self.max_fee_per_blob_gas.as_deref(),
+                    "maxFeePerBlobGas",
+                )?,
+                blob_versioned_hashes: self
+                    .blob_versioned_hashes
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|hash| parse_h256(hash, "malformed blob versioned hash"))
+                    .collect::<Result<_, _>>()?,
+            }
+            .into()),
+            Ok(TxEip7702::TX_TYPE) => Ok(TxEip7702 {
+                chain_id: require_chain_id()?,
+                nonce,
+                max_priority_fee_per_gas: required_u256(
+                    self.max_priority_fee_per_gas.as_deref(),
+                    "maxPriorityFeePerGas",
+                )?,
+                max_fee_per_gas: required_u256(self.max_fee_per_gas.as_deref(), "maxFeePerGas")?,
+                gas_limit,
+                to: require_to()?,
+                value,
+                input,
+                access_list,
+                authorization_list: self
+                    .authorization_list
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|auth| auth.into_signed_authorization(parse_address))
+                    .collect::<Result<_, _>>()?,
+            }
+            .into()),
+            _ => Err(RpcError::invalid_params(format!(
+                "unsupported transaction type: {tx_type}"
+            ))),
+        }
+    }
+}
+
+/// Access list entry as accepted by `eth_signTransaction`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItemRequest {
+    /// Address of the account to be accessed.
+    address: String,
+
+    /// Storage keys of the account to be accessed.
+    #[serde(default)]
+    storage_keys: Vec<String>,
+}
+
+impl AccessListItemRequest {
+    /// Convert this request into an [`AccessListItem`].
+    fn into_item(
+        self,
+        parse_address: impl Fn(&str) -> crate::Result<Address>,
+    ) -> Result<AccessListItem, RpcError> {
+        let storage_keys = self
+            .storage_keys
+            .iter()
+            .map(|key| parse_h256(key, "malformed storage key"))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AccessListItem {
+            address: parse_address(&self.address)?,
+            storage_keys,
+        })
+    }
+}
+
+/// Signed EIP-7702 authorization as accepted by `eth_signTransaction`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizationRequest {
+    /// Chain ID the authorization is valid on.
+    chain_id: String,
+
+    /// Address of the delegate contract.
+    address: String,
+
+    /// Nonce of the authorizing account.
+    nonce: String,
+
+    /// Signature `y_parity`.
+    y_parity: String,
+
+    /// Signature `r` component.
+    r: String,
+
+    /// Signature `s` component.
+    s: String,
+}
+
+impl AuthorizationRequest {
+    /// Convert this request into a [`SignedAuthorization`].
+    fn into_signed_authorization(
+        self,
+        parse_address: impl Fn(&str) -> crate::Result<Address>,
+    ) -> Result<SignedAuthorization, RpcError> {
+        let y_parity = match required_u64(Some(&self.y_parity), "yParity")? {
+            0 => false,
+            1 => true,
+            _ => return Err(RpcError::invalid_params("malformed yParity")),
+        };
+
+        Ok(SignedAuthorization {
+            authorization: Authorization {
+                chain_id: required_u64(Some(&self.chain_id), "chainId")?,
+                address: parse_address(&self.address)?,
+                nonce: required_u64(Some(&self.nonce), "nonce")?,
+            },
+            signature: Signature {
+                r: required_u256(Some(&self.r), "r")?,
+                s: required_u256(Some(&self.s), "s")?,
+                y_parity,
+            },
+        })
+    }
+}
+
+/// Parse a 32-byte hex value.
+fn parse_h256(s: &str, error_msg: &str) -> Result<H256, RpcError> {
+    match parse_bytes(&Value::String(s.to_owned()))? {
+        bytes if bytes.len() == 32 => Ok(H256::from_slice(&bytes)),
+        _ => Err(RpcError::invalid_params(error_msg)),
+    }
+}
+
+/// Parse an optional hex quantity as a `U256`.
+fn optional_u256(quantity: Option<&str>) -> Result<Option<U256>, RpcError> {
+    quantity
+        .map(|q| {
+            q.strip_prefix("0x")
+                .and_then(|hex| U256::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| RpcError::invalid_params(format!("malformed quantity: {q}")))
+        })
+        .transpose()
+}
+
+/// Parse a required hex quantity as a `U256`.
+fn required_u256(quantity: Option<&str>, name: &str) -> Result<U256, RpcError> {
+    optional_u256(quantity)?.ok_or_else(|| RpcError::invalid_params(format!("missing {name}")))
+}
+
+/// Parse an optional hex quantity as a `u64`.
+fn optional_u64(quantity: Option<&str>) -> Result<Option<ChainId>, RpcError> {
+    optional_u256(quantity)?
+        .map(|n| u64::try_from(n).map_err(|_| RpcError::invalid_params("quantity exceeds 64-bits")))
+        .transpose()
+}
+
+/// Parse a required hex quantity as a `u64`.
+fn required_u64(quantity: Option<&str>, name: &str) -> Result<u64, RpcError> {
+    optional_u64(quantity)?.ok_or_else(|| RpcError::invalid_params(format!("missing {name}")))
+}
@@ -1,6 +1,14 @@
 //! iqkms Ethereum services.
 //!
 //! Implements an RPC service with the following features: transaction signing.
+//!
+//! Also implements a consensus layer signer for validators, which signs block
+//! proposals, attestations, and other beacon chain messages using BLS12-381
+//! keys (see [`ConsensusSignerService`]). Block proposals and attestations are
+//! checked against a [`SlashingProtection`] database before they're signed.
+//!
+//! Also provides an Ethereum JSON-RPC signer API compatible with Clef and
+//! Web3Signer clients (see [`JsonRpcService`]).
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
@@ -17,12 +25,28 @@
     unused_lifetimes,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
 
+mod abi;
+mod chain;
+mod consensus;
 mod error;
+mod json_rpc;
+mod policy;
 mod signer;
 
+pub mod slashing_protection;
+
 pub use crate::{
+    abi::{AbiRegistry, ContractCall},
+    chain::{Chain, ChainRegistry, ChecksumStyle},
+    consensus::ConsensusSignerService,
     error::{Error, Result},
+    json_rpc::JsonRpcService,
+    policy::Policy,
     signer::SignerService,
+    slashing_protection::SlashingProtection,
+};
+pub use proto::ethereum::{
+    consensus_signer_server::ConsensusSignerServer, signer_server::SignerServer,
 };
-pub use proto::ethereum::signer_server::SignerServer;
@@ -0,0 +1,346 @@
+//! Signing policy.
+
+use crate::{ContractCall, Error, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use types::ethereum::{
+    Address, H160, U256, eip4361,
+    eip7702::Authorization,
+    transaction::{Transaction, TxEip7702},
+};
+
+/// Policy restricting which operations the signer will perform.
+///
+/// By default no EIP-7702 delegations are permitted, as a delegate contract
+/// gains full control over the delegating account. Revocations (i.e.
+/// delegating to the zero address) are always permitted.
+///
+/// Likewise, no key may sign in to any domain with Sign-In with Ethereum
+/// unless the domain has been explicitly allowed for that key.
+///
+/// ERC-20 transfers and approvals may be limited to a maximum amount per
+/// token. Limits are evaluated against decoded calldata, so the token's ABI
+/// must be registered with the [`AbiRegistry`][`crate::AbiRegistry`]. Only
+/// `transfer`, `transferFrom` and `approve` calls can be checked against a
+/// limit, so any other call to a limited token is denied, as is calldata sent
+/// to it which can't be decoded (including Safe `DELEGATECALL`s).
+///
+/// These checks only apply to requests the signer can inspect, i.e.
+/// transactions, EIP-7702 authorizations, Sign-In with Ethereum messages and
+/// Safe transactions. The same keys can also sign raw digests (gRPC
+/// `SignDigest`/`SignEip155`), EIP-191 personal messages (JSON-RPC
+/// `eth_sign`/`personal_sign`) and arbitrary EIP-712 typed data (JSON-RPC
+/// `eth_signTypedData_v4`), any of which can be used to sign something the
+/// policy would otherwise deny. Unless [`Policy::deny_unchecked_signing`] is
+/// set, the policy is advisory and can be bypassed by those requests.
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    /// Delegate contracts EIP-7702 authorizations may point to.
+    allowed_delegates: BTreeSet<H160>,
+
+    /// Domains each key may sign in to with Sign-In with Ethereum
+    /// (lowercased).
+    allowed_siwe_domains: BTreeMap<H160, BTreeSet<String>>,
+
+    /// Maximum amount of each ERC-20 token which may be transferred or
+    /// approved in a single call.
+    erc20_transfer_limits: BTreeMap<H160, U256>,
+
+    /// Deny signing of data which the policy can't be checked against.
+    deny_unchecked_signing: bool,
+}
+
+/// Signatures of ERC-20 functions which move (or allow moving) tokens, where
+/// the amount is the last argument.
+const ERC20_TRANSFER_SIGNATURES: &[&str] = &[
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+];
+
+impl Policy {
+    /// Create a new policy with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow EIP-7702 authorizations which delegate to the given contract.
+    pub fn allow_delegate(mut self, delegate: Address) -> Self {
+        self.allowed_delegates.insert(delegate.hash);
+        self
+    }
+
+    /// Allow the key with the given address to sign in to the given domain
+    /// with Sign-In with Ethereum.
+    ///
+    /// Domains are RFC 3986 authorities, i.e. they include the port if it is
+    /// non-default (e.g. `example.com:8443`), and are compared
+    /// case-insensitively.
+    pub fn allow_siwe_domain(mut self, address: Address, domain: &str) -> Self {
+        self.allowed_siwe_domains
+            .entry(address.hash)
+            .or_default()
+            .insert(domain.to_ascii_lowercase());
+        self
+    }
+
+    /// Limit the amount of the given ERC-20 token which may be transferred
+    /// or approved in a single call.
+    pub fn limit_erc20_transfers(mut self, token: Address, max_amount: U256) -> Self {
+        self.erc20_transfer_limits.insert(token.hash, max_amount);
+        self
+    }
+
+    /// Deny signing raw digests, EIP-191 personal messages, and EIP-712 typed
+    /// data, which can't be checked against this policy.
+    ///
+    /// This makes the policy enforceable: otherwise e.g. an EIP-7702
+    /// authorization to a delegate which isn't allowed can be signed as a raw
+    /// digest.
+    pub fn deny_unchecked_signing(mut self) -> Self {
+        self.deny_unchecked_signing = true;
+        self
+    }
+
+    /// Check that signing data which can't be checked against this policy
+    /// (e.g. a raw digest) is permitted.
+    pub fn check_unchecked_signing(&self) -> Result<()> {
+        if self.deny_unchecked_signing {
+            Err(Error::PolicyViolation {
+                reason: "signing raw digests and messages is denied".to_owned(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that a decoded contract call is permitted by this policy.
+    pub fn check_call(&self, call: &ContractCall) -> Result<()> {
+        let max_amount = match self.erc20_transfer_limits.get(&call.address.hash) {
+            Some(max_amount) => max_amount,
+            None => return Ok(()),
+        };
+
+        let signature = call.call.function.signature();
+        if !ERC20_TRANSFER_SIGNATURES.contains(&signature.as_str()) {
+            return Err(Error::PolicyViolation {
+                reason: format!("{call} can't be checked against the ERC-20 transfer limit"),
+            });
+        }
+
+        match call.call.args.last().and_then(|arg| arg.as_uint()) {
+            Some(amount) if amount <= *max_amount => Ok(()),
+            _ => Err(Error::PolicyViolation {
+                reason: format!("{call} exceeds maximum amount of {max_amount}"),
+            }),
+        }
+    }
+
+    /// Check that sending calldata which can't be decoded to the contract
+    /// with the given address is permitted by this policy.
+    pub fn check_undecoded_call(&self, to: &Address) -> Result<()> {
+        if self.erc20_transfer_limits.contains_key(&to.hash) {
+            Err(Error::PolicyViolation {
+                reason: format!(
+                    "calldata to {to} can't be checked against the ERC-20 transfer limit"
+                ),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that an EIP-7702 authorization is permitted by this policy.
+    pub fn check_authorization(&self, authorization: &Authorization) -> Result<()> {
+        if authorization.is_revocation()
+            || self.allowed_delegates.contains(&authorization.address.hash)
+        {
+            Ok(())
+        } else {
+            Err(Error::PolicyViolation {
+                reason: format!(
+                    "delegate contract not allowed: \"{}\"",
+                    authorization.address
+                ),
+            })
+        }
+    }
+
+    /// Check that a transaction is permitted by this policy.
+    ///
+    /// For EIP-7702 transactions, every authorization in the authorization
+    /// list must point to an allowed delegate.
+    pub fn check_transaction(&self, tx: &Transaction) -> Result<()> {
+        if let Transaction::Eip7702(TxEip7702 {
+            authorization_list, ..
+        }) = tx
+        {
+            for signed in authorization_list {
+                self.check_authorization(&signed.authorization)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that a Sign-In with Ethereum message is permitted by this
+    /// policy, i.e. that its signer may sign in to its domain.
+    pub fn check_siwe_message(&self, msg: &eip4361::Message) -> Result<()> {
+        let allowed = self
+            .allowed_siwe_domains
+            .get(&msg.address.hash)
+            .is_some_and(|domains| domains.contains(&msg.domain.to_ascii_lowercase()));
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::PolicyViolation {
+                reason: format!(
+                    "{} is not allowed to sign in to \"{}\"",
+                    msg.address, msg.domain
+                ),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+    use crate::AbiRegistry;
+    use types::ethereum::{Address, U256, abi::Abi, eip4361, eip7702::Authorization};
+
+    const DELEGATE: [u8; 20] = [0x11; 20];
+
+    fn authorization(address: [u8; 20]) -> Authorization {
+        Authorization {
+            chain_id: 1,
+            address: Address::from(address),
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn denies_delegates_by_default() {
+        let policy = Policy::new();
+        assert!(
+            policy
+                .check_authorization(&authorization(DELEGATE))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn allows_listed_delegates() {
+        let policy = Policy::new().allow_delegate(DELEGATE.into());
+        assert!(policy.check_authorization(&authorization(DELEGATE)).is_ok());
+        assert!(
+            policy
+                .check_authorization(&authorization([0x22; 20]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn unchecked_signing() {
+        assert!(Policy::new().check_unchecked_signing().is_ok());
+        assert!(
+            Policy::new()
+                .deny_unchecked_signing()
+                .check_unchecked_signing()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn allows_revocation() {
+        let policy = Policy::new();
+        assert!(policy.check_authorization(&authorization([0; 20])).is_ok());
+    }
+
+    fn siwe_message(address: [u8; 20], domain: &str) -> eip4361::Message {
+        eip4361::Message {
+            scheme: None,
+            domain: domain.to_owned(),
+            address: Address::from(address),
+            statement: None,
+            uri: format!("https://{domain}/login"),
+            chain_id: 1,
+            nonce: "32891756".to_owned(),
+            issued_at: "2021-09-30T16:25:24Z".to_owned(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        }
+    }
+
+    #[test]
+    fn allows_listed_siwe_domains() {
+        const SIGNER: [u8; 20] = [0x33; 20];
+
+        let policy = Policy::new().allow_siwe_domain(SIGNER.into(), "Example.com");
+        assert!(
+            policy
+                .check_siwe_message(&siwe_message(SIGNER, "example.com"))
+                .is_ok()
+        );
+        assert!(
+            policy
+                .check_siwe_message(&siwe_message(SIGNER, "evil.com"))
+                .is_err()
+        );
+        assert!(
+            policy
+                .check_siwe_message(&siwe_message([0x44; 20], "example.com"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn limits_erc20_transfers() {
+        const TOKEN: [u8; 20] = [0x55; 20];
+
+        let abi = Abi::from_json(
+            r#"[{
+                "type": "function",
+                "name": "transfer",
+                "inputs": [
+                    { "name": "to", "type": "address" },
+                    { "name": "amount", "type": "uint256" }
+                ]
+            }, {
+                "type": "function",
+                "name": "increaseAllowance",
+                "inputs": [
+                    { "name": "spender", "type": "address" },
+                    { "name": "addedValue", "type": "uint256" }
+                ]
+            }]"#,
+        )
+        .unwrap();
+
+        let abis = AbiRegistry::new().with_contract(TOKEN.into(), "TOKEN", abi);
+        let policy = Policy::new().limit_erc20_transfers(TOKEN.into(), U256::from(1000));
+
+        let transfer = |amount: u64| {
+            let mut calldata = vec![0xa9, 0x05, 0x9c, 0xbb];
+            calldata.extend_from_slice(&[0; 32]);
+            calldata.extend_from_slice(&[0; 24]);
+            calldata.extend_from_slice(&amount.to_be_bytes());
+            abis.decode_call(&TOKEN.into(), &calldata).unwrap().unwrap()
+        };
+
+        assert!(policy.check_call(&transfer(1000)).is_ok());
+        assert!(policy.check_call(&transfer(1001)).is_err());
+        assert!(Policy::new().check_call(&transfer(1001)).is_ok());
+
+        // Other calls and calldata which can't be decoded are denied
+        let mut calldata = vec![0x39, 0x50, 0x93, 0x51];
+        calldata.extend_from_slice(&[0; 64]);
+        let increase_allowance = abis.decode_call(&TOKEN.into(), &calldata).unwrap().unwrap();
+        assert!(policy.check_call(&increase_allowance).is_err());
+        assert!(policy.check_undecoded_call(&TOKEN.into()).is_err());
+        assert!(policy.check_undecoded_call(&[0x66; 20].into()).is_ok());
+        assert!(Policy::new().check_undecoded_call(&TOKEN.into()).is_ok());
+    }
+}
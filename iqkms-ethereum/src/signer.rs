@@ -1,17 +1,43 @@
 //! iqkms Ethereum RPC service.
 
-use crate::Error;
-use proto::ethereum::{SignDigestRequest, SignEip155Request, Signature, signer_server::Signer};
+use crate::{AbiRegistry, ChainRegistry, ContractCall, Error, Policy};
+use proto::ethereum::{
+    SignAuthorizationRequest, SignDigestRequest, SignEip155Request, SignSafeTransactionRequest,
+    SignSafeTransactionResponse, SignSiweRequest, SignSiweResponse, SignTransactionRequest,
+    SignTransactionResponse, Signature, signer_server::Signer,
+};
 use signing::{VerifyingKey, signature::ecdsa::secp256k1};
 use tonic::{Request, Response, Status};
 use tower::{Service, ServiceExt};
-use tracing::trace;
-use types::{BoxError, Bytes, ethereum::Address};
+use tracing::{info, trace};
+use types::{
+    BoxError, Bytes,
+    crypto::elliptic_curve::IsHigh,
+    ethereum::{
+        Address, ChainId, H160, U256, eip4361,
+        eip7702::Authorization,
+        safe::{self, SafeTx},
+        transaction::{self, Transaction},
+    },
+};
+
+/// `tracing` target for audit log entries.
+const AUDIT_TARGET: &str = "iqkms::audit";
 
 /// Signer gRPC service.
+#[derive(Clone)]
 pub struct SignerService<S> {
     /// Reference to the signer service.
     signing_service: S,
+
+    /// Policy restricting which operations may be performed.
+    policy: Policy,
+
+    /// Chains which requests may be signed for.
+    chains: ChainRegistry,
+
+    /// Contract ABIs used to decode calldata.
+    abis: AbiRegistry,
 }
 
 impl<S> SignerService<S>
@@ -25,32 +51,252 @@ where
 {
     /// Create a new RPC service with the given keyring.
     pub fn new(signing_service: S) -> Self {
-        Self { signing_service }
+        Self {
+            signing_service,
+            policy: Policy::default(),
+            chains: ChainRegistry::default(),
+            abis: AbiRegistry::default(),
+        }
     }
 
-    /// Parse the given string as an Ethereum address and look up the
-    /// corresponding key in the keyring.
-    async fn sign_digest(&self, address: Address, digest: Bytes) -> Result<Signature, Error> {
-        let request = signing::Request::SignPrehash {
+    /// Set the policy restricting which operations may be performed.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the chains which requests may be signed for.
+    pub fn with_chains(mut self, chains: ChainRegistry) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    /// Set the contract ABIs used to decode calldata.
+    pub fn with_abis(mut self, abis: AbiRegistry) -> Self {
+        self.abis = abis;
+        self
+    }
+
+    /// Get the registry of chains requests may be signed for.
+    pub(crate) fn chains(&self) -> &ChainRegistry {
+        &self.chains
+    }
+
+    /// Sign the given transaction after checking it against the chain
+    /// registry and policy.
+    ///
+    /// Calldata sent to contracts with registered ABIs is decoded, checked
+    /// against the policy, and recorded in the audit log.
+    ///
+    /// Returns the transaction signature, which can be used to serialize the
+    /// signed transaction with [`Transaction::encode_signed`].
+    pub(crate) async fn sign_transaction(
+        &self,
+        address: Address,
+        tx: &Transaction,
+    ) -> Result<transaction::Signature, Error> {
+        let chain = self.chains.check_transaction(tx)?;
+        self.policy.check_transaction(tx)?;
+
+        let call = match tx.to() {
+            Some(to) => self.check_call(to, tx.input())?,
+            None => None,
+        };
+
+        match (&call, tx.to()) {
+            (Some(call), _) => info!(
+                target: AUDIT_TARGET,
+                signer = %address,
+                chain = chain.name(),
+                "signing transaction: {call}"
+            ),
+            (None, Some(to)) => info!(
+                target: AUDIT_TARGET,
+                signer = %address,
+                chain = chain.name(),
+                "signing transaction to {}",
+                chain.address(to.hash)
+            ),
+            (None, None) => info!(
+                target: AUDIT_TARGET,
+                signer = %address,
+                chain = chain.name(),
+                "signing contract creation"
+            ),
+        }
+
+        let signature = self
+            .sign_digest(
+                address,
+                Bytes::copy_from_slice(tx.signing_hash().as_bytes()),
+            )
+            .await?;
+
+        let y_parity = match signature.v {
+            27 => false,
+            28 => true,
+            _ => return Err(Error::UnexpectedResponse),
+        };
+
+        Ok(transaction::Signature {
+            r: U256::from_big_endian(&signature.r),
+            s: U256::from_big_endian(&signature.s),
+            y_parity,
+        })
+    }
+
+    /// Sign the given EIP-7702 authorization after checking it against the
+    /// chain registry and policy.
+    ///
+    /// Returns a signature whose `v` value is the `y_parity`.
+    pub(crate) async fn sign_authorization(
+        &self,
+        address: Address,
+        authorization: &Authorization,
+    ) -> Result<Signature, Error> {
+        self.chains
+            .check_authorization_chain(authorization.chain_id)?;
+        self.policy.check_authorization(authorization)?;
+
+        let digest = Bytes::copy_from_slice(authorization.signing_hash().as_bytes());
+        let mut signature = self.sign_digest(address, digest).await?;
+        signature.v -= 27;
+        Ok(signature)
+    }
+
+    /// Sign the given Sign-In with Ethereum message with the key for its
+    /// address after checking it against the chain registry and policy.
+    ///
+    /// Returns an EIP-191 signature with a `v` value of 27 or 28.
+    pub(crate) async fn sign_siwe_message(
+        &self,
+        msg: &eip4361::Message,
+    ) -> Result<Signature, Error> {
+        if let Some(field) = msg.invalid_field() {
+            return Err(Error::MessageMalformed {
+                reason: format!("malformed Sign-In with Ethereum message: invalid {field}"),
+            });
+        }
+
+        self.chains.get(msg.chain_id)?;
+        self.policy.check_siwe_message(msg)?;
+
+        let digest = Bytes::copy_from_slice(msg.signing_hash().as_bytes());
+        self.sign_digest(msg.address, digest).await
+    }
+
+    /// Sign the given Safe transaction as the owner with the given address.
+    ///
+    /// If `eth_sign` is set, signs the EIP-191 encoding of the Safe
+    /// transaction hash and adds 4 to `v`, as expected by Safe's signature
+    /// verification for `eth_sign` signatures.
+    ///
+    /// Returns the signature in Safe's packed `r || s || v` format.
+    pub(crate) async fn sign_safe_transaction(
+        &self,
+        address: Address,
+        tx: &SafeTx,
+        eth_sign: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let chain = self.chains.get(tx.chain_id)?;
+
+        // Calldata of `DELEGATECALL`s executes in the context of the Safe
+        // rather than the target contract, so it can't be decoded with the
+        // target's ABI
+        let call = match tx.operation {
+            safe::Operation::Call => self.check_call(&tx.to, &tx.data)?,
+            safe::Operation::DelegateCall => {
+                self.policy.check_undecoded_call(&tx.to)?;
+                None
+            }
+        };
+
+        match &call {
+            Some(call) => info!(
+                target: AUDIT_TARGET,
+                signer = %address,
+                chain = chain.name(),
+                safe = %chain.address(tx.safe.hash),
+                "signing Safe transaction: {call}"
+            ),
+            None => info!(
+                target: AUDIT_TARGET,
+                signer = %address,
+                chain = chain.name(),
+                safe = %chain.address(tx.safe.hash),
+                "signing Safe transaction ({:?}) to {}",
+                tx.operation,
+                chain.address(tx.to.hash)
+            ),
+        }
+
+        let digest = if eth_sign {
+            tx.eth_sign_hash()
+        } else {
+            tx.signing_hash()
+        };
+
+        let signature = self
+            .sign_digest(address, Bytes::copy_from_slice(digest.as_bytes()))
+            .await?;
+
+        let mut v = signature.v;
+        if eth_sign {
+            v += u64::from(safe::ETH_SIGN_V_OFFSET);
+        }
+
+        let mut packed = Vec::with_capacity(65);
+        packed.extend_from_slice(&signature.r);
+        packed.extend_from_slice(&signature.s);
+        packed.push(u8::try_from(v).map_err(|_| Error::UnexpectedResponse)?);
+        Ok(packed)
+    }
+
+    /// Sign the given Keccak256 digest of data which can't be checked against
+    /// the policy, if the policy permits it.
+    ///
+    /// Returns a signature with a `v` value of 27 or 28.
+    pub(crate) async fn sign_unchecked_digest(
+        &self,
+        address: Address,
+        digest: Bytes,
+    ) -> Result<Signature, Error> {
+        self.policy.check_unchecked_signing()?;
+        self.sign_digest(address, digest).await
+    }
+
+    /// Sign the given Keccak256 digest using the key with the given address.
+    ///
+    /// Returns a low-S signature (as required by EIP-2) with a `v` value of 27
+    /// or 28.
+    pub(crate) async fn sign_digest(
+        &self,
+        address: Address,
+        digest: Bytes,
+    ) -> Result<Signature, Error> {
+        if digest.len() != 32 {
+            return Err(Error::DigestMalformed);
+        }
+
+        let request = signing::Request::SignPrehashRecoverable {
             key_handle: address.into(),
-            prehash: digest.clone(),
+            prehash: digest,
         };
 
-        let (signature, verifying_key) = match self.call_service(request).await {
-            Ok(signing::Response::SignPrehash {
+        let signature = match self.call_service(request).await? {
+            signing::Response::SignPrehashRecoverable {
                 signature,
-                verifying_key: VerifyingKey::EcdsaSecp256k1(verifying_key),
-            }) => (signature, verifying_key),
-            other => todo!("handle bad response: {:?}", other),
+                verifying_key: VerifyingKey::EcdsaSecp256k1(_),
+            } => secp256k1::RecoverableSignature::try_from(signature.as_ref())?,
+            _ => return Err(Error::UnexpectedResponse),
         };
 
-        // TODO(tarcieri): less janky signature recovery API
-        let digest = <[u8; 32]>::try_from(digest.as_ref()).map_err(|_| Error::DigestMalformed)?;
-        let signature = secp256k1::RecoverableSignature::from_digest_bytes_trial_recovery(
-            &verifying_key,
-            &digest.into(),
-            &secp256k1::Signature::try_from(signature.as_ref())?,
-        )?;
+        // EIP-2: reject malleable high-S signatures
+        if bool::from(IsHigh::is_high(signature.s().as_ref())) {
+            return Err(Error::SigningFailed {
+                reason: "signing service returned a high-S signature".to_owned(),
+            });
+        }
 
         let r = signature.r().to_bytes().to_vec();
         let s = signature.s().to_bytes().to_vec();
@@ -59,16 +305,67 @@ where
         Ok(Signature { r, s, v: v.into() })
     }
 
+    /// Get the Ethereum addresses of all of the secp256k1 keys in the keyring.
+    pub(crate) async fn addresses(&self) -> Result<Vec<Address>, Error> {
+        let verifying_keys = match self.call_service(signing::Request::VerifyingKeys).await? {
+            signing::Response::VerifyingKeys(verifying_keys) => verifying_keys,
+            _ => return Err(Error::UnexpectedResponse),
+        };
+
+        let mut addresses = Vec::with_capacity(verifying_keys.len());
+
+        #[allow(irrefutable_let_patterns)]
+        for verifying_key in verifying_keys {
+            if let VerifyingKey::EcdsaSecp256k1(vk) = verifying_key {
+                addresses.push(Address::try_from(&vk).map_err(|_| Error::UnexpectedResponse)?);
+            }
+        }
+
+        Ok(addresses)
+    }
+
+    /// Decode calldata sent to a contract with a registered ABI and check it
+    /// against the policy.
+    fn check_call(&self, to: &Address, calldata: &[u8]) -> Result<Option<ContractCall>, Error> {
+        let call = self.abis.decode_call(to, calldata)?;
+
+        match &call {
+            Some(call) => self.policy.check_call(call)?,
+            None if !calldata.is_empty() => self.policy.check_undecoded_call(to)?,
+            None => (),
+        }
+
+        Ok(call)
+    }
+
+    /// Parse an address, validating its checksum according to the given
+    /// chain's checksum encoding.
+    ///
+    /// Chain ID `0` (i.e. any chain) uses EIP-55 checksums.
+    fn parse_address(&self, s: &str, chain_id: ChainId) -> Result<Address, Error> {
+        self.chains.parse_address(s, chain_id)
+    }
+
+    /// Parse an address, treating the empty string as the zero address.
+    fn optional_address(&self, s: &str, chain_id: ChainId) -> Result<Address, Error> {
+        if s.is_empty() {
+            Ok(H160::zero().into())
+        } else {
+            self.parse_address(s, chain_id)
+        }
+    }
+
     /// Make a request to the signing service.
-    async fn call_service(&self, req: signing::Request) -> signing::Result<signing::Response> {
-        self.signing_service
-            .clone()
+    async fn call_service(&self, req: signing::Request) -> Result<signing::Response, Error> {
+        let mut signing_service = self.signing_service.clone();
+
+        signing_service
             .ready()
             .await
-            .expect("signing service not ready") // TODO(tarcieri): handle this?
+            .map_err(|_| Error::SigningServiceUnavailable)?
             .call(req)
             .await
-            .map_err(Into::into)
+            .map_err(|e| signing::Error::from(e).into())
     }
 }
 
@@ -89,10 +386,10 @@ where
         trace!("sign_digest[{:?}]: {:?}", request.remote_addr(), request);
 
         let request = request.into_inner();
-        let address = request.address.parse::<Address>().map_err(Error::from)?;
+        let address = self.parse_address(&request.address, 0)?;
 
         Ok(self
-            .sign_digest(address, request.digest.into())
+            .sign_unchecked_digest(address, request.digest.into())
             .await
             .map(Response::new)?)
     }
@@ -104,11 +401,539 @@ where
         trace!("sign_eip155[{:?}]: {:?}", request.remote_addr(), request);
 
         let request = request.into_inner();
-        let address = request.address.parse::<Address>().map_err(Error::from)?;
+        let address = self
+            .chains
+            .get(request.chain_id)?
+            .parse_address(&request.address)?;
 
         // Compute signature and apply EIP-155
-        let mut signature = self.sign_digest(address, request.digest.into()).await?;
+        let mut signature = self
+            .sign_unchecked_digest(address, request.digest.into())
+            .await?;
         signature.v = (request.chain_id * 2 + 35) + ((signature.v - 1) % 2);
         Ok(Response::new(signature))
     }
+
+    async fn sign_transaction(
+        &self,
+        request: Request<SignTransactionRequest>,
+    ) -> Result<Response<SignTransactionResponse>, Status> {
+        trace!(
+            "sign_transaction[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let tx = Transaction::decode_for_signing(&request.transaction).map_err(|_| {
+            Error::MessageMalformed {
+                reason: "malformed unsigned transaction".to_owned(),
+            }
+        })?;
+
+        let address = self.parse_address(&request.address, tx.chain_id().unwrap_or_default())?;
+        let signature = self.sign_transaction(address, &tx).await?;
+
+        Ok(Response::new(SignTransactionResponse {
+            signature: Some(Signature {
+                r: uint256_bytes(signature.r),
+                s: uint256_bytes(signature.s),
+                v: signature.y_parity.into(),
+            }),
+            signed_transaction: tx.encode_signed(&signature),
+        }))
+    }
+
+    async fn sign_authorization(
+        &self,
+        request: Request<SignAuthorizationRequest>,
+    ) -> Result<Response<Signature>, Status> {
+        trace!(
+            "sign_authorization[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let address = self.parse_address(&request.address, request.chain_id)?;
+
+        let authorization = Authorization {
+            chain_id: request.chain_id,
+            address: self.parse_address(&request.delegate, request.chain_id)?,
+            nonce: request.nonce,
+        };
+
+        Ok(self
+            .sign_authorization(address, &authorization)
+            .await
+            .map(Response::new)?)
+    }
+
+    async fn sign_siwe(
+        &self,
+        request: Request<SignSiweRequest>,
+    ) -> Result<Response<SignSiweResponse>, Status> {
+        trace!("sign_siwe[{:?}]: {:?}", request.remote_addr(), request);
+
+        let request = request.into_inner();
+        let optional = |s: String| Some(s).filter(|s| !s.is_empty());
+
+        // EIP-4361 requires EIP-55 checksummed addresses regardless of chain
+        let address = self.parse_address(&request.address, 0)?;
+
+        let msg = eip4361::Message {
+            scheme: optional(request.scheme),
+            domain: request.domain,
+            address,
+            statement: optional(request.statement),
+            uri: request.uri,
+            chain_id: request.chain_id,
+            nonce: request.nonce,
+            issued_at: request.issued_at,
+            expiration_time: optional(request.expiration_time),
+            not_before: optional(request.not_before),
+            request_id: optional(request.request_id),
+            resources: request.resources,
+        };
+
+        let signature = self.sign_siwe_message(&msg).await?;
+
+        Ok(Response::new(SignSiweResponse {
+            message: msg.to_string(),
+            signature: Some(signature),
+        }))
+    }
+
+    async fn sign_safe_transaction(
+        &self,
+        request: Request<SignSafeTransactionRequest>,
+    ) -> Result<Response<SignSafeTransactionResponse>, Status> {
+        trace!(
+            "sign_safe_transaction[{:?}]: {:?}",
+            request.remote_addr(),
+            request
+        );
+
+        let request = request.into_inner();
+        let address = self.parse_address(&request.address, request.chain_id)?;
+
+        let operation = u8::try_from(request.operation)
+            .ok()
+            .and_then(|op| safe::Operation::try_from(op).ok())
+            .ok_or_else(|| Error::MessageMalformed {
+                reason: format!("invalid Safe operation: {}", request.operation),
+            })?;
+
+        let tx = SafeTx {
+            safe: self.parse_address(&request.safe, request.chain_id)?,
+            chain_id: request.chain_id,
+            to: self.parse_address(&request.to, request.chain_id)?,
+            value: uint256(&request.value)?,
+            data: request.data.into(),
+            operation,
+            safe_tx_gas: request.safe_tx_gas.into(),
+            base_gas: request.base_gas.into(),
+            gas_price: uint256(&request.gas_price)?,
+            gas_token: self.optional_address(&request.gas_token, request.chain_id)?,
+            refund_receiver: self.optional_address(&request.refund_receiver, request.chain_id)?,
+            nonce: request.nonce.into(),
+        };
+
+        let signature = self
+            .sign_safe_transaction(address, &tx, request.eth_sign)
+            .await?;
+
+        Ok(Response::new(SignSafeTransactionResponse {
+            safe_tx_hash: tx.signing_hash().as_bytes().to_vec(),
+            signature,
+        }))
+    }
+}
+
+/// Parse a big endian 256-bit unsigned integer.
+fn uint256(bytes: &[u8]) -> Result<U256, Error> {
+    if bytes.len() > 32 {
+        return Err(Error::MessageMalformed {
+            reason: format!("integer too large: {} bytes", bytes.len()),
+        });
+    }
+
+    Ok(U256::from_big_endian(bytes))
+}
+
+/// Serialize a 256-bit unsigned integer as 32 big endian bytes.
+fn uint256_bytes(n: U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    n.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignerService;
+    use crate::Policy;
+    use proto::ethereum::{
+        SignDigestRequest, SignEip155Request, SignSiweRequest, SignTransactionRequest,
+        signer_server::Signer,
+    };
+    use signing::signature::ecdsa::secp256k1;
+    use std::{
+        future::{Ready, ready},
+        task::{Context, Poll},
+    };
+    use tonic::{Code, Request};
+    use tower::Service;
+    use tower::ServiceBuilder;
+    use types::BoxError;
+    use types::ethereum::{
+        Address, U256,
+        eip7702::{Authorization, SignedAuthorization},
+        safe::{Operation, SafeTx},
+        transaction::{AccessList, Transaction, TxEip1559, TxEip7702},
+    };
+
+    /// Mock signing service which fails readiness checks or returns canned
+    /// responses.
+    #[derive(Clone)]
+    struct MockService {
+        /// Is the service ready to accept requests?
+        ready: bool,
+
+        /// Produce the response to every request.
+        respond: fn() -> Result<signing::Response, BoxError>,
+    }
+
+    impl Service<signing::Request> for MockService {
+        type Response = signing::Response;
+        type Error = BoxError;
+        type Future = Ready<Result<signing::Response, BoxError>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+            if self.ready {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Ready(Err("not ready".into()))
+            }
+        }
+
+        fn call(&mut self, _req: signing::Request) -> Self::Future {
+            ready((self.respond)())
+        }
+    }
+
+    fn signer(
+        ready: bool,
+        respond: fn() -> Result<signing::Response, BoxError>,
+    ) -> SignerService<MockService> {
+        SignerService::new(MockService { ready, respond })
+    }
+
+    async fn sign_digest(service: &SignerService<MockService>) -> Code {
+        let request = SignDigestRequest {
+            address: "0x27b1fdb04752bbc536007a920d24acb045561c26".to_owned(),
+            digest: vec![0; 32],
+        };
+
+        Signer::sign_digest(service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code()
+    }
+
+    #[tokio::test]
+    async fn service_not_ready() {
+        let service = signer(false, || Ok(signing::Response::VerifyingKeys(vec![])));
+        assert_eq!(sign_digest(&service).await, Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn service_error() {
+        let service = signer(true, || Err("signing failed".into()));
+        assert_eq!(sign_digest(&service).await, Code::Internal);
+    }
+
+    #[tokio::test]
+    async fn unexpected_response() {
+        let service = signer(true, || Ok(signing::Response::VerifyingKeys(vec![])));
+        assert_eq!(sign_digest(&service).await, Code::Internal);
+    }
+
+    #[tokio::test]
+    async fn unchecked_signing_denied() {
+        let service = signer(true, || Err("signing failed".into()))
+            .with_policy(Policy::new().deny_unchecked_signing());
+        assert_eq!(sign_digest(&service).await, Code::PermissionDenied);
+
+        let request = SignEip155Request {
+            address: "0x27b1fdb04752bbc536007a920d24acb045561c26".to_owned(),
+            digest: vec![0; 32],
+            chain_id: 1,
+        };
+
+        let code = Signer::sign_eip155(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+
+        assert_eq!(code, Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn unknown_chain() {
+        let service = signer(true, || Err("signing failed".into()));
+        let request = SignEip155Request {
+            address: "0x27b1fdb04752bbc536007a920d24acb045561c26".to_owned(),
+            digest: vec![0; 32],
+            chain_id: 30,
+        };
+
+        let code = Signer::sign_eip155(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+
+        assert_eq!(code, Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn siwe_domain_not_allowed() {
+        let service = signer(true, || Err("signing failed".into()));
+        let request = SignSiweRequest {
+            domain: "example.com".to_owned(),
+            address: "0x27b1fdb04752bbc536007a920d24acb045561c26".to_owned(),
+            uri: "https://example.com/login".to_owned(),
+            chain_id: 1,
+            nonce: "32891756".to_owned(),
+            issued_at: "2021-09-30T16:25:24Z".to_owned(),
+            ..Default::default()
+        };
+
+        let code = Signer::sign_siwe(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+
+        assert_eq!(code, Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn malformed_request() {
+        let service = signer(true, || Err("signing failed".into()));
+        let request = SignDigestRequest {
+            address: "0x27b1fdb04752bbc536007a920d24acb0455".to_owned(),
+            digest: vec![0; 32],
+        };
+
+        let status = Signer::sign_digest(&service, Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(
+            status
+                .message()
+                .contains("0x27b1fdb04752bbc536007a920d24acb0455")
+        );
+
+        let request = SignSiweRequest {
+            domain: "example.com".to_owned(),
+            address: "0x27b1fdb04752bbc536007a920d24acb045561c26".to_owned(),
+            uri: "https://example.com/login".to_owned(),
+            chain_id: 1,
+            nonce: "1234".to_owned(),
+            issued_at: "2021-09-30T16:25:24Z".to_owned(),
+            ..Default::default()
+        };
+
+        let status = Signer::sign_siwe(&service, Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("invalid nonce"));
+    }
+
+    #[tokio::test]
+    async fn sign_safe_transaction() {
+        let signing_key = secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let mut signing_service = signing::SigningService::new();
+        signing_service
+            .add_key(signing::SigningKey::from(signing_key))
+            .unwrap();
+        let service = SignerService::new(ServiceBuilder::new().buffer(1).service(signing_service));
+
+        let owner = Address::try_from(&verifying_key).unwrap();
+        let tx = SafeTx {
+            safe: Address::from([0x11; 20]),
+            chain_id: 1,
+            to: Address::from([0x22; 20]),
+            value: U256::from(1u64),
+            data: Default::default(),
+            operation: Operation::Call,
+            safe_tx_gas: U256::zero(),
+            base_gas: U256::zero(),
+            gas_price: U256::zero(),
+            gas_token: Address::from([0; 20]),
+            refund_receiver: Address::from([0; 20]),
+            nonce: U256::zero(),
+        };
+
+        for (eth_sign, digest, v_offset) in [
+            (false, tx.signing_hash(), 27),
+            (true, tx.eth_sign_hash(), 31),
+        ] {
+            let packed = service
+                .sign_safe_transaction(owner, &tx, eth_sign)
+                .await
+                .unwrap();
+
+            assert_eq!(packed.len(), 65);
+            let recovery_id = packed[64] - v_offset;
+            assert!(recovery_id < 2);
+
+            let mut recoverable = packed.clone();
+            recoverable[64] = recovery_id;
+            let signature =
+                secp256k1::RecoverableSignature::try_from(recoverable.as_slice()).unwrap();
+
+            assert_eq!(
+                signature
+                    .recover_verifying_key_from_digest_bytes(digest.as_fixed_bytes().into())
+                    .unwrap(),
+                verifying_key
+            );
+        }
+    }
+
+    /// Create a signer service backed by a keyring containing the given key.
+    fn keyring_signer(
+        signing_key: secp256k1::SigningKey,
+    ) -> SignerService<tower::buffer::Buffer<signing::SigningService, signing::Request>> {
+        let mut signing_service = signing::SigningService::new();
+        signing_service
+            .add_key(signing::SigningKey::from(signing_key))
+            .unwrap();
+        SignerService::new(ServiceBuilder::new().buffer(1).service(signing_service))
+    }
+
+    #[tokio::test]
+    async fn sign_transaction() {
+        let signing_key = secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let service = keyring_signer(signing_key);
+
+        let tx = Transaction::from(TxEip1559 {
+            chain_id: 1,
+            nonce: 9,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(20_000_000_000u64),
+            gas_limit: 21000,
+            to: Some(Address::from([0x35; 20])),
+            value: U256::from(1_000_000_000_000_000_000u64),
+            input: Default::default(),
+            access_list: AccessList::default(),
+        });
+
+        let request = SignTransactionRequest {
+            address: Address::try_from(&verifying_key).unwrap().to_string(),
+            transaction: tx.encode_for_signing(),
+        };
+
+        let response = Signer::sign_transaction(&service, Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let (signed_tx, signature) =
+            Transaction::decode_signed(&response.signed_transaction).unwrap();
+        assert_eq!(signed_tx, tx);
+
+        let response_signature = response.signature.unwrap();
+        assert_eq!(response_signature.v, u64::from(signature.y_parity));
+
+        let mut recoverable = [0u8; 65];
+        recoverable[..32].copy_from_slice(&response_signature.r);
+        recoverable[32..64].copy_from_slice(&response_signature.s);
+        recoverable[64] = u8::from(signature.y_parity);
+
+        let recovered = secp256k1::RecoverableSignature::try_from(&recoverable[..])
+            .unwrap()
+            .recover_verifying_key_from_digest_bytes(tx.signing_hash().as_fixed_bytes().into())
+            .unwrap();
+        assert_eq!(recovered, verifying_key);
+
+        let request = SignTransactionRequest {
+            address: Address::try_from(&verifying_key).unwrap().to_string(),
+            transaction: vec![0x02, 0xc0],
+        };
+
+        let code = Signer::sign_transaction(&service, Request::new(request))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn sign_transaction_delegate_not_allowed() {
+        let signing_key = secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap();
+        let address = Address::try_from(&signing_key.verifying_key()).unwrap();
+        let delegate = Address::from([0x11; 20]);
+
+        let tx = Transaction::from(TxEip7702 {
+            chain_id: 1,
+            nonce: 0,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(20_000_000_000u64),
+            gas_limit: 100_000,
+            to: address,
+            value: U256::zero(),
+            input: Default::default(),
+            access_list: AccessList::default(),
+            authorization_list: vec![SignedAuthorization {
+                authorization: Authorization {
+                    chain_id: 1,
+                    address: delegate,
+                    nonce: 1,
+                },
+                signature: Default::default(),
+            }],
+        });
+
+        let request = SignTransactionRequest {
+            address: address.to_string(),
+            transaction: tx.encode_for_signing(),
+        };
+
+        let service = keyring_signer(secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap());
+        let code = Signer::sign_transaction(&service, Request::new(request.clone()))
+            .await
+            .unwrap_err()
+            .code();
+        assert_eq!(code, Code::PermissionDenied);
+
+        let service =
+            keyring_signer(signing_key).with_policy(Policy::new().allow_delegate(delegate));
+        assert!(
+            Signer::sign_transaction(&service, Request::new(request))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn unexpected_verifying_keys_response() {
+        let service = signer(true, || {
+            Ok(signing::Response::SignPrehash {
+                verifying_key: signing::VerifyingKey::EcdsaSecp256k1(
+                    secp256k1::SigningKey::from_bytes(&[0x46; 32])
+                        .unwrap()
+                        .verifying_key(),
+                ),
+                signature: Default::default(),
+            })
+        });
+
+        assert!(service.addresses().await.is_err());
+    }
 }
@@ -0,0 +1,762 @@
+//! Slashing protection for Ethereum consensus layer validators.
+//!
+//! Validators are slashed for proposing two different blocks for the same slot,
+//! or for attestations which are double votes or surround votes. Before a
+//! block or attestation is signed, it's checked against the validator's
+//! signing history and the history is persisted.
+//!
+//! Only each validator's watermarks are kept: the highest slot of a signed
+//! block, and the highest source and target epochs of signed attestations.
+//! Any block or attestation at or below them is refused (unless it's a repeat
+//! of the last one signed), which rules out double proposals as well as double
+//! and surround votes.
+//!
+//! The database is a log of JSON lines: the first line contains interchange
+//! [`Metadata`], and each subsequent line a [`ValidatorHistory`] entry.
+//! Entries are synced to disk before the corresponding message is signed, so
+//! a crash can never cause a signature to be forgotten. The log is compacted
+//! to a single entry per validator when it's opened and periodically
+//! thereafter.
+//!
+//! While open, the database holds an exclusive lock on a `.lock` file next to
+//! it, so it can't be used by more than one process at a time.
+
+pub mod interchange;
+
+pub use self::interchange::{
+    Interchange, Metadata, SignedAttestation, SignedBlock, ValidatorHistory,
+};
+
+use crate::{Error, Result};
+use signing::signature::bls12_381::VerifyingKey;
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+use types::ethereum::consensus::{Epoch, Root, Slot};
+
+/// Number of entries appended to the log after which it's compacted.
+const COMPACTION_INTERVAL: usize = 10_000;
+
+/// Slashing protection database.
+#[derive(Default)]
+pub struct SlashingProtection {
+    /// Log the signing history is persisted to (if any).
+    log: Option<Log>,
+
+    /// Root identifying the chain the signing history belongs to.
+    ///
+    /// Set when the first message is recorded.
+    genesis_validators_root: Option<Root>,
+
+    /// Signing history of each validator.
+    validators: BTreeMap<VerifyingKey, History>,
+}
+
+impl SlashingProtection {
+    /// Open the database at the given path, creating it if it doesn't exist.
+    ///
+    /// Returns an error if the database is already open, including in
+    /// another process.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let lock = lock(path)?;
+
+        let mut contents = String::new();
+        match File::open(path) {
+            Ok(mut file) => {
+                file.read_to_string(&mut contents).map_err(db_error)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(db_error(e)),
+        }
+
+        // Discard a partially written trailing entry. Entries are synced
+        // before signing, so the corresponding message was never signed.
+        contents.truncate(contents.rfind('\n').map(|pos| pos + 1).unwrap_or(0));
+
+        let mut db = Self::default();
+        let mut lines = contents.lines();
+
+        if let Some(line) = lines.next() {
+            let metadata: Metadata = serde_json::from_str(line).map_err(db_error)?;
+            db.genesis_validators_root = Some(metadata.genesis_validators_root);
+        }
+
+        for line in lines {
+            db.merge(serde_json::from_str(line).map_err(db_error)?);
+        }
+
+        db.compact(path)?;
+        db.log.as_mut().expect("log should be open").lock = Some(lock);
+        Ok(db)
+    }
+
+    /// Create a database which is only held in memory.
+    ///
+    /// The signing history is lost when the database is dropped, so this is
+    /// only suitable for testing.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Check that signing a block proposal is not slashable and record it in
+    /// the validator's signing history.
+    ///
+    /// Refuses to sign a block for a slot at or below the highest slot
+    /// already signed, unless it's the same block.
+    pub fn record_block(
+        &mut self,
+        genesis_validators_root: Root,
+        pubkey: &VerifyingKey,
+        slot: Slot,
+        signing_root: Root,
+    ) -> Result<()> {
+        self.check_chain(genesis_validators_root)?;
+
+        if let Some(prev) = self.validators.get(pubkey).and_then(|h| h.block.as_ref()) {
+            if prev.slot == slot && prev.signing_root == Some(signing_root) {
+                return Ok(());
+            }
+
+            if prev.slot == slot {
+                return Err(slashable(format!("double proposal at slot {}", slot)));
+            }
+
+            if slot < prev.slot {
+                return Err(slashable(format!(
+                    "slot {} precedes signing history (slot {})",
+                    slot, prev.slot
+                )));
+            }
+        }
+
+        self.append(ValidatorHistory {
+            pubkey: *pubkey,
+            signed_blocks: vec![SignedBlock {
+                slot,
+                signing_root: Some(signing_root),
+            }],
+            signed_attestations: vec![],
+        })
+    }
+
+    /// Check that signing an attestation is not slashable and record it in
+    /// the validator's signing history.
+    ///
+    /// Refuses to sign attestations whose source epoch is below, or whose
+    /// target epoch is at or below, those already signed, unless it's the
+    /// same attestation. This rules out double votes and surround votes.
+    pub fn record_attestation(
+        &mut self,
+        genesis_validators_root: Root,
+        pubkey: &VerifyingKey,
+        source_epoch: Epoch,
+        target_epoch: Epoch,
+        signing_root: Root,
+    ) -> Result<()> {
+        self.check_chain(genesis_validators_root)?;
+
+        if source_epoch > target_epoch {
+            return Err(slashable(format!(
+                "source epoch {} is after target epoch {}",
+                source_epoch, target_epoch
+            )));
+        }
+
+        if let Some(prev) = self
+            .validators
+            .get(pubkey)
+            .and_then(|h| h.attestation.as_ref())
+        {
+            if prev.source_epoch == source_epoch
+                && prev.target_epoch == target_epoch
+                && prev.signing_root == Some(signing_root)
+            {
+                return Ok(());
+            }
+
+            if prev.target_epoch == target_epoch {
+                return Err(slashable(format!(
+                    "double vote for target epoch {}",
+                    target_epoch
+                )));
+            }
+
+            if source_epoch < prev.source_epoch || target_epoch < prev.target_epoch {
+                return Err(slashable(format!(
+                    "attestation {}=>{} precedes signing history ({}=>{})",
+                    source_epoch, target_epoch, prev.source_epoch, prev.target_epoch
+                )));
+            }
+        }
+
+        self.append(ValidatorHistory {
+            pubkey: *pubkey,
+            signed_blocks: vec![],
+            signed_attestations: vec![SignedAttestation {
+                source_epoch,
+                target_epoch,
+                signing_root: Some(signing_root),
+            }],
+        })
+    }
+
+    /// Import signing history from an EIP-3076 interchange file.
+    ///
+    /// The imported history is merged with the existing history, and must
+    /// belong to the same chain.
+    pub fn import(&mut self, interchange: &Interchange) -> Result<()> {
+        self.check_chain(interchange.metadata.genesis_validators_root)?;
+
+        for history in &interchange.data {
+            self.merge(history.clone());
+        }
+
+        match self.log.as_ref().map(|log| log.path.clone()) {
+            Some(path) => self.compact(&path),
+            None => Ok(()),
+        }
+    }
+
+    /// Export the signing history as an EIP-3076 interchange file.
+    ///
+    /// Only the watermarks of each validator are exported, which EIP-3076
+    /// calls the minimal format.
+    pub fn export(&self) -> Result<Interchange> {
+        let genesis_validators_root =
+            self.genesis_validators_root
+                .ok_or_else(|| Error::SlashingProtectionDb {
+                    reason: "slashing protection database is empty".to_owned(),
+                })?;
+
+        Ok(Interchange {
+            metadata: self.metadata(genesis_validators_root),
+            data: self
+                .validators
+                .iter()
+                .map(|(pubkey, history)| history.to_interchange(pubkey))
+                .collect(),
+        })
+    }
+
+    /// Ensure the signing history belongs to the chain with the given genesis
+    /// validators root, initializing the database if it's empty.
+    fn check_chain(&mut self, genesis_validators_root: Root) -> Result<()> {
+        match self.genesis_validators_root {
+            Some(root) if root == genesis_validators_root => Ok(()),
+            Some(root) => Err(slashable(format!(
+                "genesis validators root mismatch: expected {:?}, got {:?}",
+                root, genesis_validators_root
+            ))),
+            None => {
+                let metadata = self.metadata(genesis_validators_root);
+                self.write_line(&serde_json::to_string(&metadata).map_err(db_error)?)?;
+                self.genesis_validators_root = Some(genesis_validators_root);
+                Ok(())
+            }
+        }
+    }
+
+    /// Interchange metadata for the chain with the given genesis validators
+    /// root.
+    fn metadata(&self, genesis_validators_root: Root) -> Metadata {
+        Metadata {
+            interchange_format_version: interchange::FORMAT_VERSION.to_owned(),
+            genesis_validators_root,
+        }
+    }
+
+    /// Persist the given history entry and merge it into the in-memory
+    /// history, compacting the log if it's grown too long.
+    fn append(&mut self, entry: ValidatorHistory) -> Result<()> {
+        self.write_line(&serde_json::to_string(&entry).map_err(db_error)?)?;
+        self.merge(entry);
+
+        match &self.log {
+            Some(log) if log.entries >= COMPACTION_INTERVAL => {
+                let path = log.path.clone();
+                self.compact(&path)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Merge the given entry into the in-memory history.
+    fn merge(&mut self, entry: ValidatorHistory) {
+        let history = self.validators.entry(entry.pubkey).or_default();
+
+        for block in entry.signed_blocks {
+            history.merge_block(block);
+        }
+
+        for attestation in entry.signed_attestations {
+            history.merge_attestation(attestation);
+        }
+    }
+
+    /// Rewrite the log at the given path so it only contains the current
+    /// watermarks, then (re)open it for appending.
+    ///
+    /// The new log is written to a temporary file which atomically replaces
+    /// the old one, so a crash can't lose any history.
+    fn compact(&mut self, path: &Path) -> Result<()> {
+        let mut contents = String::new();
+
+        if let Some(genesis_validators_root) = self.genesis_validators_root {
+            let metadata = self.metadata(genesis_validators_root);
+            contents.push_str(&serde_json::to_string(&metadata).map_err(db_error)?);
+            contents.push('\n');
+
+            for (pubkey, history) in &self.validators {
+                let entry = history.to_interchange(pubkey);
+                contents.push_str(&serde_json::to_string(&entry).map_err(db_error)?);
+                contents.push('\n');
+            }
+        }
+
+        let tmp_path = sibling_path(path, ".tmp");
+        let mut tmp = File::create(&tmp_path).map_err(db_error)?;
+        tmp.write_all(contents.as_bytes())
+            .and_then(|_| tmp.sync_all())
+            .map_err(db_error)?;
+        fs::rename(&tmp_path, path).map_err(db_error)?;
+        sync_parent_dir(path)?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(db_error)?;
+
+        let lock = self.log.take().and_then(|log| log.lock);
+        self.log = Some(Log {
+            path: path.to_owned(),
+            file,
+            lock,
+            entries: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Append a line to the log and sync it to disk.
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        if let Some(log) = &mut self.log {
+            log.file
+                .write_all(format!("{}\n", line).as_bytes())
+                .and_then(|_| log.file.sync_data())
+                .map_err(db_error)?;
+
+            log.entries += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SlashingProtection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlashingProtection")
+            .field("genesis_validators_root", &self.genesis_validators_root)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Log the signing history is persisted to.
+struct Log {
+    /// Path to the log.
+    path: PathBuf,
+
+    /// Log file, opened for appending.
+    file: File,
+
+    /// Lock file which is exclusively locked while the database is open.
+    lock: Option<File>,
+
+    /// Number of entries appended since the log was last compacted.
+    entries: usize,
+}
+
+/// Signing history of a single validator, i.e. its watermarks.
+#[derive(Debug, Default)]
+struct History {
+    /// Block with the highest slot.
+    block: Option<SignedBlock>,
+
+    /// Highest source and target epochs of any attestation, along with the
+    /// signing root of the attestation with both (if there is one).
+    attestation: Option<SignedAttestation>,
+}
+
+impl History {
+    /// Raise the block watermark to the given block's slot.
+    fn merge_block(&mut self, block: SignedBlock) {
+        match &mut self.block {
+            Some(prev) if prev.slot > block.slot => (),
+            Some(prev) if prev.slot == block.slot => {
+                if prev.signing_root != block.signing_root {
+                    prev.signing_root = None;
+                }
+            }
+            _ => self.block = Some(block),
+        }
+    }
+
+    /// Raise the attestation watermarks to the given attestation's epochs.
+    fn merge_attestation(&mut self, attestation: SignedAttestation) {
+        let Some(prev) = &mut self.attestation else {
+            self.attestation = Some(attestation);
+            return;
+        };
+
+        let source_epoch = prev.source_epoch.max(attestation.source_epoch);
+        let target_epoch = prev.target_epoch.max(attestation.target_epoch);
+        let is_watermark = |a: &SignedAttestation| {
+            a.source_epoch == source_epoch && a.target_epoch == target_epoch
+        };
+
+        prev.signing_root = match (is_watermark(prev), is_watermark(&attestation)) {
+            (true, true) if prev.signing_root == attestation.signing_root => prev.signing_root,
+            (true, false) => prev.signing_root,
+            (false, true) => attestation.signing_root,
+            _ => None,
+        };
+        prev.source_epoch = source_epoch;
+        prev.target_epoch = target_epoch;
+    }
+
+    /// Convert the watermarks to an interchange entry.
+    fn to_interchange(&self, pubkey: &VerifyingKey) -> ValidatorHistory {
+        ValidatorHistory {
+            pubkey: *pubkey,
+            signed_blocks: self.block.iter().cloned().collect(),
+            signed_attestations: self.attestation.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Exclusively lock the lock file of the database at the given path,
+/// returning an error if it's already locked.
+fn lock(path: &Path) -> Result<File> {
+    let lock_path = sibling_path(path, ".lock");
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(db_error)?;
+
+    #[cfg(unix)]
+    rustix::fs::flock(&file, rustix::fs::FlockOperation::NonBlockingLockExclusive).map_err(
+        |_| Error::SlashingProtectionDb {
+            reason: format!(
+                "slashing protection database is in use: {} is locked",
+                lock_path.display()
+            ),
+        },
+    )?;
+
+    Ok(file)
+}
+
+/// Sync the directory containing the given path, persisting renames.
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(db_error)?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Path of a file next to the given one, with the given suffix appended.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
+
+/// Error for a message which would be slashable to sign.
+fn slashable(reason: String) -> Error {
+    Error::Slashable { reason }
+}
+
+/// Error for a failure to access the database.
+fn db_error(err: impl ToString) -> Error {
+    Error::SlashingProtectionDb {
+        reason: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interchange, SignedAttestation, SlashingProtection, sibling_path};
+    use crate::Error;
+    use signing::signature::bls12_381;
+    use std::{env, fs, process};
+    use types::ethereum::H256;
+
+    const GVR: H256 = H256([0x4b; 32]);
+
+    fn pubkey() -> bls12_381::VerifyingKey {
+        bls12_381::SigningKey::from_bytes(&[0x11; 32])
+            .unwrap()
+            .verifying_key()
+    }
+
+    fn is_slashable<T>(result: Result<T, Error>) -> bool {
+        matches!(result, Err(Error::Slashable { .. }))
+    }
+
+    #[test]
+    fn double_proposal() {
+        let mut db = SlashingProtection::in_memory();
+        let pk = pubkey();
+
+        db.record_block(GVR, &pk, 10, H256([1; 32])).unwrap();
+        db.record_block(GVR, &pk, 10, H256([1; 32])).unwrap();
+        assert!(is_slashable(db.record_block(GVR, &pk, 10, H256([2; 32]))));
+        assert!(is_slashable(db.record_block(GVR, &pk, 9, H256([2; 32]))));
+        db.record_block(GVR, &pk, 11, H256([2; 32])).unwrap();
+    }
+
+    #[test]
+    fn double_vote() {
+        let mut db = SlashingProtection::in_memory();
+        let pk = pubkey();
+
+        db.record_attestation(GVR, &pk, 1, 2, H256([1; 32]))
+            .unwrap();
+        db.record_attestation(GVR, &pk, 1, 2, H256([1; 32]))
+            .unwrap();
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            1,
+            2,
+            H256([2; 32])
+        )));
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            0,
+            2,
+            H256([1; 32])
+        )));
+    }
+
+    #[test]
+    fn surround_vote() {
+        let mut db = SlashingProtection::in_memory();
+        let pk = pubkey();
+
+        db.record_attestation(GVR, &pk, 3, 4, H256([1; 32]))
+            .unwrap();
+        db.record_attestation(GVR, &pk, 4, 8, H256([2; 32]))
+            .unwrap();
+
+        // Surrounds 4=>8
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            3,
+            9,
+            H256([3; 32])
+        )));
+
+        // Surrounded by 4=>8
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            5,
+            7,
+            H256([3; 32])
+        )));
+
+        // Precedes the signing history
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            2,
+            3,
+            H256([3; 32])
+        )));
+
+        db.record_attestation(GVR, &pk, 8, 9, H256([3; 32]))
+            .unwrap();
+    }
+
+    #[test]
+    fn genesis_validators_root_mismatch() {
+        let mut db = SlashingProtection::in_memory();
+        db.record_block(GVR, &pubkey(), 1, H256([1; 32])).unwrap();
+        assert!(is_slashable(db.record_block(
+            H256([0; 32]),
+            &pubkey(),
+            2,
+            H256([1; 32])
+        )));
+    }
+
+    #[test]
+    fn persistence() {
+        let path = env::temp_dir().join(format!("iqkms-slashing-{}.jsonl", process::id()));
+        let pk = pubkey();
+
+        let mut db = SlashingProtection::open(&path).unwrap();
+        db.record_block(GVR, &pk, 10, H256([1; 32])).unwrap();
+        db.record_attestation(GVR, &pk, 1, 2, H256([1; 32]))
+            .unwrap();
+        drop(db);
+
+        // Simulate a torn write
+        let mut contents = fs::read_to_string(&path).unwrap();
+        contents.push_str("{\"pubkey\":");
+        fs::write(&path, contents).unwrap();
+
+        let mut db = SlashingProtection::open(&path).unwrap();
+        assert!(is_slashable(db.record_block(GVR, &pk, 10, H256([2; 32]))));
+        assert!(is_slashable(db.record_attestation(
+            GVR,
+            &pk,
+            1,
+            2,
+            H256([2; 32])
+        )));
+        db.record_block(GVR, &pk, 11, H256([1; 32])).unwrap();
+        drop(db);
+
+        let db = SlashingProtection::open(&path).unwrap();
+        let exported = db.export().unwrap();
+        assert_eq!(exported.data[0].signed_blocks.len(), 1);
+        assert_eq!(exported.data[0].signed_blocks[0].slot, 11);
+        drop(db);
+
+        // The log is compacted to a single entry per validator when opened
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(sibling_path(&path, ".lock")).unwrap();
+    }
+
+    #[test]
+    fn exclusive_lock() {
+        let path = env::temp_dir().join(format!("iqkms-slashing-lock-{}.jsonl", process::id()));
+
+        let db = SlashingProtection::open(&path).unwrap();
+        assert!(matches!(
+            SlashingProtection::open(&path),
+            Err(Error::SlashingProtectionDb { .. })
+        ));
+
+        drop(db);
+        SlashingProtection::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(sibling_path(&path, ".lock")).unwrap();
+    }
+
+    #[test]
+    fn interchange_round_trip() {
+        let json = r#"{
+            "metadata": {
+                "interchange_format_version": "5",
+                "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+            },
+            "data": [
+                {
+                    "pubkey": "0xb845089a1457f811bfc000588fbb4e713669be8ce060ea6be3c6ece09afc3794106c91ca73acda5e5457122d58723bed",
+                    "signed_blocks": [
+                        {
+                            "slot": "81952",
+                            "signing_root": "0x4ff6f743a43f3b4f95350831aeaf0a122a1a392922c45d804280284a69eb850b"
+                        },
+                        {
+                            "slot": "81951"
+                        }
+                    ],
+                    "signed_attestations": [
+                        {
+                            "source_epoch": "2290",
+                            "target_epoch": "3007",
+                            "signing_root": "0x587d6a4f59a58fe24f406e0502413e77fe1babddee641fda30034ed37ecc884d"
+                        },
+                        {
+                            "source_epoch": "2290",
+                            "target_epoch": "3008"
+                        }
+                    ]
+                }
+            ]
+        }"#;
+
+        let interchange = Interchange::from_json(json).unwrap();
+        let gvr = interchange.metadata.genesis_validators_root;
+        let pk = interchange.data[0].pubkey;
+
+        let mut db = SlashingProtection::in_memory();
+        db.import(&interchange).unwrap();
+
+        // Only the watermarks are exported. The attestation watermarks come
+        // from different entries, so their signing root is unknown.
+        let exported = db.export().unwrap();
+        assert_eq!(exported.metadata, interchange.metadata);
+        assert_eq!(
+            exported.data[0].signed_blocks,
+            &interchange.data[0].signed_blocks[..1]
+        );
+        assert_eq!(
+            exported.data[0].signed_attestations,
+            [SignedAttestation {
+                source_epoch: 2290,
+                target_epoch: 3008,
+                signing_root: None,
+            }]
+        );
+
+        let parsed = Interchange::from_json(&exported.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, exported);
+
+        // Imported history is enforced, including entries without a signing root
+        assert!(is_slashable(db.record_block(
+            gvr,
+            &pk,
+            81951,
+            H256([1; 32])
+        )));
+        assert!(is_slashable(db.record_attestation(
+            gvr,
+            &pk,
+            2290,
+            3008,
+            H256([1; 32])
+        )));
+        assert!(is_slashable(db.record_attestation(
+            gvr,
+            &pk,
+            2291,
+            3006,
+            H256([1; 32])
+        )));
+        db.record_block(gvr, &pk, 81953, H256([1; 32])).unwrap();
+        db.record_attestation(gvr, &pk, 3008, 3009, H256([1; 32]))
+            .unwrap();
+
+        // History for a different chain is rejected
+        let mut other = interchange.clone();
+        other.metadata.genesis_validators_root = H256::zero();
+        assert!(db.import(&other).is_err());
+    }
+}
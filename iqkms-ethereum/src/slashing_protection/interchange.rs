@@ -0,0 +1,216 @@
+//! EIP-3076: slashing protection interchange format.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-3076>
+
+use crate::{Error, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use signing::signature::bls12_381;
+use types::{
+    ethereum::{
+        H256,
+        consensus::{Epoch, Root, Slot},
+    },
+    hex,
+};
+
+/// Supported version of the interchange format.
+pub const FORMAT_VERSION: &str = "5";
+
+/// Slashing protection interchange file.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Interchange {
+    /// Interchange metadata.
+    pub metadata: Metadata,
+
+    /// Signing history of each validator.
+    pub data: Vec<ValidatorHistory>,
+}
+
+impl Interchange {
+    /// Parse an interchange file from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let interchange: Self =
+            serde_json::from_str(json).map_err(|e| Error::MessageMalformed {
+                reason: format!("malformed slashing protection interchange: {}", e),
+            })?;
+
+        if interchange.metadata.interchange_format_version != FORMAT_VERSION {
+            return Err(Error::MessageMalformed {
+                reason: format!(
+                    "unsupported interchange format version: {}",
+                    interchange.metadata.interchange_format_version
+                ),
+            });
+        }
+
+        Ok(interchange)
+    }
+
+    /// Serialize this interchange file as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::SlashingProtectionDb {
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Interchange metadata.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Metadata {
+    /// Version of the interchange format.
+    pub interchange_format_version: String,
+
+    /// Root of the validator registry at genesis of the chain the history
+    /// belongs to.
+    #[serde(with = "hex_root")]
+    pub genesis_validators_root: Root,
+}
+
+/// Signing history of a single validator.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorHistory {
+    /// Public key of the validator.
+    #[serde(with = "hex_pubkey")]
+    pub pubkey: bls12_381::VerifyingKey,
+
+    /// Blocks signed by the validator.
+    #[serde(default)]
+    pub signed_blocks: Vec<SignedBlock>,
+
+    /// Attestations signed by the validator.
+    #[serde(default)]
+    pub signed_attestations: Vec<SignedAttestation>,
+}
+
+/// Block signed by a validator.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedBlock {
+    /// Slot of the block.
+    #[serde(with = "quoted_u64")]
+    pub slot: Slot,
+
+    /// Signing root of the block, if known.
+    #[serde(
+        default,
+        with = "hex_root_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub signing_root: Option<Root>,
+}
+
+/// Attestation signed by a validator.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedAttestation {
+    /// Epoch of the source checkpoint.
+    #[serde(with = "quoted_u64")]
+    pub source_epoch: Epoch,
+
+    /// Epoch of the target checkpoint.
+    #[serde(with = "quoted_u64")]
+    pub target_epoch: Epoch,
+
+    /// Signing root of the attestation, if known.
+    #[serde(
+        default,
+        with = "hex_root_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub signing_root: Option<Root>,
+}
+
+/// Parse `0x`-prefixed hex.
+fn decode_hex<E: de::Error>(s: &str) -> std::result::Result<Vec<u8>, E> {
+    s.strip_prefix("0x")
+        .and_then(|s| hex::mixed::decode_vec(s).ok())
+        .ok_or_else(|| E::custom("expected 0x-prefixed hex"))
+}
+
+/// Encode bytes as `0x`-prefixed hex.
+fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::lower::encode_string(bytes))
+}
+
+/// Serialize integers as decimal strings.
+mod quoted_u64 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        n: &u64,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&n.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Serialize roots as `0x`-prefixed hex.
+mod hex_root {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        root: &Root,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(root.as_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Root, D::Error> {
+        let bytes = decode_hex::<D::Error>(&String::deserialize(deserializer)?)?;
+        <[u8; 32]>::try_from(bytes.as_slice())
+            .map(H256)
+            .map_err(|_| de::Error::custom("expected 32-byte root"))
+    }
+}
+
+/// Serialize optional roots as `0x`-prefixed hex.
+mod hex_root_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        root: &Option<Root>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match root {
+            Some(root) => hex_root::serialize(root, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<Root>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "hex_root")] Root);
+
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(root)| root))
+    }
+}
+
+/// Serialize BLS12-381 public keys as `0x`-prefixed hex.
+mod hex_pubkey {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        pubkey: &bls12_381::VerifyingKey,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_hex(pubkey.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<bls12_381::VerifyingKey, D::Error> {
+        let bytes = decode_hex::<D::Error>(&String::deserialize(deserializer)?)?;
+        bls12_381::VerifyingKey::from_bytes(&bytes)
+            .map_err(|_| de::Error::custom("malformed BLS12-381 public key"))
+    }
+}
@@ -10,6 +10,11 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs, rust_2018_idioms, unused_lifetimes)]
 
+/// Cosmos support.
+pub mod cosmos {
+    tonic::include_proto!("iqkms.cosmos");
+}
+
 /// Ethereum support.
 pub mod ethereum {
     tonic::include_proto!("iqkms.ethereum");
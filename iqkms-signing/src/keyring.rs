@@ -4,6 +4,9 @@ use std::{
     fmt::{self, Debug},
 };
 
+#[cfg(feature = "cosmos")]
+use types::cosmos;
+
 #[cfg(feature = "ethereum")]
 use types::ethereum;
 
@@ -13,6 +16,10 @@ pub(crate) struct Keyring {
     /// Signing keys.
     keys: Map<VerifyingKey, SigningKey>,
 
+    /// Cosmos address index.
+    #[cfg(feature = "cosmos")]
+    cosmos_index: Map<cosmos::Address, VerifyingKey>,
+
     /// Ethereum address index.
     #[cfg(feature = "ethereum")]
     eth_index: Map<ethereum::Address, VerifyingKey>,
@@ -20,10 +27,14 @@ pub(crate) struct Keyring {
 
 impl Keyring {
     /// Add a key to the ring.
-    #[allow(dead_code)] // TODO(tarcieri): use me!
     pub fn add(&mut self, signing_key: SigningKey) -> Result<()> {
         let verifying_key = signing_key.verifying_key();
 
+        #[cfg(feature = "cosmos")]
+        if let Some(cosmos_addr) = cosmos_address(&verifying_key) {
+            self.cosmos_index.insert(cosmos_addr, verifying_key.clone());
+        }
+
         #[cfg(feature = "ethereum")]
         #[allow(irrefutable_let_patterns)]
         if let VerifyingKey::EcdsaSecp256k1(vk) = &verifying_key {
@@ -37,19 +48,92 @@ impl Keyring {
         }
     }
 
+    /// Get the verifying keys for all keys in the ring.
+    pub fn verifying_keys(&self) -> Vec<VerifyingKey> {
+        self.keys.keys().cloned().collect()
+    }
+
+    /// Find a key by its verifying key.
+    pub fn find(&self, verifying_key: &VerifyingKey) -> Result<&SigningKey> {
+        self.keys.get(verifying_key).ok_or(Error)
+    }
+
+    /// Find a key by its Cosmos address.
+    #[cfg(feature = "cosmos")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cosmos")))]
+    pub fn find_by_cosmos_address(&self, cosmos_addr: &cosmos::Address) -> Result<&SigningKey> {
+        self.cosmos_index
+            .get(cosmos_addr)
+            .and_then(|vk| self.keys.get(vk))
+            .ok_or(Error)
+    }
+
     /// Find a key by its Ethereum address.
+    ///
+    /// The chain ID the address is checksummed for (if any) is ignored.
     #[cfg(feature = "ethereum")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ethereum")))]
     pub fn find_by_eth_address(&self, eth_addr: &ethereum::Address) -> Result<&SigningKey> {
         self.eth_index
-            .get(eth_addr)
+            .get(&ethereum::Address::from(eth_addr.hash))
             .and_then(|vk| self.keys.get(vk))
             .ok_or(Error)
     }
 }
 
+/// Compute the Cosmos address of the given key, if it's a kind of key with
+/// one: secp256k1 account keys and Ed25519 consensus keys.
+#[cfg(feature = "cosmos")]
+fn cosmos_address(verifying_key: &VerifyingKey) -> Option<cosmos::Address> {
+    match verifying_key {
+        VerifyingKey::EcdsaSecp256k1(vk) => Some(vk.into()),
+        #[cfg(feature = "ed25519")]
+        VerifyingKey::Ed25519(vk) => Some(vk.into()),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
 impl Debug for Keyring {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Keyring").finish_non_exhaustive()
     }
 }
+
+#[cfg(all(test, feature = "cosmos", feature = "ed25519"))]
+mod tests {
+    use super::Keyring;
+    use crate::SigningKey;
+    use crypto::signature::{ecdsa::secp256k1, ed25519};
+    use types::cosmos;
+
+    #[test]
+    fn find_by_cosmos_address() {
+        let account_key = secp256k1::SigningKey::from_bytes(&[0x01; 32]).unwrap();
+        let account_addr = cosmos::Address::from(account_key.verifying_key());
+        let account_key = SigningKey::from(account_key);
+
+        let consensus_key = ed25519::SigningKey::from_bytes(&[0x02; 32]).unwrap();
+        let consensus_addr = cosmos::Address::from(consensus_key.verifying_key());
+        let consensus_key = SigningKey::from(consensus_key);
+
+        let mut keyring = Keyring::default();
+        let account_vk = account_key.verifying_key();
+        let consensus_vk = consensus_key.verifying_key();
+        keyring.add(account_key).unwrap();
+        keyring.add(consensus_key).unwrap();
+
+        let found = keyring.find_by_cosmos_address(&account_addr).unwrap();
+        assert_eq!(found.verifying_key(), account_vk);
+
+        // Consensus keys are found through the same (prefix-agnostic) index
+        let found = keyring.find_by_cosmos_address(&consensus_addr).unwrap();
+        assert_eq!(found.verifying_key(), consensus_vk);
+
+        assert!(
+            keyring
+                .find_by_cosmos_address(&cosmos::Address::default())
+                .is_err()
+        );
+    }
+}
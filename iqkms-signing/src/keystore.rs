@@ -0,0 +1,367 @@
+//! EIP-2335: BLS12-381 keystores.
+//!
+//! Support for importing encrypted BLS12-381 keys from the JSON keystore
+//! format used by Ethereum consensus clients and the staking deposit CLI.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-2335>
+
+use crate::{Error, Result, SigningKey};
+use aes::{
+    Aes128,
+    cipher::{KeyIvInit, StreamCipher},
+};
+use crypto::{
+    digest::{Digest, sha2::Sha256},
+    signature::bls12_381,
+};
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+/// AES-128 in counter mode with a 128-bit big endian counter.
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Size of the key derived by the KDF in bytes.
+const DERIVED_KEY_SIZE: usize = 32;
+
+/// Keystore version supported by this implementation.
+const VERSION: u32 = 4;
+
+/// EIP-2335 keystore containing an encrypted BLS12-381 signing key.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Keystore {
+    /// Cryptographic parameters used to encrypt the key.
+    crypto: Crypto,
+
+    /// Optional human-readable description of the key.
+    #[serde(default)]
+    description: Option<String>,
+
+    /// Hex-encoded verifying key which corresponds to the encrypted key.
+    #[serde(default)]
+    pubkey: Option<String>,
+
+    /// EIP-2334 derivation path of the key (may be empty).
+    #[serde(default)]
+    path: String,
+
+    /// UUID of the keystore.
+    uuid: String,
+
+    /// Keystore version.
+    version: u32,
+}
+
+impl Keystore {
+    /// Parse a keystore from its JSON serialization.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let keystore: Self = serde_json::from_str(json).map_err(|_| Error)?;
+
+        if keystore.version != VERSION {
+            return Err(Error);
+        }
+
+        Ok(keystore)
+    }
+
+    /// Get the human-readable description of the key, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get the EIP-2334 derivation path of the key.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the UUID of the keystore.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// Get the verifying key which corresponds to the encrypted key, if one is
+    /// included in the keystore.
+    pub fn pubkey(&self) -> Result<Option<bls12_381::VerifyingKey>> {
+        self.pubkey
+            .as_deref()
+            .map(|pubkey| {
+                bls12_381::VerifyingKey::from_bytes(&decode_hex(pubkey)?).map_err(|_| Error)
+            })
+            .transpose()
+    }
+
+    /// Decrypt the signing key stored in this keystore using the given
+    /// password.
+    ///
+    /// The password is normalized as described in EIP-2335 (NFKD with control
+    /// codes removed). Returns an error if the password is incorrect or the
+    /// decrypted key doesn't match the keystore's `pubkey`.
+    pub fn decrypt(&self, password: &str) -> Result<SigningKey> {
+        let password = normalize_password(password);
+        let derived_key = self.crypto.kdf.derive_key(password.as_bytes())?;
+        let cipher_message = decode_hex(&self.crypto.cipher.message)?;
+
+        let checksum = Sha256::new()
+            .chain_update(&derived_key[16..])
+            .chain_update(&cipher_message)
+            .finalize();
+
+        if self.crypto.checksum.function != "sha256"
+            || checksum.as_slice() != decode_hex(&self.crypto.checksum.message)?.as_slice()
+        {
+            return Err(Error);
+        }
+
+        let secret = Zeroizing::new(
+            self.crypto
+                .cipher
+                .decrypt(&derived_key[..16], cipher_message)?,
+        );
+        let signing_key = bls12_381::SigningKey::from_bytes(&secret).map_err(|_| Error)?;
+
+        if let Some(pubkey) = self.pubkey()? {
+            if signing_key.verifying_key() != pubkey {
+                return Err(Error);
+            }
+        }
+
+        Ok(signing_key.into())
+    }
+}
+
+/// Cryptographic parameters of a keystore.
+#[derive(Clone, Debug, Deserialize)]
+struct Crypto {
+    kdf: Kdf,
+    checksum: Checksum,
+    cipher: Cipher,
+}
+
+/// Key derivation function used to derive the decryption key from a password.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "function", content = "params", rename_all = "lowercase")]
+enum Kdf {
+    /// scrypt
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+
+    /// PBKDF2 with HMAC-SHA-256
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl Kdf {
+    /// Derive a decryption key from the given (normalized) password.
+    fn derive_key(&self, password: &[u8]) -> Result<Zeroizing<[u8; DERIVED_KEY_SIZE]>> {
+        let mut derived_key = Zeroizing::new([0u8; DERIVED_KEY_SIZE]);
+
+        match self {
+            Kdf::Scrypt {
+                dklen,
+                n,
+                r,
+                p,
+                salt,
+            } => {
+                if *dklen != DERIVED_KEY_SIZE || !n.is_power_of_two() {
+                    return Err(Error);
+                }
+
+                let log_n = u8::try_from(n.trailing_zeros()).map_err(|_| Error)?;
+                let params =
+                    scrypt::Params::new(log_n, *r, *p, DERIVED_KEY_SIZE).map_err(|_| Error)?;
+                scrypt::scrypt(password, &decode_hex(salt)?, &params, &mut *derived_key)
+                    .map_err(|_| Error)?;
+            }
+            Kdf::Pbkdf2 {
+                dklen,
+                c,
+                prf,
+                salt,
+            } => {
+                if *dklen != DERIVED_KEY_SIZE || prf != "hmac-sha256" {
+                    return Err(Error);
+                }
+
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, &decode_hex(salt)?, *c, &mut *derived_key);
+            }
+        }
+
+        Ok(derived_key)
+    }
+}
+
+/// Checksum used to verify the password is correct.
+#[derive(Clone, Debug, Deserialize)]
+struct Checksum {
+    function: String,
+    message: String,
+}
+
+/// Cipher used to encrypt the signing key.
+#[derive(Clone, Debug, Deserialize)]
+struct Cipher {
+    function: String,
+    params: CipherParams,
+    message: String,
+}
+
+impl Cipher {
+    /// Decrypt the given ciphertext using the given key.
+    fn decrypt(&self, key: &[u8], mut ciphertext: Vec<u8>) -> Result<Vec<u8>> {
+        if self.function != "aes-128-ctr" {
+            return Err(Error);
+        }
+
+        let iv = decode_hex(&self.params.iv)?;
+        let mut cipher = Aes128Ctr::new_from_slices(key, &iv).map_err(|_| Error)?;
+        cipher.apply_keystream(&mut ciphertext);
+        Ok(ciphertext)
+    }
+}
+
+/// Cipher parameters.
+#[derive(Clone, Debug, Deserialize)]
+struct CipherParams {
+    iv: String,
+}
+
+/// Normalize a password as described in EIP-2335: convert to NFKD form and
+/// strip the C0, C1, and `Delete` control codes.
+fn normalize_password(password: &str) -> Zeroizing<String> {
+    Zeroizing::new(
+        password
+            .nfkd()
+            .filter(|c| !matches!(c, '\u{00}'..='\u{1f}' | '\u{7f}'..='\u{9f}'))
+            .collect(),
+    )
+}
+
+/// Decode a hex string, ignoring an optional `0x` prefix.
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    hex::mixed::decode_vec(s.strip_prefix("0x").unwrap_or(s)).map_err(|_| Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keystore;
+    use crate::VerifyingKey;
+    use crypto::signature::bls12_381;
+    use hex_literal::hex;
+
+    /// Test password from EIP-2335.
+    const PASSWORD: &str = "𝔱𝔢𝔰𝔱𝔭𝔞𝔰𝔰𝔴𝔬𝔯𝔡🔑";
+
+    /// Verifying key which corresponds to the test keystores.
+    const PUBKEY: [u8; 48] = hex!(
+        "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27"
+        "f4ae4040902382ae2910c15e2b420d07"
+    );
+
+    const SCRYPT_KEYSTORE: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "scrypt",
+                "params": {
+                    "dklen": 32,
+                    "n": 262144,
+                    "p": 1,
+                    "r": 8,
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "d2217fe5f3e9a1e34581ef8a78f7c9928e436d36dacc5e846690a5581e8ea484"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "06ae90d55fe0a6e9c5c3bc5b170827b2e5cce3929ed3f116c2811e6366dfe20f"
+            }
+        },
+        "description": "This is a test keystore that uses scrypt to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/3141592653/589793238",
+        "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+        "version": 4
+    }"#;
+
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "pbkdf2",
+                "params": {
+                    "dklen": 32,
+                    "c": 262144,
+                    "prf": "hmac-sha256",
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+            }
+        },
+        "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/0/0",
+        "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+        "version": 4
+    }"#;
+
+    fn pubkey() -> VerifyingKey {
+        bls12_381::VerifyingKey::from_bytes(&PUBKEY).unwrap().into()
+    }
+
+    #[test]
+    fn decrypt_scrypt() {
+        let keystore = Keystore::from_json(SCRYPT_KEYSTORE).unwrap();
+        assert_eq!(keystore.path(), "m/12381/60/3141592653/589793238");
+
+        let signing_key = keystore.decrypt(PASSWORD).unwrap();
+        assert_eq!(signing_key.verifying_key(), pubkey());
+    }
+
+    #[test]
+    fn decrypt_pbkdf2() {
+        let keystore = Keystore::from_json(PBKDF2_KEYSTORE).unwrap();
+        let signing_key = keystore.decrypt(PASSWORD).unwrap();
+        assert_eq!(signing_key.verifying_key(), pubkey());
+    }
+
+    #[test]
+    fn normalizes_password() {
+        // Control codes are stripped prior to key derivation
+        let keystore = Keystore::from_json(PBKDF2_KEYSTORE).unwrap();
+        assert!(keystore.decrypt(&format!("\u{7f}{PASSWORD}\u{0}")).is_ok());
+    }
+
+    #[test]
+    fn rejects_wrong_password() {
+        let keystore = Keystore::from_json(PBKDF2_KEYSTORE).unwrap();
+        assert!(keystore.decrypt("testpassword").is_err());
+    }
+}
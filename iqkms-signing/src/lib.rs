@@ -22,16 +22,24 @@
     unused_lifetimes,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+#[cfg(feature = "eip2335")]
+#[cfg_attr(docsrs, doc(cfg(feature = "eip2335")))]
+pub mod keystore;
 
 mod error;
 mod keyring;
 mod service;
+mod signature_format;
 mod signing_key;
 mod verifying_key;
 
 pub use crate::{
     error::{Error, Result},
     service::{KeyHandle, Request, Response, SigningService},
+    signature_format::SignatureFormat,
+    signing_key::SigningKey,
     verifying_key::VerifyingKey,
 };
 pub use crypto::signature;
@@ -1,4 +1,4 @@
-use crate::{Error, Result, VerifyingKey, keyring::Keyring};
+use crate::{Error, Result, SignatureFormat, SigningKey, VerifyingKey, keyring::Keyring};
 use std::{
     future::Future,
     pin::Pin,
@@ -7,6 +7,9 @@ use std::{
 use tower::Service;
 use types::Bytes;
 
+#[cfg(feature = "cosmos")]
+use types::cosmos;
+
 #[cfg(feature = "ethereum")]
 use types::ethereum;
 
@@ -23,23 +26,65 @@ impl SigningService {
         Self::default()
     }
 
-    /// Sign the given prehash using the key with the given handle.
-    fn sign_prehash(&self, key_handle: KeyHandle, prehash: &[u8]) -> Result<Response> {
-        let signing_key = match key_handle {
-            #[cfg(feature = "ethereum")]
-            KeyHandle::Ethereum(eth_addr) => self.keyring.find_by_eth_address(&eth_addr)?,
-            #[allow(unreachable_patterns)]
-            _ => return Err(Error),
-        };
+    /// Add a signing key to the keyring.
+    pub fn add_key(&mut self, signing_key: impl Into<SigningKey>) -> Result<()> {
+        self.keyring.add(signing_key.into())
+    }
+
+    /// Sign the given message using the key with the given handle.
+    fn sign(&self, key_handle: KeyHandle, msg: &[u8]) -> Result<Response> {
+        let signing_key = self.find_key(key_handle)?;
+        let verifying_key = signing_key.verifying_key();
+        let signature = signing_key.sign(msg)?;
+
+        Ok(Response::Sign {
+            signature,
+            verifying_key,
+        })
+    }
 
+    /// Sign the given prehash using the key with the given handle.
+    fn sign_prehash(
+        &self,
+        key_handle: KeyHandle,
+        prehash: &[u8],
+        format: SignatureFormat,
+    ) -> Result<Response> {
+        let signing_key = self.find_key(key_handle)?;
         let verifying_key = signing_key.verifying_key();
-        let signature = signing_key.sign_prehash(prehash)?;
+        let signature = signing_key.sign_prehash_with_format(prehash, format)?;
 
         Ok(Response::SignPrehash {
             signature,
             verifying_key,
         })
     }
+
+    /// Sign the given prehash using the key with the given handle, producing a
+    /// recoverable signature.
+    fn sign_prehash_recoverable(&self, key_handle: KeyHandle, prehash: &[u8]) -> Result<Response> {
+        let signing_key = self.find_key(key_handle)?;
+        let verifying_key = signing_key.verifying_key();
+        let signature = signing_key.sign_prehash_recoverable(prehash)?;
+
+        Ok(Response::SignPrehashRecoverable {
+            signature,
+            verifying_key,
+        })
+    }
+
+    /// Find the signing key with the given handle.
+    fn find_key(&self, key_handle: KeyHandle) -> Result<&SigningKey> {
+        match key_handle {
+            KeyHandle::VerifyingKey(verifying_key) => self.keyring.find(&verifying_key),
+            #[cfg(feature = "cosmos")]
+            KeyHandle::Cosmos(cosmos_addr) => self.keyring.find_by_cosmos_address(&cosmos_addr),
+            #[cfg(feature = "ethereum")]
+            KeyHandle::Ethereum(eth_addr) => self.keyring.find_by_eth_address(&eth_addr),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error),
+        }
+    }
 }
 
 impl Service<Request> for SigningService {
@@ -53,10 +98,21 @@ impl Service<Request> for SigningService {
 
     fn call(&mut self, request: Request) -> Self::Future {
         let result = match request {
+            Request::Sign { key_handle, msg } => self.sign(key_handle, &msg),
             Request::SignPrehash {
                 key_handle,
                 prehash,
-            } => self.sign_prehash(key_handle, &prehash),
+            } => self.sign_prehash(key_handle, &prehash, SignatureFormat::default()),
+            Request::SignPrehashWithFormat {
+                key_handle,
+                prehash,
+                format,
+            } => self.sign_prehash(key_handle, &prehash, format),
+            Request::SignPrehashRecoverable {
+                key_handle,
+                prehash,
+            } => self.sign_prehash_recoverable(key_handle, &prehash),
+            Request::VerifyingKeys => Ok(Response::VerifyingKeys(self.keyring.verifying_keys())),
         };
 
         Box::pin(async { result })
@@ -67,7 +123,20 @@ impl Service<Request> for SigningService {
 #[allow(missing_docs)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Request {
-    /// Sign the provided prehash.
+    /// Sign the provided message.
+    ///
+    /// How the message is hashed (if at all) depends on the algorithm of the
+    /// key: see [`SigningKey::sign`].
+    Sign {
+        /// Handle to the given signing key.
+        key_handle: KeyHandle,
+
+        /// Message to be signed.
+        msg: Bytes,
+    },
+
+    /// Sign the provided prehash, serializing the signature in the default
+    /// [`SignatureFormat`] (i.e. [`SignatureFormat::Fixed`]).
     SignPrehash {
         /// Handle to the given signing key.
         key_handle: KeyHandle,
@@ -75,12 +144,46 @@ pub enum Request {
         /// Message prehash to be signed.
         prehash: Bytes,
     },
+
+    /// Sign the provided prehash, serializing the signature in the given
+    /// format.
+    SignPrehashWithFormat {
+        /// Handle to the given signing key.
+        key_handle: KeyHandle,
+
+        /// Message prehash to be signed.
+        prehash: Bytes,
+
+        /// Format to serialize the signature in.
+        format: SignatureFormat,
+    },
+
+    /// Sign the provided prehash, producing a recoverable signature (i.e. one
+    /// which includes a recovery ID computed at signing time).
+    SignPrehashRecoverable {
+        /// Handle to the given signing key.
+        key_handle: KeyHandle,
+
+        /// Message prehash to be signed.
+        prehash: Bytes,
+    },
+
+    /// List the verifying keys of all keys in the keyring.
+    VerifyingKeys,
 }
 
 /// Responses from the signing service.
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub enum Response {
+    Sign {
+        /// Verifying key which corresponds to this signer.
+        verifying_key: VerifyingKey,
+
+        /// Resulting algorithm-specific signature, serialized as bytes.
+        signature: Bytes,
+    },
+
     SignPrehash {
         /// Verifying key which corresponds to this signer.
         verifying_key: VerifyingKey,
@@ -88,21 +191,116 @@ pub enum Response {
         /// Resulting algorithm-specific signature, serialized as bytes.
         signature: Bytes,
     },
+
+    SignPrehashRecoverable {
+        /// Verifying key which corresponds to this signer.
+        verifying_key: VerifyingKey,
+
+        /// Resulting recoverable signature, serialized as bytes.
+        ///
+        /// For ECDSA/secp256k1 this is `r || s || recid`.
+        signature: Bytes,
+    },
+
+    /// Verifying keys of all keys in the keyring.
+    VerifyingKeys(Vec<VerifyingKey>),
 }
 
 /// Handle to a key in the signing keyring.
 // TODO(tarcieri): OCap-like access control for key handles?
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum KeyHandle {
+    /// Key identified by its verifying key.
+    VerifyingKey(VerifyingKey),
+
+    /// Key identified by its Cosmos address, i.e. the bytes of a bech32
+    /// address like `cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c`, with any
+    /// prefix.
+    ///
+    /// Both secp256k1 account keys and Ed25519 consensus keys have Cosmos
+    /// addresses, and they share one index regardless of prefix, so this can
+    /// resolve to a consensus key (e.g. one whose `valcons` address is given).
+    #[cfg(feature = "cosmos")]
+    Cosmos(cosmos::Address),
+
     /// Key identified by its Ethereum address, e.g.
     /// `0x27b1fdb04752bbc536007a920d24acb045561c26`
     #[cfg(feature = "ethereum")]
     Ethereum(ethereum::Address),
 }
 
+impl From<VerifyingKey> for KeyHandle {
+    fn from(verifying_key: VerifyingKey) -> KeyHandle {
+        KeyHandle::VerifyingKey(verifying_key)
+    }
+}
+
+#[cfg(feature = "cosmos")]
+impl From<cosmos::Address> for KeyHandle {
+    fn from(cosmos_addr: cosmos::Address) -> KeyHandle {
+        KeyHandle::Cosmos(cosmos_addr)
+    }
+}
+
 #[cfg(feature = "ethereum")]
 impl From<ethereum::Address> for KeyHandle {
     fn from(eth_addr: ethereum::Address) -> KeyHandle {
         KeyHandle::Ethereum(eth_addr)
     }
 }
+
+#[cfg(all(test, feature = "cosmos", feature = "ed25519"))]
+mod tests {
+    use super::{KeyHandle, Request, Response, SigningService};
+    use crate::VerifyingKey;
+    use crypto::signature::{Verifier, ecdsa::secp256k1, ed25519};
+    use tower::Service;
+    use types::cosmos;
+
+    /// Sign a message with the key with the given handle, returning the
+    /// signature and the verifying key.
+    async fn sign(service: &mut SigningService, key_handle: KeyHandle) -> (Vec<u8>, VerifyingKey) {
+        let request = Request::Sign {
+            key_handle,
+            msg: b"hello"[..].into(),
+        };
+
+        match service.call(request).await.unwrap() {
+            Response::Sign {
+                signature,
+                verifying_key,
+            } => (signature.to_vec(), verifying_key),
+            other => unreachable!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_by_cosmos_address() {
+        let account_key = secp256k1::SigningKey::from_bytes(&[0x01; 32]).unwrap();
+        let account_vk = account_key.verifying_key();
+        let consensus_key = ed25519::SigningKey::from_bytes(&[0x02; 32]).unwrap();
+        let consensus_vk = consensus_key.verifying_key();
+
+        let mut service = SigningService::new();
+        service.add_key(account_key).unwrap();
+        service.add_key(consensus_key).unwrap();
+
+        let (signature, verifying_key) =
+            sign(&mut service, cosmos::Address::from(&account_vk).into()).await;
+        assert_eq!(verifying_key, VerifyingKey::EcdsaSecp256k1(account_vk));
+        let signature = secp256k1::Signature::try_from(signature.as_slice()).unwrap();
+        assert!(account_vk.verify(b"hello", &signature).is_ok());
+
+        let (signature, verifying_key) =
+            sign(&mut service, cosmos::Address::from(&consensus_vk).into()).await;
+        assert_eq!(verifying_key, VerifyingKey::Ed25519(consensus_vk));
+        let signature = ed25519::Signature::try_from(signature.as_slice()).unwrap();
+        assert!(consensus_vk.verify(b"hello", &signature).is_ok());
+
+        let request = Request::Sign {
+            key_handle: cosmos::Address::default().into(),
+            msg: b"hello"[..].into(),
+        };
+        assert!(service.call(request).await.is_err());
+    }
+}
@@ -0,0 +1,26 @@
+//! Signature serialization formats.
+
+/// Format in which signatures are serialized.
+///
+/// Not every format is supported by every algorithm: requesting an
+/// unsupported combination (e.g. [`SignatureFormat::Recoverable`] with a
+/// non-secp256k1 key) results in an error.
+///
+/// ECDSA/secp256k1 signatures are always normalized to low-S regardless of
+/// the requested format, as Ethereum (EIP-2), Bitcoin (BIP-146), and Cosmos
+/// all reject high-S signatures as malleable.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum SignatureFormat {
+    /// ASN.1 DER-encoded signature (ECDSA only).
+    Der,
+
+    /// Fixed-size signature in the algorithm's native serialization, e.g.
+    /// `r || s` for ECDSA.
+    #[default]
+    Fixed,
+
+    /// 65-byte `r || s || recid` recoverable signature (ECDSA/secp256k1 only),
+    /// with the recovery ID computed at signing time.
+    Recoverable,
+}
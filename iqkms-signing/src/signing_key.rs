@@ -1,18 +1,37 @@
-use crate::{Error, Result, VerifyingKey};
+use crate::{Error, Result, SignatureFormat, VerifyingKey};
+use crypto::digest::{Digest, sha2::Sha256};
+use std::fmt::{self, Debug};
+
+#[cfg(any(feature = "bls12_381", feature = "ed25519"))]
+use crypto::signature::Signer;
+#[cfg(feature = "bls12_381")]
+use crypto::signature::bls12_381;
+#[cfg(feature = "ed25519")]
+use crypto::signature::ed25519;
+#[cfg(feature = "secp256k1")]
 use crypto::{
-    digest::{Digest, sha2::Sha256},
+    elliptic_curve::IsHigh,
     rand::{OsRng, RngCore},
     signature::{ecdsa, hazmat::PrehashSigner},
 };
-use std::fmt::{self, Debug};
 use types::Bytes;
 
 /// Signing key.
 pub enum SigningKey {
+    /// BLS12-381
+    #[cfg(feature = "bls12_381")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+    Bls12381(bls12_381::SigningKey),
+
     /// ECDSA/secp256k1
     #[cfg(feature = "secp256k1")]
     #[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
     EcdsaSecp256k1(ecdsa::secp256k1::SigningKey),
+
+    /// Ed25519
+    #[cfg(feature = "ed25519")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+    Ed25519(ed25519::SigningKey),
 }
 
 impl SigningKey {
@@ -20,7 +39,6 @@ impl SigningKey {
     // TODO(tarcieri): unified `generate` method with algorithm parameter
     #[cfg(feature = "secp256k1")]
     #[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
-    #[allow(dead_code)] // TODO(tarcieri): use me!
     pub fn generate_secp256k1() -> Self {
         let mut bytes = [0u8; 32];
 
@@ -35,29 +53,65 @@ impl SigningKey {
     }
 
     /// Sign the given message with this key.
+    ///
+    /// ECDSA keys sign the SHA-256 digest of the message, whereas BLS12-381
+    /// and Ed25519 keys sign the message directly.
     // TODO(tarcieri): support for customizing hash function used
-    #[allow(dead_code)] // TODO(tarcieri): use me!
     pub fn sign(&self, msg: &[u8]) -> Result<Bytes> {
-        self.sign_prehash(&Sha256::digest(msg))
+        match self {
+            #[cfg(feature = "bls12_381")]
+            Self::Bls12381(sk) => Ok(Bytes::copy_from_slice(sk.try_sign(msg)?.as_ref())),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(sk) => Ok(Bytes::copy_from_slice(sk.try_sign(msg)?.as_ref())),
+            #[allow(unreachable_patterns)]
+            _ => self.sign_prehash(&Sha256::digest(msg)),
+        }
     }
 
     /// Sign the given prehashed message digest with this key.
     pub fn sign_prehash(&self, msg_digest: &[u8]) -> Result<Bytes> {
+        self.sign_prehash_with_format(msg_digest, SignatureFormat::Fixed)
+    }
+
+    /// Sign the given prehashed message digest with this key, producing a
+    /// recoverable signature.
+    ///
+    /// For ECDSA/secp256k1 the signature is serialized as `r || s || recid`,
+    /// where the recovery ID is computed at signing time.
+    pub fn sign_prehash_recoverable(&self, msg_digest: &[u8]) -> Result<Bytes> {
+        self.sign_prehash_with_format(msg_digest, SignatureFormat::Recoverable)
+    }
+
+    /// Sign the given prehashed message digest with this key, serializing the
+    /// signature in the given format.
+    ///
+    /// Returns an error for algorithms which don't support signing prehashes,
+    /// such as BLS12-381 and Ed25519.
+    #[cfg_attr(not(feature = "secp256k1"), allow(unused_variables))]
+    pub fn sign_prehash_with_format(
+        &self,
+        msg_digest: &[u8],
+        format: SignatureFormat,
+    ) -> Result<Bytes> {
         match self {
+            #[cfg(feature = "bls12_381")]
+            Self::Bls12381(_) => Err(Error),
             #[cfg(feature = "secp256k1")]
-            Self::EcdsaSecp256k1(sk) => {
-                PrehashSigner::<ecdsa::secp256k1::Signature>::sign_prehash(sk, msg_digest)
-                    .map(|sig| sig.to_vec().into())
-                    .map_err(|_| Error)
-            }
+            Self::EcdsaSecp256k1(sk) => sign_secp256k1(sk, msg_digest, format),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(_) => Err(Error),
         }
     }
 
     /// Get the [`VerifyingKey`] that corresponds to this signing key.
     pub fn verifying_key(&self) -> VerifyingKey {
         match self {
+            #[cfg(feature = "bls12_381")]
+            SigningKey::Bls12381(sk) => VerifyingKey::Bls12381(sk.verifying_key()),
             #[cfg(feature = "secp256k1")]
             SigningKey::EcdsaSecp256k1(sk) => VerifyingKey::EcdsaSecp256k1(sk.verifying_key()),
+            #[cfg(feature = "ed25519")]
+            SigningKey::Ed25519(sk) => VerifyingKey::Ed25519(sk.verifying_key()),
         }
     }
 }
@@ -70,6 +124,15 @@ impl Debug for SigningKey {
     }
 }
 
+#[cfg(feature = "bls12_381")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+impl From<bls12_381::SigningKey> for SigningKey {
+    #[inline]
+    fn from(key: bls12_381::SigningKey) -> SigningKey {
+        SigningKey::Bls12381(key)
+    }
+}
+
 #[cfg(feature = "secp256k1")]
 #[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
 impl From<ecdsa::secp256k1::SigningKey> for SigningKey {
@@ -78,3 +141,99 @@ impl From<ecdsa::secp256k1::SigningKey> for SigningKey {
         SigningKey::EcdsaSecp256k1(key)
     }
 }
+
+#[cfg(feature = "ed25519")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+impl From<ed25519::SigningKey> for SigningKey {
+    #[inline]
+    fn from(key: ed25519::SigningKey) -> SigningKey {
+        SigningKey::Ed25519(key)
+    }
+}
+
+/// Sign the given prehash with an ECDSA/secp256k1 key.
+///
+/// Signatures are always normalized to low-S (see [`SignatureFormat`]).
+#[cfg(feature = "secp256k1")]
+fn sign_secp256k1(
+    sk: &ecdsa::secp256k1::SigningKey,
+    msg_digest: &[u8],
+    format: SignatureFormat,
+) -> Result<Bytes> {
+    if format == SignatureFormat::Recoverable {
+        // The recovery ID is adjusted along with `s` when it's normalized, so
+        // normalization must happen at signing time rather than after the fact
+        let sig =
+            PrehashSigner::<ecdsa::secp256k1::RecoverableSignature>::sign_prehash(sk, msg_digest)
+                .map_err(|_| Error)?;
+
+        return if bool::from(IsHigh::is_high(sig.s().as_ref())) {
+            Err(Error)
+        } else {
+            Ok(Bytes::copy_from_slice(sig.as_ref()))
+        };
+    }
+
+    let sig = PrehashSigner::<ecdsa::secp256k1::Signature>::sign_prehash(sk, msg_digest)
+        .map_err(|_| Error)?;
+    let sig = sig.normalize_s().unwrap_or(sig);
+
+    match format {
+        SignatureFormat::Der => Ok(Bytes::copy_from_slice(sig.to_der().as_bytes())),
+        SignatureFormat::Fixed => Ok(sig.to_vec().into()),
+        SignatureFormat::Recoverable => Err(Error),
+    }
+}
+
+#[cfg(all(test, feature = "secp256k1"))]
+mod tests {
+    use super::SigningKey;
+    use crate::SignatureFormat;
+    use crypto::signature::{ecdsa::secp256k1, hazmat::PrehashVerifier};
+    use proptest::prelude::*;
+
+    /// Parse a secp256k1 signing key, rejecting out-of-range scalars.
+    fn signing_key(bytes: &[u8; 32]) -> Option<(SigningKey, secp256k1::VerifyingKey)> {
+        let sk = secp256k1::SigningKey::from_bytes(bytes).ok()?;
+        let vk = sk.verifying_key();
+        Some((sk.into(), vk))
+    }
+
+    proptest! {
+        #[test]
+        fn secp256k1_fixed_is_low_s(key in any::<[u8; 32]>(), digest in any::<[u8; 32]>()) {
+            let (sk, vk) = signing_key(&key).ok_or(TestCaseError::reject("invalid key"))?;
+
+            let bytes = sk.sign_prehash_with_format(&digest, SignatureFormat::Fixed).unwrap();
+            let sig = secp256k1::Signature::try_from(bytes.as_ref()).unwrap();
+            prop_assert!(sig.normalize_s().is_none());
+            prop_assert!(vk.verify_prehash(&digest, &sig).is_ok());
+        }
+
+        #[test]
+        fn secp256k1_der_is_low_s(key in any::<[u8; 32]>(), digest in any::<[u8; 32]>()) {
+            let (sk, vk) = signing_key(&key).ok_or(TestCaseError::reject("invalid key"))?;
+
+            let bytes = sk.sign_prehash_with_format(&digest, SignatureFormat::Der).unwrap();
+            let sig = secp256k1::Signature::from_der(&bytes).unwrap();
+            prop_assert!(sig.normalize_s().is_none());
+            prop_assert!(vk.verify_prehash(&digest, &sig).is_ok());
+        }
+
+        #[test]
+        fn secp256k1_recoverable_is_low_s(key in any::<[u8; 32]>(), digest in any::<[u8; 32]>()) {
+            let (sk, vk) = signing_key(&key).ok_or(TestCaseError::reject("invalid key"))?;
+
+            let bytes = sk.sign_prehash_recoverable(&digest).unwrap();
+            prop_assert_eq!(bytes.len(), 65);
+
+            let sig = secp256k1::RecoverableSignature::try_from(bytes.as_ref()).unwrap();
+            let recovered = sig.recover_verifying_key_from_digest_bytes(&digest.into()).unwrap();
+            prop_assert_eq!(recovered, vk);
+
+            let sig = secp256k1::Signature::from(sig);
+            prop_assert!(sig.normalize_s().is_none());
+            prop_assert!(vk.verify_prehash(&digest, &sig).is_ok());
+        }
+    }
+}
@@ -1,10 +1,71 @@
-use crypto::signature::ecdsa;
+#[cfg(feature = "bls12_381")]
+use crypto::signature::bls12_381;
+#[cfg(feature = "ed25519")]
+use crypto::signature::ed25519;
+#[cfg(feature = "secp256k1")]
+use crypto::{elliptic_curve::sec1::ToEncodedPoint, signature::ecdsa};
+use std::hash::{Hash, Hasher};
 
 /// Verifying key.
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 pub enum VerifyingKey {
+    /// BLS12-381
+    #[cfg(feature = "bls12_381")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+    Bls12381(bls12_381::VerifyingKey),
+
     /// ECDSA/secp256k1
     #[cfg(feature = "secp256k1")]
     #[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
     EcdsaSecp256k1(ecdsa::secp256k1::VerifyingKey),
+
+    /// Ed25519
+    #[cfg(feature = "ed25519")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+    Ed25519(ed25519::VerifyingKey),
+}
+
+// Implemented manually because ECDSA verifying keys don't impl `Hash`
+impl Hash for VerifyingKey {
+    #[cfg_attr(
+        not(any(feature = "bls12_381", feature = "ed25519", feature = "secp256k1")),
+        allow(unused_variables)
+    )]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            #[cfg(feature = "bls12_381")]
+            Self::Bls12381(vk) => {
+                0u8.hash(state);
+                vk.hash(state);
+            }
+            #[cfg(feature = "secp256k1")]
+            Self::EcdsaSecp256k1(vk) => {
+                1u8.hash(state);
+                vk.to_encoded_point(true).as_bytes().hash(state);
+            }
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(vk) => {
+                2u8.hash(state);
+                vk.hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bls12_381")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bls12_381")))]
+impl From<bls12_381::VerifyingKey> for VerifyingKey {
+    #[inline]
+    fn from(key: bls12_381::VerifyingKey) -> VerifyingKey {
+        VerifyingKey::Bls12381(key)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+impl From<ed25519::VerifyingKey> for VerifyingKey {
+    #[inline]
+    fn from(key: ed25519::VerifyingKey) -> VerifyingKey {
+        VerifyingKey::Ed25519(key)
+    }
 }
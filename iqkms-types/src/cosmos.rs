@@ -0,0 +1,321 @@
+//! Cosmos support.
+
+use crate::{Error, Result};
+use bech32::{Bech32, Hrp, primitives::decode::CheckedHrpstring};
+use crypto::{
+    digest::{Digest, ripemd::Ripemd160, sha2::Sha256},
+    signature::{ecdsa::secp256k1, ed25519},
+};
+use std::str::FromStr;
+
+/// Address serialized as bytes.
+type AddrBytes = [u8; Address::LENGTH];
+
+/// Cosmos addresses: 20-byte digests of public keys.
+///
+/// Addresses are encoded as bech32 with a human-readable prefix ("HRP") which
+/// depends on the chain and the kind of address (see [`Bech32Prefixes`]).
+///
+/// - secp256k1 account keys: `RIPEMD160(SHA256(compressed public key))`
+/// - Ed25519 consensus keys: the first 20 bytes of `SHA256(public key)`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Address(AddrBytes);
+
+impl Address {
+    /// Length of an address in bytes.
+    pub const LENGTH: usize = 20;
+
+    /// Parse a bech32 address with the given human-readable prefix.
+    pub fn from_bech32(s: &str, hrp: &str) -> Result<Address> {
+        let checked = CheckedHrpstring::new::<Bech32>(s).map_err(|_| Error)?;
+
+        if checked.hrp() != Hrp::parse(hrp).map_err(|_| Error)? {
+            return Err(Error);
+        }
+
+        checked
+            .byte_iter()
+            .collect::<Vec<u8>>()
+            .as_slice()
+            .try_into()
+    }
+
+    /// Encode this address as bech32 with the given human-readable prefix.
+    pub fn to_bech32(&self, hrp: &str) -> Result<String> {
+        let hrp = Hrp::parse(hrp).map_err(|_| Error)?;
+        bech32::encode::<Bech32>(hrp, &self.0).map_err(|_| Error)
+    }
+
+    /// Serialize the address as bytes.
+    pub fn to_bytes(&self) -> AddrBytes {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<AddrBytes> for Address {
+    fn from(bytes: AddrBytes) -> Address {
+        Address(bytes)
+    }
+}
+
+impl From<Address> for AddrBytes {
+    fn from(addr: Address) -> AddrBytes {
+        addr.0
+    }
+}
+
+impl TryFrom<&[u8]> for Address {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Address> {
+        AddrBytes::try_from(bytes).map(Address).map_err(|_| Error)
+    }
+}
+
+impl From<&secp256k1::VerifyingKey> for Address {
+    fn from(verifying_key: &secp256k1::VerifyingKey) -> Address {
+        let digest = Ripemd160::digest(Sha256::digest(verifying_key.to_bytes()));
+        Address(digest.into())
+    }
+}
+
+impl From<secp256k1::VerifyingKey> for Address {
+    fn from(verifying_key: secp256k1::VerifyingKey) -> Address {
+        Address::from(&verifying_key)
+    }
+}
+
+impl From<&ed25519::VerifyingKey> for Address {
+    fn from(verifying_key: &ed25519::VerifyingKey) -> Address {
+        let digest = Sha256::digest(verifying_key.to_bytes());
+        let mut bytes = AddrBytes::default();
+        bytes.copy_from_slice(&digest[..Address::LENGTH]);
+        Address(bytes)
+    }
+}
+
+impl From<ed25519::VerifyingKey> for Address {
+    fn from(verifying_key: ed25519::VerifyingKey) -> Address {
+        Address::from(&verifying_key)
+    }
+}
+
+/// Kinds of Cosmos addresses, each of which has its own bech32 prefix.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum AddressKind {
+    /// Account address, e.g. `cosmos1...`.
+    Account,
+
+    /// Validator operator address, e.g. `cosmosvaloper1...`.
+    Validator,
+
+    /// Validator consensus address, e.g. `cosmosvalcons1...`.
+    Consensus,
+}
+
+/// Bech32 human-readable prefixes ("HRPs") used by a chain.
+///
+/// Defaults to the Cosmos Hub's prefixes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Bech32Prefixes {
+    /// Account address prefix.
+    account: String,
+
+    /// Validator operator address prefix.
+    validator: String,
+
+    /// Validator consensus address prefix.
+    consensus: String,
+}
+
+impl Bech32Prefixes {
+    /// Create prefixes from an account prefix, deriving the validator and
+    /// consensus prefixes the way the Cosmos SDK does by default (e.g.
+    /// `osmo`, `osmovaloper`, and `osmovalcons`).
+    pub fn new(account: &str) -> Result<Self> {
+        Self::from_parts(
+            account,
+            &format!("{}valoper", account),
+            &format!("{}valcons", account),
+        )
+    }
+
+    /// Create prefixes from explicitly specified account, validator, and
+    /// consensus prefixes.
+    pub fn from_parts(account: &str, validator: &str, consensus: &str) -> Result<Self> {
+        for hrp in [account, validator, consensus] {
+            // Encode a dummy address to validate the HRP
+            Address::default().to_bech32(hrp)?;
+        }
+
+        Ok(Self {
+            account: account.to_owned(),
+            validator: validator.to_owned(),
+            consensus: consensus.to_owned(),
+        })
+    }
+
+    /// Get the prefix for the given kind of address.
+    pub fn get(&self, kind: AddressKind) -> &str {
+        match kind {
+            AddressKind::Account => &self.account,
+            AddressKind::Validator => &self.validator,
+            AddressKind::Consensus => &self.consensus,
+        }
+    }
+
+    /// Encode an address as bech32 with the prefix for the given kind.
+    pub fn encode(&self, kind: AddressKind, addr: &Address) -> Result<String> {
+        addr.to_bech32(self.get(kind))
+    }
+
+    /// Parse a bech32 address with the prefix for the given kind.
+    pub fn decode(&self, kind: AddressKind, s: &str) -> Result<Address> {
+        Address::from_bech32(s, self.get(kind))
+    }
+
+    /// Parse a bech32 address with any of these prefixes, returning its kind
+    /// along with the address.
+    pub fn parse(&self, s: &str) -> Result<(AddressKind, Address)> {
+        [
+            AddressKind::Account,
+            AddressKind::Validator,
+            AddressKind::Consensus,
+        ]
+        .into_iter()
+        .find_map(|kind| Some((kind, self.decode(kind, s).ok()?)))
+        .ok_or(Error)
+    }
+}
+
+impl Default for Bech32Prefixes {
+    fn default() -> Self {
+        Self {
+            account: "cosmos".to_owned(),
+            validator: "cosmosvaloper".to_owned(),
+            consensus: "cosmosvalcons".to_owned(),
+        }
+    }
+}
+
+impl FromStr for Bech32Prefixes {
+    type Err = Error;
+
+    fn from_str(account: &str) -> Result<Self> {
+        Self::new(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Address, AddressKind, Bech32Prefixes};
+    use crypto::signature::{ecdsa::secp256k1, ed25519};
+    use hex_literal::hex;
+
+    /// secp256k1 generator point, i.e. the public key for the secret scalar 1.
+    const SECP256K1_PUBLIC_KEY: [u8; 33] =
+        hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+
+    /// Ed25519 public key from RFC 8032 test 1.
+    const ED25519_PUBLIC_KEY: [u8; 32] =
+        hex!("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a");
+
+    #[test]
+    fn secp256k1_address() {
+        let verifying_key =
+            secp256k1::VerifyingKey::from_sec1_bytes(&SECP256K1_PUBLIC_KEY).unwrap();
+        let addr = Address::from(&verifying_key);
+
+        // Same digest as BIP-173's P2WPKH test vector for this key
+        assert_eq!(
+            addr.to_bytes(),
+            hex!("751e76e8199196d454941c45d1b3a323f1433bd6")
+        );
+
+        let prefixes = Bech32Prefixes::default();
+        assert_eq!(
+            prefixes.encode(AddressKind::Account, &addr).unwrap(),
+            "cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c"
+        );
+        assert_eq!(
+            prefixes.encode(AddressKind::Validator, &addr).unwrap(),
+            "cosmosvaloper1w508d6qejxtdg4y5r3zarvary0c5xw7klfr0rt"
+        );
+        assert_eq!(
+            addr.to_bech32("osmo").unwrap(),
+            "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2"
+        );
+    }
+
+    #[test]
+    fn ed25519_address() {
+        let verifying_key = ed25519::VerifyingKey::from_bytes(&ED25519_PUBLIC_KEY).unwrap();
+        let addr = Address::from(&verifying_key);
+
+        assert_eq!(
+            Bech32Prefixes::default()
+                .encode(AddressKind::Consensus, &addr)
+                .unwrap(),
+            "cosmosvalcons1y8lrrhap2j3xzcntlp2qgm7jyudhhm2tfeslut"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let prefixes = Bech32Prefixes::default();
+        let addr = Address::from(hex!("751e76e8199196d454941c45d1b3a323f1433bd6"));
+
+        assert_eq!(
+            prefixes
+                .parse("cosmosvaloper1w508d6qejxtdg4y5r3zarvary0c5xw7klfr0rt")
+                .unwrap(),
+            (AddressKind::Validator, addr)
+        );
+        assert_eq!(
+            prefixes
+                .decode(
+                    AddressKind::Account,
+                    "cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60c"
+                )
+                .unwrap(),
+            addr
+        );
+
+        // Wrong prefix, bad checksum, and wrong length
+        assert!(
+            prefixes
+                .decode(
+                    AddressKind::Account,
+                    "osmo1w508d6qejxtdg4y5r3zarvary0c5xw7kjxy2e2"
+                )
+                .is_err()
+        );
+        assert!(
+            prefixes
+                .parse("cosmos1w508d6qejxtdg4y5r3zarvary0c5xw7k6ah60d")
+                .is_err()
+        );
+        assert!(
+            prefixes
+                .parse(&addr.to_bech32("cosmos").unwrap()[..40])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn custom_prefixes() {
+        let prefixes = "osmo".parse::<Bech32Prefixes>().unwrap();
+        assert_eq!(prefixes.get(AddressKind::Account), "osmo");
+        assert_eq!(prefixes.get(AddressKind::Validator), "osmovaloper");
+        assert_eq!(prefixes.get(AddressKind::Consensus), "osmovalcons");
+
+        assert!(Bech32Prefixes::new("").is_err());
+        assert!(Bech32Prefixes::from_parts("terra", "terravaloper", "Terra valcons").is_err());
+    }
+}
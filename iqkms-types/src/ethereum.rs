@@ -1,5 +1,15 @@
 //! Ethereum support.
 
+pub mod abi;
+pub mod consensus;
+pub mod eip191;
+pub mod eip4361;
+pub mod eip712;
+pub mod eip7702;
+pub mod rlp;
+pub mod safe;
+pub mod transaction;
+
 // Re-export select types from the `ethereum-types` crate.
 pub use ethereum_types::{
     BigEndianHash, FromDecStrErr, FromStrRadixErr, FromStrRadixErrKind, H32, H64, H128, H160, H256,
@@ -83,51 +93,84 @@ impl From<Address> for H160 {
     }
 }
 
-impl FromStr for Address {
-    type Err = Error;
+impl Address {
+    /// Parse an address, validating its mixed-case checksum against the given
+    /// EIP-1191 chain ID (or EIP-55 if `None`).
+    ///
+    /// Addresses which are entirely lower or upper case carry no checksum and
+    /// are accepted as-is. The returned address retains the given `chain_id`.
+    pub fn parse_checksummed(s: &str, chain_id: Option<ChainId>) -> Result<Address> {
+        let mut addr = Self::parse_lenient(s)?;
+        addr.chain_id = chain_id;
+
+        let hex = &s[Self::PREFIX.len()..];
+        let is_lower = !hex.bytes().any(|c| c.is_ascii_uppercase());
+        let is_upper = !hex.bytes().any(|c| c.is_ascii_lowercase());
+
+        if is_lower || is_upper || addr.checksum_hex().as_bytes() == hex.as_bytes() {
+            Ok(addr)
+        } else {
+            Err(Error)
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Address> {
+    /// Parse an address without validating its checksum.
+    ///
+    /// Prefer the [`FromStr`] impl or [`Address::parse_checksummed`] unless
+    /// the input is known to use a nonstandard checksum.
+    pub fn parse_lenient(s: &str) -> Result<Address> {
         let s = s.strip_prefix(Self::PREFIX).ok_or(Error)?;
         let mut bytes = AddrBytes::default();
 
-        // TODO(tarcieri): validate EIP-55 checksum
         if hex::mixed::decode(s, &mut bytes).map_err(|_| Error)?.len() == Self::LENGTH {
-            Ok(Self {
-                hash: bytes.into(),
-                chain_id: None,
-            })
+            Ok(bytes.into())
         } else {
             Err(Error)
         }
     }
-}
 
-/// Encode address as EIP-55 mixed-case checksum encoding.
-///
-/// <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-55.md>
-#[allow(clippy::to_string_trait_impl)] // TODO(tarcieri): implement in a `Display`-friendly way?
-impl ToString for Address {
-    fn to_string(&self) -> String {
+    /// Compute the unprefixed mixed-case checksum encoding of this address.
+    fn checksum_hex(&self) -> String {
         let addr_hex = hex::lower::encode_string(self.as_ref());
 
-        #[allow(clippy::redundant_clone)]
-        let prefixed_addr = match self.chain_id {
-            Some(chain_id) => format!("{}0x{}", chain_id, addr_hex),
-            None => addr_hex.clone(),
+        let hash = match self.chain_id {
+            Some(chain_id) => Keccak256::new()
+                .chain(chain_id.to_string())
+                .chain(Self::PREFIX)
+                .chain(&addr_hex)
+                .finalize(),
+            None => Keccak256::digest(addr_hex.as_bytes()),
         };
 
-        let hash_hex = hex::lower::encode_string(&Keccak256::digest(prefixed_addr.as_bytes()));
-        let mut ret = Self::PREFIX.to_owned();
+        let hash_hex = hex::lower::encode_string(&hash);
 
-        for (mut c, &h) in addr_hex.as_bytes().iter().copied().zip(hash_hex.as_bytes()) {
-            if h >= 56 {
-                c = c.to_ascii_uppercase();
-            }
+        addr_hex
+            .chars()
+            .zip(hash_hex.bytes())
+            .map(|(c, h)| if h >= b'8' { c.to_ascii_uppercase() } else { c })
+            .collect()
+    }
+}
 
-            ret.push(c.into());
-        }
+/// Parse an address, validating its EIP-55 mixed-case checksum.
+///
+/// Use [`Address::parse_checksummed`] for EIP-1191 chain-specific checksums,
+/// or [`Address::parse_lenient`] to skip checksum validation.
+impl FromStr for Address {
+    type Err = Error;
 
-        ret
+    fn from_str(s: &str) -> Result<Address> {
+        Self::parse_checksummed(s, None)
+    }
+}
+
+/// Encode address as EIP-55 mixed-case checksum encoding, or EIP-1191 if
+/// `chain_id` is set.
+///
+/// <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-55.md>
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Self::PREFIX, self.checksum_hex())
     }
 }
 
@@ -141,7 +184,7 @@ impl TryFrom<&EncodedPoint> for Address {
 
                 // Take the last 20 bytes of the digest as the address
                 #[allow(clippy::arithmetic_side_effects)]
-                digest[(Address::LENGTH - 20)..].try_into()
+                digest[(digest.len() - Address::LENGTH)..].try_into()
             }
             _ => Err(Error),
         }
@@ -185,7 +228,7 @@ impl TryFrom<VerifyingKey> for Address {
 impl fmt::LowerHex for Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for byte in self.to_bytes() {
-            write!(f, "{:x}", byte)?;
+            write!(f, "{:02x}", byte)?;
         }
 
         Ok(())
@@ -216,4 +259,44 @@ mod tests {
             assert_eq!(addr.to_string(), checksummed_addr);
         }
     }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        // Last character of a valid checksummed address with its case flipped
+        let addr = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD";
+        assert!(addr.parse::<Address>().is_err());
+        assert!(Address::parse_lenient(addr).is_ok());
+    }
+
+    #[test]
+    fn accepts_single_case() {
+        let lower = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+        let upper = "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED";
+        assert_eq!(
+            lower.parse::<Address>().unwrap(),
+            upper.parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn eip1191_checksum_encoding() {
+        // https://eips.ethereum.org/EIPS/eip-1191#test-cases
+        let addr_list = [
+            (30, "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"),
+            (30, "0xFb6916095cA1Df60bb79ce92cE3EA74c37c5d359"),
+            (30, "0xDBF03B407c01E7CD3cBea99509D93F8Dddc8C6FB"),
+            (30, "0xD1220A0Cf47c7B9BE7a2e6ba89F429762E7B9adB"),
+            (31, "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd"),
+            (31, "0xFb6916095CA1dF60bb79CE92ce3Ea74C37c5D359"),
+            (31, "0xdbF03B407C01E7cd3cbEa99509D93f8dDDc8C6fB"),
+            (31, "0xd1220a0CF47c7B9Be7A2E6Ba89f429762E7b9adB"),
+        ];
+
+        for (chain_id, checksummed_addr) in addr_list {
+            let addr = Address::parse_checksummed(checksummed_addr, Some(chain_id)).unwrap();
+            assert_eq!(addr.chain_id, Some(chain_id));
+            assert_eq!(addr.to_string(), checksummed_addr);
+            assert!(checksummed_addr.parse::<Address>().is_err());
+        }
+    }
 }
@@ -0,0 +1,709 @@
+//! Contract ABI: decoding of function calldata.
+//!
+//! Parses JSON ABIs as emitted by the Solidity compiler and decodes calldata
+//! for calls to the functions they describe.
+//!
+//! <https://docs.soliditylang.org/en/latest/abi-spec.html>
+
+use super::{Address, U256, eip712::keccak256};
+use crate::{Error, Result};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt};
+
+/// Size of a function selector in bytes.
+pub const SELECTOR_SIZE: usize = 4;
+
+/// Size of an ABI word in bytes.
+const WORD_SIZE: usize = 32;
+
+/// Function selector: the first 4 bytes of the Keccak256 hash of a function's
+/// signature.
+pub type Selector = [u8; SELECTOR_SIZE];
+
+/// ABI word.
+type Word = [u8; WORD_SIZE];
+
+/// Contract ABI.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Abi {
+    /// Functions by selector.
+    functions: BTreeMap<Selector, Function>,
+}
+
+impl Abi {
+    /// Parse a JSON ABI.
+    ///
+    /// Entries other than functions (e.g. events, errors, constructors) are
+    /// ignored.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let entries = serde_json::from_str::<Vec<Entry>>(json).map_err(|_| Error)?;
+        let mut functions = BTreeMap::new();
+
+        for entry in entries.into_iter().filter(|entry| entry.kind == "function") {
+            let function = Function {
+                name: entry.name,
+                inputs: entry.inputs,
+            };
+
+            // Ensure all of the input types are supported
+            for param in &function.inputs {
+                param.kind()?;
+            }
+
+            functions.insert(function.selector(), function);
+        }
+
+        Ok(Self { functions })
+    }
+
+    /// Get the function with the given selector.
+    pub fn function(&self, selector: &Selector) -> Option<&Function> {
+        self.functions.get(selector)
+    }
+
+    /// Iterate over the functions in this ABI.
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.values()
+    }
+
+    /// Decode calldata for a call to one of the functions in this ABI.
+    pub fn decode_call(&self, calldata: &[u8]) -> Result<Call> {
+        let (selector, args) = calldata.split_first_chunk::<SELECTOR_SIZE>().ok_or(Error)?;
+
+        self.function(selector).ok_or(Error)?.decode_call(args)
+    }
+}
+
+/// Entry in a JSON ABI.
+#[derive(Deserialize)]
+struct Entry {
+    /// Type of entry (`function` if omitted).
+    #[serde(rename = "type", default = "function_entry_type")]
+    kind: String,
+
+    /// Name of the function, event, or error.
+    #[serde(default)]
+    name: String,
+
+    /// Input parameters.
+    #[serde(default)]
+    inputs: Vec<Param>,
+}
+
+/// Older ABIs omit the type of function entries.
+fn function_entry_type() -> String {
+    "function".to_owned()
+}
+
+/// Contract function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Function {
+    /// Name of the function.
+    pub name: String,
+
+    /// Input parameters.
+    pub inputs: Vec<Param>,
+}
+
+impl Function {
+    /// Get the function's signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, canonical_types(&self.inputs))
+    }
+
+    /// Compute the function's selector.
+    pub fn selector(&self) -> Selector {
+        let hash = keccak256(self.signature().as_bytes());
+        let mut selector = Selector::default();
+        selector.copy_from_slice(&hash.as_bytes()[..SELECTOR_SIZE]);
+        selector
+    }
+
+    /// Decode the ABI-encoded arguments of a call to this function, i.e.
+    /// calldata following the selector.
+    pub fn decode_call(&self, args: &[u8]) -> Result<Call> {
+        let kinds = self
+            .inputs
+            .iter()
+            .map(Param::kind)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Call {
+            function: self.clone(),
+            args: decode_tuple(&kinds, args)?,
+        })
+    }
+}
+
+/// Function parameter.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Param {
+    /// Name of the parameter (may be empty).
+    #[serde(default)]
+    pub name: String,
+
+    /// Type of the parameter.
+    #[serde(rename = "type")]
+    pub r#type: String,
+
+    /// Components of `tuple` types.
+    #[serde(default)]
+    pub components: Vec<Param>,
+}
+
+impl Param {
+    /// Get the canonical type of this parameter as used in function
+    /// signatures, i.e. with tuples expanded into their component types.
+    pub fn canonical_type(&self) -> String {
+        match self.r#type.strip_prefix("tuple") {
+            Some(suffix) => format!("({}){}", canonical_types(&self.components), suffix),
+            None => match self.r#type.strip_prefix("uint") {
+                Some(suffix) if suffix.is_empty() || suffix.starts_with('[') => {
+                    format!("uint256{suffix}")
+                }
+                _ => match self.r#type.strip_prefix("int") {
+                    Some(suffix) if suffix.is_empty() || suffix.starts_with('[') => {
+                        format!("int256{suffix}")
+                    }
+                    _ => self.r#type.clone(),
+                },
+            },
+        }
+    }
+
+    /// Parse the type of this parameter.
+    fn kind(&self) -> Result<Kind> {
+        Kind::parse(&self.r#type, &self.components)
+    }
+}
+
+/// Join the canonical types of the given parameters with commas.
+fn canonical_types(params: &[Param]) -> String {
+    params
+        .iter()
+        .map(Param::canonical_type)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decoded call to a contract function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+    /// Function being called.
+    pub function: Function,
+
+    /// Arguments to the function, in the order of its inputs.
+    pub args: Vec<Token>,
+}
+
+impl Call {
+    /// Get an argument by parameter name.
+    pub fn arg(&self, name: &str) -> Option<&Token> {
+        self.function
+            .inputs
+            .iter()
+            .zip(&self.args)
+            .find(|(param, _)| param.name == name)
+            .map(|(_, arg)| arg)
+    }
+}
+
+/// Renders the call as e.g. `transfer(to: 0x..., amount: 1000)`.
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.function.name)?;
+
+        for (i, (param, arg)) in self.function.inputs.iter().zip(&self.args).enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            if !param.name.is_empty() {
+                write!(f, "{}: ", param.name)?;
+            }
+
+            write!(f, "{arg}")?;
+        }
+
+        f.write_str(")")
+    }
+}
+
+/// Decoded ABI value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Token {
+    /// `address`
+    Address(Address),
+
+    /// `bool`
+    Bool(bool),
+
+    /// `uint<M>`
+    Uint(U256),
+
+    /// `int<M>` in two's complement representation.
+    Int(U256),
+
+    /// `bytes<M>`
+    FixedBytes(Vec<u8>),
+
+    /// `bytes`
+    Bytes(Vec<u8>),
+
+    /// `string`
+    String(String),
+
+    /// `T[]` and `T[k]`
+    Array(Vec<Token>),
+
+    /// Tuples, i.e. structs.
+    Tuple(Vec<Token>),
+}
+
+impl Token {
+    /// Get the value of an `address` token.
+    pub fn as_address(&self) -> Option<&Address> {
+        match self {
+            Token::Address(address) => Some(address),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a `uint<M>` token.
+    pub fn as_uint(&self) -> Option<U256> {
+        match self {
+            Token::Uint(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Address(address) => write!(f, "{address}"),
+            Token::Bool(b) => write!(f, "{b}"),
+            Token::Uint(n) => write!(f, "{n}"),
+            Token::Int(n) if n.bit(255) => write!(f, "-{}", (!*n).overflowing_add(U256::one()).0),
+            Token::Int(n) => write!(f, "{n}"),
+            Token::FixedBytes(bytes) | Token::Bytes(bytes) => {
+                write!(f, "0x{}", hex::lower::encode_string(bytes))
+            }
+            Token::String(s) => write!(f, "{s:?}"),
+            Token::Array(tokens) => write_list(f, "[", tokens, "]"),
+            Token::Tuple(tokens) => write_list(f, "(", tokens, ")"),
+        }
+    }
+}
+
+/// Write a comma-separated list of tokens between the given delimiters.
+fn write_list(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    tokens: &[Token],
+    close: &str,
+) -> fmt::Result {
+    f.write_str(open)?;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+
+        write!(f, "{token}")?;
+    }
+
+    f.write_str(close)
+}
+
+/// Parsed ABI type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Kind {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<Kind>),
+    FixedArray(Box<Kind>, usize),
+    Tuple(Vec<Kind>),
+}
+
+impl Kind {
+    /// Parse a type, using the given components for `tuple` types.
+    fn parse(ty: &str, components: &[Param]) -> Result<Self> {
+        if let Some(prefix) = ty.strip_suffix(']') {
+            let (elem, len) = prefix.rsplit_once('[').ok_or(Error)?;
+            let elem = Box::new(Self::parse(elem, components)?);
+
+            return if len.is_empty() {
+                Ok(Kind::Array(elem))
+            } else {
+                Ok(Kind::FixedArray(elem, len.parse().map_err(|_| Error)?))
+            };
+        }
+
+        match ty {
+            "address" => Ok(Kind::Address),
+            "bool" => Ok(Kind::Bool),
+            "bytes" => Ok(Kind::Bytes),
+            "string" => Ok(Kind::String),
+            "function" => Ok(Kind::FixedBytes(24)),
+            "tuple" => components
+                .iter()
+                .map(Param::kind)
+                .collect::<Result<_>>()
+                .map(Kind::Tuple),
+            _ => {
+                if let Some(bits) = ty.strip_prefix("uint") {
+                    int_size(bits).map(Kind::Uint)
+                } else if let Some(bits) = ty.strip_prefix("int") {
+                    int_size(bits).map(Kind::Int)
+                } else if let Some(len) = ty.strip_prefix("bytes") {
+                    match len.parse() {
+                        Ok(len) if (1..=WORD_SIZE).contains(&len) => Ok(Kind::FixedBytes(len)),
+                        _ => Err(Error),
+                    }
+                } else {
+                    Err(Error)
+                }
+            }
+        }
+    }
+
+    /// Is this a dynamically sized type, i.e. one encoded in the tail?
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Kind::Bytes | Kind::String | Kind::Array(_) => true,
+            Kind::FixedArray(elem, _) => elem.is_dynamic(),
+            Kind::Tuple(kinds) => kinds.iter().any(Kind::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Size of this type's encoding in the head of an enclosing tuple.
+    fn head_size(&self) -> Result<usize> {
+        if self.is_dynamic() {
+            return Ok(WORD_SIZE);
+        }
+
+        match self {
+            Kind::FixedArray(elem, len) => elem.head_size()?.checked_mul(*len).ok_or(Error),
+            Kind::Tuple(kinds) => kinds.iter().try_fold(0usize, |sum, kind| {
+                sum.checked_add(kind.head_size()?).ok_or(Error)
+            }),
+            _ => Ok(WORD_SIZE),
+        }
+    }
+
+    /// Decode a value of this type from the start of the given data.
+    fn decode(&self, data: &[u8]) -> Result<Token> {
+        match self {
+            Kind::Address => {
+                let (padding, address) = read_word(data)?.split_at(WORD_SIZE - Address::LENGTH);
+                check_zero(padding)?;
+                Address::try_from(address).map(Token::Address)
+            }
+            Kind::Bool => {
+                let n = read_uint(data)?;
+
+                if n.is_zero() {
+                    Ok(Token::Bool(false))
+                } else if n == U256::one() {
+                    Ok(Token::Bool(true))
+                } else {
+                    Err(Error)
+                }
+            }
+            Kind::Uint(bits) => {
+                let n = read_uint(data)?;
+
+                if n.bits() <= *bits {
+                    Ok(Token::Uint(n))
+                } else {
+                    Err(Error)
+                }
+            }
+            Kind::Int(bits) => {
+                let word = read_word(data)?;
+                let (extension, value) =
+                    word.split_at(WORD_SIZE.checked_sub(bits / 8).ok_or(Error)?);
+                let sign = if value.first().ok_or(Error)? & 0x80 == 0 {
+                    0
+                } else {
+                    0xff
+                };
+
+                if extension.iter().all(|&b| b == sign) {
+                    Ok(Token::Int(U256::from_big_endian(word)))
+                } else {
+                    Err(Error)
+                }
+            }
+            Kind::FixedBytes(len) => {
+                let (bytes, padding) = read_word(data)?.split_at(*len);
+                check_zero(padding)?;
+                Ok(Token::FixedBytes(bytes.to_vec()))
+            }
+            Kind::Bytes => read_bytes(data).map(|bytes| Token::Bytes(bytes.to_vec())),
+            Kind::String => String::from_utf8(read_bytes(data)?.to_vec())
+                .map(Token::String)
+                .map_err(|_| Error),
+            Kind::Array(elem) => {
+                let len = read_usize(data)?;
+                decode_array(elem, len, data.get(WORD_SIZE..).ok_or(Error)?)
+            }
+            Kind::FixedArray(elem, len) => decode_array(elem, *len, data),
+            Kind::Tuple(kinds) => decode_tuple(kinds, data).map(Token::Tuple),
+        }
+    }
+}
+
+/// Parse the size of an integer type in bits (256 if omitted).
+fn int_size(bits: &str) -> Result<usize> {
+    if bits.is_empty() {
+        return Ok(256);
+    }
+
+    match bits.parse() {
+        Ok(n) if n > 0 && n <= 256 && n % 8 == 0 => Ok(n),
+        _ => Err(Error),
+    }
+}
+
+/// Decode a tuple whose encoding starts at the beginning of `data`.
+///
+/// Offsets of dynamically sized values are relative to the start of the
+/// tuple.
+fn decode_tuple(kinds: &[Kind], data: &[u8]) -> Result<Vec<Token>> {
+    let mut tokens = Vec::with_capacity(kinds.len());
+    let mut offset = 0usize;
+
+    for kind in kinds {
+        let head = data.get(offset..).ok_or(Error)?;
+
+        let token = if kind.is_dynamic() {
+            kind.decode(data.get(read_usize(head)?..).ok_or(Error)?)?
+        } else {
+            kind.decode(head)?
+        };
+
+        tokens.push(token);
+        offset = offset.checked_add(kind.head_size()?).ok_or(Error)?;
+    }
+
+    Ok(tokens)
+}
+
+/// Decode an array of `len` elements of the given type.
+fn decode_array(elem: &Kind, len: usize, data: &[u8]) -> Result<Token> {
+    // Every element occupies at least one word, so bound the length by the
+    // size of the data before allocating
+    if len > data.len() / WORD_SIZE {
+        return Err(Error);
+    }
+
+    decode_tuple(&vec![elem.clone(); len], data).map(Token::Array)
+}
+
+/// Read the word at the start of the given data.
+fn read_word(data: &[u8]) -> Result<&Word> {
+    data.first_chunk::<WORD_SIZE>().ok_or(Error)
+}
+
+/// Read the word at the start of the given data as an unsigned integer.
+fn read_uint(data: &[u8]) -> Result<U256> {
+    read_word(data).map(|word| U256::from_big_endian(word))
+}
+
+/// Read the word at the start of the given data as a length or offset.
+fn read_usize(data: &[u8]) -> Result<usize> {
+    let n = read_uint(data)?;
+
+    if n.bits() <= 64 {
+        usize::try_from(n.low_u64()).map_err(|_| Error)
+    } else {
+        Err(Error)
+    }
+}
+
+/// Read a length-prefixed byte string.
+fn read_bytes(data: &[u8]) -> Result<&[u8]> {
+    let len = read_usize(data)?;
+    data.get(WORD_SIZE..)
+        .and_then(|bytes| bytes.get(..len))
+        .ok_or(Error)
+}
+
+/// Ensure padding bytes are zero.
+fn check_zero(padding: &[u8]) -> Result<()> {
+    if padding.iter().all(|&b| b == 0) {
+        Ok(())
+    } else {
+        Err(Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Abi, Token};
+    use crate::ethereum::{Address, U256};
+    use hex_literal::hex;
+
+    const ERC20_ABI: &str = r#"[
+        {
+            "type": "function",
+            "name": "transfer",
+            "inputs": [
+                { "name": "to", "type": "address" },
+                { "name": "amount", "type": "uint256" }
+            ],
+            "outputs": [{ "name": "", "type": "bool" }],
+            "stateMutability": "nonpayable"
+        },
+        {
+            "type": "event",
+            "name": "Transfer",
+            "inputs": [
+                { "name": "from", "type": "address", "indexed": true },
+                { "name": "to", "type": "address", "indexed": true },
+                { "name": "value", "type": "uint256", "indexed": false }
+            ],
+            "anonymous": false
+        }
+    ]"#;
+
+    #[test]
+    fn erc20_transfer() {
+        let abi = Abi::from_json(ERC20_ABI).unwrap();
+        assert_eq!(abi.functions().count(), 1);
+
+        let function = abi.function(&hex!("a9059cbb")).unwrap();
+        assert_eq!(function.signature(), "transfer(address,uint256)");
+
+        let calldata = hex!(
+            "a9059cbb"
+            "0000000000000000000000005aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            "00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+
+        let call = abi.decode_call(&calldata).unwrap();
+        assert_eq!(
+            call.arg("to").and_then(Token::as_address),
+            Some(
+                &"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+                    .parse::<Address>()
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            call.arg("amount").and_then(Token::as_uint),
+            Some(U256::from(1000))
+        );
+        assert_eq!(
+            call.to_string(),
+            "transfer(to: 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed, amount: 1000)"
+        );
+
+        // Unknown selector
+        assert!(abi.decode_call(&hex!("095ea7b3")).is_err());
+
+        // Truncated arguments
+        assert!(abi.decode_call(&calldata[..40]).is_err());
+
+        // Dirty address padding
+        let mut dirty = calldata;
+        dirty[4] = 1;
+        assert!(abi.decode_call(&dirty).is_err());
+    }
+
+    /// Example from the Solidity ABI specification.
+    #[test]
+    fn dynamic_types() {
+        let abi = Abi::from_json(
+            r#"[{
+                "type": "function",
+                "name": "f",
+                "inputs": [
+                    { "name": "a", "type": "uint" },
+                    { "name": "b", "type": "uint32[]" },
+                    { "name": "c", "type": "bytes10" },
+                    { "name": "d", "type": "bytes" }
+                ]
+            }]"#,
+        )
+        .unwrap();
+
+        let calldata = hex!(
+            "8be65246"
+            "0000000000000000000000000000000000000000000000000000000000000123"
+            "0000000000000000000000000000000000000000000000000000000000000080"
+            "3132333435363738393000000000000000000000000000000000000000000000"
+            "00000000000000000000000000000000000000000000000000000000000000e0"
+            "0000000000000000000000000000000000000000000000000000000000000002"
+            "0000000000000000000000000000000000000000000000000000000000000456"
+            "0000000000000000000000000000000000000000000000000000000000000789"
+            "000000000000000000000000000000000000000000000000000000000000000d"
+            "48656c6c6f2c20776f726c642100000000000000000000000000000000000000"
+        );
+
+        let call = abi.decode_call(&calldata).unwrap();
+        assert_eq!(
+            call.function.signature(),
+            "f(uint256,uint32[],bytes10,bytes)"
+        );
+        assert_eq!(
+            call.args,
+            [
+                Token::Uint(U256::from(0x123)),
+                Token::Array(vec![
+                    Token::Uint(U256::from(0x456)),
+                    Token::Uint(U256::from(0x789))
+                ]),
+                Token::FixedBytes(b"1234567890".to_vec()),
+                Token::Bytes(b"Hello, world!".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tuples_and_signed_integers() {
+        let abi = Abi::from_json(
+            r#"[{
+                "name": "g",
+                "inputs": [
+                    {
+                        "name": "order",
+                        "type": "tuple",
+                        "components": [
+                            { "name": "delta", "type": "int8" },
+                            { "name": "memo", "type": "string" }
+                        ]
+                    }
+                ]
+            }]"#,
+        )
+        .unwrap();
+
+        let function = abi.functions().next().unwrap();
+        assert_eq!(function.signature(), "g((int8,string))");
+
+        let mut calldata = function.selector().to_vec();
+        calldata.extend_from_slice(&hex!(
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe"
+            "0000000000000000000000000000000000000000000000000000000000000040"
+            "0000000000000000000000000000000000000000000000000000000000000002"
+            "6869000000000000000000000000000000000000000000000000000000000000"
+        ));
+
+        let call = abi.decode_call(&calldata).unwrap();
+        assert_eq!(call.to_string(), r#"g(order: (-2, "hi"))"#);
+
+        // int8 value which isn't sign extended
+        calldata[36] = 0;
+        assert!(abi.decode_call(&calldata).is_err());
+    }
+}
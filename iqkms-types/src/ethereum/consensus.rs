@@ -0,0 +1,653 @@
+//! Ethereum consensus layer (a.k.a. "beacon chain") support.
+//!
+//! Messages signed by validators and the computation of their signing roots,
+//! including the domain separation which binds signatures to a particular
+//! fork of a particular chain.
+//!
+//! <https://github.com/ethereum/consensus-specs>
+
+pub mod ssz;
+
+use self::ssz::{Chunk, TreeHash};
+use super::H256;
+use crate::{Error, Result};
+use hex_literal::hex;
+
+/// Slot number.
+pub type Slot = u64;
+
+/// Epoch number.
+pub type Epoch = u64;
+
+/// Index of a validator in the validator registry.
+pub type ValidatorIndex = u64;
+
+/// Index of a committee within a slot.
+pub type CommitteeIndex = u64;
+
+/// Merkle root of an SSZ object.
+pub type Root = H256;
+
+/// Fork version.
+pub type Version = [u8; 4];
+
+/// Domain type which identifies the kind of message being signed.
+pub type DomainType = [u8; 4];
+
+/// Domain used to compute signing roots.
+pub type Domain = H256;
+
+/// BLS12-381 signature serialized as a compressed G2 point.
+pub type BlsSignature = [u8; 96];
+
+/// Number of slots in an epoch.
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+/// Maximum number of validators in a committee.
+pub const MAX_VALIDATORS_PER_COMMITTEE: usize = 2048;
+
+/// Maximum number of committees per slot.
+pub const MAX_COMMITTEES_PER_SLOT: usize = 64;
+
+/// Domain type of block proposals.
+pub const DOMAIN_BEACON_PROPOSER: DomainType = [0x00, 0x00, 0x00, 0x00];
+
+/// Domain type of attestations.
+pub const DOMAIN_BEACON_ATTESTER: DomainType = [0x01, 0x00, 0x00, 0x00];
+
+/// Domain type of RANDAO reveals.
+pub const DOMAIN_RANDAO: DomainType = [0x02, 0x00, 0x00, 0x00];
+
+/// Domain type of deposits.
+pub const DOMAIN_DEPOSIT: DomainType = [0x03, 0x00, 0x00, 0x00];
+
+/// Domain type of voluntary exits.
+pub const DOMAIN_VOLUNTARY_EXIT: DomainType = [0x04, 0x00, 0x00, 0x00];
+
+/// Domain type of aggregator selection proofs.
+pub const DOMAIN_SELECTION_PROOF: DomainType = [0x05, 0x00, 0x00, 0x00];
+
+/// Domain type of aggregate-and-proof messages.
+pub const DOMAIN_AGGREGATE_AND_PROOF: DomainType = [0x06, 0x00, 0x00, 0x00];
+
+/// Capella fork versions of known networks, keyed by genesis validators root.
+const CAPELLA_FORK_VERSIONS: &[([u8; 32], Version)] = &[
+    // Mainnet
+    (
+        hex!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"),
+        [0x03, 0x00, 0x00, 0x00],
+    ),
+    // Sepolia
+    (
+        hex!("d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"),
+        [0x90, 0x00, 0x00, 0x72],
+    ),
+    // Holesky
+    (
+        hex!("9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"),
+        [0x04, 0x01, 0x70, 0x00],
+    ),
+    // Hoodi
+    (
+        hex!("212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"),
+        [0x40, 0x00, 0x09, 0x10],
+    ),
+];
+
+/// Get the Capella fork version of the known network with the given genesis
+/// validators root.
+pub fn capella_fork_version(genesis_validators_root: Root) -> Option<Version> {
+    CAPELLA_FORK_VERSIONS
+        .iter()
+        .find(|(root, _)| *root == genesis_validators_root.0)
+        .map(|(_, version)| *version)
+}
+
+/// Compute the epoch the given slot is in.
+pub fn compute_epoch_at_slot(slot: Slot) -> Epoch {
+    slot / SLOTS_PER_EPOCH
+}
+
+/// Compute the root of the `ForkData` for the given fork version and
+/// genesis validators root.
+pub fn compute_fork_data_root(current_version: Version, genesis_validators_root: Root) -> Root {
+    ssz::hash_container(&[current_version.tree_hash_root(), genesis_validators_root])
+}
+
+/// Compute the domain for the given domain type, fork version, and genesis
+/// validators root: `domain_type || fork_data_root[..28]`.
+pub fn compute_domain(
+    domain_type: DomainType,
+    fork_version: Version,
+    genesis_validators_root: Root,
+) -> Domain {
+    let fork_data_root = compute_fork_data_root(fork_version, genesis_validators_root);
+
+    let mut domain = Chunk::default();
+    domain[..4].copy_from_slice(&domain_type);
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain.into()
+}
+
+/// Compute the signing root of an object: the root of the `SigningData`
+/// container which combines the object's root with the domain.
+pub fn compute_signing_root(object: &impl TreeHash, domain: Domain) -> Root {
+    ssz::hash_container(&[object.tree_hash_root(), domain])
+}
+
+/// Fork of the beacon chain.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Fork {
+    /// Version of the previous fork.
+    pub previous_version: Version,
+
+    /// Version of the current fork.
+    pub current_version: Version,
+
+    /// Epoch at which the current fork was activated.
+    pub epoch: Epoch,
+}
+
+impl Fork {
+    /// Get the fork version which is active at the given epoch.
+    pub fn version_at(&self, epoch: Epoch) -> Version {
+        if epoch < self.epoch {
+            self.previous_version
+        } else {
+            self.current_version
+        }
+    }
+}
+
+/// Information about the fork and chain a message is being signed for.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ForkInfo {
+    /// Fork of the beacon chain.
+    pub fork: Fork,
+
+    /// Root of the validator registry at genesis, which identifies the chain.
+    pub genesis_validators_root: Root,
+}
+
+impl ForkInfo {
+    /// Compute the domain for the given domain type at the given epoch.
+    pub fn domain(&self, domain_type: DomainType, epoch: Epoch) -> Domain {
+        compute_domain(
+            domain_type,
+            self.fork.version_at(epoch),
+            self.genesis_validators_root,
+        )
+    }
+
+    /// Compute the signing root of the given object.
+    ///
+    /// The fork version is selected according to the given epoch, which
+    /// should be the epoch of the message being signed.
+    pub fn signing_root(
+        &self,
+        object: &impl TreeHash,
+        domain_type: DomainType,
+        epoch: Epoch,
+    ) -> Root {
+        compute_signing_root(object, self.domain(domain_type, epoch))
+    }
+}
+
+/// Header of a beacon block.
+///
+/// The root of a header is identical to the root of the corresponding block,
+/// so block proposals are signed by signing their header.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BeaconBlockHeader {
+    /// Slot of the block.
+    pub slot: Slot,
+
+    /// Index of the validator which proposed the block.
+    pub proposer_index: ValidatorIndex,
+
+    /// Root of the parent block.
+    pub parent_root: Root,
+
+    /// Root of the beacon state after applying the block.
+    pub state_root: Root,
+
+    /// Root of the block body.
+    pub body_root: Root,
+}
+
+impl BeaconBlockHeader {
+    /// Compute the signing root of this block header.
+    pub fn signing_root(&self, fork_info: &ForkInfo) -> Root {
+        fork_info.signing_root(
+            self,
+            DOMAIN_BEACON_PROPOSER,
+            compute_epoch_at_slot(self.slot),
+        )
+    }
+}
+
+impl TreeHash for BeaconBlockHeader {
+    fn tree_hash_root(&self) -> H256 {
+        ssz::hash_container(&[
+            self.slot.tree_hash_root(),
+            self.proposer_index.tree_hash_root(),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ])
+    }
+}
+
+/// Checkpoint: an epoch along with the root of the block at its start.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Checkpoint {
+    /// Epoch of the checkpoint.
+    pub epoch: Epoch,
+
+    /// Root of the checkpoint block.
+    pub root: Root,
+}
+
+impl TreeHash for Checkpoint {
+    fn tree_hash_root(&self) -> H256 {
+        ssz::hash_container(&[self.epoch.tree_hash_root(), self.root])
+    }
+}
+
+/// Data which is attested to by validators.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AttestationData {
+    /// Slot of the attestation.
+    pub slot: Slot,
+
+    /// Index of the committee within the slot.
+    pub index: CommitteeIndex,
+
+    /// LMD GHOST vote.
+    pub beacon_block_root: Root,
+
+    /// FFG source checkpoint.
+    pub source: Checkpoint,
+
+    /// FFG target checkpoint.
+    pub target: Checkpoint,
+}
+
+impl AttestationData {
+    /// Compute the signing root of this attestation data.
+    pub fn signing_root(&self, fork_info: &ForkInfo) -> Root {
+        fork_info.signing_root(self, DOMAIN_BEACON_ATTESTER, self.target.epoch)
+    }
+}
+
+impl TreeHash for AttestationData {
+    fn tree_hash_root(&self) -> H256 {
+        ssz::hash_container(&[
+            self.slot.tree_hash_root(),
+            self.index.tree_hash_root(),
+            self.beacon_block_root,
+            self.source.tree_hash_root(),
+            self.target.tree_hash_root(),
+        ])
+    }
+}
+
+/// SSZ bitlist, stored in its serialized form (i.e. including the trailing
+/// delimiter bit).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bitlist {
+    bytes: Vec<u8>,
+}
+
+impl Bitlist {
+    /// Parse a serialized bitlist.
+    pub fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes.last() {
+            Some(&last) if last != 0 => Ok(Self {
+                bytes: bytes.to_vec(),
+            }),
+            _ => Err(Error),
+        }
+    }
+
+    /// Get the serialized bitlist.
+    pub fn as_ssz_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the number of bits in the list (excluding the delimiter bit).
+    pub fn len(&self) -> usize {
+        let last = self.bytes.last().copied().unwrap_or(1);
+        let delimiter = usize::try_from(last.ilog2()).unwrap_or_default();
+        self.bytes
+            .len()
+            .saturating_sub(1)
+            .saturating_mul(8)
+            .saturating_add(delimiter)
+    }
+
+    /// Is the list empty?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Compute the `hash_tree_root` of this bitlist given the maximum number
+    /// of bits it may contain.
+    pub fn tree_hash_root(&self, limit: usize) -> H256 {
+        let mut bytes = self.bytes.clone();
+
+        // Clear the delimiter bit, dropping the last byte if it's now empty
+        if let Some(last) = bytes.last_mut() {
+            *last ^= 1 << last.ilog2();
+            if *last == 0 {
+                bytes.pop();
+            }
+        }
+
+        let limit = limit.div_ceil(ssz::CHUNK_SIZE.saturating_mul(8));
+        let root = ssz::merkleize(&ssz::pack(&bytes), limit);
+        ssz::mix_in_length(&root, self.len() as u64).into()
+    }
+}
+
+/// Aggregated attestation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    /// Bits identifying which validators in the committee(s) attested.
+    pub aggregation_bits: Bitlist,
+
+    /// Data which was attested to.
+    pub data: AttestationData,
+
+    /// Aggregate signature of the attesting validators.
+    pub signature: BlsSignature,
+
+    /// Bits identifying which committees the attestation covers
+    /// (`Bitvector[MAX_COMMITTEES_PER_SLOT]`).
+    ///
+    /// Only present from the Electra fork onward (EIP-7549), which also
+    /// increases the maximum size of `aggregation_bits`.
+    pub committee_bits: Option<[u8; MAX_COMMITTEES_PER_SLOT / 8]>,
+}
+
+impl Attestation {
+    /// Maximum number of bits in `aggregation_bits`.
+    pub fn aggregation_bits_limit(&self) -> usize {
+        if self.committee_bits.is_some() {
+            MAX_VALIDATORS_PER_COMMITTEE.saturating_mul(MAX_COMMITTEES_PER_SLOT)
+        } else {
+            MAX_VALIDATORS_PER_COMMITTEE
+        }
+    }
+}
+
+impl TreeHash for Attestation {
+    fn tree_hash_root(&self) -> H256 {
+        let mut field_roots = vec![
+            self.aggregation_bits
+                .tree_hash_root(self.aggregation_bits_limit()),
+            self.data.tree_hash_root(),
+            self.signature.tree_hash_root(),
+        ];
+
+        if let Some(committee_bits) = &self.committee_bits {
+            field_roots.push(committee_bits.tree_hash_root());
+        }
+
+        ssz::hash_container(&field_roots)
+    }
+}
+
+/// Aggregate attestation along with the aggregator's selection proof.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AggregateAndProof {
+    /// Index of the aggregating validator.
+    pub aggregator_index: ValidatorIndex,
+
+    /// Aggregate attestation.
+    pub aggregate: Attestation,
+
+    /// Proof the aggregator was selected: signature over the slot.
+    pub selection_proof: BlsSignature,
+}
+
+impl AggregateAndProof {
+    /// Compute the signing root of this aggregate-and-proof.
+    pub fn signing_root(&self, fork_info: &ForkInfo) -> Root {
+        fork_info.signing_root(
+            self,
+            DOMAIN_AGGREGATE_AND_PROOF,
+            compute_epoch_at_slot(self.aggregate.data.slot),
+        )
+    }
+}
+
+impl TreeHash for AggregateAndProof {
+    fn tree_hash_root(&self) -> H256 {
+        ssz::hash_container(&[
+            self.aggregator_index.tree_hash_root(),
+            self.aggregate.tree_hash_root(),
+            self.selection_proof.tree_hash_root(),
+        ])
+    }
+}
+
+/// Voluntary exit of a validator.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VoluntaryExit {
+    /// Earliest epoch the exit may be processed at.
+    pub epoch: Epoch,
+
+    /// Index of the exiting validator.
+    pub validator_index: ValidatorIndex,
+}
+
+impl VoluntaryExit {
+    /// Compute the signing root of this voluntary exit.
+    ///
+    /// Since the Deneb fork (EIP-7044) voluntary exits are always signed using
+    /// the Capella fork version rather than the current one, so it must be
+    /// given explicitly (see [`capella_fork_version`]).
+    pub fn signing_root(
+        &self,
+        capella_fork_version: Version,
+        genesis_validators_root: Root,
+    ) -> Root {
+        let domain = compute_domain(
+            DOMAIN_VOLUNTARY_EXIT,
+            capella_fork_version,
+            genesis_validators_root,
+        );
+        compute_signing_root(self, domain)
+    }
+}
+
+impl TreeHash for VoluntaryExit {
+    fn tree_hash_root(&self) -> H256 {
+        ssz::hash_container(&[
+            self.epoch.tree_hash_root(),
+            self.validator_index.tree_hash_root(),
+        ])
+    }
+}
+
+/// Compute the signing root of a RANDAO reveal for the given epoch.
+pub fn randao_signing_root(epoch: Epoch, fork_info: &ForkInfo) -> Root {
+    fork_info.signing_root(&epoch, DOMAIN_RANDAO, epoch)
+}
+
+/// Compute the signing root of an aggregator selection proof for the given
+/// slot.
+pub fn selection_proof_signing_root(slot: Slot, fork_info: &ForkInfo) -> Root {
+    fork_info.signing_root(&slot, DOMAIN_SELECTION_PROOF, compute_epoch_at_slot(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    /// Mainnet genesis validators root.
+    const GENESIS_VALIDATORS_ROOT: [u8; 32] =
+        hex!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95");
+
+    /// Bellatrix -> Capella fork.
+    const FORK_INFO: ForkInfo = ForkInfo {
+        fork: Fork {
+            previous_version: [0x02, 0, 0, 0],
+            current_version: [0x03, 0, 0, 0],
+            epoch: 194048,
+        },
+        genesis_validators_root: H256(GENESIS_VALIDATORS_ROOT),
+    };
+
+    /// Deneb -> Electra fork.
+    const ELECTRA_FORK_INFO: ForkInfo = ForkInfo {
+        fork: Fork {
+            previous_version: [0x04, 0, 0, 0],
+            current_version: [0x05, 0, 0, 0],
+            epoch: 364032,
+        },
+        genesis_validators_root: H256(GENESIS_VALIDATORS_ROOT),
+    };
+
+    fn attestation_data() -> AttestationData {
+        AttestationData {
+            slot: 11649024,
+            index: 3,
+            beacon_block_root: H256([0x11; 32]),
+            source: Checkpoint {
+                epoch: 364030,
+                root: H256([0x22; 32]),
+            },
+            target: Checkpoint {
+                epoch: 364031,
+                root: H256([0x33; 32]),
+            },
+        }
+    }
+
+    fn aggregate_and_proof(committee_bits: Option<[u8; 8]>) -> AggregateAndProof {
+        let signature = (0..96).collect::<Vec<u8>>().try_into().unwrap();
+        let selection_proof = (96..192).collect::<Vec<u8>>().try_into().unwrap();
+
+        AggregateAndProof {
+            aggregator_index: 9,
+            aggregate: Attestation {
+                aggregation_bits: Bitlist::from_ssz_bytes(&hex!("050c")).unwrap(),
+                data: attestation_data(),
+                signature,
+                committee_bits,
+            },
+            selection_proof,
+        }
+    }
+
+    // Expected values in the following tests were computed using an
+    // independent implementation of the consensus specs
+
+    #[test]
+    fn deposit_domain() {
+        assert_eq!(
+            compute_domain(DOMAIN_DEPOSIT, [0; 4], H256::zero()).as_bytes(),
+            hex!("03000000f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9")
+        );
+    }
+
+    #[test]
+    fn attestation_signing_root() {
+        let data = attestation_data();
+        assert_eq!(
+            data.tree_hash_root().as_bytes(),
+            hex!("16ad40cf30dd237800144ff3d488b376c50fe9b693d4f8f043ea09136b5c7add")
+        );
+
+        // Target epoch precedes the fork, so the previous version is used
+        assert_eq!(
+            data.signing_root(&ELECTRA_FORK_INFO).as_bytes(),
+            hex!("566e9dfc069669711f64fb21ef4183987da4afab459e261a4e1f261ae804e33b")
+        );
+    }
+
+    #[test]
+    fn block_signing_root() {
+        let header = BeaconBlockHeader {
+            slot: 11649024,
+            proposer_index: 42,
+            parent_root: H256([0x44; 32]),
+            state_root: H256([0x55; 32]),
+            body_root: H256([0x66; 32]),
+        };
+
+        assert_eq!(
+            header.signing_root(&ELECTRA_FORK_INFO).as_bytes(),
+            hex!("16d4e30707a1dcb1a82c731874238013c26b17b4c6d277d6b9e7b800779be566")
+        );
+    }
+
+    #[test]
+    fn randao_and_selection_proof_signing_roots() {
+        assert_eq!(
+            randao_signing_root(364032, &ELECTRA_FORK_INFO).as_bytes(),
+            hex!("9ab52f75aa4dfd5236a6c11f01aeb9c9e5ee31985f21f0917117f6baa69c867f")
+        );
+        assert_eq!(
+            randao_signing_root(364031, &ELECTRA_FORK_INFO).as_bytes(),
+            hex!("6250d58db38a39486b8b2fbdab01ae5ba4c70f38d8ef9f290218f1163526e3a7")
+        );
+        assert_eq!(
+            selection_proof_signing_root(11649024, &ELECTRA_FORK_INFO).as_bytes(),
+            hex!("5ed3be9e2759557512080f8cd0b273bcd6e0ead1cb9ee4ab0959f5b8ba953227")
+        );
+    }
+
+    #[test]
+    fn voluntary_exit_signing_root() {
+        let exit = VoluntaryExit {
+            epoch: 194048,
+            validator_index: 7,
+        };
+
+        let capella_fork_version = capella_fork_version(FORK_INFO.genesis_validators_root).unwrap();
+        assert_eq!(capella_fork_version, FORK_INFO.fork.current_version);
+
+        assert_eq!(
+            exit.signing_root(capella_fork_version, FORK_INFO.genesis_validators_root)
+                .as_bytes(),
+            hex!("77afe4d9dc0d33a547d4bcb5085d53d856903de15fb79fdd0dfc1797313d1269")
+        );
+    }
+
+    #[test]
+    fn aggregate_and_proof_signing_root() {
+        assert_eq!(
+            aggregate_and_proof(None)
+                .signing_root(&ELECTRA_FORK_INFO)
+                .as_bytes(),
+            hex!("d6ff899cce2223a17e19906241dad7b072fdcce290aaf2f8c6d6257dbfcedc97")
+        );
+
+        let electra = aggregate_and_proof(Some([0b1000, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(electra.aggregate.aggregation_bits_limit(), 131072);
+        assert_eq!(
+            electra.signing_root(&ELECTRA_FORK_INFO).as_bytes(),
+            hex!("952f9557e133bbb19599979a69c9f128a5ba6f93286125fa4287ab85360a871a")
+        );
+    }
+
+    #[test]
+    fn bitlist() {
+        let bits = Bitlist::from_ssz_bytes(&hex!("050c")).unwrap();
+        assert_eq!(bits.len(), 11);
+
+        let bits = Bitlist::from_ssz_bytes(&hex!("ff01")).unwrap();
+        assert_eq!(bits.len(), 8);
+        assert_eq!(
+            bits.tree_hash_root(MAX_VALIDATORS_PER_COMMITTEE).as_bytes(),
+            hex!("eebfa0d92b6c11efb3105805eae7ab6f4120ca797b958f5c7e7e5c46e2fb23be")
+        );
+
+        assert!(Bitlist::from_ssz_bytes(&[]).is_err());
+        assert!(Bitlist::from_ssz_bytes(&hex!("0500")).is_err());
+    }
+}
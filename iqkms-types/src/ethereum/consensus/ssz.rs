@@ -0,0 +1,115 @@
+//! Simple Serialize (SSZ) merkleization.
+//!
+//! Only the subset of SSZ needed to compute the `hash_tree_root` of the
+//! messages signed by validators is implemented.
+//!
+//! <https://github.com/ethereum/consensus-specs/blob/dev/ssz/simple-serialize.md>
+
+use crate::ethereum::H256;
+use crypto::digest::{Digest, Update, sha2::Sha256};
+
+/// Size of a chunk in bytes.
+pub const CHUNK_SIZE: usize = 32;
+
+/// 32-byte chunk of data which is merkleized.
+pub type Chunk = [u8; CHUNK_SIZE];
+
+/// Compute the `hash_tree_root` of an SSZ object.
+pub trait TreeHash {
+    /// Compute the `hash_tree_root` of this object.
+    fn tree_hash_root(&self) -> H256;
+}
+
+impl TreeHash for u64 {
+    fn tree_hash_root(&self) -> H256 {
+        let mut chunk = Chunk::default();
+        chunk[..8].copy_from_slice(&self.to_le_bytes());
+        chunk.into()
+    }
+}
+
+impl TreeHash for H256 {
+    fn tree_hash_root(&self) -> H256 {
+        *self
+    }
+}
+
+impl<const N: usize> TreeHash for [u8; N] {
+    fn tree_hash_root(&self) -> H256 {
+        merkleize(&pack(self), 0).into()
+    }
+}
+
+/// Compute the `hash_tree_root` of a container from the roots of its fields.
+pub fn hash_container(field_roots: &[H256]) -> H256 {
+    let chunks = field_roots.iter().map(|root| root.0).collect::<Vec<_>>();
+    merkleize(&chunks, 0).into()
+}
+
+/// Pack the given bytes into zero-padded chunks.
+pub fn pack(bytes: &[u8]) -> Vec<Chunk> {
+    bytes
+        .chunks(CHUNK_SIZE)
+        .map(|bytes| {
+            let mut chunk = Chunk::default();
+            chunk[..bytes.len()].copy_from_slice(bytes);
+            chunk
+        })
+        .collect()
+}
+
+/// Merkleize the given chunks, padding them with zero chunks to the next
+/// power of two of `limit` (or of the number of chunks, if greater).
+pub fn merkleize(chunks: &[Chunk], limit: usize) -> Chunk {
+    let width = limit.max(chunks.len()).max(1).next_power_of_two();
+    let mut layer = chunks.to_vec();
+    let mut zero_hash = Chunk::default();
+
+    for _ in 0..width.trailing_zeros() {
+        if layer.len() & 1 == 1 || layer.is_empty() {
+            layer.push(zero_hash);
+        }
+
+        layer = layer
+            .chunks_exact(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+
+        zero_hash = hash_pair(&zero_hash, &zero_hash);
+    }
+
+    layer.first().copied().unwrap_or(zero_hash)
+}
+
+/// Mix the length of a list into its root.
+pub fn mix_in_length(root: &Chunk, length: u64) -> Chunk {
+    hash_pair(root, &length.tree_hash_root().0)
+}
+
+/// Hash two chunks together.
+pub(crate) fn hash_pair(left: &Chunk, right: &Chunk) -> Chunk {
+    Sha256::new().chain(left).chain(right).finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chunk, hash_pair, merkleize};
+
+    #[test]
+    fn merkleize_pads_to_limit() {
+        let zero = Chunk::default();
+        let one = [1u8; 32];
+
+        assert_eq!(merkleize(&[], 0), zero);
+        assert_eq!(merkleize(&[one], 0), one);
+        assert_eq!(merkleize(&[one], 2), hash_pair(&one, &zero));
+        assert_eq!(
+            merkleize(&[one, one, one], 0),
+            hash_pair(&hash_pair(&one, &one), &hash_pair(&one, &zero))
+        );
+        assert_eq!(
+            merkleize(&[], 4),
+            hash_pair(&hash_pair(&zero, &zero), &hash_pair(&zero, &zero))
+        );
+    }
+}
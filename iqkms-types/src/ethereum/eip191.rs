@@ -0,0 +1,41 @@
+//! EIP-191: signed data standard.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-191>
+
+use super::H256;
+use crypto::digest::{Digest, Update, sha3::Keccak256};
+
+/// Prefix applied to "personal" messages (version `0x45`).
+pub const PERSONAL_MESSAGE_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+
+/// Compute the hash of a "personal" message, i.e. the message as prefixed by
+/// `eth_sign` and `personal_sign`:
+///
+/// ```text
+/// keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)
+/// ```
+pub fn hash_message(msg: impl AsRef<[u8]>) -> H256 {
+    let msg = msg.as_ref();
+
+    let digest = Keccak256::new()
+        .chain(PERSONAL_MESSAGE_PREFIX)
+        .chain(msg.len().to_string())
+        .chain(msg)
+        .finalize();
+
+    H256::from_slice(&digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash_message;
+    use hex_literal::hex;
+
+    #[test]
+    fn hello_world() {
+        assert_eq!(
+            hash_message(b"Hello World").as_bytes(),
+            hex!("a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2")
+        );
+    }
+}
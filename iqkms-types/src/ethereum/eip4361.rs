@@ -0,0 +1,376 @@
+//! EIP-4361: Sign-In with Ethereum.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-4361>
+
+use super::{Address, ChainId, H256, eip191};
+use crate::{Error, Result};
+use std::fmt;
+
+/// Version of the message format.
+pub const VERSION: &str = "1";
+
+/// Minimum length of a nonce.
+pub const MIN_NONCE_LEN: usize = 8;
+
+/// Sign-In with Ethereum message.
+///
+/// Rendered in its canonical form by the [`fmt::Display`] impl, and signed
+/// as an EIP-191 "personal" message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Message {
+    /// URI scheme of the origin of the request, if not `https`.
+    pub scheme: Option<String>,
+
+    /// RFC 3986 authority (i.e. host and optional port) requesting the
+    /// signing.
+    pub domain: String,
+
+    /// Address of the account performing the signing.
+    pub address: Address,
+
+    /// Human-readable assertion the user will sign (must not contain `\n`).
+    pub statement: Option<String>,
+
+    /// RFC 3986 URI referring to the resource that is the subject of the
+    /// signing.
+    pub uri: String,
+
+    /// EIP-155 chain ID the session is bound to.
+    pub chain_id: ChainId,
+
+    /// Randomized token used to prevent replay attacks (at least 8
+    /// alphanumeric characters).
+    pub nonce: String,
+
+    /// RFC 3339 timestamp of when the message was generated.
+    pub issued_at: String,
+
+    /// RFC 3339 timestamp after which the message is no longer valid.
+    pub expiration_time: Option<String>,
+
+    /// RFC 3339 timestamp before which the message is not yet valid.
+    pub not_before: Option<String>,
+
+    /// System-specific identifier used to uniquely refer to the sign-in
+    /// request.
+    pub request_id: Option<String>,
+
+    /// RFC 3986 URIs the user wishes to have resolved as part of
+    /// authentication.
+    pub resources: Vec<String>,
+}
+
+impl Message {
+    /// Check that the fields of this message are well-formed, i.e. that
+    /// rendering it produces an unambiguous message.
+    pub fn validate(&self) -> Result<()> {
+        match self.invalid_field() {
+            Some(_) => Err(Error),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the name of the first field of this message which is malformed,
+    /// if any (see [`Message::validate`]).
+    pub fn invalid_field(&self) -> Option<&'static str> {
+        if let Some(scheme) = &self.scheme {
+            let mut chars = scheme.chars();
+
+            if !chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                || !chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            {
+                return Some("scheme");
+            }
+        }
+
+        if !is_token(&self.domain) {
+            return Some("domain");
+        }
+
+        if !is_token(&self.uri) {
+            return Some("uri");
+        }
+
+        if self
+            .statement
+            .as_deref()
+            .is_some_and(|s| s.chars().any(|c| c.is_control()))
+        {
+            return Some("statement");
+        }
+
+        if self.nonce.len() < MIN_NONCE_LEN
+            || !self.nonce.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Some("nonce");
+        }
+
+        if !is_timestamp(&self.issued_at) {
+            return Some("issued_at");
+        }
+
+        if self
+            .expiration_time
+            .as_deref()
+            .is_some_and(|t| !is_timestamp(t))
+        {
+            return Some("expiration_time");
+        }
+
+        if self.not_before.as_deref().is_some_and(|t| !is_timestamp(t)) {
+            return Some("not_before");
+        }
+
+        if self.request_id.as_deref().is_some_and(|id| !is_token(id)) {
+            return Some("request_id");
+        }
+
+        if !self.resources.iter().all(|uri| is_token(uri)) {
+            return Some("resources");
+        }
+
+        None
+    }
+
+    /// Compute the EIP-191 hash of the rendered message which is signed.
+    pub fn signing_hash(&self) -> H256 {
+        eip191::hash_message(self.to_string())
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}://", scheme)?;
+        }
+
+        writeln!(
+            f,
+            "{} wants you to sign in with your Ethereum account:",
+            self.domain
+        )?;
+
+        // Addresses are always rendered with an EIP-55 checksum
+        writeln!(f, "{}", Address::from(self.address.hash))?;
+        writeln!(f)?;
+
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", VERSION)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", self.issued_at)?;
+
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", expiration_time)?;
+        }
+
+        if let Some(not_before) = &self.not_before {
+            write!(f, "\nNot Before: {}", not_before)?;
+        }
+
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Is the given string non-empty and free of whitespace and control
+/// characters?
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && !s.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Is the given string an RFC 3339 `date-time`, e.g.
+/// `2021-09-30T16:25:24.000Z` or `2021-09-30T16:25:24+02:00`?
+fn is_timestamp(s: &str) -> bool {
+    let bytes = s.as_bytes();
+
+    let digits = |range: std::ops::Range<usize>| {
+        bytes
+            .get(range)
+            .is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+    };
+
+    let date_time = digits(0..4)
+        && bytes.get(4) == Some(&b'-')
+        && digits(5..7)
+        && bytes.get(7) == Some(&b'-')
+        && digits(8..10)
+        && matches!(bytes.get(10), Some(b'T' | b't'))
+        && digits(11..13)
+        && bytes.get(13) == Some(&b':')
+        && digits(14..16)
+        && bytes.get(16) == Some(&b':')
+        && digits(17..19);
+
+    if !date_time {
+        return false;
+    }
+
+    let mut offset = &s[19..];
+
+    if let Some(frac) = offset.strip_prefix('.') {
+        let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+
+        if len == 0 {
+            return false;
+        }
+
+        offset = &frac[len..];
+    }
+
+    match offset.as_bytes() {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => [h1, h2, m1, m2].iter().all(|b| b.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Message;
+
+    /// Example message from EIP-4361.
+    const EXAMPLE: &str = "example.com wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+I accept the ExampleOrg Terms of Service: https://example.com/tos
+
+URI: https://example.com/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    fn example() -> Message {
+        Message {
+            scheme: None,
+            domain: "example.com".to_owned(),
+            address: "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+                .parse()
+                .unwrap(),
+            statement: Some(
+                "I accept the ExampleOrg Terms of Service: https://example.com/tos".to_owned(),
+            ),
+            uri: "https://example.com/login".to_owned(),
+            chain_id: 1,
+            nonce: "32891756".to_owned(),
+            issued_at: "2021-09-30T16:25:24Z".to_owned(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![
+                "ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/".to_owned(),
+                "https://example.com/my-web2-claim.json".to_owned(),
+            ],
+        }
+    }
+
+    #[test]
+    fn render_example() {
+        let msg = example();
+        assert!(msg.validate().is_ok());
+        assert_eq!(msg.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn render_optional_fields() {
+        let msg = Message {
+            scheme: Some("https".to_owned()),
+            statement: None,
+            expiration_time: Some("2021-10-01T16:25:24.000+02:00".to_owned()),
+            not_before: Some("2021-09-30T16:25:24Z".to_owned()),
+            request_id: Some("42".to_owned()),
+            resources: vec![],
+            ..example()
+        };
+
+        assert!(msg.validate().is_ok());
+        assert_eq!(
+            msg.to_string(),
+            "https://example.com wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+
+URI: https://example.com/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Expiration Time: 2021-10-01T16:25:24.000+02:00
+Not Before: 2021-09-30T16:25:24Z
+Request ID: 42"
+        );
+    }
+
+    #[test]
+    fn reject_malformed_fields() {
+        let cases = [
+            (
+                "domain",
+                Message {
+                    domain: "example.com\nURI: https://evil.com".to_owned(),
+                    ..example()
+                },
+            ),
+            (
+                "statement",
+                Message {
+                    statement: Some("line one\nline two".to_owned()),
+                    ..example()
+                },
+            ),
+            (
+                "nonce",
+                Message {
+                    nonce: "1234567".to_owned(),
+                    ..example()
+                },
+            ),
+            (
+                "issued_at",
+                Message {
+                    issued_at: "2021-09-30 16:25:24Z".to_owned(),
+                    ..example()
+                },
+            ),
+            (
+                "expiration_time",
+                Message {
+                    expiration_time: Some("tomorrow".to_owned()),
+                    ..example()
+                },
+            ),
+            (
+                "scheme",
+                Message {
+                    scheme: Some("1http".to_owned()),
+                    ..example()
+                },
+            ),
+        ];
+
+        for (field, msg) in cases {
+            assert!(msg.validate().is_err(), "{:?}", msg);
+            assert_eq!(msg.invalid_field(), Some(field));
+        }
+    }
+}
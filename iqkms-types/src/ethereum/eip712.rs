@@ -0,0 +1,344 @@
+//! EIP-712: typed structured data hashing and signing.
+//!
+//! Implements hashing of the JSON representation of typed data used by
+//! `eth_signTypedData_v4`.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-712>
+
+use super::{H256, U256};
+use crate::{Error, Result};
+use crypto::digest::{Digest, sha3::Keccak256};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Name of the domain separator type.
+pub const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// Typed data as accepted by `eth_signTypedData_v4`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    /// Struct type definitions, including `EIP712Domain`.
+    pub types: BTreeMap<String, Vec<Field>>,
+
+    /// Name of the type of `message`.
+    pub primary_type: String,
+
+    /// Domain separator values.
+    pub domain: Value,
+
+    /// Message to be signed.
+    pub message: Value,
+}
+
+impl TypedData {
+    /// Compute the domain separator: `hashStruct(domain)`.
+    pub fn domain_separator(&self) -> Result<H256> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    /// Compute the hash of the message: `hashStruct(message)`.
+    pub fn message_hash(&self) -> Result<H256> {
+        self.hash_struct(&self.primary_type, &self.message)
+    }
+
+    /// Compute the digest to be signed:
+    ///
+    /// ```text
+    /// keccak256("\x19\x01" || domainSeparator || hashStruct(message))
+    /// ```
+    pub fn signing_hash(&self) -> Result<H256> {
+        Ok(signing_hash(
+            &self.domain_separator()?,
+            &self.message_hash()?,
+        ))
+    }
+
+    /// Compute `hashStruct` for a value of the given struct type.
+    pub fn hash_struct(&self, type_name: &str, value: &Value) -> Result<H256> {
+        let mut encoded = self.type_hash(type_name)?.as_bytes().to_vec();
+        let fields = self.types.get(type_name).ok_or(Error)?;
+        let object = value.as_object().ok_or(Error)?;
+
+        for field in fields {
+            let field_value = object.get(&field.name).unwrap_or(&Value::Null);
+            encoded.extend_from_slice(self.encode_value(&field.r#type, field_value)?.as_bytes());
+        }
+
+        Ok(keccak256(&encoded))
+    }
+
+    /// Compute `typeHash`, i.e. the hash of `encodeType`.
+    pub fn type_hash(&self, type_name: &str) -> Result<H256> {
+        Ok(keccak256(self.encode_type(type_name)?.as_bytes()))
+    }
+
+    /// Compute `encodeType`: the struct signature of the given type followed
+    /// by the alphabetically sorted signatures of its dependencies.
+    pub fn encode_type(&self, type_name: &str) -> Result<String> {
+        let mut deps = BTreeSet::new();
+        self.find_dependencies(type_name, &mut deps)?;
+        deps.remove(type_name);
+
+        let mut encoded = String::new();
+
+        for name in std::iter::once(type_name).chain(deps.iter().map(String::as_str)) {
+            let fields = self.types.get(name).ok_or(Error)?;
+            let params = fields
+                .iter()
+                .map(|field| format!("{} {}", field.r#type, field.name))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            encoded.push_str(&format!("{name}({params})"));
+        }
+
+        Ok(encoded)
+    }
+
+    /// Recursively collect the struct types referenced by the given type.
+    fn find_dependencies(&self, type_name: &str, deps: &mut BTreeSet<String>) -> Result<()> {
+        let base_type = strip_array(type_name);
+
+        if deps.contains(base_type) {
+            return Ok(());
+        }
+
+        let Some(fields) = self.types.get(base_type) else {
+            return Ok(());
+        };
+
+        deps.insert(base_type.to_owned());
+
+        for field in fields {
+            self.find_dependencies(&field.r#type, deps)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encode a value of the given type as a 32-byte word (`encodeData`).
+    fn encode_value(&self, type_name: &str, value: &Value) -> Result<H256> {
+        if let Some(elem_type) = array_element_type(type_name) {
+            let mut encoded = Vec::new();
+
+            for elem in value.as_array().ok_or(Error)? {
+                encoded.extend_from_slice(self.encode_value(elem_type, elem)?.as_bytes());
+            }
+
+            return Ok(keccak256(&encoded));
+        }
+
+        if self.types.contains_key(type_name) {
+            return self.hash_struct(type_name, value);
+        }
+
+        match type_name {
+            "string" => Ok(keccak256(value.as_str().ok_or(Error)?.as_bytes())),
+            "bytes" => Ok(keccak256(&parse_hex_bytes(value)?)),
+            "bool" => {
+                let b = match value {
+                    Value::Bool(b) => *b,
+                    Value::String(s) if s == "true" => true,
+                    Value::String(s) if s == "false" => false,
+                    _ => return Err(Error),
+                };
+                Ok(word(U256::from(u8::from(b))))
+            }
+            "address" => {
+                let bytes = parse_hex_bytes(value)?;
+                if bytes.len() != 20 {
+                    return Err(Error);
+                }
+
+                let mut out = H256::zero();
+                out.as_bytes_mut()[12..].copy_from_slice(&bytes);
+                Ok(out)
+            }
+            _ => {
+                if let Some(size) = type_name.strip_prefix("bytes") {
+                    let size = size.parse::<usize>().map_err(|_| Error)?;
+                    let bytes = parse_hex_bytes(value)?;
+
+                    if !(1..=32).contains(&size) || bytes.len() != size {
+                        return Err(Error);
+                    }
+
+                    let mut out = H256::zero();
+                    out.as_bytes_mut()[..size].copy_from_slice(&bytes);
+                    Ok(out)
+                } else if let Some(bits) = type_name.strip_prefix("uint") {
+                    check_int_size(bits)?;
+                    Ok(word(parse_uint(value)?))
+                } else if let Some(bits) = type_name.strip_prefix("int") {
+                    check_int_size(bits)?;
+                    Ok(word(parse_int(value)?))
+                } else {
+                    Err(Error)
+                }
+            }
+        }
+    }
+}
+
+/// Field in a struct type definition.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Field {
+    /// Name of the field.
+    pub name: String,
+
+    /// Type of the field.
+    #[serde(rename = "type")]
+    pub r#type: String,
+}
+
+/// Compute the EIP-712 signing hash from a domain separator and the hash of a
+/// message struct:
+///
+/// ```text
+/// keccak256("\x19\x01" || domainSeparator || hashStruct(message))
+/// ```
+pub fn signing_hash(domain_separator: &H256, message_hash: &H256) -> H256 {
+    let mut encoded = Vec::with_capacity(66);
+    encoded.extend_from_slice(b"\x19\x01");
+    encoded.extend_from_slice(domain_separator.as_bytes());
+    encoded.extend_from_slice(message_hash.as_bytes());
+    keccak256(&encoded)
+}
+
+/// Compute the Keccak256 hash of the given data.
+pub(crate) fn keccak256(data: &[u8]) -> H256 {
+    H256::from_slice(&Keccak256::digest(data))
+}
+
+/// Serialize an integer as a big endian 32-byte word.
+pub(crate) fn word(n: U256) -> H256 {
+    let mut out = H256::zero();
+    n.to_big_endian(out.as_bytes_mut());
+    out
+}
+
+/// If the given type is an array, get the type of its elements.
+fn array_element_type(type_name: &str) -> Option<&str> {
+    type_name
+        .strip_suffix(']')
+        .and_then(|t| t.rfind('[').map(|i| &t[..i]))
+}
+
+/// Strip all array suffixes from the given type.
+fn strip_array(type_name: &str) -> &str {
+    type_name.split('[').next().unwrap_or(type_name)
+}
+
+/// Ensure the given integer size (in bits) is valid.
+fn check_int_size(bits: &str) -> Result<()> {
+    match bits.parse::<usize>() {
+        Ok(n) if n > 0 && n <= 256 && n % 8 == 0 => Ok(()),
+        _ => Err(Error),
+    }
+}
+
+/// Parse a `0x`-prefixed hex string as bytes.
+fn parse_hex_bytes(value: &Value) -> Result<Vec<u8>> {
+    let s = value.as_str().ok_or(Error)?;
+    let s = s.strip_prefix("0x").ok_or(Error)?;
+    hex::mixed::decode_vec(s).map_err(|_| Error)
+}
+
+/// Parse an unsigned integer from a JSON number, decimal string, or
+/// `0x`-prefixed hex string.
+fn parse_uint(value: &Value) -> Result<U256> {
+    match value {
+        Value::Number(n) => n.as_u64().map(U256::from).ok_or(Error),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map_err(|_| Error),
+            None => U256::from_dec_str(s).map_err(|_| Error),
+        },
+        _ => Err(Error),
+    }
+}
+
+/// Parse a signed integer as its two's complement representation.
+fn parse_int(value: &Value) -> Result<U256> {
+    let negative_magnitude = match value {
+        Value::Number(n) => match n.as_i64() {
+            Some(i) if i < 0 => Some(U256::from(i.unsigned_abs())),
+            _ => None,
+        },
+        Value::String(s) => match s.strip_prefix('-') {
+            Some(magnitude) => Some(parse_uint(&Value::String(magnitude.to_owned()))?),
+            None => None,
+        },
+        _ => None,
+    };
+
+    match negative_magnitude {
+        Some(magnitude) if magnitude.is_zero() => Ok(U256::zero()),
+        Some(magnitude) => Ok((!magnitude).overflowing_add(U256::one()).0),
+        None => parse_uint(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypedData;
+    use hex_literal::hex;
+
+    /// Example from EIP-712.
+    ///
+    /// <https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js>
+    const EXAMPLE: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn eip712_example() {
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE).unwrap();
+
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            typed_data.domain_separator().unwrap().as_bytes(),
+            hex!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+        assert_eq!(
+            typed_data.message_hash().unwrap().as_bytes(),
+            hex!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            typed_data.signing_hash().unwrap().as_bytes(),
+            hex!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+}
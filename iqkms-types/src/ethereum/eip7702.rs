@@ -0,0 +1,121 @@
+//! EIP-7702: Set EOA account code.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-7702>
+
+use super::{
+    Address, ChainId, H256,
+    rlp::{self, Decode, Encode},
+    transaction::Signature,
+};
+use crate::{Error, Result};
+use crypto::digest::{Digest, Update, sha3::Keccak256};
+
+/// Magic byte prepended to authorization tuples when computing their signing hash.
+pub const MAGIC: u8 = 0x05;
+
+/// Authorization for an EOA to delegate its code to the given contract address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Authorization {
+    /// Chain ID the authorization is valid on (`0` for any chain).
+    pub chain_id: ChainId,
+
+    /// Address of the delegate contract whose code the EOA will execute.
+    ///
+    /// Delegating to the zero address clears any existing delegation.
+    pub address: Address,
+
+    /// Nonce of the authorizing account.
+    pub nonce: u64,
+}
+
+impl Authorization {
+    /// Does this authorization clear an existing delegation (i.e. delegate to
+    /// the zero address)?
+    pub fn is_revocation(&self) -> bool {
+        self.address.hash.is_zero()
+    }
+
+    /// Compute the hash of this authorization which is signed:
+    ///
+    /// `keccak(MAGIC || rlp([chain_id, address, nonce]))`
+    pub fn signing_hash(&self) -> H256 {
+        let mut payload = Vec::new();
+        self.append_fields(&mut payload);
+
+        let mut encoded = Vec::new();
+        rlp::append_list(&payload, &mut encoded);
+
+        let digest = Keccak256::new().chain([MAGIC]).chain(encoded).finalize();
+        H256::from_slice(&digest)
+    }
+
+    /// Append the RLP-encoded fields of this authorization.
+    fn append_fields(&self, payload: &mut Vec<u8>) {
+        self.chain_id.rlp_append(payload);
+        self.address.rlp_append(payload);
+        self.nonce.rlp_append(payload);
+    }
+}
+
+/// Authorization along with the authorizing account's signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SignedAuthorization {
+    /// Authorization which was signed.
+    pub authorization: Authorization,
+
+    /// Signature over the authorization's signing hash.
+    pub signature: Signature,
+}
+
+impl Encode for SignedAuthorization {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        self.authorization.append_fields(&mut payload);
+        self.signature.y_parity.rlp_append(&mut payload);
+        self.signature.r.rlp_append(&mut payload);
+        self.signature.s.rlp_append(&mut payload);
+        rlp::append_list(&payload, out);
+    }
+}
+
+impl Decode for SignedAuthorization {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        let mut payload = rlp::decode_list(buf)?;
+
+        let signed = Self {
+            authorization: Authorization {
+                chain_id: ChainId::rlp_decode(&mut payload)?,
+                address: Address::rlp_decode(&mut payload)?,
+                nonce: u64::rlp_decode(&mut payload)?,
+            },
+            signature: Signature::decode_y_parity_r_s(&mut payload)?,
+        };
+
+        if payload.is_empty() {
+            Ok(signed)
+        } else {
+            Err(Error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Authorization;
+    use hex_literal::hex;
+
+    #[test]
+    fn signing_hash() {
+        let authorization = Authorization {
+            chain_id: 1,
+            address: [0x11; 20].into(),
+            nonce: 0,
+        };
+
+        // keccak256(0x05 || rlp([1, 0x1111..11, 0]))
+        assert_eq!(
+            authorization.signing_hash().as_bytes(),
+            hex!("d35655e0048045ff05a91979004fc1afa8325086bbab53c2498ecfcfe431d99f")
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Recursive Length Prefix (RLP) serialization and deserialization.
+//!
+//! <https://ethereum.org/en/developers/docs/data-structures-and-encoding/rlp/>
+
+use super::{Address, H256, U256};
+use crate::{Error, Result};
+
+/// Offset added to the length of short strings.
+const STRING_OFFSET: u8 = 0x80;
+
+/// Offset added to the length of short lists.
+const LIST_OFFSET: u8 = 0xc0;
+
+/// Maximum payload length which can be encoded in a single-byte header.
+const SHORT_LENGTH_MAX: usize = 55;
+
+/// Types which can be serialized as RLP.
+pub trait Encode {
+    /// Append the RLP encoding of this value to the given buffer.
+    fn rlp_append(&self, out: &mut Vec<u8>);
+
+    /// Serialize this value as RLP.
+    fn to_rlp(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.rlp_append(&mut out);
+        out
+    }
+}
+
+/// Types which can be deserialized from RLP.
+pub trait Decode: Sized {
+    /// Decode a value from the front of the given buffer, advancing it past
+    /// the decoded item.
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self>;
+
+    /// Deserialize a value from RLP, ensuring the entire input is consumed.
+    fn from_rlp(mut bytes: &[u8]) -> Result<Self> {
+        let value = Self::rlp_decode(&mut bytes)?;
+
+        if bytes.is_empty() {
+            Ok(value)
+        } else {
+            Err(Error)
+        }
+    }
+}
+
+/// Append an RLP list to the given buffer, where `payload` is the concatenation
+/// of the RLP encodings of the list's items.
+pub fn append_list(payload: &[u8], out: &mut Vec<u8>) {
+    append_header(LIST_OFFSET, payload.len(), out);
+    out.extend_from_slice(payload);
+}
+
+/// Append an RLP string (i.e. byte string) to the given buffer.
+pub fn append_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    match bytes {
+        [byte] if *byte < STRING_OFFSET => out.push(*byte),
+        _ => {
+            append_header(STRING_OFFSET, bytes.len(), out);
+            out.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Append a string or list header with the given offset and payload length.
+#[allow(clippy::arithmetic_side_effects, clippy::cast_possible_truncation)]
+fn append_header(offset: u8, len: usize, out: &mut Vec<u8>) {
+    if len <= SHORT_LENGTH_MAX {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let len_bytes = strip_leading_zeros(&len_bytes);
+        out.push(offset + SHORT_LENGTH_MAX as u8 + len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+/// Decode an RLP list from the front of the given buffer, returning its
+/// payload (i.e. the concatenation of the RLP encodings of its items).
+pub fn decode_list<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+    match decode_item(buf)? {
+        (true, payload) => Ok(payload),
+        (false, _) => Err(Error),
+    }
+}
+
+/// Decode an RLP string (i.e. byte string) from the front of the given
+/// buffer.
+pub fn decode_bytes<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+    match decode_item(buf)? {
+        (false, bytes) => Ok(bytes),
+        (true, _) => Err(Error),
+    }
+}
+
+/// Decode an item from the front of the given buffer, returning whether it's
+/// a list along with its payload.
+///
+/// Rejects non-canonical encodings, i.e. headers which could have been
+/// encoded more compactly.
+fn decode_item<'a>(buf: &mut &'a [u8]) -> Result<(bool, &'a [u8])> {
+    let (&prefix, rest) = buf.split_first().ok_or(Error)?;
+
+    if prefix < STRING_OFFSET {
+        let (byte, rest) = buf.split_at(1);
+        *buf = rest;
+        return Ok((false, byte));
+    }
+
+    let (is_list, short) = if prefix < LIST_OFFSET {
+        (false, prefix.wrapping_sub(STRING_OFFSET))
+    } else {
+        (true, prefix.wrapping_sub(LIST_OFFSET))
+    };
+
+    let (len, rest) = match usize::from(short).checked_sub(SHORT_LENGTH_MAX) {
+        None | Some(0) => (usize::from(short), rest),
+        Some(len_of_len) => {
+            let len_bytes = rest.get(..len_of_len).ok_or(Error)?;
+
+            if len_bytes.first() == Some(&0) || len_bytes.len() > size_of::<usize>() {
+                return Err(Error);
+            }
+
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | usize::from(b));
+
+            if len <= SHORT_LENGTH_MAX {
+                return Err(Error);
+            }
+
+            (len, &rest[len_of_len..])
+        }
+    };
+
+    let payload = rest.get(..len).ok_or(Error)?;
+
+    // A single byte below `0x80` must be encoded as itself
+    if !is_list && matches!(payload, [byte] if *byte < STRING_OFFSET) {
+        return Err(Error);
+    }
+
+    *buf = &rest[len..];
+    Ok((is_list, payload))
+}
+
+/// Decode a big endian unsigned integer of at most `N` bytes, rejecting
+/// leading zeros.
+fn decode_uint<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N]> {
+    let bytes = decode_bytes(buf)?;
+
+    if bytes.len() > N || bytes.first() == Some(&0) {
+        return Err(Error);
+    }
+
+    let mut out = [0u8; N];
+    out[N.saturating_sub(bytes.len())..].copy_from_slice(bytes);
+    Ok(out)
+}
+
+/// Strip leading zeros from a big endian integer.
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+
+    &bytes[start..]
+}
+
+impl Encode for [u8] {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(self, out);
+    }
+}
+
+impl Encode for Vec<u8> {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(self, out);
+    }
+}
+
+impl Encode for bytes::Bytes {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(self, out);
+    }
+}
+
+impl Encode for bool {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        u64::from(*self).rlp_append(out);
+    }
+}
+
+impl Encode for u64 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(strip_leading_zeros(&self.to_be_bytes()), out);
+    }
+}
+
+impl Encode for u128 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(strip_leading_zeros(&self.to_be_bytes()), out);
+    }
+}
+
+impl Encode for U256 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut bytes = [0u8; 32];
+        self.to_big_endian(&mut bytes);
+        append_bytes(strip_leading_zeros(&bytes), out);
+    }
+}
+
+impl Encode for H256 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(self.as_bytes(), out);
+    }
+}
+
+impl Encode for Address {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(self.as_ref(), out);
+    }
+}
+
+/// `None` is encoded as the empty string, as is the convention for e.g. the
+/// `to` field of contract creation transactions.
+impl<T: Encode> Encode for Option<T> {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => value.rlp_append(out),
+            None => append_bytes(&[], out),
+        }
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+
+        for item in self {
+            item.rlp_append(&mut payload);
+        }
+
+        append_list(&payload, out);
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        self.as_slice().rlp_append(out);
+    }
+}
+
+impl Decode for Vec<u8> {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        decode_bytes(buf).map(<[u8]>::to_vec)
+    }
+}
+
+impl Decode for bytes::Bytes {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        decode_bytes(buf).map(bytes::Bytes::copy_from_slice)
+    }
+}
+
+impl Decode for bool {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        match u64::rlp_decode(buf)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error),
+        }
+    }
+}
+
+impl Decode for u64 {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        decode_uint(buf).map(u64::from_be_bytes)
+    }
+}
+
+impl Decode for u128 {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        decode_uint(buf).map(u128::from_be_bytes)
+    }
+}
+
+impl Decode for U256 {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        decode_uint::<32>(buf).map(|bytes| U256::from_big_endian(&bytes))
+    }
+}
+
+impl Decode for H256 {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        <[u8; 32]>::try_from(decode_bytes(buf)?)
+            .map(H256)
+            .map_err(|_| Error)
+    }
+}
+
+impl Decode for Address {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        Address::try_from(decode_bytes(buf)?)
+    }
+}
+
+/// The empty string is decoded as `None`, as is the convention for e.g. the
+/// `to` field of contract creation transactions.
+impl Decode for Option<Address> {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        if buf.first() == Some(&STRING_OFFSET) {
+            *buf = &buf[1..];
+            Ok(None)
+        } else {
+            Address::rlp_decode(buf).map(Some)
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        let mut payload = decode_list(buf)?;
+        let mut items = Vec::new();
+
+        while !payload.is_empty() {
+            items.push(T::rlp_decode(&mut payload)?);
+        }
+
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decode, Encode, U256};
+
+    #[test]
+    fn encode_strings() {
+        assert_eq!(b"dog".to_vec().to_rlp(), b"\x83dog");
+        assert_eq!(Vec::<u8>::new().to_rlp(), [0x80]);
+        assert_eq!(vec![0x0fu8].to_rlp(), [0x0f]);
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+        let mut expected = vec![0xb8, 0x38];
+        expected.extend_from_slice(&lorem);
+        assert_eq!(lorem.to_rlp(), expected);
+    }
+
+    #[test]
+    fn encode_integers() {
+        assert_eq!(0u64.to_rlp(), [0x80]);
+        assert_eq!(15u64.to_rlp(), [0x0f]);
+        assert_eq!(1024u64.to_rlp(), [0x82, 0x04, 0x00]);
+        assert_eq!(U256::from(1024u64).to_rlp(), [0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn encode_lists() {
+        let list = vec![b"cat".to_vec(), b"dog".to_vec()];
+        assert_eq!(list.to_rlp(), b"\xc8\x83cat\x83dog");
+        assert_eq!(Vec::<Vec<u8>>::new().to_rlp(), [0xc0]);
+    }
+
+    #[test]
+    fn decode_round_trip() {
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+        assert_eq!(Vec::<u8>::from_rlp(&lorem.to_rlp()).unwrap(), lorem);
+        assert_eq!(Vec::<u8>::from_rlp(b"\x83dog").unwrap(), b"dog");
+        assert_eq!(u64::from_rlp(&[0x82, 0x04, 0x00]).unwrap(), 1024);
+        assert_eq!(u64::from_rlp(&[0x80]).unwrap(), 0);
+        assert_eq!(U256::from_rlp(&[0x0f]).unwrap(), U256::from(15u64));
+        assert_eq!(
+            Vec::<Vec<u8>>::from_rlp(b"\xc8\x83cat\x83dog").unwrap(),
+            [b"cat".to_vec(), b"dog".to_vec()]
+        );
+
+        assert_eq!(
+            Vec::<Vec<Vec<u8>>>::from_rlp(&[0xc4, 0xc0, 0xc2, 0x80, 0x01]).unwrap(),
+            [vec![], vec![vec![], vec![0x01]]]
+        );
+    }
+
+    #[test]
+    fn decode_rejects_non_canonical() {
+        // Single byte below 0x80 encoded with a length prefix
+        assert!(Vec::<u8>::from_rlp(&[0x81, 0x0f]).is_err());
+
+        // Short string encoded with a long-form header
+        assert!(Vec::<u8>::from_rlp(&[0xb8, 0x03, b'd', b'o', b'g']).is_err());
+
+        // Integer with leading zeros
+        assert!(u64::from_rlp(&[0x82, 0x00, 0x01]).is_err());
+
+        // Integer exceeding the target type
+        assert!(u64::from_rlp(&[0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+
+        // Truncated input and trailing data
+        assert!(Vec::<u8>::from_rlp(&[0x83, b'd', b'o']).is_err());
+        assert!(Vec::<u8>::from_rlp(&[0x83, b'd', b'o', b'g', 0x00]).is_err());
+
+        // List where a string is expected and vice versa
+        assert!(Vec::<u8>::from_rlp(&[0xc0]).is_err());
+        assert!(Vec::<Vec<u8>>::from_rlp(&[0x80]).is_err());
+    }
+}
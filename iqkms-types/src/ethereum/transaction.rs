@@ -0,0 +1,1121 @@
+//! Ethereum transactions.
+//!
+//! Supports legacy transactions (with or without EIP-155 replay protection) as
+//! well as EIP-2718 typed transactions.
+
+use super::{
+    Address, ChainId, H256, U256,
+    eip7702::SignedAuthorization,
+    rlp::{self, Decode, Encode},
+};
+use crate::{Bytes, Error, Result};
+use crypto::digest::{Digest, sha3::Keccak256};
+
+/// Ethereum transactions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Transaction {
+    /// Legacy transaction.
+    Legacy(TxLegacy),
+
+    /// EIP-2930 access list transaction (type `0x01`).
+    Eip2930(TxEip2930),
+
+    /// EIP-1559 dynamic fee transaction (type `0x02`).
+    Eip1559(TxEip1559),
+
+    /// EIP-4844 blob transaction (type `0x03`).
+    Eip4844(TxEip4844),
+
+    /// EIP-7702 set code transaction (type `0x04`).
+    Eip7702(TxEip7702),
+}
+
+impl Transaction {
+    /// Get the chain ID this transaction is bound to, if any.
+    pub fn chain_id(&self) -> Option<ChainId> {
+        match self {
+            Transaction::Legacy(tx) => tx.chain_id,
+            Transaction::Eip2930(tx) => Some(tx.chain_id),
+            Transaction::Eip1559(tx) => Some(tx.chain_id),
+            Transaction::Eip4844(tx) => Some(tx.chain_id),
+            Transaction::Eip7702(tx) => Some(tx.chain_id),
+        }
+    }
+
+    /// Get the EIP-2718 transaction type, or `None` for legacy transactions.
+    pub fn tx_type(&self) -> Option<u8> {
+        match self {
+            Transaction::Legacy(_) => None,
+            Transaction::Eip2930(_) => Some(TxEip2930::TX_TYPE),
+            Transaction::Eip1559(_) => Some(TxEip1559::TX_TYPE),
+            Transaction::Eip4844(_) => Some(TxEip4844::TX_TYPE),
+            Transaction::Eip7702(_) => Some(TxEip7702::TX_TYPE),
+        }
+    }
+
+    /// Get the recipient of this transaction (`None` for contract creation).
+    pub fn to(&self) -> Option<&Address> {
+        match self {
+            Transaction::Legacy(tx) => tx.to.as_ref(),
+            Transaction::Eip2930(tx) => tx.to.as_ref(),
+            Transaction::Eip1559(tx) => tx.to.as_ref(),
+            Transaction::Eip4844(tx) => Some(&tx.to),
+            Transaction::Eip7702(tx) => Some(&tx.to),
+        }
+    }
+
+    /// Get the input data (a.k.a. calldata) of this transaction.
+    pub fn input(&self) -> &Bytes {
+        match self {
+            Transaction::Legacy(tx) => &tx.input,
+            Transaction::Eip2930(tx) => &tx.input,
+            Transaction::Eip1559(tx) => &tx.input,
+            Transaction::Eip4844(tx) => &tx.input,
+            Transaction::Eip7702(tx) => &tx.input,
+        }
+    }
+
+    /// Serialize the payload which is hashed to compute the signing hash.
+    pub fn encode_for_signing(&self) -> Vec<u8> {
+        match self {
+            Transaction::Legacy(tx) => tx.encode_for_signing(),
+            Transaction::Eip2930(tx) => typed(TxEip2930::TX_TYPE, tx.fields(None)),
+            Transaction::Eip1559(tx) => typed(TxEip1559::TX_TYPE, tx.fields(None)),
+            Transaction::Eip4844(tx) => typed(TxEip4844::TX_TYPE, tx.fields(None)),
+            Transaction::Eip7702(tx) => typed(TxEip7702::TX_TYPE, tx.fields(None)),
+        }
+    }
+
+    /// Compute the Keccak256 hash of this transaction which is signed.
+    pub fn signing_hash(&self) -> H256 {
+        H256::from_slice(&Keccak256::digest(self.encode_for_signing()))
+    }
+
+    /// Serialize this transaction along with the given signature as a signed
+    /// transaction envelope, suitable for `eth_sendRawTransaction`.
+    ///
+    /// For EIP-4844 transactions this is the canonical form without the blob
+    /// sidecar, which must be attached separately before broadcasting.
+    pub fn encode_signed(&self, signature: &Signature) -> Vec<u8> {
+        match self {
+            Transaction::Legacy(tx) => tx.encode_signed(signature),
+            Transaction::Eip2930(tx) => typed(TxEip2930::TX_TYPE, tx.fields(Some(signature))),
+            Transaction::Eip1559(tx) => typed(TxEip1559::TX_TYPE, tx.fields(Some(signature))),
+            Transaction::Eip4844(tx) => typed(TxEip4844::TX_TYPE, tx.fields(Some(signature))),
+            Transaction::Eip7702(tx) => typed(TxEip7702::TX_TYPE, tx.fields(Some(signature))),
+        }
+    }
+
+    /// Deserialize a signed transaction envelope, e.g. as accepted by
+    /// `eth_sendRawTransaction`.
+    ///
+    /// EIP-4844 transactions must be in their canonical form, i.e. without
+    /// the blob sidecar.
+    pub fn decode_signed(bytes: &[u8]) -> Result<(Transaction, Signature)> {
+        let (tx, signature) = Self::decode(bytes, true)?;
+        Ok((tx, signature.ok_or(Error)?))
+    }
+
+    /// Deserialize the payload which is hashed to compute the signing hash,
+    /// i.e. the output of [`Transaction::encode_for_signing`].
+    pub fn decode_for_signing(bytes: &[u8]) -> Result<Transaction> {
+        Self::decode(bytes, false).map(|(tx, _)| tx)
+    }
+
+    /// Deserialize a transaction, along with its signature if `signed`.
+    fn decode(bytes: &[u8], signed: bool) -> Result<(Transaction, Option<Signature>)> {
+        let (&tx_type, rest) = bytes.split_first().ok_or(Error)?;
+
+        // Legacy transactions are RLP lists, whose first byte is `0xc0` or above
+        if tx_type >= 0xc0 {
+            return TxLegacy::decode(bytes, signed);
+        }
+
+        let mut payload = list_payload(rest)?;
+        let tx = match tx_type {
+            TxEip2930::TX_TYPE => TxEip2930::decode_fields(&mut payload)?.into(),
+            TxEip1559::TX_TYPE => TxEip1559::decode_fields(&mut payload)?.into(),
+            TxEip4844::TX_TYPE => TxEip4844::decode_fields(&mut payload)?.into(),
+            TxEip7702::TX_TYPE => TxEip7702::decode_fields(&mut payload)?.into(),
+            _ => return Err(Error),
+        };
+
+        let signature = if signed {
+            Some(Signature::decode_y_parity_r_s(&mut payload)?)
+        } else {
+            None
+        };
+
+        if payload.is_empty() {
+            Ok((tx, signature))
+        } else {
+            Err(Error)
+        }
+    }
+}
+
+impl From<TxLegacy> for Transaction {
+    fn from(tx: TxLegacy) -> Transaction {
+        Transaction::Legacy(tx)
+    }
+}
+
+impl From<TxEip2930> for Transaction {
+    fn from(tx: TxEip2930) -> Transaction {
+        Transaction::Eip2930(tx)
+    }
+}
+
+impl From<TxEip1559> for Transaction {
+    fn from(tx: TxEip1559) -> Transaction {
+        Transaction::Eip1559(tx)
+    }
+}
+
+impl From<TxEip4844> for Transaction {
+    fn from(tx: TxEip4844) -> Transaction {
+        Transaction::Eip4844(tx)
+    }
+}
+
+impl From<TxEip7702> for Transaction {
+    fn from(tx: TxEip7702) -> Transaction {
+        Transaction::Eip7702(tx)
+    }
+}
+
+/// Legacy transaction.
+///
+/// When `chain_id` is set, EIP-155 replay protection is applied.
+///
+/// <https://eips.ethereum.org/EIPS/eip-155>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TxLegacy {
+    /// EIP-155 chain ID.
+    pub chain_id: Option<ChainId>,
+
+    /// Sender account nonce.
+    pub nonce: u64,
+
+    /// Gas price in wei.
+    pub gas_price: U256,
+
+    /// Maximum amount of gas this transaction may consume.
+    pub gas_limit: u64,
+
+    /// Recipient address (`None` for contract creation).
+    pub to: Option<Address>,
+
+    /// Amount of wei to transfer.
+    pub value: U256,
+
+    /// Input data.
+    pub input: Bytes,
+}
+
+impl TxLegacy {
+    /// Append the fields shared between the signing payload and the signed
+    /// transaction.
+    fn append_fields(&self, payload: &mut Vec<u8>) {
+        self.nonce.rlp_append(payload);
+        self.gas_price.rlp_append(payload);
+        self.gas_limit.rlp_append(payload);
+        self.to.rlp_append(payload);
+        self.value.rlp_append(payload);
+        self.input.rlp_append(payload);
+    }
+
+    /// Serialize the payload which is hashed to compute the signing hash.
+    fn encode_for_signing(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.append_fields(&mut payload);
+
+        if let Some(chain_id) = self.chain_id {
+            chain_id.rlp_append(&mut payload);
+            0u64.rlp_append(&mut payload);
+            0u64.rlp_append(&mut payload);
+        }
+
+        let mut out = Vec::new();
+        rlp::append_list(&payload, &mut out);
+        out
+    }
+
+    /// Deserialize a legacy transaction, along with its signature if
+    /// `signed`.
+    fn decode(bytes: &[u8], signed: bool) -> Result<(Transaction, Option<Signature>)> {
+        let mut payload = list_payload(bytes)?;
+
+        let mut tx = TxLegacy {
+            chain_id: None,
+            nonce: u64::rlp_decode(&mut payload)?,
+            gas_price: U256::rlp_decode(&mut payload)?,
+            gas_limit: u64::rlp_decode(&mut payload)?,
+            to: Option::<Address>::rlp_decode(&mut payload)?,
+            value: U256::rlp_decode(&mut payload)?,
+            input: Bytes::rlp_decode(&mut payload)?,
+        };
+
+        let signature = if signed {
+            let (chain_id, y_parity) = Signature::decode_v(u64::rlp_decode(&mut payload)?)?;
+            tx.chain_id = chain_id;
+
+            Some(Signature {
+                r: U256::rlp_decode(&mut payload)?,
+                s: U256::rlp_decode(&mut payload)?,
+                y_parity,
+            })
+        } else {
+            // EIP-155 signing payloads end with `[chain_id, 0, 0]`
+            if !payload.is_empty() {
+                tx.chain_id = Some(u64::rlp_decode(&mut payload)?);
+
+                if u64::rlp_decode(&mut payload)? != 0 || u64::rlp_decode(&mut payload)? != 0 {
+                    return Err(Error);
+                }
+            }
+
+            None
+        };
+
+        if payload.is_empty() {
+            Ok((tx.into(), signature))
+        } else {
+            Err(Error)
+        }
+    }
+
+    /// Serialize this transaction with the given signature.
+    fn encode_signed(&self, signature: &Signature) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.append_fields(&mut payload);
+        signature.v(self.chain_id).rlp_append(&mut payload);
+        signature.r.rlp_append(&mut payload);
+        signature.s.rlp_append(&mut payload);
+
+        let mut out = Vec::new();
+        rlp::append_list(&payload, &mut out);
+        out
+    }
+}
+
+/// EIP-2930 access list transaction.
+///
+/// <https://eips.ethereum.org/EIPS/eip-2930>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TxEip2930 {
+    /// EIP-155 chain ID.
+    pub chain_id: ChainId,
+
+    /// Sender account nonce.
+    pub nonce: u64,
+
+    /// Gas price in wei.
+    pub gas_price: U256,
+
+    /// Maximum amount of gas this transaction may consume.
+    pub gas_limit: u64,
+
+    /// Recipient address (`None` for contract creation).
+    pub to: Option<Address>,
+
+    /// Amount of wei to transfer.
+    pub value: U256,
+
+    /// Input data.
+    pub input: Bytes,
+
+    /// Addresses and storage keys the transaction plans to access.
+    pub access_list: AccessList,
+}
+
+impl TxEip2930 {
+    /// EIP-2718 transaction type.
+    pub const TX_TYPE: u8 = 0x01;
+
+    /// Serialize the RLP payload of this transaction, optionally including
+    /// the signature.
+    fn fields(&self, signature: Option<&Signature>) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.chain_id.rlp_append(&mut payload);
+        self.nonce.rlp_append(&mut payload);
+        self.gas_price.rlp_append(&mut payload);
+        self.gas_limit.rlp_append(&mut payload);
+        self.to.rlp_append(&mut payload);
+        self.value.rlp_append(&mut payload);
+        self.input.rlp_append(&mut payload);
+        self.access_list.rlp_append(&mut payload);
+
+        if let Some(signature) = signature {
+            signature.append_y_parity_r_s(&mut payload);
+        }
+
+        payload
+    }
+
+    /// Decode the fields of this transaction from an RLP list payload.
+    fn decode_fields(payload: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            chain_id: u64::rlp_decode(payload)?,
+            nonce: u64::rlp_decode(payload)?,
+            gas_price: U256::rlp_decode(payload)?,
+            gas_limit: u64::rlp_decode(payload)?,
+            to: Option::<Address>::rlp_decode(payload)?,
+            value: U256::rlp_decode(payload)?,
+            input: Bytes::rlp_decode(payload)?,
+            access_list: AccessList::rlp_decode(payload)?,
+        })
+    }
+}
+
+/// EIP-1559 dynamic fee transaction.
+///
+/// <https://eips.ethereum.org/EIPS/eip-1559>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TxEip1559 {
+    /// EIP-155 chain ID.
+    pub chain_id: ChainId,
+
+    /// Sender account nonce.
+    pub nonce: u64,
+
+    /// Maximum priority fee per gas (a.k.a. tip) in wei.
+    pub max_priority_fee_per_gas: U256,
+
+    /// Maximum total fee per gas in wei.
+    pub max_fee_per_gas: U256,
+
+    /// Maximum amount of gas this transaction may consume.
+    pub gas_limit: u64,
+
+    /// Recipient address (`None` for contract creation).
+    pub to: Option<Address>,
+
+    /// Amount of wei to transfer.
+    pub value: U256,
+
+    /// Input data.
+    pub input: Bytes,
+
+    /// Addresses and storage keys the transaction plans to access.
+    pub access_list: AccessList,
+}
+
+impl TxEip1559 {
+    /// EIP-2718 transaction type.
+    pub const TX_TYPE: u8 = 0x02;
+
+    /// Serialize the RLP payload of this transaction, optionally including
+    /// the signature.
+    fn fields(&self, signature: Option<&Signature>) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.chain_id.rlp_append(&mut payload);
+        self.nonce.rlp_append(&mut payload);
+        self.max_priority_fee_per_gas.rlp_append(&mut payload);
+        self.max_fee_per_gas.rlp_append(&mut payload);
+        self.gas_limit.rlp_append(&mut payload);
+        self.to.rlp_append(&mut payload);
+        self.value.rlp_append(&mut payload);
+        self.input.rlp_append(&mut payload);
+        self.access_list.rlp_append(&mut payload);
+
+        if let Some(signature) = signature {
+            signature.append_y_parity_r_s(&mut payload);
+        }
+
+        payload
+    }
+
+    /// Decode the fields of this transaction from an RLP list payload.
+    fn decode_fields(payload: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            chain_id: u64::rlp_decode(payload)?,
+            nonce: u64::rlp_decode(payload)?,
+            max_priority_fee_per_gas: U256::rlp_decode(payload)?,
+            max_fee_per_gas: U256::rlp_decode(payload)?,
+            gas_limit: u64::rlp_decode(payload)?,
+            to: Option::<Address>::rlp_decode(payload)?,
+            value: U256::rlp_decode(payload)?,
+            input: Bytes::rlp_decode(payload)?,
+            access_list: AccessList::rlp_decode(payload)?,
+        })
+    }
+}
+
+/// EIP-4844 blob transaction.
+///
+/// <https://eips.ethereum.org/EIPS/eip-4844>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxEip4844 {
+    /// EIP-155 chain ID.
+    pub chain_id: ChainId,
+
+    /// Sender account nonce.
+    pub nonce: u64,
+
+    /// Maximum priority fee per gas (a.k.a. tip) in wei.
+    pub max_priority_fee_per_gas: U256,
+
+    /// Maximum total fee per gas in wei.
+    pub max_fee_per_gas: U256,
+
+    /// Maximum amount of gas this transaction may consume.
+    pub gas_limit: u64,
+
+    /// Recipient address (blob transactions cannot create contracts).
+    pub to: Address,
+
+    /// Amount of wei to transfer.
+    pub value: U256,
+
+    /// Input data.
+    pub input: Bytes,
+
+    /// Addresses and storage keys the transaction plans to access.
+    pub access_list: AccessList,
+
+    /// Maximum fee per unit of blob gas in wei.
+    pub max_fee_per_blob_gas: U256,
+
+    /// Versioned hashes of the KZG commitments to the blobs.
+    pub blob_versioned_hashes: Vec<H256>,
+}
+
+impl TxEip4844 {
+    /// EIP-2718 transaction type.
+    pub const TX_TYPE: u8 = 0x03;
+
+    /// Serialize the RLP payload of this transaction, optionally including
+    /// the signature.
+    fn fields(&self, signature: Option<&Signature>) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.chain_id.rlp_append(&mut payload);
+        self.nonce.rlp_append(&mut payload);
+        self.max_priority_fee_per_gas.rlp_append(&mut payload);
+        self.max_fee_per_gas.rlp_append(&mut payload);
+        self.gas_limit.rlp_append(&mut payload);
+        self.to.rlp_append(&mut payload);
+        self.value.rlp_append(&mut payload);
+        self.input.rlp_append(&mut payload);
+        self.access_list.rlp_append(&mut payload);
+        self.max_fee_per_blob_gas.rlp_append(&mut payload);
+        self.blob_versioned_hashes.rlp_append(&mut payload);
+
+        if let Some(signature) = signature {
+            signature.append_y_parity_r_s(&mut payload);
+        }
+
+        payload
+    }
+
+    /// Decode the fields of this transaction from an RLP list payload.
+    fn decode_fields(payload: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            chain_id: u64::rlp_decode(payload)?,
+            nonce: u64::rlp_decode(payload)?,
+            max_priority_fee_per_gas: U256::rlp_decode(payload)?,
+            max_fee_per_gas: U256::rlp_decode(payload)?,
+            gas_limit: u64::rlp_decode(payload)?,
+            to: Address::rlp_decode(payload)?,
+            value: U256::rlp_decode(payload)?,
+            input: Bytes::rlp_decode(payload)?,
+            access_list: AccessList::rlp_decode(payload)?,
+            max_fee_per_blob_gas: U256::rlp_decode(payload)?,
+            blob_versioned_hashes: Vec::rlp_decode(payload)?,
+        })
+    }
+}
+
+/// EIP-7702 set code transaction.
+///
+/// <https://eips.ethereum.org/EIPS/eip-7702>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxEip7702 {
+    /// EIP-155 chain ID.
+    pub chain_id: ChainId,
+
+    /// Sender account nonce.
+    pub nonce: u64,
+
+    /// Maximum priority fee per gas (a.k.a. tip) in wei.
+    pub max_priority_fee_per_gas: U256,
+
+    /// Maximum total fee per gas in wei.
+    pub max_fee_per_gas: U256,
+
+    /// Maximum amount of gas this transaction may consume.
+    pub gas_limit: u64,
+
+    /// Recipient address (set code transactions cannot create contracts).
+    pub to: Address,
+
+    /// Amount of wei to transfer.
+    pub value: U256,
+
+    /// Input data.
+    pub input: Bytes,
+
+    /// Addresses and storage keys the transaction plans to access.
+    pub access_list: AccessList,
+
+    /// Signed authorizations delegating the code of their signers.
+    pub authorization_list: Vec<SignedAuthorization>,
+}
+
+impl TxEip7702 {
+    /// EIP-2718 transaction type.
+    pub const TX_TYPE: u8 = 0x04;
+
+    /// Serialize the RLP payload of this transaction, optionally including
+    /// the signature.
+    fn fields(&self, signature: Option<&Signature>) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.chain_id.rlp_append(&mut payload);
+        self.nonce.rlp_append(&mut payload);
+        self.max_priority_fee_per_gas.rlp_append(&mut payload);
+        self.max_fee_per_gas.rlp_append(&mut payload);
+        self.gas_limit.rlp_append(&mut payload);
+        self.to.rlp_append(&mut payload);
+        self.value.rlp_append(&mut payload);
+        self.input.rlp_append(&mut payload);
+        self.access_list.rlp_append(&mut payload);
+        self.authorization_list.rlp_append(&mut payload);
+
+        if let Some(signature) = signature {
+            signature.append_y_parity_r_s(&mut payload);
+        }
+
+        payload
+    }
+
+    /// Decode the fields of this transaction from an RLP list payload.
+    fn decode_fields(payload: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            chain_id: u64::rlp_decode(payload)?,
+            nonce: u64::rlp_decode(payload)?,
+            max_priority_fee_per_gas: U256::rlp_decode(payload)?,
+            max_fee_per_gas: U256::rlp_decode(payload)?,
+            gas_limit: u64::rlp_decode(payload)?,
+            to: Address::rlp_decode(payload)?,
+            value: U256::rlp_decode(payload)?,
+            input: Bytes::rlp_decode(payload)?,
+            access_list: AccessList::rlp_decode(payload)?,
+            authorization_list: Vec::rlp_decode(payload)?,
+        })
+    }
+}
+
+/// EIP-2930 access list.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AccessList(pub Vec<AccessListItem>);
+
+impl Encode for AccessList {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        self.0.rlp_append(out);
+    }
+}
+
+impl Decode for AccessList {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        Vec::rlp_decode(buf).map(AccessList)
+    }
+}
+
+/// Entry in an EIP-2930 access list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessListItem {
+    /// Address of the account to be accessed.
+    pub address: Address,
+
+    /// Storage keys of the account to be accessed.
+    pub storage_keys: Vec<H256>,
+}
+
+impl Encode for AccessListItem {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        self.address.rlp_append(&mut payload);
+        self.storage_keys.rlp_append(&mut payload);
+        rlp::append_list(&payload, out);
+    }
+}
+
+impl Decode for AccessListItem {
+    fn rlp_decode(buf: &mut &[u8]) -> Result<Self> {
+        let mut payload = rlp::decode_list(buf)?;
+
+        let item = Self {
+            address: Address::rlp_decode(&mut payload)?,
+            storage_keys: Vec::rlp_decode(&mut payload)?,
+        };
+
+        if payload.is_empty() {
+            Ok(item)
+        } else {
+            Err(Error)
+        }
+    }
+}
+
+/// Transaction signature.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Signature {
+    /// ECDSA signature `r` component.
+    pub r: U256,
+
+    /// ECDSA signature `s` component.
+    pub s: U256,
+
+    /// Parity of the `y` coordinate of the curve point `R` (i.e. recovery ID).
+    pub y_parity: bool,
+}
+
+impl Signature {
+    /// Compute the legacy `v` value for this signature, applying EIP-155 if
+    /// a chain ID is given.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn v(&self, chain_id: Option<ChainId>) -> u64 {
+        let y_parity = u64::from(self.y_parity);
+
+        match chain_id {
+            Some(chain_id) => chain_id * 2 + 35 + y_parity,
+            None => 27 + y_parity,
+        }
+    }
+
+    /// Decode a legacy `v` value into the EIP-155 chain ID (if any) and
+    /// `y_parity`.
+    ///
+    /// Rejects `v` values of 35 and 36, which would imply a chain ID of 0.
+    pub fn decode_v(v: u64) -> Result<(Option<ChainId>, bool)> {
+        match v {
+            27 | 28 => Ok((None, v == 28)),
+            _ => {
+                let n = v.checked_sub(35).ok_or(Error)?;
+
+                match n / 2 {
+                    0 => Err(Error),
+                    chain_id => Ok((Some(chain_id), n % 2 == 1)),
+                }
+            }
+        }
+    }
+
+    /// Append the `y_parity`, `r`, and `s` fields used by typed transactions.
+    fn append_y_parity_r_s(&self, payload: &mut Vec<u8>) {
+        self.y_parity.rlp_append(payload);
+        self.r.rlp_append(payload);
+        self.s.rlp_append(payload);
+    }
+
+    /// Decode the `y_parity`, `r`, and `s` fields used by typed transactions.
+    pub(crate) fn decode_y_parity_r_s(payload: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            y_parity: bool::rlp_decode(payload)?,
+            r: U256::rlp_decode(payload)?,
+            s: U256::rlp_decode(payload)?,
+        })
+    }
+}
+
+/// Decode an RLP list spanning the entirety of the given bytes, returning its
+/// payload.
+fn list_payload(mut bytes: &[u8]) -> Result<&[u8]> {
+    let payload = rlp::decode_list(&mut bytes)?;
+
+    if bytes.is_empty() {
+        Ok(payload)
+    } else {
+        Err(Error)
+    }
+}
+
+/// Serialize the given RLP list payload as an EIP-2718 typed transaction.
+fn typed(tx_type: u8, payload: Vec<u8>) -> Vec<u8> {
+    let mut out = vec![tx_type];
+    rlp::append_list(&payload, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessList, Signature, Transaction, TxEip2930, TxEip4844, TxEip7702, TxLegacy};
+    use crate::{
+        Bytes,
+        ethereum::{
+            Address, H256, U256,
+            eip7702::{Authorization, SignedAuthorization},
+        },
+    };
+    use crypto::{
+        digest::{Digest, sha3::Keccak256},
+        signature::ecdsa::secp256k1,
+    };
+    use hex_literal::hex;
+
+    /// Recover the address of the signer of a transaction.
+    fn recover_signer(tx: &Transaction, signature: &Signature) -> Address {
+        let mut bytes = [0u8; 65];
+        signature.r.to_big_endian(&mut bytes[..32]);
+        signature.s.to_big_endian(&mut bytes[32..64]);
+        bytes[64] = u8::from(signature.y_parity);
+
+        let verifying_key = secp256k1::RecoverableSignature::try_from(&bytes[..])
+            .unwrap()
+            .recover_verifying_key_from_digest_bytes(tx.signing_hash().as_fixed_bytes().into())
+            .unwrap();
+
+        Address::try_from(&verifying_key).unwrap()
+    }
+
+    /// Check that a signed transaction decodes, re-encodes identically, and
+    /// was signed by the expected sender.
+    fn check_signed_vector(raw: &[u8], tx_hash: [u8; 32], sender: &str) -> Transaction {
+        assert_eq!(Keccak256::digest(raw).as_slice(), tx_hash);
+
+        let (tx, signature) = Transaction::decode_signed(raw).unwrap();
+        assert_eq!(tx.encode_signed(&signature), raw);
+        assert_eq!(recover_signer(&tx, &signature), sender.parse().unwrap());
+
+        let unsigned = tx.encode_for_signing();
+        assert_eq!(Transaction::decode_for_signing(&unsigned).unwrap(), tx);
+        assert!(Transaction::decode_signed(&unsigned).is_err());
+        tx
+    }
+
+    /// Example from EIP-155.
+    ///
+    /// <https://eips.ethereum.org/EIPS/eip-155#example>
+    #[test]
+    fn eip155_example() {
+        let tx = Transaction::from(TxLegacy {
+            chain_id: Some(1),
+            nonce: 9,
+            gas_price: U256::from(20_000_000_000u64),
+            gas_limit: 21000,
+            to: Some([0x35; 20].into()),
+            value: U256::from(1_000_000_000_000_000_000u64),
+            input: Bytes::new(),
+        });
+
+        assert_eq!(
+            tx.encode_for_signing(),
+            hex!(
+                "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+            )
+        );
+        assert_eq!(
+            tx.signing_hash().as_bytes(),
+            hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+
+        let signature = Signature {
+            r: U256::from_dec_str(
+                "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+            )
+            .unwrap(),
+            s: U256::from_dec_str(
+                "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+            )
+            .unwrap(),
+            y_parity: false,
+        };
+
+        assert_eq!(
+            tx.encode_signed(&signature),
+            hex!(
+                "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+            )
+        );
+    }
+
+    /// Signature used for typed transaction vectors.
+    ///
+    /// Test vectors were generated using `alloy-consensus`.
+    const TEST_SIGNATURE: Signature = Signature {
+        r: U256([1, 0, 0, 0]),
+        s: U256([2, 0, 0, 0]),
+        y_parity: true,
+    };
+
+    #[test]
+    fn eip4844_transaction() {
+        let tx = Transaction::from(TxEip4844 {
+            chain_id: 1,
+            nonce: 3,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(40_000_000_000u64),
+            gas_limit: 21000,
+            to: [0x22; 20].into(),
+            value: U256::from(5u64),
+            input: Bytes::from_static(&[0xab, 0xcd]),
+            access_list: AccessList::default(),
+            max_fee_per_blob_gas: U256::from(7u64),
+            blob_versioned_hashes: vec![H256::repeat_byte(0x01)],
+        });
+
+        assert_eq!(
+            tx.signing_hash().as_bytes(),
+            hex!("808b2843ed489739fddbb0d2ad1ee3c0ce2e9467c98f6a073c5b457120d3457c")
+        );
+        assert_eq!(
+            tx.encode_signed(&TEST_SIGNATURE),
+            hex!(
+                "03f8500103843b9aca008509502f90008252089422222222222222222222222222222222222222220582abcdc007e1a00101010101010101010101010101010101010101010101010101010101010101010102"
+            )
+        );
+    }
+
+    #[test]
+    fn eip7702_transaction() {
+        let authorization = SignedAuthorization {
+            authorization: Authorization {
+                chain_id: 1,
+                address: [0x11; 20].into(),
+                nonce: 0,
+            },
+            signature: Signature {
+                r: U256::from(3u64),
+                s: U256::from(4u64),
+                y_parity: false,
+            },
+        };
+
+        let tx = Transaction::from(TxEip7702 {
+            chain_id: 1,
+            nonce: 3,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(40_000_000_000u64),
+            gas_limit: 50000,
+            to: [0x22; 20].into(),
+            value: U256::zero(),
+            input: Bytes::new(),
+            access_list: AccessList::default(),
+            authorization_list: vec![authorization],
+        });
+
+        assert_eq!(
+            tx.signing_hash().as_bytes(),
+            hex!("edadb1399226079eacd50e96c8e456c4d234cdfe296b729f5bc087b3afc7f3be")
+        );
+        assert_eq!(
+            tx.encode_signed(&TEST_SIGNATURE),
+            hex!(
+                "04f8470103843b9aca008509502f900082c3509422222222222222222222222222222222222222228080c0dbda0194111111111111111111111111111111111111111180800304010102"
+            )
+        );
+    }
+
+    /// Pre-EIP-155 legacy transaction on Sepolia.
+    ///
+    /// <https://sepolia.etherscan.io/tx/0xe5b458ba9de30b47cb7c0ea836bec7b072053123a7416c5082c97f959a4eebd6>
+    #[test]
+    fn legacy_unprotected_vector() {
+        let raw = hex!(
+            "f8aa0285018ef61d0a832dc6c094cb33aa5b38d79e3d9fa8b10aff38aa201399a7e380b844af7b421018842e4628f3d9ee0e2c7679e29ed5dbaa75be75efecd392943503c9c68adce800000000000000000000000000000000000000000000000000000000000000641ca05e28679806caa50d25e9cb16aef8c0c08b235241b8f6e9d86faadf70421ba664a02353bba82ef2c7ce4dd6695942399163160000272b14f9aa6cbadf011b76efa4"
+        );
+
+        let (tx, signature) = Transaction::decode_signed(&raw).unwrap();
+        assert_eq!(tx.chain_id(), None);
+        assert_eq!(tx.encode_signed(&signature), raw);
+        assert_eq!(
+            Keccak256::digest(raw).as_slice(),
+            hex!("e5b458ba9de30b47cb7c0ea836bec7b072053123a7416c5082c97f959a4eebd6")
+        );
+    }
+
+    /// EIP-155 legacy transaction on mainnet.
+    ///
+    /// <https://etherscan.io/tx/0x280cde7cdefe4b188750e76c888f13bd05ce9a4d7767730feefe8a0e50ca6fc4>
+    #[test]
+    fn legacy_eip155_vector() {
+        let tx = check_signed_vector(
+            &hex!(
+                "f9015482078b8505d21dba0083022ef1947a250d5630b4cf539739df2c5dacb4c659f2488d880c46549a521b13d8b8e47ff36ab50000000000000000000000000000000000000000000066ab5a608bd00a23f2fe000000000000000000000000000000000000000000000000000000000000008000000000000000000000000048c04ed5691981c42154c6167398f95e8f38a7ff00000000000000000000000000000000000000000000000000000000632ceac70000000000000000000000000000000000000000000000000000000000000002000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000006c6ee5e31d828de241282b9606c8e98ea48526e225a0c9077369501641a92ef7399ff81c21639ed4fd8fc69cb793cfa1dbfab342e10aa0615facb2f1bcf3274a354cfe384a38d0cc008a11c2dd23a69111bc6930ba27a8"
+            ),
+            hex!("280cde7cdefe4b188750e76c888f13bd05ce9a4d7767730feefe8a0e50ca6fc4"),
+            "0xa12e1462d0ceD572f396F58B6E2D03894cD7C8a4",
+        );
+
+        assert_eq!(tx.chain_id(), Some(1));
+        assert_eq!(
+            tx.to(),
+            Some(
+                &"0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+                    .parse()
+                    .unwrap()
+            )
+        );
+    }
+
+    /// EIP-2930 transaction from the go-ethereum test suite.
+    ///
+    /// <https://github.com/ethereum/go-ethereum/blob/c503f98/core/types/transaction_test.go#L59-L67>
+    #[test]
+    fn eip2930_vector() {
+        let tx = Transaction::from(TxEip2930 {
+            chain_id: 1,
+            nonce: 3,
+            gas_price: U256::from(1u64),
+            gas_limit: 25000,
+            to: Some(
+                "0xb94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+                    .parse()
+                    .unwrap(),
+            ),
+            value: U256::from(10u64),
+            input: Bytes::from_static(&[0x55, 0x44]),
+            access_list: AccessList::default(),
+        });
+
+        assert_eq!(
+            tx.signing_hash().as_bytes(),
+            hex!("49b486f0ec0a60dfbbca2d30cb07c9e8ffb2a2ff41f29a1ab6737475f6ff69f3")
+        );
+
+        let signature = Signature {
+            r: U256::from_big_endian(&hex!(
+                "c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660"
+            )),
+            s: U256::from_big_endian(&hex!(
+                "32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521"
+            )),
+            y_parity: true,
+        };
+
+        let raw = hex!(
+            "01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c001a0c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521"
+        );
+
+        assert_eq!(tx.encode_signed(&signature), raw);
+        assert_eq!(Transaction::decode_signed(&raw).unwrap(), (tx, signature));
+    }
+
+    /// EIP-1559 transaction on mainnet.
+    ///
+    /// <https://etherscan.io/tx/0xce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31>
+    #[test]
+    fn eip1559_vector() {
+        let tx = check_signed_vector(
+            &hex!(
+                "02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac080a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a028ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8"
+            ),
+            hex!("ce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31"),
+            "0x001e2b7dE757bA469a57bF6b23d982458a07eFcE",
+        );
+
+        assert_eq!(tx.tx_type(), Some(0x02));
+        assert_eq!(
+            tx.to(),
+            Some(
+                &"0xD9e1459A7A482635700cBc20BBAF52D495Ab9C96"
+                    .parse()
+                    .unwrap()
+            )
+        );
+    }
+
+    /// EIP-4844 transaction on Sepolia.
+    ///
+    /// <https://sepolia.etherscan.io/tx/0x9a22ccb0029bc8b0ddd073be1a1d923b7ae2b2ea52100bae0db4424f9107e9c0>
+    #[test]
+    fn eip4844_vector() {
+        let tx = check_signed_vector(
+            &hex!(
+                "03f9011d83aa36a7820fa28477359400852e90edd0008252089411e9ca82a3a762b4b5bd264d4173a242e7a770648080c08504a817c800f8a5a0012ec3d6f66766bedb002a190126b3549fce0047de0d4c25cffce0dc1c57921aa00152d8e24762ff22b1cfd9f8c0683786a7ca63ba49973818b3d1e9512cd2cec4a0013b98c6c83e066d5b14af2b85199e3d4fc7d1e778dd53130d180f5077e2d1c7a001148b495d6e859114e670ca54fb6e2657f0cbae5b08063605093a4b3dc9f8f1a0011ac212f13c5dff2b2c6b600a79635103d6f580a4221079951181b25c7e654901a0c8de4cced43169f9aa3d36506363b2d2c44f6c49fc1fd91ea114c86f3757077ea01e11fdd0d1934eda0492606ee0bb80a7bf8f35cc5f86ec60fe5031ba48bfd544"
+            ),
+            hex!("9a22ccb0029bc8b0ddd073be1a1d923b7ae2b2ea52100bae0db4424f9107e9c0"),
+            "0xA83C816D4f9b2783761a22BA6FADB0eB0606D7B2",
+        );
+
+        assert!(matches!(
+            tx,
+            Transaction::Eip4844(tx)
+                if tx.chain_id == 11155111 && tx.blob_versioned_hashes.len() == 5
+        ));
+    }
+
+    #[test]
+    fn decode_round_trip() {
+        let signed = hex!(
+            "04f8470103843b9aca008509502f900082c3509422222222222222222222222222222222222222228080c0dbda0194111111111111111111111111111111111111111180800304010102"
+        );
+
+        let (tx, signature) = Transaction::decode_signed(&signed).unwrap();
+        assert_eq!(signature, TEST_SIGNATURE);
+        assert_eq!(tx.encode_signed(&signature), signed);
+
+        assert!(matches!(
+            &tx,
+            Transaction::Eip7702(tx) if tx.authorization_list.len() == 1
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_malformed() {
+        let signed = hex!(
+            "02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac080a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a028ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8"
+        );
+
+        // Unknown transaction type
+        let mut unknown_type = signed;
+        unknown_type[0] = 0x7f;
+        assert!(Transaction::decode_signed(&unknown_type).is_err());
+
+        // Truncated and trailing data
+        assert!(Transaction::decode_signed(signed.split_last().unwrap().1).is_err());
+        let mut trailing = signed.to_vec();
+        trailing.push(0);
+        assert!(Transaction::decode_signed(&trailing).is_err());
+
+        // Empty input
+        assert!(Transaction::decode_signed(&[]).is_err());
+
+        // Legacy `v` values which are neither 27/28 nor EIP-155
+        assert!(Signature::decode_v(29).is_err());
+        assert!(Signature::decode_v(35).is_err());
+        assert!(Signature::decode_v(36).is_err());
+        assert_eq!(Signature::decode_v(37).unwrap(), (Some(1), false));
+        assert_eq!(Signature::decode_v(28).unwrap(), (None, true));
+    }
+
+    /// Transaction vectors: the EIP-155 example and Ethereum mainnet
+    /// transactions (identified by their hash), along with invalid RLP,
+    /// non-canonical encodings, and invalid `v` values derived from them.
+    ///
+    /// Each valid vector is decoded, re-encoded, and has its signer recovered
+    /// from the signing hash, which is also checked where it's published.
+    #[test]
+    fn transaction_vectors() {
+        let vectors: serde_json::Map<String, serde_json::Value> = serde_json::from_str(
+            include_str!("../../tests/fixtures/transaction_vectors.json"),
+        )
+        .unwrap();
+
+        let decode_hex = |s: &serde_json::Value| hex::lower::decode_vec(&s.as_str().unwrap()[2..]);
+
+        for (name, vector) in &vectors {
+            let raw = decode_hex(&vector["txbytes"]).unwrap();
+
+            if vector["valid"] == false {
+                assert!(
+                    Transaction::decode_signed(&raw).is_err(),
+                    "{name}: {}",
+                    vector["comment"]
+                );
+                continue;
+            }
+
+            let hash = decode_hex(&vector["hash"]).unwrap();
+            let sender = vector["sender"].as_str().unwrap();
+            let tx = check_signed_vector(&raw, hash.try_into().unwrap(), sender);
+            assert!(tx.chain_id().is_some(), "{name}: missing chain ID");
+
+            if let Some(signing_hash) = vector.get("signingHash") {
+                let signing_hash = decode_hex(signing_hash).unwrap();
+                assert_eq!(tx.signing_hash().as_bytes(), signing_hash, "{name}");
+            }
+        }
+    }
+}
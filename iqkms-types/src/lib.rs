@@ -16,6 +16,11 @@
     unused_lifetimes,
     unused_qualifications
 )]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
+
+#[cfg(feature = "cosmos")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cosmos")))]
+pub mod cosmos;
 
 #[cfg(feature = "ethereum")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ethereum")))]
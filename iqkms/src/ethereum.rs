@@ -2,11 +2,17 @@
 
 pub use types::{
     crypto::digest::{Digest, sha3::Keccak256},
-    ethereum::{Address, ChainId, H256},
+    ethereum::{
+        Address, ChainId, H256, U256, eip4361, eip7702::Authorization, safe::SafeTx,
+        transaction::Transaction,
+    },
 };
 
 use crate::{Error, StdError};
-use proto::ethereum::{SignDigestRequest, SignEip155Request, Signature};
+use proto::ethereum::{
+    SignAuthorizationRequest, SignDigestRequest, SignEip155Request, SignSafeTransactionRequest,
+    SignSiweRequest, SignTransactionRequest, Signature,
+};
 use tonic::{Request, transport};
 
 /// Tonic-generated inner gRPC client.
@@ -75,6 +81,134 @@ impl SignerClient {
         self.sign_digest_with_eip155(address, digest, chain_id)
             .await
     }
+
+    /// Sign the given transaction using the private key with the given
+    /// address, after the server has checked it against its chain registry
+    /// and policy.
+    ///
+    /// Returns the signature, whose `v` component is the `y_parity`, along
+    /// with the signed transaction envelope.
+    pub async fn sign_transaction(
+        &mut self,
+        address: Address,
+        tx: &Transaction,
+    ) -> Result<(Signature, Vec<u8>), Error> {
+        let request = SignTransactionRequest {
+            address: address.to_string(),
+            transaction: tx.encode_for_signing(),
+        };
+
+        let response = self
+            .inner
+            .sign_transaction(Request::new(request))
+            .await?
+            .into_inner();
+        let signature = response
+            .signature
+            .ok_or_else(|| Error::from(tonic::Status::internal("response is missing signature")))?;
+
+        Ok((signature, response.signed_transaction))
+    }
+
+    /// Sign the given EIP-7702 authorization using the private key with the
+    /// given address.
+    ///
+    /// The `v` component of the returned signature is the `y_parity`.
+    pub async fn sign_authorization(
+        &mut self,
+        address: Address,
+        authorization: &Authorization,
+    ) -> Result<Signature, Error> {
+        let request = SignAuthorizationRequest {
+            address: address.to_string(),
+            chain_id: authorization.chain_id,
+            delegate: authorization.address.to_string(),
+            nonce: authorization.nonce,
+        };
+
+        let response = self.inner.sign_authorization(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Sign the given Sign-In with Ethereum message using the private key
+    /// with the message's address.
+    ///
+    /// Returns the canonical rendering of the message which was signed along
+    /// with its EIP-191 signature.
+    pub async fn sign_siwe_message(
+        &mut self,
+        msg: &eip4361::Message,
+    ) -> Result<(String, Signature), Error> {
+        let request = SignSiweRequest {
+            domain: msg.domain.clone(),
+            address: msg.address.to_string(),
+            statement: msg.statement.clone().unwrap_or_default(),
+            uri: msg.uri.clone(),
+            chain_id: msg.chain_id,
+            nonce: msg.nonce.clone(),
+            issued_at: msg.issued_at.clone(),
+            expiration_time: msg.expiration_time.clone().unwrap_or_default(),
+            not_before: msg.not_before.clone().unwrap_or_default(),
+            request_id: msg.request_id.clone().unwrap_or_default(),
+            resources: msg.resources.clone(),
+            scheme: msg.scheme.clone().unwrap_or_default(),
+        };
+
+        let response = self
+            .inner
+            .sign_siwe(Request::new(request))
+            .await?
+            .into_inner();
+        let signature = response
+            .signature
+            .ok_or_else(|| Error::from(tonic::Status::internal("response is missing signature")))?;
+
+        Ok((response.message, signature))
+    }
+
+    /// Sign the given Safe transaction as the owner with the given address.
+    ///
+    /// If `eth_sign` is set, the `eth_sign` (EIP-191) encoding of the Safe
+    /// transaction hash is signed instead of the hash itself.
+    ///
+    /// Returns the signature in Safe's packed `r || s || v` format.
+    pub async fn sign_safe_transaction(
+        &mut self,
+        owner: Address,
+        tx: &SafeTx,
+        eth_sign: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let gas = |n: U256| {
+            u64::try_from(n).map_err(|_| {
+                Error::from(tonic::Status::invalid_argument("gas value exceeds 64 bits"))
+            })
+        };
+
+        let request = SignSafeTransactionRequest {
+            address: owner.to_string(),
+            safe: tx.safe.to_string(),
+            chain_id: tx.chain_id,
+            to: tx.to.to_string(),
+            value: uint256_bytes(tx.value),
+            data: tx.data.to_vec(),
+            operation: tx.operation as u32,
+            safe_tx_gas: gas(tx.safe_tx_gas)?,
+            base_gas: gas(tx.base_gas)?,
+            gas_price: uint256_bytes(tx.gas_price),
+            gas_token: tx.gas_token.to_string(),
+            refund_receiver: tx.refund_receiver.to_string(),
+            nonce: u64::try_from(tx.nonce).map_err(|_| {
+                Error::from(tonic::Status::invalid_argument("nonce exceeds 64 bits"))
+            })?,
+            eth_sign,
+        };
+
+        let response = self
+            .inner
+            .sign_safe_transaction(Request::new(request))
+            .await?;
+        Ok(response.into_inner().signature)
+    }
 }
 
 impl From<SignerClientInner> for SignerClient {
@@ -82,3 +216,10 @@ impl From<SignerClientInner> for SignerClient {
         SignerClient { inner }
     }
 }
+
+/// Serialize a 256-bit integer as big endian bytes.
+fn uint256_bytes(n: U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    n.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
@@ -0,0 +1,114 @@
+//! CometBFT validator remote signer.
+//!
+//! Also provides a command for importing double-sign protection state from
+//! tmkms or CometBFT. Importing is refused while the state is in use (e.g. by
+//! a running iqkmsd).
+//!
+//! Usage: `iqkmsd <config> cometbft-state import <chain ID> <priv_validator_state.json>`
+
+use crate::config::{CometbftConfig, Config};
+use cometbft::{Client, PrivValidator};
+use signing::SigningService;
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use tower::{BoxError, Service};
+use tracing::info;
+
+/// Add the consensus keys of the configured validators to the keyring.
+///
+/// Validators may share a consensus key (e.g. across chains), in which case
+/// it's only added once.
+pub fn add_keys(
+    config: &CometbftConfig,
+    signing_service: &mut SigningService,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut verifying_keys = Vec::new();
+
+    for validator in &config.validators {
+        let signing_key = validator.load_key()?;
+        let verifying_key = signing_key.verifying_key();
+
+        if !verifying_keys.contains(&verifying_key) {
+            signing_service.add_key(signing_key)?;
+            verifying_keys.push(verifying_key);
+        }
+    }
+
+    Ok(())
+}
+
+/// Spawn a client for each of the configured validators.
+pub fn spawn<S>(
+    config: &CometbftConfig,
+    signing_service: S,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    let identity_key = config.identity_key()?;
+    let mut sign_states = BTreeMap::new();
+
+    for validator in &config.validators {
+        // Each chain has a single double-sign protection state, which is
+        // shared by all of its validator nodes (e.g. sentries)
+        let sign_state = match sign_states.get(&validator.chain_id) {
+            Some(sign_state) => Arc::clone(sign_state),
+            None => {
+                let sign_state = Arc::new(Mutex::new(config.sign_state(&validator.chain_id)?));
+                sign_states.insert(validator.chain_id.clone(), Arc::clone(&sign_state));
+                sign_state
+            }
+        };
+
+        let addr = validator.addr()?;
+        let verifying_key = validator.load_key()?.verifying_key();
+        let priv_validator =
+            PrivValidator::with_shared_state(verifying_key, signing_service.clone(), sign_state);
+
+        info!(
+            "CometBFT signer for {} dialing {}",
+            validator.chain_id, addr
+        );
+        let client = Client::new(addr, identity_key.clone(), priv_validator)
+            .with_timeouts(validator.timeouts());
+        tokio::spawn(client.run());
+    }
+
+    Ok(())
+}
+
+/// Run a double-sign protection state command.
+pub fn run(config: &Config, args: &[OsString]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "usage: iqkmsd <config> cometbft-state import <chain ID> <file>";
+    let (chain_id, path) = match args {
+        [_, subcommand, chain_id, path] if subcommand == "import" => (chain_id, path),
+        _ => return Err(usage.into()),
+    };
+
+    let chain_id = chain_id.to_str().ok_or("invalid chain ID")?;
+    let mut sign_state = config
+        .cometbft
+        .as_ref()
+        .ok_or("cometbft is not configured")?
+        .sign_state(chain_id)?;
+
+    sign_state.import(&fs::read_to_string(path)?)?;
+    println!(
+        "Imported {} double-sign protection state ({}) from {}",
+        chain_id,
+        sign_state.consensus_state(),
+        Path::new(path).display()
+    );
+
+    Ok(())
+}
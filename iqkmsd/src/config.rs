@@ -0,0 +1,504 @@
+//! iqkmsd configuration.
+
+use crate::logging::Logger;
+use base64ct::Encoding;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::Level;
+
+/// Default address to listen for gRPC connections on.
+pub const DEFAULT_GRPC_ADDR: &str = "[::1]:27100";
+
+/// iqkmsd configuration file (TOML).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// gRPC service configuration.
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+
+    /// Ethereum signing policy configuration.
+    #[serde(default)]
+    pub ethereum: EthereumConfig,
+
+    /// Ethereum JSON-RPC signer endpoint configuration (disabled if absent).
+    pub json_rpc: Option<JsonRpcConfig>,
+
+    /// CometBFT validator remote signer configuration (disabled if absent).
+    pub cometbft: Option<CometbftConfig>,
+
+    /// Cosmos SDK transaction signing policy configuration.
+    #[serde(default)]
+    pub cosmos: CosmosConfig,
+
+    /// Logging configuration.
+    #[serde(default)]
+    pub log: LogConfig,
+}
+
+impl Config {
+    /// Load configuration from the given TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let toml_string = fs::read_to_string(path)?;
+        Ok(toml::from_str(&toml_string)?)
+    }
+}
+
+/// gRPC service configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcConfig {
+    /// Address to listen on.
+    pub addr: SocketAddr,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            addr: DEFAULT_GRPC_ADDR.parse().expect("invalid default address"),
+        }
+    }
+}
+
+/// Logging configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogConfig {
+    /// Most verbose level of events to log to stderr (`error`, `warn`,
+    /// `info`, `debug`, or `trace`), defaulting to `info`.
+    pub level: Option<String>,
+
+    /// Path to the audit log, which records everything that is signed.
+    ///
+    /// Audit events are appended to it in addition to being logged to stderr.
+    pub audit_log: Option<PathBuf>,
+}
+
+impl LogConfig {
+    /// Build the logger described by this configuration.
+    pub fn logger(&self) -> Result<Logger, Box<dyn std::error::Error>> {
+        let level = match &self.level {
+            Some(level) => level
+                .parse()
+                .map_err(|_| format!("invalid log level: {level}"))?,
+            None => Level::INFO,
+        };
+
+        let logger = Logger::new(level);
+
+        match &self.audit_log {
+            Some(path) => {
+                let audit_log = OpenOptions::new().append(true).create(true).open(path)?;
+                Ok(logger.with_audit_log(audit_log))
+            }
+            None => Ok(logger),
+        }
+    }
+}
+
+/// Cosmos SDK transaction signing policy configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CosmosConfig {
+    /// Message types transactions may contain (all types if empty).
+    ///
+    /// Protobuf type URLs (e.g. `/cosmos.bank.v1beta1.MsgSend`) apply to
+    /// `SIGN_MODE_DIRECT`, and amino type names (e.g. `cosmos-sdk/MsgSend`)
+    /// to amino JSON.
+    #[serde(default)]
+    pub allowed_msg_types: Vec<String>,
+}
+
+impl CosmosConfig {
+    /// Build the signing policy described by this configuration.
+    pub fn policy(&self) -> cosmos::Policy {
+        self.allowed_msg_types
+            .iter()
+            .fold(cosmos::Policy::new(), |policy, msg_type| {
+                policy.allow_msg_type(msg_type.as_str())
+            })
+    }
+}
+
+/// Ethereum signing policy configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EthereumConfig {
+    /// Delegate contracts EIP-7702 authorizations may point to.
+    #[serde(default)]
+    pub allowed_delegates: Vec<String>,
+
+    /// Chains requests may be signed for (defaults to Ethereum mainnet and
+    /// its public testnets if empty).
+    #[serde(default)]
+    pub chains: Vec<ChainConfig>,
+
+    /// Contracts whose ABIs are used to decode calldata.
+    #[serde(default)]
+    pub contracts: Vec<ContractConfig>,
+
+    /// Domains each key (by address) may sign in to with Sign-In with
+    /// Ethereum.
+    #[serde(default)]
+    pub allowed_siwe_domains: BTreeMap<String, Vec<String>>,
+
+    /// Deny signing raw digests, personal messages, and typed data, which
+    /// would otherwise bypass the signing policy.
+    #[serde(default)]
+    pub deny_unchecked_signing: bool,
+
+    /// EIP-2335 keystores containing consensus layer validator keys.
+    #[serde(default)]
+    pub keystores: Vec<KeystoreConfig>,
+
+    /// Path to the slashing protection database.
+    ///
+    /// Required to sign consensus layer messages.
+    pub slashing_protection_db: Option<PathBuf>,
+}
+
+impl EthereumConfig {
+    /// Build the signing policy described by this configuration.
+    pub fn policy(&self) -> Result<ethereum::Policy, Box<dyn std::error::Error>> {
+        let mut policy = ethereum::Policy::new();
+
+        for delegate in &self.allowed_delegates {
+            let address = delegate
+                .parse()
+                .map_err(|_| format!("malformed delegate address: {delegate}"))?;
+
+            policy = policy.allow_delegate(address);
+        }
+
+        for (address, domains) in &self.allowed_siwe_domains {
+            let address = address
+                .parse()
+                .map_err(|_| format!("malformed signer address: {address}"))?;
+
+            for domain in domains {
+                policy = policy.allow_siwe_domain(address, domain);
+            }
+        }
+
+        for contract in &self.contracts {
+            if let Some(max_amount) = &contract.max_transfer_amount {
+                let max_amount = types::ethereum::U256::from_dec_str(max_amount)
+                    .map_err(|_| format!("malformed max_transfer_amount: {max_amount}"))?;
+
+                policy = policy.limit_erc20_transfers(contract.address()?, max_amount);
+            }
+        }
+
+        if self.deny_unchecked_signing {
+            policy = policy.deny_unchecked_signing();
+        }
+
+        Ok(policy)
+    }
+
+    /// Load the contract ABIs described by this configuration.
+    pub fn abis(&self) -> Result<ethereum::AbiRegistry, Box<dyn std::error::Error>> {
+        let mut abis = ethereum::AbiRegistry::new();
+
+        for contract in &self.contracts {
+            let abi = types::ethereum::abi::Abi::from_json(&fs::read_to_string(&contract.abi)?)
+                .map_err(|_| format!("malformed ABI: {}", contract.abi.display()))?;
+
+            abis = abis.with_contract(contract.address()?, &contract.name, abi);
+        }
+
+        Ok(abis)
+    }
+
+    /// Build the chain registry described by this configuration.
+    pub fn chains(&self) -> Result<ethereum::ChainRegistry, Box<dyn std::error::Error>> {
+        if self.chains.is_empty() {
+            return Ok(ethereum::ChainRegistry::default());
+        }
+
+        let mut registry = ethereum::ChainRegistry::empty();
+
+        for chain in &self.chains {
+            if registry.get(chain.id).is_ok() {
+                return Err(format!("duplicate chain ID: {}", chain.id).into());
+            }
+
+            registry = registry.with_chain(
+                ethereum::Chain::new(chain.id, &chain.name)
+                    .with_eip1559(chain.eip1559)
+                    .with_checksum(chain.checksum.into()),
+            );
+        }
+
+        Ok(registry)
+    }
+
+    /// Open the slashing protection database, if configured.
+    pub fn slashing_protection(
+        &self,
+    ) -> Result<Option<ethereum::SlashingProtection>, Box<dyn std::error::Error>> {
+        match &self.slashing_protection_db {
+            Some(path) => Ok(Some(ethereum::SlashingProtection::open(path)?)),
+            None if self.keystores.is_empty() => Ok(None),
+            None => Err("slashing_protection_db must be configured to use keystores".into()),
+        }
+    }
+}
+
+/// Contract ABI configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractConfig {
+    /// Address of the contract.
+    pub address: String,
+
+    /// Human-readable name of the contract (e.g. token symbol).
+    pub name: String,
+
+    /// Path to the contract's JSON ABI.
+    pub abi: PathBuf,
+
+    /// Maximum amount (in the token's base units, as a decimal string) which
+    /// may be transferred or approved in a single ERC-20 call.
+    ///
+    /// If set, calls to the contract other than `transfer`, `transferFrom`
+    /// and `approve` are denied.
+    pub max_transfer_amount: Option<String>,
+}
+
+impl ContractConfig {
+    /// Parse the address of the contract.
+    fn address(&self) -> Result<types::ethereum::Address, Box<dyn std::error::Error>> {
+        self.address
+            .parse()
+            .map_err(|_| format!("malformed contract address: {}", self.address).into())
+    }
+}
+
+/// Chain configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainConfig {
+    /// EIP-155 chain ID.
+    pub id: u64,
+
+    /// Human-readable name of the chain.
+    pub name: String,
+
+    /// Does the chain support EIP-1559 (and later) typed transactions?
+    #[serde(default = "default_eip1559")]
+    pub eip1559: bool,
+
+    /// Checksum encoding used for addresses on the chain.
+    #[serde(default)]
+    pub checksum: ChecksumConfig,
+}
+
+/// Chains support EIP-1559 unless configured otherwise.
+fn default_eip1559() -> bool {
+    true
+}
+
+/// Address checksum encoding.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumConfig {
+    /// EIP-55 checksums.
+    #[default]
+    Eip55,
+
+    /// EIP-1191 chain-specific checksums.
+    Eip1191,
+}
+
+impl From<ChecksumConfig> for ethereum::ChecksumStyle {
+    fn from(checksum: ChecksumConfig) -> ethereum::ChecksumStyle {
+        match checksum {
+            ChecksumConfig::Eip55 => ethereum::ChecksumStyle::Eip55,
+            ChecksumConfig::Eip1191 => ethereum::ChecksumStyle::Eip1191,
+        }
+    }
+}
+
+/// EIP-2335 keystore configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeystoreConfig {
+    /// Path to the keystore JSON file.
+    pub path: PathBuf,
+
+    /// Path to a file containing the keystore's password.
+    pub password_file: PathBuf,
+}
+
+impl KeystoreConfig {
+    /// Load and decrypt the signing key stored in this keystore.
+    pub fn load(&self) -> Result<signing::SigningKey, Box<dyn std::error::Error>> {
+        let keystore = signing::keystore::Keystore::from_json(&fs::read_to_string(&self.path)?)?;
+        let password = fs::read_to_string(&self.password_file)?;
+
+        keystore
+            .decrypt(&password)
+            .map_err(|_| format!("couldn't decrypt keystore: {}", self.path.display()).into())
+    }
+}
+
+/// Ethereum JSON-RPC signer endpoint configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonRpcConfig {
+    /// Address to listen for HTTP connections on.
+    pub addr: SocketAddr,
+
+    /// ID of the chain whose address checksums are used by requests which
+    /// don't carry a chain ID, e.g. `eth_accounts` (EIP-55 if absent).
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+}
+
+/// CometBFT validator remote signer configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CometbftConfig {
+    /// Path to the 32-byte Ed25519 seed used to authenticate secret
+    /// connections to validators.
+    pub identity_key: PathBuf,
+
+    /// Directory containing the double-sign protection state of each chain.
+    pub state_dir: PathBuf,
+
+    /// Validators to dial.
+    ///
+    /// Several nodes of the same validator (e.g. sentries) may be dialed for
+    /// a chain, in which case they share its double-sign protection state.
+    #[serde(default)]
+    pub validators: Vec<ValidatorConfig>,
+}
+
+impl CometbftConfig {
+    /// Load the identity key used to authenticate to validators.
+    pub fn identity_key(&self) -> Result<cometbft::IdentitySecret, Box<dyn std::error::Error>> {
+        let bytes = fs::read(&self.identity_key)?;
+        let seed = bytes.as_slice().try_into().map_err(|_| {
+            format!(
+                "malformed identity key (expected 32 bytes): {}",
+                self.identity_key.display()
+            )
+        })?;
+
+        Ok(cometbft::IdentitySecret::from_bytes(seed))
+    }
+
+    /// Open the double-sign protection state of the given chain.
+    pub fn sign_state(
+        &self,
+        chain_id: &str,
+    ) -> Result<cometbft::SignState, Box<dyn std::error::Error>> {
+        let valid_chain_id = !chain_id.is_empty()
+            && chain_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !chain_id.starts_with('.');
+
+        if !valid_chain_id {
+            return Err(format!("invalid chain ID: {chain_id}").into());
+        }
+
+        let path = self
+            .state_dir
+            .join(format!("{chain_id}_priv_validator_state.json"));
+
+        Ok(cometbft::SignState::open(chain_id, path)?)
+    }
+}
+
+/// Validator dialed by the CometBFT remote signer.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorConfig {
+    /// Chain ID of the validator.
+    pub chain_id: String,
+
+    /// Address of the validator's privval listener, e.g.
+    /// `tcp://<peer ID>@host:port` or `unix:///path/to/socket`.
+    pub addr: String,
+
+    /// Path to the validator's consensus key in CometBFT's
+    /// `priv_validator_key.json` format.
+    pub key: PathBuf,
+
+    /// Timeout in seconds for connecting to the validator, and for reads and
+    /// writes which make no progress once connected (defaults to 10).
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl ValidatorConfig {
+    /// Parse the validator's address.
+    pub fn addr(&self) -> Result<cometbft::ValidatorAddr, Box<dyn std::error::Error>> {
+        Ok(self.addr.parse()?)
+    }
+
+    /// Get the timeouts for connections to the validator.
+    pub fn timeouts(&self) -> cometbft::Timeouts {
+        self.timeout
+            .map(|secs| cometbft::Timeouts::new(Duration::from_secs(secs)))
+            .unwrap_or_default()
+    }
+
+    /// Load the validator's consensus key.
+    pub fn load_key(
+        &self,
+    ) -> Result<signing::signature::ed25519::SigningKey, Box<dyn std::error::Error>> {
+        let malformed = || format!("malformed validator key: {}", self.key.display());
+        let key_file: PrivValidatorKey = serde_json::from_str(&fs::read_to_string(&self.key)?)?;
+
+        if key_file.priv_key.r#type != "tendermint/PrivKeyEd25519" {
+            return Err(format!(
+                "unsupported validator key type: {}",
+                key_file.priv_key.r#type
+            )
+            .into());
+        }
+
+        // The private key is serialized as `seed || public key`
+        let keypair =
+            base64ct::Base64::decode_vec(&key_file.priv_key.value).map_err(|_| malformed())?;
+        if keypair.len() != 64 {
+            return Err(malformed().into());
+        }
+
+        let (seed, public_key) = keypair.split_at(32);
+        let signing_key =
+            signing::signature::ed25519::SigningKey::from_bytes(seed).map_err(|_| malformed())?;
+
+        if signing_key.verifying_key().as_ref() != public_key {
+            return Err(malformed().into());
+        }
+
+        Ok(signing_key)
+    }
+}
+
+/// CometBFT `priv_validator_key.json` file.
+#[derive(Deserialize)]
+struct PrivValidatorKey {
+    /// Private key.
+    priv_key: TypedKey,
+}
+
+/// Amino JSON-encoded key.
+#[derive(Deserialize)]
+struct TypedKey {
+    /// Amino type name.
+    r#type: String,
+
+    /// Base64-encoded key.
+    value: String,
+}
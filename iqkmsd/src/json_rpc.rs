@@ -0,0 +1,83 @@
+//! HTTP server for the Ethereum JSON-RPC signer endpoint.
+
+use ethereum::JsonRpcService;
+use hyper::{
+    Body, Method, Request, Response, Server, StatusCode,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+};
+use std::{convert::Infallible, net::SocketAddr};
+use tower::{BoxError, Service};
+
+/// Maximum size of a JSON-RPC request body.
+const MAX_BODY_SIZE: u64 = 1_048_576; // 1 MiB
+
+/// Bind the given address, returning a future which serves the given JSON-RPC
+/// service over HTTP on it.
+pub fn serve<S>(
+    addr: SocketAddr,
+    service: JsonRpcService<S>,
+) -> Result<impl Future<Output = Result<(), hyper::Error>>, hyper::Error>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    let make_service = make_service_fn(move |_conn| {
+        let service = service.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let service = service.clone();
+                async move { Ok::<_, Infallible>(handle(&service, req).await) }
+            }))
+        }
+    });
+
+    Ok(Server::try_bind(&addr)?.serve(make_service))
+}
+
+/// Handle an incoming HTTP request.
+async fn handle<S>(service: &JsonRpcService<S>, req: Request<Body>) -> Response<Body>
+where
+    S: Service<signing::Request, Response = signing::Response, Error = BoxError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    if req.method() != Method::POST {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    if hyper::body::HttpBody::size_hint(req.body()).lower() > MAX_BODY_SIZE {
+        return status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) if body.len() as u64 <= MAX_BODY_SIZE => body,
+        Ok(_) => return status(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(_) => return status(StatusCode::BAD_REQUEST),
+    };
+
+    let Some(response_body) = service.handle(&body).await else {
+        // Notifications don't get a response
+        return status(StatusCode::NO_CONTENT);
+    };
+
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(response_body))
+        .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+/// Create an empty response with the given status code.
+fn status(code: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = code;
+    response
+}
@@ -0,0 +1,221 @@
+//! Logging.
+//!
+//! Events are written to stderr by a minimal `tracing` subscriber. Audit
+//! events (i.e. those with the `iqkms::audit` target, which record what was
+//! signed) are also appended to the audit log, if one is configured.
+
+use std::{
+    fmt::{self, Write as _},
+    io::Write,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+
+/// `tracing` target for audit log entries.
+const AUDIT_TARGET: &str = "iqkms::audit";
+
+/// `tracing` subscriber which writes events to stderr and audit events to the
+/// audit log.
+pub struct Logger {
+    /// Most verbose level of events to log.
+    level: Level,
+
+    /// Audit log (if any).
+    audit_log: Option<Mutex<Box<dyn Write + Send>>>,
+
+    /// ID of the next span.
+    next_span_id: AtomicU64,
+}
+
+impl Logger {
+    /// Create a new logger which logs events up to the given level.
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            audit_log: None,
+            next_span_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Append audit events to the given audit log.
+    pub fn with_audit_log(mut self, audit_log: impl Write + Send + 'static) -> Self {
+        self.audit_log = Some(Mutex::new(Box::new(audit_log)));
+        self
+    }
+
+    /// Append a line to the audit log and flush it.
+    fn audit(&self, line: &str) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+
+        let Ok(mut audit_log) = audit_log.lock() else {
+            eprintln!("audit log lock poisoned: {line}");
+            return;
+        };
+
+        if let Err(e) = writeln!(audit_log, "{line}").and_then(|_| audit_log.flush()) {
+            eprintln!("error writing audit log: {e}: {line}");
+        }
+    }
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level || metadata.target() == AUDIT_TARGET
+    }
+
+    fn new_span(&self, _attrs: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(self.next_span_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let line = format!(
+            "{}.{:03} {} {}: {}{}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.fields
+        );
+
+        if metadata.target() == AUDIT_TARGET {
+            self.audit(&line);
+        }
+
+        eprintln!("{line}");
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+/// Fields of an event, formatted as a message followed by `name=value` pairs.
+#[derive(Default)]
+struct Fields {
+    /// Event message.
+    message: String,
+
+    /// Other fields, each preceded by a space.
+    fields: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Logger;
+    use ethereum::{JsonRpcService, SignerService};
+    use serde_json::json;
+    use signing::{SigningKey, SigningService};
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+    use tracing::Level;
+    use types::crypto::signature::ecdsa::secp256k1;
+
+    /// Audit log which can be inspected by the test.
+    #[derive(Clone, Default)]
+    struct AuditLog(Arc<Mutex<Vec<u8>>>);
+
+    impl AuditLog {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for AuditLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn audit_log() {
+        let audit_log = AuditLog::default();
+        let logger = Logger::new(Level::WARN).with_audit_log(audit_log.clone());
+        let _guard = tracing::subscriber::set_default(logger);
+
+        let mut signing_service = SigningService::new();
+        let signing_key = secp256k1::SigningKey::from_bytes(&[0x46; 32]).unwrap();
+        signing_service
+            .add_key(SigningKey::from(signing_key))
+            .unwrap();
+
+        let signing_service = tower::ServiceBuilder::new()
+            .buffer(1)
+            .service(signing_service);
+        let service = JsonRpcService::new(SignerService::new(signing_service));
+
+        let tx = json!({
+            "from": "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F",
+            "to": "0x3535353535353535353535353535353535353535",
+            "nonce": "0x9",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "value": "0xde0b6b3a7640000",
+            "chainId": "0x1"
+        });
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_signTransaction",
+            "params": [tx]
+        });
+        service
+            .handle(request.to_string().as_bytes())
+            .await
+            .unwrap();
+
+        let contents = audit_log.contents();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains(
+            "INFO iqkms::audit: signing transaction to 0x3535353535353535353535353535353535353535"
+        ));
+        assert!(lines[0].contains("signer=0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"));
+
+        // Events which aren't audit events are not written to the audit log
+        tracing::warn!("not an audit event");
+        assert_eq!(audit_log.contents(), contents);
+    }
+}
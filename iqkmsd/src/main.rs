@@ -1,22 +1,97 @@
+mod cometbft;
+mod config;
+mod json_rpc;
+mod logging;
+mod slashing_protection;
+
+use crate::config::Config;
 use signing::SigningService;
+use std::{env, future};
+use tracing::info;
+
+/// Command-line usage.
+const USAGE: &str = "usage: iqkmsd [<config> [(cometbft-state|slashing-protection) <args>...]]";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let addr = "[::1]:27100".parse().unwrap();
+    let mut args = env::args_os().skip(1);
+    let config = match args.next() {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
+    tracing::subscriber::set_global_default(config.log.logger()?)?;
+
+    let command = args.collect::<Vec<_>>();
+    if let Some(subcommand) = command.first() {
+        return match subcommand.to_str() {
+            Some("cometbft-state") => cometbft::run(&config, &command),
+            Some("slashing-protection") => slashing_protection::run(&config, &command),
+            _ => Err(USAGE.into()),
+        };
+    }
+
+    let mut signing_service = SigningService::new();
+
+    for keystore in &config.ethereum.keystores {
+        signing_service.add_key(keystore.load()?)?;
+    }
+
+    if let Some(cometbft_config) = &config.cometbft {
+        cometbft::add_keys(cometbft_config, &mut signing_service)?;
+    }
 
     let signing_service = tower::ServiceBuilder::new()
         .buffer(10) // TODO(tarcieri): tune buffer size
-        .service(SigningService::new());
+        .service(signing_service);
+
+    if let Some(cometbft_config) = &config.cometbft {
+        cometbft::spawn(cometbft_config, signing_service.clone())?;
+    }
+
+    let consensus_service = config
+        .ethereum
+        .slashing_protection()?
+        .map(|db| ethereum::ConsensusSignerService::new(signing_service.clone(), db));
+    let cosmos_service =
+        cosmos::SignerService::new(signing_service.clone()).with_policy(config.cosmos.policy());
+    let eth_service = ethereum::SignerService::new(signing_service)
+        .with_policy(config.ethereum.policy()?)
+        .with_chains(config.ethereum.chains()?)
+        .with_abis(config.ethereum.abis()?);
 
-    let eth_service = ethereum::SignerService::new(signing_service);
+    // Bind the JSON-RPC listener up front so failing to do so is fatal
+    let json_rpc_server = match &config.json_rpc {
+        Some(json_rpc_config) => {
+            let json_rpc_service = ethereum::JsonRpcService::new(eth_service.clone())
+                .with_chain_id(json_rpc_config.chain_id.unwrap_or_default());
+            let server = json_rpc::serve(json_rpc_config.addr, json_rpc_service)?;
+            info!("JSON-RPC listening on {}", json_rpc_config.addr);
+            Some(server)
+        }
+        None => None,
+    };
 
-    // TODO(tarcieri): use tracing for logging
-    println!("Listening on {}", addr);
+    info!("Listening on {}", config.grpc.addr);
 
-    tonic::transport::Server::builder()
+    let grpc_server = tonic::transport::Server::builder()
+        .add_service(cosmos::SignerServer::new(cosmos_service))
         .add_service(ethereum::SignerServer::new(eth_service))
-        .serve(addr)
-        .await?;
+        .add_optional_service(consensus_service.map(ethereum::ConsensusSignerServer::new))
+        .serve(config.grpc.addr);
+
+    let json_rpc_server = async {
+        match json_rpc_server {
+            Some(server) => server.await,
+            None => future::pending().await,
+        }
+    };
+
+    // Exit if either server stops
+    tokio::select! {
+        result = grpc_server => result?,
+        result = json_rpc_server => result?,
+    }
 
     Ok(())
 }
@@ -0,0 +1,40 @@
+//! Slashing protection database management commands.
+//!
+//! Usage: `iqkmsd <config> slashing-protection (import|export) <interchange.json>`
+
+use crate::config::Config;
+use ethereum::slashing_protection::Interchange;
+use std::{ffi::OsString, fs, path::Path};
+
+/// Run a slashing protection command.
+pub fn run(config: &Config, args: &[OsString]) -> Result<(), Box<dyn std::error::Error>> {
+    let (subcommand, path) = match args {
+        [command, subcommand, path] if command == "slashing-protection" => (subcommand, path),
+        _ => {
+            return Err("usage: iqkmsd <config> slashing-protection (import|export) <file>".into());
+        }
+    };
+
+    let mut db = config
+        .ethereum
+        .slashing_protection()?
+        .ok_or("slashing_protection_db is not configured")?;
+
+    if subcommand == "import" {
+        db.import(&Interchange::from_json(&fs::read_to_string(path)?)?)?;
+        println!(
+            "Imported slashing protection data from {}",
+            Path::new(path).display()
+        );
+    } else if subcommand == "export" {
+        fs::write(path, db.export()?.to_json()?)?;
+        println!(
+            "Exported slashing protection data to {}",
+            Path::new(path).display()
+        );
+    } else {
+        return Err(format!("unknown subcommand: {}", subcommand.to_string_lossy()).into());
+    }
+
+    Ok(())
+}